@@ -0,0 +1,159 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BitSlice, BitVec, Indexable, Logic, Slice, Vector, XorShift};
+
+/// The moves used by the simulated annealing to find a neighbor of the
+/// current element. Moves that leave the domain are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Flips a single random bit of the element.
+    BitFlip,
+    /// Swaps two random positions within every consecutive block of the
+    /// given size, which relabels the values of one-hot encoded elements,
+    /// like composing a permutation with a transposition.
+    Transposition(usize),
+    /// Replaces the element with a uniformly random element of the domain.
+    Random,
+}
+
+/// A simulated annealing search over the concrete elements of a domain,
+/// for optimization problems where the SAT encoding would be too large.
+/// The temperature decreases geometrically, and the objective is evaluated
+/// with the concrete logic.
+#[derive(Debug, Clone)]
+pub struct Annealing<DOM>
+where
+    DOM: Indexable,
+{
+    domain: DOM,
+    neighborhood: Neighborhood,
+    steps: usize,
+    start_temp: f64,
+    end_temp: f64,
+    rng: XorShift,
+}
+
+impl<DOM> Annealing<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates a new search with the given neighborhood and random seed,
+    /// which runs for 10000 steps while cooling from 1.0 to 0.01.
+    pub fn new(domain: DOM, neighborhood: Neighborhood, seed: u64) -> Self {
+        if let Neighborhood::Transposition(block) = neighborhood {
+            assert!(block >= 2);
+            assert_eq!(domain.num_bits() % block, 0);
+        }
+        Self {
+            domain,
+            neighborhood,
+            steps: 10000,
+            start_temp: 1.0,
+            end_temp: 0.01,
+            rng: XorShift::new(seed),
+        }
+    }
+
+    /// Returns the domain of the search.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Sets the number of steps and the initial and final temperatures.
+    pub fn set_schedule(&mut self, steps: usize, start_temp: f64, end_temp: f64) {
+        assert!(start_temp >= end_temp && end_temp > 0.0);
+        self.steps = steps;
+        self.start_temp = start_temp;
+        self.end_temp = end_temp;
+    }
+
+    /// Returns a uniformly random element of the domain.
+    pub fn random_elem(&mut self) -> BitVec {
+        self.domain.random_elem(&mut self.rng)
+    }
+
+    /// Returns a random neighbor of the given element, or `None` if the
+    /// move left the domain.
+    fn neighbor(&mut self, elem: BitSlice<'_>) -> Option<BitVec> {
+        let mut result: BitVec = elem.copy_iter().collect();
+        match self.neighborhood {
+            Neighborhood::BitFlip => {
+                let index = self.rng.below(result.len());
+                result.set(index, !result.get(index));
+            }
+            Neighborhood::Transposition(block) => {
+                let i = self.rng.below(block);
+                let j = (i + 1 + self.rng.below(block - 1)) % block;
+                for start in (0..result.len()).step_by(block) {
+                    let a = result.get(start + i);
+                    let b = result.get(start + j);
+                    result.set(start + i, b);
+                    result.set(start + j, a);
+                }
+            }
+            Neighborhood::Random => return Some(self.random_elem()),
+        }
+        if self.domain.contains(&mut Logic(), result.slice()) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Searches for an element with small objective value starting from
+    /// the given element, and returns the best value and element found.
+    pub fn minimize<FUNC>(&mut self, start: BitSlice<'_>, mut objective: FUNC) -> (f64, BitVec)
+    where
+        FUNC: FnMut(&mut Logic, BitSlice<'_>) -> f64,
+    {
+        assert!(self.domain.contains(&mut Logic(), start));
+        let mut logic = Logic();
+        let mut current: BitVec = start.copy_iter().collect();
+        let mut value = objective(&mut logic, current.slice());
+        let mut best = (value, current.clone());
+
+        let ratio = self.end_temp / self.start_temp;
+        for step in 0..self.steps {
+            let temp = self.start_temp * ratio.powf(step as f64 / self.steps as f64);
+            let next = match self.neighbor(current.slice()) {
+                Some(next) => next,
+                None => continue,
+            };
+            let next_value = objective(&mut logic, next.slice());
+            let delta = next_value - value;
+            if delta <= 0.0 || self.rng.next_f64() < (-delta / temp).exp() {
+                current = next;
+                value = next_value;
+                if value < best.0 {
+                    best = (value, current.clone());
+                }
+            }
+        }
+        best
+    }
+
+    /// Searches for an element with large objective value starting from
+    /// the given element, and returns the best value and element found.
+    pub fn maximize<FUNC>(&mut self, start: BitSlice<'_>, mut objective: FUNC) -> (f64, BitVec)
+    where
+        FUNC: FnMut(&mut Logic, BitSlice<'_>) -> f64,
+    {
+        let (value, elem) = self.minimize(start, |logic, elem| -objective(logic, elem));
+        (-value, elem)
+    }
+}
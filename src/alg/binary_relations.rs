@@ -16,8 +16,9 @@
 */
 
 use super::{
-    BitSlice, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Indexable,
-    Lattice, MeetSemilattice, Monoid, PartialOrder, Relations, Semigroup, Slice, Vector,
+    BitMatrix, BitSlice, BitVec, BooleanLattice, BooleanLogic, BooleanSolver, BoundedOrder,
+    DirectedGraph, Domain, Indexable, Lattice, MeetSemilattice, Monoid, OnehotEncoding,
+    PartialOrder, Relations, Semigroup, Slice, Vector, XorShift,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -100,6 +101,104 @@ where
         self.is_top(logic, elem.slice())
     }
 
+    /// Returns the number of elements that are not related to themselves
+    /// as a binary number with the least significant bit first.
+    pub fn num_reflexivity_violations<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut violations = Vec::with_capacity(size);
+        for a in 0..size {
+            violations.push(logic.bool_not(elem.get(a * (size + 1))));
+        }
+        logic
+            .bool_fold_binary(violations.into_iter())
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the number of pairs `(a, b)` that are related but `(b, a)` is
+    /// not as a binary number with the least significant bit first.
+    pub fn num_symmetry_violations<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut violations = Vec::with_capacity(size * size);
+        for a in 0..size {
+            for b in 0..size {
+                if a != b {
+                    let test = logic.bool_not(elem.get(b + a * size));
+                    violations.push(logic.bool_and(elem.get(a + b * size), test));
+                }
+            }
+        }
+        logic
+            .bool_fold_binary(violations.into_iter())
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the number of pairs `a < b` that are related in both
+    /// directions as a binary number with the least significant bit first.
+    pub fn num_antisymmetry_violations<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut violations = Vec::with_capacity(size * size / 2);
+        for a in 0..size {
+            for b in (a + 1)..size {
+                violations.push(logic.bool_and(elem.get(a + b * size), elem.get(b + a * size)));
+            }
+        }
+        logic
+            .bool_fold_binary(violations.into_iter())
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the number of triples `(a, b, c)` where `(a, b)` and `(b, c)`
+    /// are related but `(a, c)` is not as a binary number with the least
+    /// significant bit first.
+    pub fn num_transitivity_violations<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut violations = Vec::with_capacity(size * size * size);
+        for a in 0..size {
+            for b in 0..size {
+                for c in 0..size {
+                    let test = logic.bool_and(elem.get(a + b * size), elem.get(b + c * size));
+                    let missing = logic.bool_not(elem.get(a + c * size));
+                    violations.push(logic.bool_and(test, missing));
+                }
+            }
+        }
+        logic
+            .bool_fold_binary(violations.into_iter())
+            .into_iter()
+            .collect()
+    }
+
     /// Returns true if the given binary relation is an equivalence relation.
     pub fn is_equivalence<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
     where
@@ -112,6 +211,86 @@ where
         logic.bool_and(test2, test3)
     }
 
+    /// Returns true if the given binary relation is contained in a total order
+    /// relation, that is it has no directed cycles of length at least two
+    /// (loops are allowed). The loops are removed and the transitive closure
+    /// of the rest is checked to be irreflexive.
+    pub fn is_acyclic<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let diag = self.get_identity(logic);
+        let diag = self.complement(logic, diag.slice());
+        let elem = self.meet(logic, elem, diag.slice());
+        self.is_dag(logic, elem.slice())
+    }
+
+    /// Returns true if the given binary relation is a directed acyclic graph,
+    /// that is its transitive closure is irreflexive.
+    pub fn is_dag<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let closure = self.transitive_closure(logic, elem);
+        self.is_fixed_point_free(logic, closure.slice())
+    }
+
+    /// Requires the given binary relation to have a Hamiltonian path, that
+    /// is a listing of all elements where consecutive elements are related.
+    /// The listing is introduced as a fresh witness total order whose
+    /// covering pairs must be edges, so this constraint cannot be negated.
+    pub fn add_hamiltonian_path<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>)
+    where
+        LOGIC: BooleanSolver,
+    {
+        let order = self.add_variable(logic);
+        let test = self.is_total_order(logic, order.slice());
+        logic.bool_add_clause1(test);
+
+        let diag = self.get_identity(logic);
+        let diag = self.complement(logic, diag.slice());
+        let strict = self.meet(logic, order.slice(), diag.slice());
+        let comp = Semigroup::product(self, logic, strict.slice(), strict.slice());
+        let comp = self.complement(logic, comp.slice());
+        let covers = self.meet(logic, strict.slice(), comp.slice());
+        let test = self.is_edge(logic, covers.slice(), elem);
+        logic.bool_add_clause1(test);
+    }
+
+    /// Requires the vertices of the given binary relation to be colorable
+    /// with the given positive number of colors so that related elements get
+    /// different colors. The direction of the edges is ignored, and a loop
+    /// makes the relation uncolorable. The coloring is introduced as a fresh
+    /// witness, so this constraint cannot be negated.
+    pub fn add_chromatic_number_at_most<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        colors: usize,
+    ) where
+        LOGIC: BooleanSolver,
+    {
+        assert!(colors > 0);
+        let size = self.domain().size();
+        let mut coloring: Vec<Vec<LOGIC::Elem>> = Vec::with_capacity(size);
+        for _ in 0..size {
+            let lits: Vec<LOGIC::Elem> = (0..colors).map(|_| logic.bool_add_variable()).collect();
+            logic.bool_add_exactly_one(&lits, OnehotEncoding::Pairwise);
+            coloring.push(lits);
+        }
+
+        for i in 0..size {
+            for j in 0..size {
+                let edge = logic.bool_not(elem.get(i + j * size));
+                for (&a, &b) in coloring[i].iter().zip(coloring[j].iter()) {
+                    let a = logic.bool_not(a);
+                    let b = logic.bool_not(b);
+                    logic.bool_add_clause(&[edge, a, b]);
+                }
+            }
+        }
+    }
+
     /// Returns true if the given binary relation is a partial order relation.
     pub fn is_partial_order<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
     where
@@ -177,6 +356,59 @@ where
         logic.bool_and(test1, test2)
     }
 
+    /// Returns true if the relation has exactly the given number of loops,
+    /// which are the fixed points if the relation is an operation.
+    pub fn has_fixed_points<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        count: usize,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let loops = (0..size).map(|i| elem.get(i * (size + 1)));
+        logic.bool_fold_count(loops, count)
+    }
+
+    /// Returns true if the relation has no loops.
+    pub fn is_fixed_point_free<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let loops = (0..size).map(|i| elem.get(i * (size + 1)));
+        let test = logic.bool_fold_any(loops);
+        logic.bool_not(test)
+    }
+
+    /// Returns true if this binary relation encodes the graph of a
+    /// permutation without fixed points.
+    pub fn is_derangement<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let test1 = self.is_permutation(logic, elem);
+        let test2 = self.is_fixed_point_free(logic, elem);
+        logic.bool_and(test1, test2)
+    }
+
+    /// Returns true if this binary relation encodes the graph of an
+    /// involution, that is of a symmetric operation.
+    pub fn is_involution<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let test1 = self.is_operation(logic, elem);
+        let test2 = self.is_symmetric(logic, elem);
+        logic.bool_and(test1, test2)
+    }
+
     /// Returns true if the parity of the permutation is odd.
     pub fn is_odd_permutation<LOGIC>(
         &self,
@@ -208,6 +440,214 @@ where
         logic.bool_not(res)
     }
 
+    /// Returns the composition of the two relations, using the generic
+    /// polymer and fold operations of relations. This is the reference
+    /// implementation of the `product` method.
+    pub fn compose_by_polymers<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let elem0: LOGIC::Vector = self.0.polymer(elem0, 3, &[1, 0]);
+        let elem1: LOGIC::Vector = self.0.polymer(elem1, 3, &[0, 2]);
+
+        let rels = Relations::new(self.domain().clone(), 3);
+        let elem2 = rels.meet(logic, elem0.slice(), elem1.slice());
+        rels.fold_any(logic, elem2.slice(), 1)
+    }
+
+    /// Returns the composition of two concrete relations, where the rows of
+    /// the relations are packed into words and combined word by word.
+    pub fn compose_packed(&self, elem0: BitSlice<'_>, elem1: BitSlice<'_>) -> BitVec {
+        let size = self.domain().size();
+        let mat0 = BitMatrix::from_slice(size, size, elem0);
+        let mat1 = BitMatrix::from_slice(size, size, elem1);
+        mat1.multiply(&mat0).to_bitvec()
+    }
+
+    /// Returns the converse of the given concrete relation, which is
+    /// calculated with packed bit matrix transposition.
+    pub fn converse_packed(&self, elem: BitSlice<'_>) -> BitVec {
+        let size = self.domain().size();
+        BitMatrix::from_slice(size, size, elem)
+            .transpose()
+            .to_bitvec()
+    }
+
+    /// Returns the transitive closure of the given concrete relation, which
+    /// is calculated on packed rows.
+    pub fn transitive_closure_packed(&self, elem: BitSlice<'_>) -> BitVec {
+        let size = self.domain().size();
+        let mut mat = BitMatrix::from_slice(size, size, elem);
+        mat.transitive_closure();
+        mat.to_bitvec()
+    }
+
+    /// Returns the reflexive and transitive closure of the given relation,
+    /// which is calculated by repeated squaring of the reflexive closure.
+    pub fn reflexive_transitive_closure<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let diag = self.get_identity(logic);
+        let mut elem = self.join(logic, elem, diag.slice());
+        let mut length = 1;
+        while length + 1 < self.domain().size() {
+            elem = Semigroup::product(self, logic, elem.slice(), elem.slice());
+            length *= 2;
+        }
+        elem
+    }
+
+    /// Returns the transitive closure of the given relation, which is the
+    /// composition of the relation with its reflexive and transitive
+    /// closure, so no loops are added. Concrete relations are closed with
+    /// the algorithm of Warshall on packed rows.
+    pub fn transitive_closure<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        if let Some(rel) = logic.bool_lower_vec(elem.copy_iter()) {
+            let closure = self.transitive_closure_packed(rel.slice());
+            return closure.copy_iter().map(|b| logic.bool_lift(b)).collect();
+        }
+
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        Semigroup::product(self, logic, elem, closure.slice())
+    }
+
+    /// Returns the unary relation of elements reachable by a directed path,
+    /// possibly of length zero, from some element of the given unary
+    /// relation. The pair `(a, b)` is at index `a + b * size`.
+    pub fn reachable_from<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        subset: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        assert_eq!(subset.len(), size);
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        let mut result: LOGIC::Vector = Vector::with_capacity(size);
+        for b in 0..size {
+            let mut value = logic.bool_zero();
+            for a in 0..size {
+                let test = logic.bool_and(subset.get(a), closure.get(a + b * size));
+                value = logic.bool_or(value, test);
+            }
+            result.push(value);
+        }
+        result
+    }
+
+    /// Returns the strongly connected component structure of the given
+    /// relation. The components are represented by their smallest elements,
+    /// and the condensation is the partial order of the closure restricted
+    /// to these leaders.
+    pub fn condensation<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> Condensation<LOGIC>
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        let conv = self.converse(closure.slice());
+        let components = self.meet(logic, closure.slice(), conv.slice());
+
+        let mut leaders: LOGIC::Vector = Vector::with_capacity(size);
+        for i in 0..size {
+            let prev = logic.bool_fold_any((0..i).map(|j| components.get(j + i * size)));
+            leaders.push(logic.bool_not(prev));
+        }
+
+        let unary = Relations::new(self.domain().clone(), 1);
+        let leaders0: LOGIC::Vector = unary.polymer(leaders.slice(), 2, &[0]);
+        let leaders1: LOGIC::Vector = unary.polymer(leaders.slice(), 2, &[1]);
+        let order = self.meet(logic, leaders0.slice(), leaders1.slice());
+        let order = self.meet(logic, order.slice(), closure.slice());
+
+        Condensation {
+            closure,
+            components,
+            leaders,
+            order,
+        }
+    }
+
+    /// Returns true if the given binary relation is strongly connected, that
+    /// is any element can be reached from any other by a directed path.
+    pub fn is_strongly_connected<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        self.is_top(logic, closure.slice())
+    }
+
+    /// Returns true if the given binary relation is connected, that is any
+    /// two elements are connected by a path that ignores the directions.
+    pub fn is_connected<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let conv = self.converse(elem);
+        let elem = self.join(logic, elem, conv.slice());
+        self.is_strongly_connected(logic, elem.slice())
+    }
+
+    /// Returns true if the given binary relation has no directed cycles of
+    /// length at least two (loops are allowed), which is checked by the
+    /// antisymmetry of its reflexive and transitive closure. This is
+    /// equivalent to `is_acyclic`.
+    pub fn has_antisymmetric_closure<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        self.is_antisymmetric(logic, closure.slice())
+    }
+
+    /// Returns true if the condensation of the given relation is a chain,
+    /// that is any two elements are connected by a directed path in one
+    /// of the directions.
+    pub fn has_chain_condensation<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let closure = self.reflexive_transitive_closure(logic, elem);
+        self.is_complete(logic, closure.slice())
+    }
+
     /// Creates a concrete relation as specified by the given predicate
     pub fn get_element_with<LOGIC, PRED>(&self, logic: &LOGIC, pred: PRED) -> LOGIC::Vector
     where
@@ -227,6 +667,23 @@ where
     }
 }
 
+/// The strongly connected component structure of a binary relation as
+/// calculated by the `BinaryRelations::condensation` method.
+pub struct Condensation<LOGIC>
+where
+    LOGIC: BooleanLogic,
+{
+    /// The reflexive and transitive closure of the relation.
+    pub closure: LOGIC::Vector,
+    /// The equivalence relation whose classes are the strongly connected
+    /// components.
+    pub components: LOGIC::Vector,
+    /// The unary relation containing the smallest element of each component.
+    pub leaders: LOGIC::Vector,
+    /// The condensation partial order on the leaders of the components.
+    pub order: LOGIC::Vector,
+}
+
 impl<DOM> Domain for BinaryRelations<DOM>
 where
     DOM: Indexable,
@@ -279,6 +736,11 @@ where
     fn get_index(&self, elem: BitSlice<'_>) -> usize {
         self.0.get_index(elem)
     }
+
+    #[inline]
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.0.random_elem(rng)
+    }
 }
 
 impl<DOM> DirectedGraph for BinaryRelations<DOM>
@@ -442,12 +904,28 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let elem0: LOGIC::Vector = self.0.polymer(elem0, 3, &[1, 0]);
-        let elem1: LOGIC::Vector = self.0.polymer(elem1, 3, &[0, 2]);
+        // concrete relations are composed on packed rows
+        if let (Some(rel0), Some(rel1)) = (
+            logic.bool_lower_vec(elem0.copy_iter()),
+            logic.bool_lower_vec(elem1.copy_iter()),
+        ) {
+            let rel2 = self.compose_packed(rel0.slice(), rel1.slice());
+            return rel2.copy_iter().map(|b| logic.bool_lift(b)).collect();
+        }
 
-        let rels = Relations::new(self.domain().clone(), 3);
-        let elem2 = rels.meet(logic, elem0.slice(), elem1.slice());
-        rels.fold_any(logic, elem2.slice(), 1)
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(size * size);
+        for c in 0..size {
+            for b in 0..size {
+                let mut value = logic.bool_zero();
+                for a in 0..size {
+                    let test = logic.bool_and(elem0.get(b + a * size), elem1.get(a + c * size));
+                    value = logic.bool_or(value, test);
+                }
+                result.push(value);
+            }
+        }
+        result
     }
 }
 
@@ -0,0 +1,270 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::orderly::{canonical_code, relation_from_code};
+use super::{
+    generate_digraphs, generate_graphs, generate_posets, BinaryRelations, BitSlice, BitVec, Slice,
+    SmallSet, Vector, XorShift,
+};
+
+/// The magic bytes and version at the start of census files.
+const MAGIC: &[u8; 4] = b"UACN";
+const VERSION: u8 = 1;
+
+/// A census of binary relations on a fixed number of elements up to
+/// isomorphism, like all posets with seven elements. Each class is stored
+/// by its canonical code, and the classes are sorted by their number of
+/// edges, which serves as the invariant index for lookups. A census can be
+/// saved to disk in a compact bit-packed format, so repeated experiments
+/// can reuse a precomputed one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Census {
+    size: usize,
+    codes: Vec<u64>,
+    offsets: Vec<usize>,
+}
+
+impl Census {
+    /// Creates the census of the isomorphism classes of the given
+    /// relations. The size must be at most eight.
+    pub fn new<ITER>(size: usize, elems: ITER) -> Self
+    where
+        ITER: IntoIterator<Item = BitVec>,
+    {
+        let codes = elems
+            .into_iter()
+            .map(|elem| canonical_code(size, elem.slice()))
+            .collect();
+        Self::from_codes(size, codes)
+    }
+
+    /// Sorts the codes by the number of edges and builds the index.
+    fn from_codes(size: usize, mut codes: Vec<u64>) -> Self {
+        assert!(size <= 8);
+        codes.sort_unstable_by_key(|&code| (code.count_ones(), code));
+        codes.dedup();
+
+        let mut offsets = vec![0; size * size + 2];
+        for &code in codes.iter() {
+            offsets[code.count_ones() as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        Self {
+            size,
+            codes,
+            offsets,
+        }
+    }
+
+    /// Returns the census of simple graphs with the given number of vertices.
+    pub fn graphs(size: usize) -> Self {
+        let mut elems = Vec::new();
+        generate_graphs(size, |elem| elems.push(elem.copy_iter().collect()));
+        Self::new(size, elems)
+    }
+
+    /// Returns the census of loopless digraphs with the given number of
+    /// vertices.
+    pub fn digraphs(size: usize) -> Self {
+        let mut elems = Vec::new();
+        generate_digraphs(size, |elem| elems.push(elem.copy_iter().collect()));
+        Self::new(size, elems)
+    }
+
+    /// Returns the census of partial orders with the given number of elements.
+    pub fn posets(size: usize) -> Self {
+        let mut elems = Vec::new();
+        generate_posets(size, |elem| elems.push(elem.copy_iter().collect()));
+        Self::new(size, elems)
+    }
+
+    /// Returns the number of elements of the underlying set.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of isomorphism classes.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Returns true if the census has no isomorphism classes.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// Returns the canonical representative of the given class.
+    pub fn get(&self, index: usize) -> BitVec {
+        relation_from_code(self.size, self.codes[index])
+    }
+
+    /// Returns an iterator over the canonical representatives.
+    pub fn iter(&self) -> impl Iterator<Item = BitVec> + '_ {
+        self.codes
+            .iter()
+            .map(move |&code| relation_from_code(self.size, code))
+    }
+
+    /// Returns the range of the indices of the classes with the given
+    /// number of edges.
+    pub fn with_edges(&self, edges: usize) -> std::ops::Range<usize> {
+        if edges > self.size * self.size {
+            return 0..0;
+        }
+        self.offsets[edges]..self.offsets[edges + 1]
+    }
+
+    /// Returns the index of the class of the given relation, if it is in
+    /// the census.
+    pub fn position(&self, elem: BitSlice<'_>) -> Option<usize> {
+        let code = canonical_code(self.size, elem);
+        let range = self.with_edges(code.count_ones() as usize);
+        let start = range.start;
+        self.codes[range]
+            .binary_search(&code)
+            .ok()
+            .map(|i| start + i)
+    }
+
+    /// Returns the indices of the classes whose representatives satisfy the
+    /// given predicate, which must be invariant under isomorphism.
+    pub fn filter<PRED>(&self, mut pred: PRED) -> Vec<usize>
+    where
+        PRED: FnMut(&BinaryRelations<SmallSet>, BitSlice<'_>) -> bool,
+    {
+        let domain = BinaryRelations::new(SmallSet::new(self.size));
+        (0..self.len())
+            .filter(|&index| pred(&domain, self.get(index).slice()))
+            .collect()
+    }
+
+    /// Returns the indices of the given number of distinct classes chosen
+    /// uniformly at random by an xorshift generator from the given seed.
+    pub fn sample(&self, count: usize, seed: u64) -> Vec<usize> {
+        let mut rng = XorShift::new(seed);
+        let mut indices: Vec<usize> = (0..self.len()).collect();
+        let count = count.min(indices.len());
+        for i in 0..count {
+            let j = i + rng.below(indices.len() - i);
+            indices.swap(i, j);
+        }
+        indices.truncate(count);
+        indices
+    }
+
+    /// Returns the number of bytes of a packed canonical code.
+    fn code_bytes(&self) -> usize {
+        (self.size * self.size + 7) / 8
+    }
+
+    /// Writes the census in the binary census format: the magic bytes, the
+    /// version, the size and the number of classes, followed by the class
+    /// counts per number of edges and the bit-packed canonical codes.
+    pub fn write<WRITER>(&self, writer: &mut WRITER) -> io::Result<()>
+    where
+        WRITER: Write,
+    {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, self.size as u8])?;
+        writer.write_all(&(self.len() as u32).to_le_bytes())?;
+        for edges in 0..=self.size * self.size {
+            writer.write_all(&(self.with_edges(edges).len() as u32).to_le_bytes())?;
+        }
+        for &code in self.codes.iter() {
+            writer.write_all(&code.to_le_bytes()[..self.code_bytes()])?;
+        }
+        Ok(())
+    }
+
+    /// Reads a census written in the binary census format.
+    pub fn read<READER>(reader: &mut READER) -> io::Result<Self>
+    where
+        READER: Read,
+    {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let read_u32 = |reader: &mut READER| -> io::Result<usize> {
+            let mut buf = [0; 4];
+            reader.read_exact(&mut buf)?;
+            Ok(u32::from_le_bytes(buf) as usize)
+        };
+
+        let mut header = [0; 6];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(invalid("not a census file"));
+        }
+        let size = header[5] as usize;
+        if size > 8 {
+            return Err(invalid("census size is too large"));
+        }
+        let len = read_u32(reader)?;
+        let mut counts = Vec::with_capacity(size * size + 1);
+        for _ in 0..=size * size {
+            counts.push(read_u32(reader)?);
+        }
+        if counts.iter().sum::<usize>() != len {
+            return Err(invalid("corrupt census index"));
+        }
+
+        // the length is not trusted for the allocation before the codes are read
+        let bytes = (size * size + 7) / 8;
+        let mut codes = Vec::with_capacity(len.min(1 << 16));
+        for _ in 0..len {
+            let mut buf = [0; 8];
+            reader.read_exact(&mut buf[..bytes])?;
+            let code = u64::from_le_bytes(buf);
+            if size * size < 64 && code >> (size * size) != 0 {
+                return Err(invalid("census code is out of range"));
+            }
+            codes.push(code);
+        }
+
+        let census = Self::from_codes(size, codes);
+        let valid = census.len() == len
+            && (0..=size * size).all(|edges| census.with_edges(edges).len() == counts[edges]);
+        if valid {
+            Ok(census)
+        } else {
+            Err(invalid("corrupt census index"))
+        }
+    }
+
+    /// Saves the census to the file with the given path.
+    pub fn save<PATH>(&self, path: PATH) -> io::Result<()>
+    where
+        PATH: AsRef<Path>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a census from the file with the given path.
+    pub fn load<PATH>(path: PATH) -> io::Result<Self>
+    where
+        PATH: AsRef<Path>,
+    {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read(&mut reader)
+    }
+}
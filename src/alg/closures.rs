@@ -0,0 +1,140 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BitSlice, BitVec, Indexable, Logic, Power, Slice, Vector, BOOLEAN};
+
+/// An implication between two subsets of a finite set, where the subsets
+/// are represented as characteristic bit vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Implication {
+    /// The set of elements that must be present.
+    pub premise: BitVec,
+    /// The set of elements that are implied by the premise.
+    pub conclusion: BitVec,
+}
+
+/// Returns true if the first set is a subset of the second one.
+fn is_subset(set0: BitSlice<'_>, set1: BitSlice<'_>) -> bool {
+    set0.copy_iter().zip(set1.copy_iter()).all(|(a, b)| !a || b)
+}
+
+/// Returns the smallest superset of the given set that is closed under
+/// all implications whose premise is a proper subset of the set. This is
+/// the closure operator whose closed sets are the closed and pseudo-closed
+/// sets of the closure system.
+fn pseudo_closure(basis: &[Implication], set: BitSlice<'_>) -> BitVec {
+    let mut result: BitVec = set.copy_iter().collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for imp in basis {
+            if is_subset(imp.premise.slice(), result.slice())
+                && imp.premise != result
+                && !is_subset(imp.conclusion.slice(), result.slice())
+            {
+                for (i, b) in imp.conclusion.copy_iter().enumerate() {
+                    if b {
+                        result.set(i, true);
+                    }
+                }
+                changed = true;
+            }
+        }
+    }
+    result
+}
+
+/// Returns the Duquenne-Guigues canonical implication basis of the given
+/// closure operator on the set `0..size`. The premises of the returned
+/// implications are the pseudo-closed sets listed in lectic order, and the
+/// conclusions are their closures. The basis is calculated with the next
+/// closure algorithm of Ganter.
+pub fn canonical_basis<CLOSE>(size: usize, mut close: CLOSE) -> Vec<Implication>
+where
+    CLOSE: FnMut(BitSlice<'_>) -> BitVec,
+{
+    let mut basis: Vec<Implication> = Vec::new();
+    let mut set = pseudo_closure(&basis, BitVec::with_values(size, false).slice());
+    loop {
+        let closed = close(set.slice());
+        debug_assert!(is_subset(set.slice(), closed.slice()));
+        if closed != set {
+            basis.push(Implication {
+                premise: set.clone(),
+                conclusion: closed,
+            });
+        }
+
+        let mut next = None;
+        for i in (0..size).rev() {
+            if set.get(i) {
+                continue;
+            }
+            let mut candidate: BitVec = set.copy_iter().take(i).collect();
+            candidate.push(true);
+            candidate.extend((i + 1..size).map(|_| false));
+            let candidate = pseudo_closure(&basis, candidate.slice());
+            if (0..i).all(|j| candidate.get(j) == set.get(j)) {
+                next = Some(candidate);
+                break;
+            }
+        }
+
+        match next {
+            Some(elem) => set = elem,
+            None => return basis,
+        }
+    }
+}
+
+/// Returns the canonical implication basis of the closure system on the
+/// set `0..size` given by the list of its closed sets. The full set is
+/// always considered to be closed.
+pub fn canonical_basis_of_closed_sets(size: usize, closed: &[BitVec]) -> Vec<Implication> {
+    canonical_basis(size, |set| {
+        let mut result = BitVec::with_values(size, true);
+        for elem in closed {
+            if is_subset(set, elem.slice()) {
+                for (i, b) in elem.copy_iter().enumerate() {
+                    if !b {
+                        result.set(i, false);
+                    }
+                }
+            }
+        }
+        result
+    })
+}
+
+/// Returns the canonical implication basis of the closure system on the
+/// set `0..size` whose closed sets are the elements of the subset domain
+/// `Power::new(BOOLEAN, size)` satisfying the given predicate.
+pub fn canonical_basis_of_predicate<PRED>(size: usize, pred: PRED) -> Vec<Implication>
+where
+    PRED: FnMut(&mut Logic, BitSlice<'_>) -> bool,
+{
+    let domain = Power::new(BOOLEAN, size);
+    let mut logic = Logic();
+    let closed: Vec<BitVec> = domain
+        .evaluate_all(&mut logic, pred)
+        .copy_iter()
+        .enumerate()
+        .filter(|(_, b)| *b)
+        .map(|(i, _)| domain.get_elem(&logic, i))
+        .collect();
+    canonical_basis_of_closed_sets(size, &closed)
+}
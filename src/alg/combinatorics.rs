@@ -0,0 +1,104 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Reference values of classical combinatorial numbers, computed by plain
+//! recurrences, that are used to validate the model counts of predicates.
+
+/// Returns the number of permutations of an `n` element set.
+pub fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+/// Returns the number of `k` element subsets of an `n` element set.
+pub fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut result = 1;
+    for i in 0..k {
+        result = result * (n - i) / (i + 1);
+    }
+    result
+}
+
+/// Returns the Stirling number of the second kind, which is the number of
+/// partitions of an `n` element set into `k` nonempty blocks.
+pub fn stirling2(n: usize, k: usize) -> usize {
+    let mut row = vec![0; k + 1];
+    row[0] = 1;
+    for _ in 0..n {
+        for j in (1..=k).rev() {
+            row[j] = j * row[j] + row[j - 1];
+        }
+        row[0] = 0;
+    }
+    row[k]
+}
+
+/// Returns the Bell number, which is the number of partitions of an `n`
+/// element set.
+pub fn bell(n: usize) -> usize {
+    (0..=n).map(|k| stirling2(n, k)).sum()
+}
+
+/// Returns the Catalan number, which is the number of order preserving
+/// and extensive maps of an `n` element chain.
+pub fn catalan(n: usize) -> usize {
+    binomial(2 * n, n) / (n + 1)
+}
+
+/// Returns the Eulerian number, which is the number of permutations of an
+/// `n` element chain with exactly `k` descents.
+pub fn eulerian(n: usize, k: usize) -> usize {
+    let mut row = vec![0; k + 1];
+    row[0] = 1;
+    for m in 1..n {
+        for j in (1..=k.min(m)).rev() {
+            row[j] = (j + 1) * row[j] + (m + 1 - j) * row[j - 1];
+        }
+    }
+    row[k]
+}
+
+/// Returns the number of permutations of an `n` element set without
+/// fixed points.
+pub fn subfactorial(n: usize) -> usize {
+    let mut result = vec![1, 0];
+    for m in 2..=n {
+        result.push((m - 1) * (result[m - 1] + result[m - 2]));
+    }
+    result[n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinatorics() {
+        assert_eq!(factorial(5), 120);
+        assert_eq!(binomial(6, 2), 15);
+        assert_eq!(stirling2(5, 2), 15);
+        assert_eq!(bell(7), 877);
+        assert_eq!(catalan(5), 42);
+        assert_eq!(
+            (0..5).map(|k| eulerian(5, k)).collect::<Vec<_>>(),
+            vec![1, 26, 66, 26, 1]
+        );
+        assert_eq!(subfactorial(4), 9);
+    }
+}
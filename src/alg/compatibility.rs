@@ -0,0 +1,241 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{
+    canonical_basis_of_closed_sets, BipartiteGraph, BitSlice, BitVec, Indexable, Logic,
+    Preservation, Slice, Vector,
+};
+
+/// The compatibility matrix of a list of operations and a list of relations,
+/// whose rows are indexed by the operations and columns by the relations,
+/// and an entry is set if the operation preserves the relation. Sets of rows
+/// and columns are represented as characteristic bit vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityMatrix {
+    num_cols: usize,
+    rows: Vec<BitVec>,
+}
+
+impl CompatibilityMatrix {
+    /// Calculates the compatibility matrix of the given operations and
+    /// relations with the arities of the preservation graph.
+    pub fn new<DOM>(pres: &Preservation<DOM>, operations: &[BitVec], relations: &[BitVec]) -> Self
+    where
+        DOM: Indexable,
+    {
+        let mut logic = Logic();
+        let rows = operations
+            .iter()
+            .map(|op| {
+                relations
+                    .iter()
+                    .map(|rel| pres.preserves(&mut logic, op.slice(), rel.slice()))
+                    .collect()
+            })
+            .collect();
+        Self {
+            num_cols: relations.len(),
+            rows,
+        }
+    }
+
+    /// Calculates the compatibility matrix of all operations and all
+    /// relations with the arities of the preservation graph.
+    pub fn from_preservation<DOM>(pres: &Preservation<DOM>) -> Self
+    where
+        DOM: Indexable,
+    {
+        let logic = Logic();
+        let operations: Vec<BitVec> = (0..pres.dom0().size())
+            .map(|i| pres.dom0().get_elem(&logic, i))
+            .collect();
+        let relations: Vec<BitVec> = (0..pres.dom1().size())
+            .map(|i| pres.dom1().get_elem(&logic, i))
+            .collect();
+        Self::new(pres, &operations, &relations)
+    }
+
+    /// Returns the number of rows, that is the number of operations.
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the number of columns, that is the number of relations.
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Returns true if the operation of the given row preserves the
+    /// relation of the given column.
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    /// Returns the number of compatible pairs.
+    pub fn num_edges(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.copy_iter().filter(|&b| b).count())
+            .sum()
+    }
+
+    /// Returns the set of columns that are compatible with all of the given
+    /// rows.
+    pub fn common_cols(&self, rows: BitSlice<'_>) -> BitVec {
+        assert_eq!(rows.len(), self.num_rows());
+        let mut result: BitVec = Vector::with_values(self.num_cols, true);
+        for (row, _) in self.rows.iter().zip(rows.copy_iter()).filter(|(_, b)| *b) {
+            for (col, b) in row.copy_iter().enumerate() {
+                if !b {
+                    result.set(col, false);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the set of rows that are compatible with all of the given
+    /// columns.
+    pub fn common_rows(&self, cols: BitSlice<'_>) -> BitVec {
+        assert_eq!(cols.len(), self.num_cols);
+        self.rows
+            .iter()
+            .map(|row| row.copy_iter().zip(cols.copy_iter()).all(|(a, b)| a || !b))
+            .collect()
+    }
+
+    /// Returns the Galois closure of the given set of rows, which is the set
+    /// of operations preserving every relation that the given ones preserve.
+    pub fn row_closure(&self, rows: BitSlice<'_>) -> BitVec {
+        let cols = self.common_cols(rows);
+        self.common_rows(cols.slice())
+    }
+
+    /// Returns the Galois closure of the given set of columns, which is the
+    /// set of relations preserved by every operation preserving the given
+    /// ones.
+    pub fn col_closure(&self, cols: BitSlice<'_>) -> BitVec {
+        let rows = self.common_rows(cols);
+        self.common_cols(rows.slice())
+    }
+
+    /// Returns all Galois closed sets of rows ordered by their sizes. These
+    /// are the intersections of the sets of rows compatible with a single
+    /// column, together with the set of all rows.
+    pub fn closed_row_sets(&self) -> Vec<BitVec> {
+        let mut result: Vec<BitVec> = vec![Vector::with_values(self.num_rows(), true)];
+        let mut seen: HashSet<Vec<bool>> = HashSet::new();
+        seen.insert(result[0].copy_iter().collect());
+        for col in 0..self.num_cols {
+            let extent: BitVec = self.rows.iter().map(|row| row.get(col)).collect();
+            for index in 0..result.len() {
+                let meet: BitVec = result[index]
+                    .copy_iter()
+                    .zip(extent.copy_iter())
+                    .map(|(a, b)| a && b)
+                    .collect();
+                if seen.insert(meet.copy_iter().collect()) {
+                    result.push(meet);
+                }
+            }
+        }
+        result.sort_by_key(|set| set.copy_iter().filter(|&b| b).count());
+        result
+    }
+
+    /// Returns the report of the derived invariants of the matrix.
+    pub fn report(&self) -> CompatibilityReport {
+        let closed = self.closed_row_sets();
+
+        // longest chains ending in each closed set, which are ordered by size
+        let mut height = vec![0; closed.len()];
+        for i in 0..closed.len() {
+            for j in 0..i {
+                let below = closed[j]
+                    .copy_iter()
+                    .zip(closed[i].copy_iter())
+                    .all(|(a, b)| !a || b);
+                if below && closed[j] != closed[i] {
+                    height[i] = height[i].max(height[j] + 1);
+                }
+            }
+        }
+
+        CompatibilityReport {
+            num_rows: self.num_rows(),
+            num_cols: self.num_cols,
+            num_edges: self.num_edges(),
+            num_closed_sets: closed.len(),
+            height: height.into_iter().max().unwrap_or(0),
+            num_implications: canonical_basis_of_closed_sets(self.num_rows(), &closed).len(),
+        }
+    }
+}
+
+/// The derived invariants of a compatibility matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// The number of operations.
+    pub num_rows: usize,
+    /// The number of relations.
+    pub num_cols: usize,
+    /// The number of compatible pairs.
+    pub num_edges: usize,
+    /// The number of Galois closed sets of operations.
+    pub num_closed_sets: usize,
+    /// The length of the longest chain in the lattice of closed sets.
+    pub height: usize,
+    /// The size of the canonical implication basis of the closed sets.
+    pub num_implications: usize,
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} operations, {} relations, {} edges, {} closed sets of height {}, {} implications",
+            self.num_rows,
+            self.num_cols,
+            self.num_edges,
+            self.num_closed_sets,
+            self.height,
+            self.num_implications
+        )
+    }
+}
+
+/// Returns the reports of the compatibility matrices of all operations of
+/// arity at most the given bound against all relations of the given arity,
+/// which describe the lattices of closed operation sets truncated by arity.
+pub fn compatibility_reports<DOM>(
+    domain: DOM,
+    max_op_arity: usize,
+    rel_arity: usize,
+) -> Vec<CompatibilityReport>
+where
+    DOM: Indexable,
+{
+    (1..=max_op_arity)
+        .map(|op_arity| {
+            let pres = Preservation::new(domain.clone(), op_arity, rel_arity);
+            CompatibilityMatrix::from_preservation(&pres).report()
+        })
+        .collect()
+}
@@ -0,0 +1,206 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Domain, Logic, Semigroup, Slice, Solver, Vector,
+};
+
+/// A map from a domain to itself that can be evaluated symbolically.
+pub trait Endomap {
+    /// The domain the map is acting on.
+    type Domain: Domain;
+
+    /// Returns the domain of the map.
+    fn domain(&self) -> &Self::Domain;
+
+    /// Returns the image of the given element.
+    fn apply<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic;
+
+    /// Returns the list of iterates `x, f(x), ..., f^k(x)` of the given
+    /// element, so that all intermediate powers are available at once.
+    fn iterates<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        exp: usize,
+    ) -> Vec<LOGIC::Vector>
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result: Vec<LOGIC::Vector> = Vec::with_capacity(exp + 1);
+        result.push(elem.copy_iter().collect());
+        for _ in 0..exp {
+            let elem = self.apply(logic, result.last().unwrap().slice());
+            result.push(elem);
+        }
+        result
+    }
+
+    /// Returns the image of the given element under the `exp`-th power
+    /// of the map.
+    fn iterate<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>, exp: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.iterates(logic, elem, exp).pop().unwrap()
+    }
+
+    /// Returns true if the element is a periodic point whose period
+    /// divides `exp`, that is `f^exp(x) = x`.
+    fn is_periodic<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        exp: usize,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let other = self.iterate(logic, elem, exp);
+        self.domain().equals(logic, elem, other.slice())
+    }
+
+    /// Finds all elements of the domain with `f^exp(x) = x` using a SAT
+    /// solver.
+    fn find_periodic_points(&self, exp: usize) -> Vec<BitVec> {
+        let mut logic = Solver::new("");
+        let elem = self.domain().add_variable(&mut logic);
+        let test = self.domain().contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let test = self.is_periodic(&mut logic, elem.slice(), exp);
+        logic.bool_add_clause1(test);
+
+        let result = logic.bool_all_models(elem.copy_iter()).collect();
+        result
+    }
+
+    /// Finds all fixed points of the map using a SAT solver.
+    fn find_fixed_points(&self) -> Vec<BitVec> {
+        self.find_periodic_points(1)
+    }
+
+    /// Iterates the map on a concrete element until it reaches a cycle, and
+    /// returns the length of the pre-periodic tail and the length of the
+    /// cycle. The iterates are memoized, so each is evaluated only once.
+    fn cycle_structure(&self, elem: BitSlice<'_>) -> (usize, usize) {
+        let mut logic = Logic();
+        let mut seen: HashMap<Vec<bool>, usize> = HashMap::new();
+        let mut elem: BitVec = elem.copy_iter().collect();
+        loop {
+            let key: Vec<bool> = elem.copy_iter().collect();
+            let index = seen.len();
+            if let Some(&start) = seen.get(&key) {
+                return (start, index - start);
+            }
+            seen.insert(key, index);
+            elem = self.apply(&mut logic, elem.slice());
+        }
+    }
+}
+
+/// Unrolls the given number of iterations of a symbolic operator starting
+/// from the given element, and returns the last iterate. The operator is
+/// given as a closure, so any monotone operator on relations (such as one
+/// step of a closure computation) can be unrolled this way.
+pub fn bounded_iteration<LOGIC, OP>(
+    logic: &mut LOGIC,
+    elem: LOGIC::Slice<'_>,
+    steps: usize,
+    mut op: OP,
+) -> LOGIC::Vector
+where
+    LOGIC: BooleanLogic,
+    OP: FnMut(&mut LOGIC, LOGIC::Slice<'_>) -> LOGIC::Vector,
+{
+    let mut elem: LOGIC::Vector = elem.copy_iter().collect();
+    for _ in 0..steps {
+        elem = op(logic, elem.slice());
+    }
+    elem
+}
+
+/// Unrolls the given number of iterations of a symbolic operator on the
+/// domain, and returns the last iterate together with a literal that is
+/// true if it is already a fixed point of the operator. For a monotone
+/// operator this literal expresses that the least fixed point above the
+/// starting element is reached within the given number of steps.
+pub fn bounded_fixpoint<DOM, LOGIC, OP>(
+    domain: &DOM,
+    logic: &mut LOGIC,
+    elem: LOGIC::Slice<'_>,
+    steps: usize,
+    mut op: OP,
+) -> (LOGIC::Vector, LOGIC::Elem)
+where
+    DOM: Domain,
+    LOGIC: BooleanLogic,
+    OP: FnMut(&mut LOGIC, LOGIC::Slice<'_>) -> LOGIC::Vector,
+{
+    let elem = bounded_iteration(logic, elem, steps, &mut op);
+    let next = op(logic, elem.slice());
+    let test = domain.equals(logic, elem.slice(), next.slice());
+    (elem, test)
+}
+
+/// The left translation `x -> a * x` of a semigroup by a fixed element.
+#[derive(Debug, Clone)]
+pub struct Translation<DOM>
+where
+    DOM: Semigroup,
+{
+    domain: DOM,
+    elem: BitVec,
+}
+
+impl<DOM> Translation<DOM>
+where
+    DOM: Semigroup,
+{
+    /// Creates the left translation by the given element.
+    pub fn new(domain: DOM, elem: BitVec) -> Self {
+        assert_eq!(elem.len(), domain.num_bits());
+        Self { domain, elem }
+    }
+
+    /// Returns the element by which the domain is translated.
+    pub fn elem(&self) -> BitSlice<'_> {
+        self.elem.slice()
+    }
+}
+
+impl<DOM> Endomap for Translation<DOM>
+where
+    DOM: Semigroup,
+{
+    type Domain = DOM;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.domain
+    }
+
+    fn apply<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let other = self.domain.lift(logic, self.elem.slice());
+        self.domain.product(logic, other.slice(), elem)
+    }
+}
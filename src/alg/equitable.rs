@@ -0,0 +1,169 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BitSlice, BitVec, Indexable, Slice, Stabilizer, Vector};
+
+/// The signature of an element in a refinement round: its previous color
+/// and the sorted list of the colored tuples containing it, each given by
+/// the relation, the position of the element and the colors of the tuple.
+type Signature = (usize, Vec<(usize, usize, Vec<usize>)>);
+
+/// The coarsest equitable partition of a concrete relational structure,
+/// obtained by color refinement from the single block partition. The
+/// colors are numbered by the sorted order of the signatures, so they
+/// are preserved by every automorphism, and isomorphic structures have
+/// the same colors up to the isomorphism.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquitablePartition {
+    size: usize,
+    colors: Vec<usize>,
+    signatures: Vec<(Signature, usize)>,
+}
+
+impl EquitablePartition {
+    /// Calculates the coarsest equitable partition of the structure on the
+    /// given number of elements with the given relations and arities.
+    pub fn new<'a, ITER>(size: usize, relations: ITER) -> Self
+    where
+        ITER: IntoIterator<Item = (usize, BitSlice<'a>)>,
+    {
+        let mut tuples: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (rel, (arity, relation)) in relations.into_iter().enumerate() {
+            assert_eq!(relation.len(), size.pow(arity as u32));
+            for index in (0..relation.len()).filter(|&i| relation.get(i)) {
+                let mut index = index;
+                let tuple = (0..arity)
+                    .map(|_| {
+                        let coord = index % size;
+                        index /= size;
+                        coord
+                    })
+                    .collect();
+                tuples.push((rel, tuple));
+            }
+        }
+
+        let mut colors = vec![0; size];
+        let mut num_colors = if size > 0 { 1 } else { 0 };
+        loop {
+            let mut signatures: Vec<Signature> = colors.iter().map(|&c| (c, Vec::new())).collect();
+            for (rel, tuple) in tuples.iter() {
+                let coloring: Vec<usize> = tuple.iter().map(|&x| colors[x]).collect();
+                for (pos, &elem) in tuple.iter().enumerate() {
+                    signatures[elem].1.push((*rel, pos, coloring.clone()));
+                }
+            }
+            for sig in signatures.iter_mut() {
+                sig.1.sort();
+            }
+
+            let mut distinct = signatures.clone();
+            distinct.sort();
+            distinct.dedup();
+            colors = signatures
+                .iter()
+                .map(|sig| distinct.binary_search(sig).unwrap())
+                .collect();
+
+            if distinct.len() == num_colors {
+                let signatures = distinct
+                    .into_iter()
+                    .enumerate()
+                    .map(|(c, sig)| (sig, colors.iter().filter(|&&d| d == c).count()))
+                    .collect();
+                return Self {
+                    size,
+                    colors,
+                    signatures,
+                };
+            }
+            num_colors = distinct.len();
+        }
+    }
+
+    /// Calculates the coarsest equitable partition of the relations of the
+    /// given structure.
+    pub fn from_structure<DOM>(structure: &Stabilizer<DOM>) -> Self
+    where
+        DOM: Indexable,
+    {
+        Self::new(structure.domain().size(), structure.relations())
+    }
+
+    /// Returns the number of elements of the structure.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of blocks of the partition.
+    pub fn num_colors(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Returns the color of the given element.
+    pub fn color(&self, elem: usize) -> usize {
+        self.colors[elem]
+    }
+
+    /// Returns the colors of all elements.
+    pub fn colors(&self) -> &[usize] {
+        &self.colors
+    }
+
+    /// Returns the blocks of the partition in the order of their colors.
+    pub fn blocks(&self) -> Vec<Vec<usize>> {
+        let mut blocks = vec![Vec::new(); self.num_colors()];
+        for (elem, &color) in self.colors.iter().enumerate() {
+            blocks[color].push(elem);
+        }
+        blocks
+    }
+
+    /// Returns true if every block is a singleton, in which case the
+    /// structure has no nontrivial automorphisms.
+    pub fn is_discrete(&self) -> bool {
+        self.num_colors() == self.size
+    }
+
+    /// Returns false if the two structures are certainly not isomorphic,
+    /// because their stable colorings have different signatures or block
+    /// sizes. Isomorphic structures always pass this test.
+    pub fn may_be_isomorphic(&self, other: &EquitablePartition) -> bool {
+        self.size == other.size && self.signatures == other.signatures
+    }
+
+    /// Returns the quotient of the given relation by the partition, which
+    /// is the relation of the given arity on the colors containing the
+    /// color tuples of the tuples of the relation.
+    pub fn quotient(&self, arity: usize, relation: BitSlice<'_>) -> BitVec {
+        assert_eq!(relation.len(), self.size.pow(arity as u32));
+        let num_colors = self.num_colors();
+        let mut result: BitVec = Vector::with_values(num_colors.pow(arity as u32), false);
+        for index in (0..relation.len()).filter(|&i| relation.get(i)) {
+            let mut index = index;
+            let mut target = 0;
+            let mut power = 1;
+            for _ in 0..arity {
+                target += self.colors[index % self.size] * power;
+                index /= self.size;
+                power *= num_colors;
+            }
+            result.set(target, true);
+        }
+        result
+    }
+}
@@ -0,0 +1,131 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Domain, Logic, Slice, Vector, XorShift,
+};
+
+/// A linear hash function on the elements of a domain, where each bit of
+/// the hash is the exclusive or of a pseudo-random subset of the bits of
+/// the element. Different hash values imply different elements, so hash
+/// inequality can be used as a cheap sufficient condition of distinctness
+/// inside the solver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint<DOM>
+where
+    DOM: Domain,
+{
+    domain: DOM,
+    masks: Vec<BitVec>,
+}
+
+impl<DOM> Fingerprint<DOM>
+where
+    DOM: Domain,
+{
+    /// Creates a new hash function with the given number of hash bits,
+    /// where the subsets are generated by an xorshift generator from the
+    /// given seed.
+    pub fn new(domain: DOM, width: usize, seed: u64) -> Self {
+        let mut rng = XorShift::new(seed);
+        let mut masks = Vec::with_capacity(width);
+        for _ in 0..width {
+            let mask: BitVec = (0..domain.num_bits()).map(|_| rng.next_bool()).collect();
+            masks.push(mask);
+        }
+        Self { domain, masks }
+    }
+
+    /// Returns the domain whose elements are hashed.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Returns the number of bits of the hash values.
+    pub fn width(&self) -> usize {
+        self.masks.len()
+    }
+
+    /// Returns the hash value of the given element.
+    pub fn hash<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.domain.num_bits());
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.width());
+        for mask in self.masks.iter() {
+            let bits = elem
+                .copy_iter()
+                .zip(mask.copy_iter())
+                .filter(|(_, b)| *b)
+                .map(|(a, _)| a);
+            result.push(logic.bool_fold_sum(bits));
+        }
+        result
+    }
+
+    /// Returns true if the hash values of the two elements are different,
+    /// which implies that the elements are different.
+    pub fn is_different<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let hash0 = self.hash(logic, elem0);
+        let hash1 = self.hash(logic, elem1);
+        logic.bool_cmp_neq(hash0.copy_iter().zip(hash1.copy_iter()))
+    }
+
+    /// Returns true if two of the given concrete elements have the same
+    /// hash value.
+    pub fn has_collision(&self, elems: &[BitSlice<'_>]) -> bool {
+        let mut logic = Logic();
+        let mut hashes: Vec<BitVec> = elems.iter().map(|e| self.hash(&mut logic, *e)).collect();
+        hashes.sort_by_key(|h| h.copy_iter().collect::<Vec<bool>>());
+        hashes.windows(2).any(|w| w[0] == w[1])
+    }
+
+    /// Adds constraints that force the given elements to have pairwise
+    /// different hash values. If the fallback is enabled, then colliding
+    /// hash values are allowed for different elements, so no solutions are
+    /// lost, and the hash still helps the solver to propagate distinctness.
+    pub fn add_distinct<LOGIC>(&self, logic: &mut LOGIC, elems: &[LOGIC::Vector], fallback: bool)
+    where
+        LOGIC: BooleanSolver,
+    {
+        let hashes: Vec<LOGIC::Vector> =
+            elems.iter().map(|e| self.hash(logic, e.slice())).collect();
+        for i in 0..elems.len() {
+            for j in (i + 1)..elems.len() {
+                let mut clause = Vec::with_capacity(2);
+                let pairs = hashes[i].copy_iter().zip(hashes[j].copy_iter());
+                clause.push(logic.bool_cmp_neq(pairs));
+                if fallback {
+                    let test = self
+                        .domain
+                        .equals(logic, elems[i].slice(), elems[j].slice());
+                    clause.push(logic.bool_not(test));
+                }
+                logic.bool_add_clause(&clause);
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BitSlice, BitVec, BooleanLogic, Domain, Indexable, Slice, SmallSet};
+
+/// A fixed finite set of named elements encoded as a one-hot vector of
+/// booleans, which is convenient for hand-defined algebras. It is a small
+/// set whose elements are displayed by their names, but unlike small sets
+/// the elements are not ordered.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FixedSet {
+    set: SmallSet,
+    names: Vec<String>,
+}
+
+impl FixedSet {
+    /// Creates a new fixed set with the given distinct element names.
+    pub fn new<ITER, NAME>(names: ITER) -> Self
+    where
+        ITER: IntoIterator<Item = NAME>,
+        NAME: Into<String>,
+    {
+        let names: Vec<String> = names.into_iter().map(|n| n.into()).collect();
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[..i].contains(name), "duplicate name {}", name);
+        }
+        Self {
+            set: SmallSet::new(names.len()),
+            names,
+        }
+    }
+
+    /// Creates a new fixed set of the given size whose elements are named
+    /// by their indices.
+    pub fn with_size(size: usize) -> Self {
+        Self::new((0..size).map(|i| i.to_string()))
+    }
+
+    /// Returns the name of the element with the given index.
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    /// Returns the index of the element with the given name, if it exists.
+    pub fn find_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Returns the subset of elements whose index satisfies the given
+    /// predicate, as an element of the unary relations of this set.
+    pub fn get_subset<PRED>(&self, pred: PRED) -> BitVec
+    where
+        PRED: FnMut(usize) -> bool,
+    {
+        (0..self.names.len()).map(pred).collect()
+    }
+
+    /// Returns the subset of elements whose bit is set in the given mask,
+    /// where the least significant bit corresponds to the first element.
+    pub fn get_subset_from_mask(&self, mask: u64) -> BitVec {
+        assert!(self.names.len() <= 64);
+        self.get_subset(|i| (mask >> i) & 1 != 0)
+    }
+
+    /// Returns the names of the elements of the given subset.
+    pub fn subset_names(&self, subset: BitSlice<'_>) -> Vec<&str> {
+        assert_eq!(subset.len(), self.names.len());
+        subset
+            .copy_iter()
+            .zip(self.names.iter())
+            .filter(|(v, _)| *v)
+            .map(|(_, n)| n.as_str())
+            .collect()
+    }
+}
+
+impl Domain for FixedSet {
+    fn num_bits(&self) -> usize {
+        self.set.num_bits()
+    }
+
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.names[self.get_index(elem)])
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.set.contains(logic, elem)
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.set.equals(logic, elem0, elem1)
+    }
+}
+
+impl Indexable for FixedSet {
+    fn size(&self) -> usize {
+        self.set.size()
+    }
+
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.set.get_elem(logic, index)
+    }
+
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        self.set.get_index(elem)
+    }
+
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.set.onehot(logic, elem)
+    }
+}
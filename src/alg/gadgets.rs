@@ -0,0 +1,384 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Reduction gadgets between constraint satisfaction problems. Instances
+//! and templates are relational structures given by their stabilizers
+//! over small sets, and a solution of an instance is a homomorphism into
+//! the template.
+
+use super::{
+    BitSlice, BitVec, BooleanSolver, Domain, Homomorphisms, Indexable, Power, Slice, SmallSet,
+    Solver, Stabilizer, Vector,
+};
+
+/// Returns the tuples of the relation, where the first coordinate is the
+/// least significant one.
+fn relation_tuples(size: usize, arity: usize, relation: BitSlice<'_>) -> Vec<Vec<usize>> {
+    (0..relation.len())
+        .filter(|&index| relation.get(index))
+        .map(|mut index| {
+            let mut tuple = Vec::with_capacity(arity);
+            for _ in 0..arity {
+                tuple.push(index % size);
+                index /= size;
+            }
+            tuple
+        })
+        .collect()
+}
+
+/// Returns the relation containing the given tuples.
+fn relation_from_tuples<'a, ITER>(size: usize, arity: usize, tuples: ITER) -> BitVec
+where
+    ITER: IntoIterator<Item = &'a Vec<usize>>,
+{
+    let mut relation: Vec<bool> = vec![false; size.pow(arity as u32)];
+    for tuple in tuples {
+        assert_eq!(tuple.len(), arity);
+        let index = tuple.iter().rev().fold(0, |index, &a| index * size + a);
+        relation[index] = true;
+    }
+    relation.into_iter().collect()
+}
+
+/// Returns the arities of the relations of the structure.
+fn arities(structure: &Stabilizer<SmallSet>) -> Vec<usize> {
+    structure.relations().map(|(arity, _)| arity).collect()
+}
+
+/// A gadget is a small instance of the target template whose first few
+/// elements are the ports. Replacing a constraint by a copy of the gadget,
+/// with the ports identified with the constrained variables, simulates the
+/// relation defined by the gadget, which consists of the restrictions of
+/// the solutions of the gadget to its ports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gadget {
+    ports: usize,
+    size: usize,
+    tuples: Vec<(usize, Vec<usize>)>,
+}
+
+impl Gadget {
+    /// Creates a gadget with the given number of ports and elements and
+    /// without any constraints.
+    pub fn new(ports: usize, size: usize) -> Self {
+        assert!(ports <= size);
+        Self {
+            ports,
+            size,
+            tuples: Vec::new(),
+        }
+    }
+
+    /// Creates the gadget consisting of a single constraint of the given
+    /// relation of the target template applied to the ports.
+    pub fn identity(arity: usize, relation: usize) -> Self {
+        let mut gadget = Self::new(arity, arity);
+        gadget.add_tuple(relation, (0..arity).collect());
+        gadget
+    }
+
+    /// Returns the number of ports.
+    pub fn ports(&self) -> usize {
+        self.ports
+    }
+
+    /// Returns the number of elements including the ports.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adds the constraint that the given tuple of elements belongs to the
+    /// relation of the target template with the given index.
+    pub fn add_tuple(&mut self, relation: usize, tuple: Vec<usize>) {
+        assert!(tuple.iter().all(|&a| a < self.size));
+        self.tuples.push((relation, tuple));
+    }
+
+    /// Adds a copy of the other gadget whose ports are identified with the
+    /// given elements of this gadget, and whose other elements are new.
+    pub fn add_gadget(&mut self, other: &Gadget, ports: &[usize]) {
+        assert_eq!(ports.len(), other.ports);
+        let offset = self.size - other.ports;
+        self.size += other.size - other.ports;
+        for (relation, tuple) in other.tuples.iter() {
+            let tuple = tuple
+                .iter()
+                .map(|&a| {
+                    if a < other.ports {
+                        ports[a]
+                    } else {
+                        a + offset
+                    }
+                })
+                .collect();
+            self.add_tuple(*relation, tuple);
+        }
+    }
+
+    /// Returns the gadget as an instance of the given template.
+    fn instance(&self, arities: &[usize]) -> Stabilizer<SmallSet> {
+        let mut instance = Stabilizer::new(SmallSet::new(self.size));
+        for (index, &arity) in arities.iter().enumerate() {
+            let tuples = self
+                .tuples
+                .iter()
+                .filter(|(relation, _)| *relation == index)
+                .map(|(_, tuple)| tuple);
+            instance.add_relation(arity, relation_from_tuples(self.size, arity, tuples));
+        }
+        instance
+    }
+
+    /// Returns the relation defined by the gadget in the given template,
+    /// which is the set of restrictions of the solutions to the ports. The
+    /// solutions are enumerated with blocking clauses on the ports only.
+    pub fn defined_relation(&self, template: &Stabilizer<SmallSet>) -> BitVec {
+        let instance = self.instance(&arities(template));
+        let homs = Homomorphisms::from_structures(&instance, template);
+        let mut logic = Solver::new("");
+        let map = homs.maps().add_variable(&mut logic);
+        let test = homs.is_homomorphism(&mut logic, map.slice());
+        logic.bool_add_clause1(test);
+
+        let ports = Power::new(template.domain().clone(), self.ports);
+        let tuples: Vec<Vec<usize>> = logic
+            .bool_all_models(map.head(ports.num_bits()).copy_iter())
+            .map(|model| {
+                ports
+                    .part_iter(model.slice())
+                    .map(|part| ports.base().get_index(part))
+                    .collect()
+            })
+            .collect();
+        relation_from_tuples(template.domain().size(), self.ports, tuples.iter())
+    }
+
+    /// Returns true if the gadget defines the given relation in the
+    /// template.
+    pub fn defines(&self, template: &Stabilizer<SmallSet>, relation: BitSlice<'_>) -> bool {
+        let defined = self.defined_relation(template);
+        defined.len() == relation.len() && defined.copy_iter().eq(relation.copy_iter())
+    }
+
+    /// Returns true if the gadget is an equality gadget in the template,
+    /// that is, it has two ports which must have the same value.
+    pub fn is_equality(&self, template: &Stabilizer<SmallSet>) -> bool {
+        let size = template.domain().size();
+        let diagonal: Vec<Vec<usize>> = (0..size).map(|a| vec![a, a]).collect();
+        self.ports == 2 && self.defines(template, relation_from_tuples(size, 2, &diagonal).slice())
+    }
+
+    /// Returns true if the gadget is a crossover gadget in the template,
+    /// that is, it has four ports where the first two values are copied to
+    /// the last two independently of each other.
+    pub fn is_crossover(&self, template: &Stabilizer<SmallSet>) -> bool {
+        let size = template.domain().size();
+        let mut tuples = Vec::new();
+        for a in 0..size {
+            for b in 0..size {
+                tuples.push(vec![a, b, a, b]);
+            }
+        }
+        self.ports == 4 && self.defines(template, relation_from_tuples(size, 4, &tuples).slice())
+    }
+}
+
+/// A gadget reduction from the constraint satisfaction problem of a source
+/// template to that of a target template, given by one gadget for each
+/// relation of the source template, and an injective encoding of the source
+/// elements as target elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reduction {
+    gadgets: Vec<Gadget>,
+    encoding: Option<Vec<usize>>,
+}
+
+impl Reduction {
+    /// Creates the reduction with the given gadgets, which encodes every
+    /// source element by the same target element.
+    pub fn new(gadgets: Vec<Gadget>) -> Self {
+        Self {
+            gadgets,
+            encoding: None,
+        }
+    }
+
+    /// Sets the encoding of the reduction, which lists the distinct target
+    /// elements that encode the source elements.
+    pub fn with_encoding(mut self, encoding: Vec<usize>) -> Self {
+        for (i, a) in encoding.iter().enumerate() {
+            assert!(!encoding[..i].contains(a));
+        }
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Returns the gadgets of the reduction.
+    pub fn gadgets(&self) -> &[Gadget] {
+        &self.gadgets
+    }
+
+    /// Returns the target element encoding the given source element.
+    fn encode(&self, elem: usize) -> usize {
+        self.encoding
+            .as_ref()
+            .map_or(elem, |encoding| encoding[elem])
+    }
+
+    /// Returns true if the encoding is defined on the source domain and each
+    /// gadget defines the image of the corresponding relation of the source
+    /// template under the encoding in the target template. In this case
+    /// applying the reduction preserves the solvability of instances.
+    pub fn verify(&self, source: &Stabilizer<SmallSet>, target: &Stabilizer<SmallSet>) -> bool {
+        let size0 = source.domain().size();
+        let size1 = target.domain().size();
+        let encoded = match self.encoding.as_ref() {
+            Some(encoding) => encoding.len() == size0 && encoding.iter().all(|&a| a < size1),
+            None => size0 == size1,
+        };
+        encoded
+            && source.relations().count() == self.gadgets.len()
+            && source
+                .relations()
+                .zip(self.gadgets.iter())
+                .all(|((arity, relation), gadget)| {
+                    let image: Vec<Vec<usize>> = relation_tuples(size0, arity, relation)
+                        .into_iter()
+                        .map(|tuple| tuple.into_iter().map(|a| self.encode(a)).collect())
+                        .collect();
+                    let image = relation_from_tuples(size1, arity, &image);
+                    gadget.ports() == arity && gadget.defines(target, image.slice())
+                })
+    }
+
+    /// Replaces each constraint of the instance of the source template by a
+    /// copy of the corresponding gadget, and returns the resulting instance
+    /// of the target template, whose relations have the given arities. The
+    /// variables of the original instance come first.
+    pub fn apply(
+        &self,
+        instance: &Stabilizer<SmallSet>,
+        arities: &[usize],
+    ) -> Stabilizer<SmallSet> {
+        assert_eq!(instance.relations().count(), self.gadgets.len());
+        let size = instance.domain().size();
+        let mut result = Gadget::new(size, size);
+        for ((arity, relation), gadget) in instance.relations().zip(self.gadgets.iter()) {
+            for tuple in relation_tuples(size, arity, relation) {
+                result.add_gadget(gadget, &tuple);
+            }
+        }
+        result.instance(arities)
+    }
+}
+
+/// Returns the template extended with the equality relation as its last
+/// relation.
+pub fn add_equality(template: &Stabilizer<SmallSet>) -> Stabilizer<SmallSet> {
+    let size = template.domain().size();
+    let mut result = template.clone();
+    let diagonal: Vec<Vec<usize>> = (0..size).map(|a| vec![a, a]).collect();
+    result.add_relation(2, relation_from_tuples(size, 2, &diagonal));
+    result
+}
+
+/// Duplicates the variables of the instance so that every variable occurs
+/// in at most one constraint. Each occurrence gets a fresh copy of its
+/// variable, which is linked to the original variable by a constraint of
+/// the equality relation added as the last relation, so the result is an
+/// instance of the template extended by `add_equality`. The original
+/// variables come first.
+pub fn split_occurrences(instance: &Stabilizer<SmallSet>) -> Stabilizer<SmallSet> {
+    let size = instance.domain().size();
+    let mut constraints = Vec::new();
+    let mut links = Vec::new();
+    let mut next = size;
+    for (index, (arity, relation)) in instance.relations().enumerate() {
+        for tuple in relation_tuples(size, arity, relation) {
+            let copies: Vec<usize> = (next..next + arity).collect();
+            for (&a, &b) in tuple.iter().zip(copies.iter()) {
+                links.push(vec![a, b]);
+            }
+            next += arity;
+            constraints.push((index, copies));
+        }
+    }
+
+    let mut result = Stabilizer::new(SmallSet::new(next));
+    for (index, (arity, _)) in instance.relations().enumerate() {
+        let tuples = constraints
+            .iter()
+            .filter(|(i, _)| *i == index)
+            .map(|(_, tuple)| tuple);
+        result.add_relation(arity, relation_from_tuples(next, arity, tuples));
+    }
+    result.add_relation(2, relation_from_tuples(next, 2, &links));
+    result
+}
+
+/// Removes the binary relation with the given index from the instance,
+/// whose constraints are equalities, by identifying the variables that are
+/// connected by them. Returns the contracted instance together with the
+/// map from the old variables to the new ones.
+pub fn contract_equalities(
+    instance: &Stabilizer<SmallSet>,
+    relation: usize,
+) -> (Stabilizer<SmallSet>, Vec<usize>) {
+    let size = instance.domain().size();
+    let mut parent: Vec<usize> = (0..size).collect();
+    fn find(parent: &mut [usize], a: usize) -> usize {
+        let mut root = a;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[a] = root;
+        root
+    }
+
+    let (arity, equalities) = instance.relations().nth(relation).unwrap();
+    assert_eq!(arity, 2);
+    for tuple in relation_tuples(size, 2, equalities) {
+        let a = find(&mut parent, tuple[0]);
+        let b = find(&mut parent, tuple[1]);
+        parent[a.max(b)] = a.min(b);
+    }
+
+    let mut map = vec![0; size];
+    let mut count = 0;
+    for a in 0..size {
+        let root = find(&mut parent, a);
+        map[a] = if root == a {
+            count += 1;
+            count - 1
+        } else {
+            map[root]
+        };
+    }
+
+    let mut result = Stabilizer::new(SmallSet::new(count));
+    for (index, (arity, rel)) in instance.relations().enumerate() {
+        if index != relation {
+            let tuples: Vec<Vec<usize>> = relation_tuples(size, arity, rel)
+                .into_iter()
+                .map(|tuple| tuple.into_iter().map(|a| map[a]).collect())
+                .collect();
+            result.add_relation(arity, relation_from_tuples(count, arity, &tuples));
+        }
+    }
+    (result, map)
+}
@@ -0,0 +1,480 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BooleanLogic, Domain, Group, Indexable, Monoid, PermutationGroup, Power, Product2,
+    Semigroup, Slice, Vector,
+};
+
+/// The direct product of two permutation groups acting on the disjoint
+/// union of the two sets, where the points of the first group come first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectProduct<DOM0, DOM1>(Product2<DOM0, DOM1>);
+
+impl<DOM0, DOM1> DirectProduct<DOM0, DOM1>
+where
+    DOM0: Domain,
+    DOM1: Domain,
+{
+    /// Creates the direct product of two groups.
+    pub fn new(dom0: DOM0, dom1: DOM1) -> Self {
+        Self(Product2::new(dom0, dom1))
+    }
+
+    /// Returns the first factor of the product.
+    pub fn dom0(&self) -> &DOM0 {
+        self.0.dom0()
+    }
+
+    /// Returns the second factor of the product.
+    pub fn dom1(&self) -> &DOM1 {
+        self.0.dom1()
+    }
+}
+
+impl<DOM0, DOM1> Domain for DirectProduct<DOM0, DOM1>
+where
+    DOM0: Domain,
+    DOM1: Domain,
+{
+    #[inline]
+    fn num_bits(&self) -> usize {
+        self.0.num_bits()
+    }
+
+    #[inline]
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        self.0.display_elem(f, elem)
+    }
+
+    #[inline]
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.contains(logic, elem)
+    }
+
+    #[inline]
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.equals(logic, elem0, elem1)
+    }
+}
+
+impl<DOM0, DOM1> Indexable for DirectProduct<DOM0, DOM1>
+where
+    DOM0: Indexable,
+    DOM1: Indexable,
+{
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    #[inline]
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.get_elem(logic, index)
+    }
+
+    #[inline]
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        self.0.get_index(elem)
+    }
+
+    #[inline]
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.onehot(logic, elem)
+    }
+}
+
+impl<DOM0, DOM1> Semigroup for DirectProduct<DOM0, DOM1>
+where
+    DOM0: Semigroup,
+    DOM1: Semigroup,
+{
+    #[inline]
+    fn product<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        Semigroup::product(&self.0, logic, elem0, elem1)
+    }
+}
+
+impl<DOM0, DOM1> Monoid for DirectProduct<DOM0, DOM1>
+where
+    DOM0: Monoid,
+    DOM1: Monoid,
+{
+    #[inline]
+    fn get_identity<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.get_identity(logic)
+    }
+
+    #[inline]
+    fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_identity(logic, elem)
+    }
+}
+
+impl<DOM0, DOM1> Group for DirectProduct<DOM0, DOM1>
+where
+    DOM0: Group,
+    DOM1: Group,
+{
+    #[inline]
+    fn inverse<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.inverse(logic, elem)
+    }
+}
+
+impl<DOM0, DOM1> PermutationGroup for DirectProduct<DOM0, DOM1>
+where
+    DOM0: PermutationGroup,
+    DOM1: PermutationGroup,
+{
+    fn degree(&self) -> usize {
+        self.dom0().degree() + self.dom1().degree()
+    }
+
+    fn as_permutation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let perm0 = self.dom0().as_permutation(logic, self.0.part0(elem));
+        let perm1 = self.dom1().as_permutation(logic, self.0.part1(elem));
+
+        let degree0 = self.dom0().degree();
+        let degree1 = self.dom1().degree();
+        let degree = degree0 + degree1;
+
+        let mut result: LOGIC::Vector = Vector::with_values(degree * degree, logic.bool_zero());
+        for i in 0..degree0 {
+            for j in 0..degree0 {
+                result.set(j + i * degree, perm0.get(j + i * degree0));
+            }
+        }
+        for i in 0..degree1 {
+            for j in 0..degree1 {
+                let pos = (degree0 + j) + (degree0 + i) * degree;
+                result.set(pos, perm1.get(j + i * degree1));
+            }
+        }
+        result
+    }
+}
+
+/// The wreath product of a base group with a permutation group, where the
+/// top group permutes the copies of the base group. The elements are pairs
+/// of a tuple of base group elements and a top group element, and the group
+/// is acting on the product of the two sets, where a point `(x, c)` is
+/// mapped to `(f_c(x), h(c))` by the element `(f, h)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WreathProduct<BASE, TOP>(Product2<Power<BASE>, TOP>);
+
+impl<BASE, TOP> WreathProduct<BASE, TOP>
+where
+    BASE: Domain,
+    TOP: PermutationGroup,
+{
+    /// Creates the wreath product of the given groups.
+    pub fn new(base: BASE, top: TOP) -> Self {
+        let power = Power::new(base, top.degree());
+        Self(Product2::new(power, top))
+    }
+
+    /// Returns the base group of the wreath product.
+    pub fn base(&self) -> &BASE {
+        self.0.dom0().base()
+    }
+
+    /// Returns the top group of the wreath product.
+    pub fn top(&self) -> &TOP {
+        self.0.dom1()
+    }
+
+    /// Returns the tuple of base group elements of an element.
+    pub fn part0<'a, ELEM>(&self, elem: ELEM) -> ELEM
+    where
+        ELEM: Slice<'a>,
+    {
+        self.0.part0(elem)
+    }
+
+    /// Returns the top group element of an element.
+    pub fn part1<'a, ELEM>(&self, elem: ELEM) -> ELEM
+    where
+        ELEM: Slice<'a>,
+    {
+        self.0.part1(elem)
+    }
+
+    /// Permutes the parts of a tuple of base group elements by the given
+    /// top element. The part at position `c` of the result is the part of
+    /// the tuple at position `h(c)`, or at `h^-1(c)` if inverse is set.
+    fn permute_parts<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        top: LOGIC::Slice<'_>,
+        parts: LOGIC::Slice<'_>,
+        inverse: bool,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let perm = self.top().as_permutation(logic, top);
+        let degree = self.top().degree();
+        let bits = self.base().num_bits();
+        debug_assert_eq!(parts.len(), degree * bits);
+
+        let mut result: LOGIC::Vector = Vector::with_capacity(parts.len());
+        for c in 0..degree {
+            for b in 0..bits {
+                let mut value = logic.bool_zero();
+                for j in 0..degree {
+                    let test = if inverse {
+                        perm.get(c + j * degree)
+                    } else {
+                        perm.get(j + c * degree)
+                    };
+                    let test = logic.bool_and(test, parts.get(j * bits + b));
+                    value = logic.bool_or(value, test);
+                }
+                result.push(value);
+            }
+        }
+        result
+    }
+}
+
+impl<BASE, TOP> Domain for WreathProduct<BASE, TOP>
+where
+    BASE: Domain,
+    TOP: PermutationGroup,
+{
+    #[inline]
+    fn num_bits(&self) -> usize {
+        self.0.num_bits()
+    }
+
+    #[inline]
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        self.0.display_elem(f, elem)
+    }
+
+    #[inline]
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.contains(logic, elem)
+    }
+
+    #[inline]
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.equals(logic, elem0, elem1)
+    }
+}
+
+impl<BASE, TOP> Indexable for WreathProduct<BASE, TOP>
+where
+    BASE: Indexable,
+    TOP: PermutationGroup + Indexable,
+{
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    #[inline]
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.get_elem(logic, index)
+    }
+
+    #[inline]
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        self.0.get_index(elem)
+    }
+
+    #[inline]
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.onehot(logic, elem)
+    }
+}
+
+impl<BASE, TOP> Semigroup for WreathProduct<BASE, TOP>
+where
+    BASE: Semigroup,
+    TOP: PermutationGroup,
+{
+    fn product<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let power = self.0.dom0();
+        let parts0 = self.permute_parts(logic, self.part1(elem1), self.part0(elem0), false);
+        let parts1 = self.part0(elem1);
+
+        let base = power.make_elem(
+            power
+                .part_iter(parts0.slice())
+                .zip(power.part_iter(parts1))
+                .map(|(part0, part1)| Semigroup::product(self.base(), logic, part0, part1)),
+        );
+        let top = Semigroup::product(self.top(), logic, self.part1(elem0), self.part1(elem1));
+        self.0.make_elem(base, top)
+    }
+}
+
+impl<BASE, TOP> Monoid for WreathProduct<BASE, TOP>
+where
+    BASE: Monoid,
+    TOP: PermutationGroup,
+{
+    #[inline]
+    fn get_identity<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.get_identity(logic)
+    }
+
+    #[inline]
+    fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_identity(logic, elem)
+    }
+}
+
+impl<BASE, TOP> Group for WreathProduct<BASE, TOP>
+where
+    BASE: Group,
+    TOP: PermutationGroup,
+{
+    fn inverse<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let power = self.0.dom0();
+        let parts = self.permute_parts(logic, self.part1(elem), self.part0(elem), true);
+
+        let base = power.make_elem(
+            power
+                .part_iter(parts.slice())
+                .map(|part| self.base().inverse(logic, part)),
+        );
+        let top = self.top().inverse(logic, self.part1(elem));
+        self.0.make_elem(base, top)
+    }
+}
+
+impl<BASE, TOP> PermutationGroup for WreathProduct<BASE, TOP>
+where
+    BASE: PermutationGroup,
+    TOP: PermutationGroup,
+{
+    fn degree(&self) -> usize {
+        self.base().degree() * self.top().degree()
+    }
+
+    fn as_permutation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let power = self.0.dom0();
+        let top = self.top().as_permutation(logic, self.part1(elem));
+        let parts: Vec<LOGIC::Vector> = power
+            .part_iter(self.part0(elem))
+            .map(|part| self.base().as_permutation(logic, part))
+            .collect();
+
+        let degree0 = self.base().degree();
+        let degree1 = self.top().degree();
+        let degree = degree0 * degree1;
+
+        let mut result: LOGIC::Vector = Vector::with_capacity(degree * degree);
+        for (c0, part) in parts.iter().enumerate() {
+            for x in 0..degree0 {
+                for c1 in 0..degree1 {
+                    for y in 0..degree0 {
+                        let test0 = top.get(c1 + c0 * degree1);
+                        let test1 = part.get(y + x * degree0);
+                        result.push(logic.bool_and(test0, test1));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
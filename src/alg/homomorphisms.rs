@@ -0,0 +1,305 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Domain, Indexable, Power, Slice, Solver,
+    Stabilizer, Vector,
+};
+
+/// Returns the coordinates of the tuple with the given index, where the
+/// first coordinate is the least significant one.
+fn decode_tuple(mut index: usize, size: usize, arity: usize) -> Vec<usize> {
+    let mut tuple = Vec::with_capacity(arity);
+    for _ in 0..arity {
+        tuple.push(index % size);
+        index /= size;
+    }
+    tuple
+}
+
+/// Returns the permutation encoded by the given binary relation as the
+/// list of images.
+fn decode_permutation(elem: BitSlice<'_>, size: usize) -> Vec<usize> {
+    (0..size)
+        .map(|i| (0..size).find(|&j| elem.get(j + i * size)).unwrap())
+        .collect()
+}
+
+/// Returns the list of all homomorphisms between two relational structures
+/// of the same signature. The structures are given by their stabilizers,
+/// which hold the underlying domains and the lists of relations. Each
+/// homomorphism is returned as the list of images of the source elements.
+pub fn find_homomorphisms<DOM0, DOM1>(
+    source: &Stabilizer<DOM0>,
+    target: &Stabilizer<DOM1>,
+) -> Vec<Vec<usize>>
+where
+    DOM0: Indexable,
+    DOM1: Indexable,
+{
+    let homs = Homomorphisms::from_structures(source, target);
+    homs.find_all_homomorphisms()
+        .iter()
+        .map(|map| homs.get_images(map.slice()))
+        .collect()
+}
+
+/// Counts the homomorphisms between two relational structures of the same
+/// signature up to the automorphisms of the target structure, that is, it
+/// returns the number of orbits of the automorphism group acting on the
+/// set of homomorphisms by composition. The orbits are calculated using
+/// the generators of the automorphism group.
+pub fn count_homomorphisms_up_to_auto<DOM0, DOM1>(
+    source: &Stabilizer<DOM0>,
+    target: &Stabilizer<DOM1>,
+) -> usize
+where
+    DOM0: Indexable,
+    DOM1: Indexable,
+{
+    let size1 = target.domain().size();
+    let homs = find_homomorphisms(source, target);
+    let gens: Vec<Vec<usize>> = target
+        .generators()
+        .iter()
+        .map(|g| decode_permutation(g.slice(), size1))
+        .collect();
+
+    let indices: HashMap<&[usize], usize> = homs
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.as_slice(), i))
+        .collect();
+
+    let mut visited = vec![false; homs.len()];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    for start in 0..homs.len() {
+        if visited[start] {
+            continue;
+        }
+        count += 1;
+        visited[start] = true;
+        stack.push(start);
+        while let Some(index) = stack.pop() {
+            for g in gens.iter() {
+                let image: Vec<usize> = homs[index].iter().map(|&a| g[a]).collect();
+                let other = indices[image.as_slice()];
+                if !visited[other] {
+                    visited[other] = true;
+                    stack.push(other);
+                }
+            }
+        }
+    }
+    count
+}
+
+/// The maps between two relational structures of the same signature, where
+/// the maps are represented as elements of the power of the target domain
+/// listing the images of the source elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Homomorphisms<DOM0, DOM1>
+where
+    DOM0: Indexable,
+    DOM1: Indexable,
+{
+    source: DOM0,
+    maps: Power<DOM1>,
+    relations: Vec<(usize, BitVec, BitVec)>,
+}
+
+impl<DOM0, DOM1> Homomorphisms<DOM0, DOM1>
+where
+    DOM0: Indexable,
+    DOM1: Indexable,
+{
+    /// Creates the maps between the given domains without relations.
+    pub fn new(source: DOM0, target: DOM1) -> Self {
+        let maps = Power::new(target, source.size());
+        Self {
+            source,
+            maps,
+            relations: Vec::new(),
+        }
+    }
+
+    /// Creates the maps between the two structures given by their
+    /// stabilizers, which hold the domains and the lists of relations.
+    pub fn from_structures(source: &Stabilizer<DOM0>, target: &Stabilizer<DOM1>) -> Self {
+        let mut result = Self::new(source.domain().clone(), target.domain().clone());
+        assert_eq!(source.relations().count(), target.relations().count());
+        for ((arity0, rel0), (arity1, rel1)) in source.relations().zip(target.relations()) {
+            assert_eq!(arity0, arity1);
+            result.add_relation(
+                arity0,
+                rel0.copy_iter().collect(),
+                rel1.copy_iter().collect(),
+            );
+        }
+        result
+    }
+
+    /// Returns the domain of the source structure.
+    pub fn source(&self) -> &DOM0 {
+        &self.source
+    }
+
+    /// Returns the domain of the target structure.
+    pub fn target(&self) -> &DOM1 {
+        self.maps.base()
+    }
+
+    /// Returns the domain of all maps from the source to the target.
+    pub fn maps(&self) -> &Power<DOM1> {
+        &self.maps
+    }
+
+    /// Adds a pair of corresponding relations of the given arity, the first
+    /// on the source and the second on the target domain.
+    pub fn add_relation(&mut self, arity: usize, source_rel: BitVec, target_rel: BitVec) {
+        let mut len0 = 1;
+        let mut len1 = 1;
+        for _ in 0..arity {
+            len0 *= self.source.size();
+            len1 *= self.target().size();
+        }
+        assert_eq!(source_rel.len(), len0);
+        assert_eq!(target_rel.len(), len1);
+        self.relations.push((arity, source_rel, target_rel));
+    }
+
+    /// Returns the list of images of the source elements under the given
+    /// map.
+    pub fn get_images(&self, map: BitSlice<'_>) -> Vec<usize> {
+        self.maps
+            .part_iter(map)
+            .map(|part| self.target().get_index(part))
+            .collect()
+    }
+
+    /// Returns true if the given map sends every tuple of the source
+    /// relations into the target relations, and also every tuple outside
+    /// of the source relations outside of the target relations if `strong`
+    /// is set.
+    fn preserves<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        map: LOGIC::Slice<'_>,
+        strong: bool,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size0 = self.source.size();
+        let size1 = self.target().size();
+        let onehots: Vec<LOGIC::Vector> = self
+            .maps
+            .part_iter(map)
+            .map(|part| self.target().onehot(logic, part))
+            .collect();
+
+        let mut result = logic.bool_unit();
+        for (arity, rel0, rel1) in self.relations.iter() {
+            for index0 in 0..rel0.len() {
+                let value = rel0.get(index0);
+                if !value && !strong {
+                    continue;
+                }
+                let tuple0 = decode_tuple(index0, size0, *arity);
+                for index1 in (0..rel1.len()).filter(|&i| rel1.get(i) != value) {
+                    let tuple1 = decode_tuple(index1, size1, *arity);
+                    let mut test = logic.bool_unit();
+                    for (&a, &b) in tuple0.iter().zip(tuple1.iter()) {
+                        test = logic.bool_and(test, onehots[a].get(b));
+                    }
+                    let test = logic.bool_not(test);
+                    result = logic.bool_and(result, test);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns true if the given map is a homomorphism.
+    pub fn is_homomorphism<LOGIC>(&self, logic: &mut LOGIC, map: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let valid = self.maps.contains(logic, map);
+        let test = self.preserves(logic, map, false);
+        logic.bool_and(valid, test)
+    }
+
+    /// Returns true if the given map is an isomorphism, that is, a bijective
+    /// homomorphism whose inverse is also a homomorphism.
+    pub fn is_isomorphism<LOGIC>(&self, logic: &mut LOGIC, map: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        if self.source.size() != self.target().size() {
+            return logic.bool_zero();
+        }
+        let valid = self.maps.contains(logic, map);
+        let parts: Vec<LOGIC::Vector> = self.maps.split_elem(map);
+        let test = self.target().all_different(logic, &parts);
+        let valid = logic.bool_and(valid, test);
+        let test = self.preserves(logic, map, true);
+        logic.bool_and(valid, test)
+    }
+
+    /// Returns a homomorphism from the source to the target structure, or
+    /// `None` if there is none.
+    pub fn find_homomorphism(&self) -> Option<BitVec> {
+        let mut logic = Solver::new("");
+        let map = self.maps.add_variable(&mut logic);
+        let test = self.is_homomorphism(&mut logic, map.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_one_model(&[], map.copy_iter())
+    }
+
+    /// Returns an isomorphism between the source and target structures, or
+    /// `None` if they are not isomorphic.
+    pub fn find_isomorphism(&self) -> Option<BitVec> {
+        let mut logic = Solver::new("");
+        let map = self.maps.add_variable(&mut logic);
+        let test = self.is_isomorphism(&mut logic, map.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_one_model(&[], map.copy_iter())
+    }
+
+    /// Returns all homomorphisms from the source to the target structure.
+    pub fn find_all_homomorphisms(&self) -> Vec<BitVec> {
+        let mut logic = Solver::new("");
+        let map = self.maps.add_variable(&mut logic);
+        let test = self.is_homomorphism(&mut logic, map.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_all_models(map.copy_iter()).collect()
+    }
+
+    /// Returns the number of homomorphisms from the source to the target
+    /// structure.
+    pub fn count_homomorphisms(&self) -> usize {
+        let mut logic = Solver::new("");
+        let map = self.maps.add_variable(&mut logic);
+        let test = self.is_homomorphism(&mut logic, map.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_num_models_method1(map.copy_iter())
+    }
+}
@@ -0,0 +1,262 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashSet;
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Domain, Indexable, Logic, OnehotEncoding,
+    OperationTable, Operations, Solver, Vector,
+};
+
+/// One side of an identity for a single term symbol, which is either one
+/// of the variables or the term applied to a list of variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermSide {
+    Var(usize),
+    Term(Vec<usize>),
+}
+
+/// A finite algebra given by its basic operations on an indexable domain,
+/// that can decide whether it has a term operation satisfying a system of
+/// identities in a single term symbol. The identities only constrain the
+/// term at the argument tuples obtained by substituting elements into the
+/// variables, so the term operations restricted to these tuples are
+/// generated from the restricted projections. The term is then an operation
+/// variable of a SAT solver that is selected from the generated term
+/// operations, and the identities are added as equalities of its polymers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaltsevConditions<DOM>
+where
+    DOM: Indexable,
+{
+    domain: DOM,
+    operations: Vec<OperationTable>,
+}
+
+impl<DOM> MaltsevConditions<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the algebra without basic operations on the given domain.
+    pub fn new(domain: DOM) -> Self {
+        Self {
+            domain,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying domain of the algebra.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Adds a new basic operation of the given arity.
+    pub fn add_operation(&mut self, arity: usize, operation: BitSlice<'_>) {
+        let ops = Operations::new(self.domain.clone(), arity);
+        self.operations.push(ops.get_table(operation));
+    }
+
+    /// Returns the tables of the basic operations.
+    pub fn operations(&self) -> &[OperationTable] {
+        &self.operations
+    }
+
+    /// Returns a term operation of the given arity that satisfies all of
+    /// the given identities, or `None` if there is none.
+    pub fn find_term(&self, arity: usize, identities: &[(TermSide, TermSide)]) -> Option<BitVec> {
+        let size = self.domain.size();
+        let mut len = 1;
+        for _ in 0..arity {
+            len *= size;
+        }
+
+        let mut num_vars = 0;
+        for (lhs, rhs) in identities {
+            for side in [lhs, rhs] {
+                match side {
+                    TermSide::Var(v) => num_vars = num_vars.max(v + 1),
+                    TermSide::Term(args) => {
+                        assert_eq!(args.len(), arity);
+                        num_vars = num_vars.max(args.iter().map(|v| v + 1).max().unwrap_or(0));
+                    }
+                }
+            }
+        }
+
+        // every instance is a pair of sides, where a side is the index of
+        // an argument tuple or a constant value encoded as len + value
+        let mut instances: Vec<(usize, usize)> = Vec::new();
+        let mut values = vec![0; num_vars];
+        'outer: loop {
+            let side = |side: &TermSide| match side {
+                TermSide::Var(v) => len + values[*v],
+                TermSide::Term(args) => args.iter().rev().fold(0, |i, &v| i * size + values[v]),
+            };
+            for (lhs, rhs) in identities {
+                instances.push((side(lhs), side(rhs)));
+            }
+            for v in values.iter_mut() {
+                *v += 1;
+                if *v < size {
+                    continue 'outer;
+                }
+                *v = 0;
+            }
+            break;
+        }
+        let mut coords: Vec<usize> = instances
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .filter(|&i| i < len)
+            .collect();
+        coords.sort_unstable();
+        coords.dedup();
+
+        // generate the restricted term operations with their full tables
+        let mut tables: Vec<Vec<usize>> = Vec::new();
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut stride = 1;
+        for _ in 0..arity {
+            let table: Vec<usize> = (0..len).map(|i| (i / stride) % size).collect();
+            if seen.insert(coords.iter().map(|&i| table[i]).collect()) {
+                tables.push(table);
+            }
+            stride *= size;
+        }
+        let mut next = 0;
+        while next < tables.len() {
+            for op in self.operations.iter() {
+                let mut args = vec![0; op.arity()];
+                'tuples: loop {
+                    if args.contains(&next) {
+                        let table: Vec<usize> = (0..len)
+                            .map(|i| {
+                                let values: Vec<usize> =
+                                    args.iter().map(|&a| tables[a][i]).collect();
+                                op.apply(&values)
+                            })
+                            .collect();
+                        if seen.insert(coords.iter().map(|&i| table[i]).collect()) {
+                            tables.push(table);
+                        }
+                    }
+                    for a in args.iter_mut() {
+                        *a += 1;
+                        if *a <= next {
+                            continue 'tuples;
+                        }
+                        *a = 0;
+                    }
+                    break;
+                }
+            }
+            next += 1;
+        }
+
+        // the term is one of the generated operations, chosen by selectors
+        let ops = Operations::new(self.domain.clone(), arity);
+        let mut logic = Solver::new("");
+        let selectors: Vec<_> = tables.iter().map(|_| logic.bool_add_variable()).collect();
+        logic.bool_add_exactly_one(&selectors, OnehotEncoding::Pairwise);
+        let tables: Vec<BitVec> = tables
+            .into_iter()
+            .map(|table| self.to_elem(arity, table))
+            .collect();
+        let elem: <Solver as BooleanLogic>::Vector = (0..ops.num_bits())
+            .map(|k| {
+                let lits = tables
+                    .iter()
+                    .zip(selectors.iter())
+                    .filter(|(table, _)| table.get(k))
+                    .map(|(_, &sel)| sel);
+                logic.bool_fold_any(lits)
+            })
+            .collect();
+
+        let vars = Operations::new(self.domain.clone(), num_vars);
+        for (lhs, rhs) in identities {
+            let mut side = |side: &TermSide| match side {
+                TermSide::Var(v) => vars.get_projection(&mut logic, *v),
+                TermSide::Term(args) => ops.polymer(elem.slice(), num_vars, args),
+            };
+            let lhs = side(lhs);
+            let rhs = side(rhs);
+            let test = vars.equals(&mut logic, lhs.slice(), rhs.slice());
+            logic.bool_add_clause1(test);
+        }
+        logic.bool_find_one_model(&[], elem.copy_iter())
+    }
+
+    /// Converts a table of values to an element of the operations domain.
+    fn to_elem(&self, arity: usize, values: Vec<usize>) -> BitVec {
+        let size = self.domain.size();
+        let ops = Operations::new(self.domain.clone(), arity);
+        ops.from_table(&Logic(), &OperationTable::new(size, arity, values))
+    }
+
+    /// Returns a Maltsev term, which satisfies `m(x,y,y) = x = m(y,y,x)`.
+    pub fn find_maltsev_term(&self) -> Option<BitVec> {
+        let identities = [
+            (TermSide::Term(vec![0, 1, 1]), TermSide::Var(0)),
+            (TermSide::Term(vec![1, 1, 0]), TermSide::Var(0)),
+        ];
+        self.find_term(3, &identities)
+    }
+
+    /// Returns a majority term, which is a near-unanimity term of arity 3.
+    pub fn find_majority_term(&self) -> Option<BitVec> {
+        self.find_near_unanimity_term(3)
+    }
+
+    /// Returns a near-unanimity term of the given arity, which returns the
+    /// value of the majority if all but one of its arguments are equal.
+    pub fn find_near_unanimity_term(&self, arity: usize) -> Option<BitVec> {
+        assert!(arity >= 2);
+        let identities: Vec<(TermSide, TermSide)> = (0..arity)
+            .map(|i| {
+                let args = (0..arity).map(|j| if i == j { 1 } else { 0 }).collect();
+                (TermSide::Term(args), TermSide::Var(0))
+            })
+            .collect();
+        self.find_term(arity, &identities)
+    }
+
+    /// Returns an idempotent weak near-unanimity term of the given arity,
+    /// whose value does not depend on the position of the single different
+    /// argument.
+    pub fn find_weak_near_unanimity_term(&self, arity: usize) -> Option<BitVec> {
+        assert!(arity >= 2);
+        let side =
+            |i: usize| TermSide::Term((0..arity).map(|j| if i == j { 1 } else { 0 }).collect());
+        let mut identities = vec![(TermSide::Term(vec![0; arity]), TermSide::Var(0))];
+        for i in 1..arity {
+            identities.push((side(0), side(i)));
+        }
+        self.find_term(arity, &identities)
+    }
+
+    /// Returns a Siggers term, which satisfies `s(r,a,r,e) = s(a,r,e,a)`.
+    /// A finite algebra has such a term if and only if it is a Taylor
+    /// algebra.
+    pub fn find_taylor_term(&self) -> Option<BitVec> {
+        let identities = [(
+            TermSide::Term(vec![0, 1, 0, 2]),
+            TermSide::Term(vec![1, 0, 2, 1]),
+        )];
+        self.find_term(4, &identities)
+    }
+}
@@ -0,0 +1,127 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BooleanLogic, BooleanSolver, Slice};
+
+/// The symmetries of a boolean matrix model, where the rows within each row
+/// block and the columns within each column block can be permuted
+/// independently of each other. The entry in row `i` and column `j` is
+/// stored at index `j + i * cols`, as in binary relations. Requiring the
+/// rows and columns of each block to be lexicographically ordered keeps at
+/// least one matrix from each orbit, and is much cheaper than full
+/// lex-leader constraints. Note, that this is not sound for binary
+/// relations on a single set, where rows and columns are permuted together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixSymmetry {
+    rows: usize,
+    cols: usize,
+    row_blocks: Vec<Vec<usize>>,
+    col_blocks: Vec<Vec<usize>>,
+}
+
+impl MatrixSymmetry {
+    /// Creates the trivial symmetry of matrices of the given dimensions.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            row_blocks: Vec::new(),
+            col_blocks: Vec::new(),
+        }
+    }
+
+    /// Returns the number of rows of the matrices.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of the matrices.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Adds a block of interchangeable rows, which must be disjoint from
+    /// the other row blocks.
+    pub fn add_row_block(&mut self, block: Vec<usize>) {
+        assert!(block.iter().all(|&i| i < self.rows));
+        assert!(self
+            .row_blocks
+            .iter()
+            .all(|b| b.iter().all(|i| !block.contains(i))));
+        self.row_blocks.push(block);
+    }
+
+    /// Adds a block of interchangeable columns, which must be disjoint from
+    /// the other column blocks.
+    pub fn add_col_block(&mut self, block: Vec<usize>) {
+        assert!(block.iter().all(|&j| j < self.cols));
+        assert!(self
+            .col_blocks
+            .iter()
+            .all(|b| b.iter().all(|j| !block.contains(j))));
+        self.col_blocks.push(block);
+    }
+
+    /// Returns true if the consecutive rows of each row block and the
+    /// consecutive columns of each column block are in weakly increasing
+    /// lexicographic order, where the last entries are the most significant.
+    pub fn is_lex_ordered<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.rows * self.cols);
+        let mut result = logic.bool_unit();
+        for test in self.lex_tests(logic, elem) {
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Adds the lexicographic ordering constraints to the solver.
+    pub fn add_lex_constraints<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>)
+    where
+        LOGIC: BooleanSolver,
+    {
+        assert_eq!(elem.len(), self.rows * self.cols);
+        for test in self.lex_tests(logic, elem) {
+            logic.bool_add_clause1(test);
+        }
+    }
+
+    fn lex_tests<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> Vec<LOGIC::Elem>
+    where
+        LOGIC: BooleanLogic,
+    {
+        let (rows, cols) = (self.rows, self.cols);
+        let mut tests = Vec::new();
+        for block in self.row_blocks.iter() {
+            for pair in block.windows(2) {
+                let row0 = (0..cols).map(|j| elem.get(j + pair[0] * cols));
+                let row1 = (0..cols).map(|j| elem.get(j + pair[1] * cols));
+                tests.push(logic.bool_cmp_leq(row0.zip(row1)));
+            }
+        }
+        for block in self.col_blocks.iter() {
+            for pair in block.windows(2) {
+                let col0 = (0..rows).map(|i| elem.get(pair[0] + i * cols));
+                let col1 = (0..rows).map(|i| elem.get(pair[1] + i * cols));
+                tests.push(logic.bool_cmp_leq(col0.zip(col1)));
+            }
+        }
+        tests
+    }
+}
@@ -18,8 +18,14 @@
 //! Module for working with abstract data types.
 
 #[allow(unused_imports)]
-use super::core::{BooleanLogic, BooleanSolver, Logic, Solver};
-use super::genvec::{BitSlice, BitVec, Slice, Vector};
+use super::core::{
+    check_num_models, BooleanLogic, BooleanSolver, CompiledPredicate, CountSequence, EncodingSizes,
+    Logic, OnehotEncoding, SoftConstraintSet, Solver, XorShift,
+};
+use super::genvec::{BitMatrix, BitSlice, BitVec, Slice, Vector};
+
+mod annealing;
+pub use annealing::*;
 
 mod binary_relations;
 pub use binary_relations::*;
@@ -27,12 +33,69 @@ pub use binary_relations::*;
 mod boolean;
 pub use boolean::*;
 
+mod census;
+pub use census::*;
+
+mod closures;
+pub use closures::*;
+
+mod combinatorics;
+pub use combinatorics::*;
+
+mod compatibility;
+pub use compatibility::*;
+
+mod endomaps;
+pub use endomaps::*;
+
+mod equitable;
+pub use equitable::*;
+
+mod fingerprint;
+pub use fingerprint::*;
+
+mod fixed_set;
+pub use fixed_set::*;
+
+mod gadgets;
+pub use gadgets::*;
+
+mod group_products;
+pub use group_products::*;
+
+mod homomorphisms;
+pub use homomorphisms::*;
+
+mod maltsev;
+pub use maltsev::*;
+
+mod matrix_symmetry;
+pub use matrix_symmetry::*;
+
+mod mono_unary;
+pub use mono_unary::*;
+
+mod one_hot;
+pub use one_hot::*;
+
 mod operations;
 pub use operations::*;
 
+mod orderly;
+pub use orderly::*;
+
+mod partial_operations;
+pub use partial_operations::*;
+
 mod permutations;
 pub use permutations::*;
 
+mod polymorphisms;
+pub use polymorphisms::*;
+
+mod posets;
+pub use posets::*;
+
 mod power;
 pub use power::*;
 
@@ -45,9 +108,18 @@ pub use relations::*;
 mod small_set;
 pub use small_set::*;
 
+mod sparse_relations;
+pub use sparse_relations::*;
+
+mod subuniverses;
+pub use subuniverses::*;
+
 mod traits;
 pub use traits::*;
 
+mod tuples;
+pub use tuples::*;
+
 mod unary_operations;
 pub use unary_operations::*;
 
@@ -61,11 +133,14 @@ pub use preservation::*;
 mod rel_clone;
 pub use rel_clone::*;
 
+mod stabilizer;
+pub use stabilizer::*;
+
 mod structure;
 pub use structure::*;
 
-#[cfg(test)]
 mod validate;
+pub use validate::{validate_suite, ValidationTier};
 
 pub fn test() {
     let mut logic = Logic();
@@ -0,0 +1,191 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashSet;
+
+use super::OperationTable;
+
+/// A concrete mono-unary algebra, which is a single unary operation on
+/// the set `{0, ..., n-1}` given by its list of values. Every connected
+/// component of such an algebra contains exactly one cycle, with rooted
+/// trees hanging off the cyclic elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoUnary {
+    values: Vec<usize>,
+}
+
+impl MonoUnary {
+    /// Creates the mono-unary algebra with the given operation values.
+    pub fn new(values: Vec<usize>) -> Self {
+        assert!(values.iter().all(|&v| v < values.len()));
+        Self { values }
+    }
+
+    /// Creates the mono-unary algebra of the given unary operation table.
+    pub fn from_table(table: &OperationTable) -> Self {
+        assert_eq!(table.arity(), 1);
+        Self::new(table.values().to_vec())
+    }
+
+    /// Returns the number of elements of the algebra.
+    pub fn size(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the image of the given element.
+    pub fn apply(&self, elem: usize) -> usize {
+        self.values[elem]
+    }
+
+    /// Returns the length of the pre-periodic tail and the length of the
+    /// cycle reached from the given element.
+    pub fn orbit_shape(&self, elem: usize) -> (usize, usize) {
+        let mut seen = vec![usize::MAX; self.size()];
+        let mut elem = elem;
+        let mut step = 0;
+        while seen[elem] == usize::MAX {
+            seen[elem] = step;
+            elem = self.values[elem];
+            step += 1;
+        }
+        (seen[elem], step - seen[elem])
+    }
+
+    /// Returns the tail lengths of all elements, which is zero exactly for
+    /// the cyclic elements.
+    pub fn tail_lengths(&self) -> Vec<usize> {
+        (0..self.size()).map(|i| self.orbit_shape(i).0).collect()
+    }
+
+    /// Returns the lengths of the cycles reached from all elements.
+    pub fn cycle_lengths(&self) -> Vec<usize> {
+        (0..self.size()).map(|i| self.orbit_shape(i).1).collect()
+    }
+
+    /// Returns true if the element lies on a cycle.
+    pub fn is_cyclic(&self, elem: usize) -> bool {
+        self.orbit_shape(elem).0 == 0
+    }
+
+    /// Returns the connected components of the algebra, each sorted and
+    /// listed in the order of their least elements.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        // the least element of the cycle reached from each element
+        let roots: Vec<usize> = (0..self.size())
+            .map(|i| {
+                let (tail, cycle) = self.orbit_shape(i);
+                let mut elem = i;
+                for _ in 0..tail {
+                    elem = self.values[elem];
+                }
+                let mut least = elem;
+                for _ in 0..cycle {
+                    elem = self.values[elem];
+                    least = least.min(elem);
+                }
+                least
+            })
+            .collect();
+
+        let mut result: Vec<Vec<usize>> = Vec::new();
+        let mut index = vec![usize::MAX; self.size()];
+        for (elem, &root) in roots.iter().enumerate() {
+            if index[root] == usize::MAX {
+                index[root] = result.len();
+                result.push(Vec::new());
+            }
+            result[index[root]].push(elem);
+        }
+        result
+    }
+
+    /// Returns the canonical code of the tree of non-cyclic elements
+    /// hanging off the given element, where children are the preimages.
+    fn tree_code(&self, elem: usize, cyclic: &[bool]) -> String {
+        let mut codes: Vec<String> = (0..self.size())
+            .filter(|&i| self.values[i] == elem && !cyclic[i])
+            .map(|i| self.tree_code(i, cyclic))
+            .collect();
+        codes.sort();
+        format!("({})", codes.concat())
+    }
+
+    /// Returns a canonical form of the algebra, which is the same string
+    /// for two algebras exactly when they are isomorphic. Each component
+    /// is encoded by the lexicographically least rotation of the codes of
+    /// the trees along its cycle.
+    pub fn canonical_form(&self) -> String {
+        let cyclic: Vec<bool> = (0..self.size()).map(|i| self.is_cyclic(i)).collect();
+        let mut codes: Vec<String> = self
+            .components()
+            .into_iter()
+            .map(|comp| {
+                let start = *comp.iter().find(|&&i| cyclic[i]).unwrap();
+                let mut cycle = vec![self.tree_code(start, &cyclic)];
+                let mut elem = self.values[start];
+                while elem != start {
+                    cycle.push(self.tree_code(elem, &cyclic));
+                    elem = self.values[elem];
+                }
+                let best = (0..cycle.len())
+                    .map(|i| [&cycle[i..], &cycle[..i]].concat())
+                    .min()
+                    .unwrap();
+                format!("[{}]", best.concat())
+            })
+            .collect();
+        codes.sort();
+        codes.concat()
+    }
+
+    /// Returns true if the two algebras are isomorphic. The sorted lists of
+    /// tail and cycle lengths are compared first, and the canonical forms
+    /// only when these invariants agree.
+    pub fn is_isomorphic(&self, other: &MonoUnary) -> bool {
+        if self.size() != other.size() {
+            return false;
+        }
+        let mut shapes0: Vec<(usize, usize)> =
+            (0..self.size()).map(|i| self.orbit_shape(i)).collect();
+        let mut shapes1: Vec<(usize, usize)> =
+            (0..other.size()).map(|i| other.orbit_shape(i)).collect();
+        shapes0.sort();
+        shapes1.sort();
+        shapes0 == shapes1 && self.canonical_form() == other.canonical_form()
+    }
+}
+
+/// Returns the number of isomorphism classes of mono-unary algebras on
+/// `n` elements by enumerating all `n^n` unary operations.
+pub fn count_mono_unary_algebras(n: usize) -> usize {
+    let mut forms = HashSet::new();
+    let mut values = vec![0; n];
+    loop {
+        forms.insert(MonoUnary::new(values.clone()).canonical_form());
+
+        let mut i = 0;
+        while i < n && values[i] + 1 == n {
+            values[i] = 0;
+            i += 1;
+        }
+        if i == n {
+            break;
+        }
+        values[i] += 1;
+    }
+    forms.len()
+}
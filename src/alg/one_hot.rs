@@ -0,0 +1,201 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BooleanLogic, BooleanSolver, DirectedGraph, Domain, Indexable, Logic, OnehotEncoding,
+    PartialOrder, Slice, Vector,
+};
+
+/// An indexable domain whose elements are represented by their one hot
+/// encodings instead of the encoding of the base domain. This uses more
+/// bits, but equality tests and lookups in tables indexed by the elements
+/// become single literals, which is often smaller overall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneHot<DOM>
+where
+    DOM: Indexable,
+{
+    base: DOM,
+    encoding: OnehotEncoding,
+}
+
+impl<DOM> OneHot<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the one hot encoded version of the given domain, whose
+    /// variables are constrained with pairwise exclusion clauses.
+    pub fn new(base: DOM) -> Self {
+        Self::with_encoding(base, OnehotEncoding::Pairwise)
+    }
+
+    /// Creates the one hot encoded version of the given domain, whose
+    /// variables are constrained with the given at most one encoding.
+    pub fn with_encoding(base: DOM, encoding: OnehotEncoding) -> Self {
+        Self { base, encoding }
+    }
+
+    /// Returns the base domain.
+    pub fn base(&self) -> &DOM {
+        &self.base
+    }
+
+    /// Converts an element of the base domain to its one hot encoding.
+    pub fn encode<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.base.onehot(logic, elem)
+    }
+
+    /// Converts a one hot encoded element back to the base domain.
+    pub fn decode<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let elems: Vec<LOGIC::Vector> = (0..self.base.size())
+            .map(|index| self.base.get_elem(logic, index))
+            .collect();
+        self.base.select_by_onehot(logic, elem, &elems)
+    }
+}
+
+impl<DOM> Domain for OneHot<DOM>
+where
+    DOM: Indexable,
+{
+    fn num_bits(&self) -> usize {
+        self.base.size()
+    }
+
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        let elem = self.base.get_elem(&Logic(), self.get_index(elem));
+        self.base.display_elem(f, elem.slice())
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.base.size());
+        logic.bool_fold_one(elem.copy_iter())
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        debug_assert_eq!(elem0.len(), self.base.size());
+        debug_assert_eq!(elem1.len(), self.base.size());
+        let mut test = logic.bool_zero();
+        for (a, b) in elem0.copy_iter().zip(elem1.copy_iter()) {
+            let v = logic.bool_and(a, b);
+            test = logic.bool_or(test, v);
+        }
+        test
+    }
+
+    fn add_variable<LOGIC>(&self, logic: &mut LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanSolver,
+    {
+        let lits: Vec<LOGIC::Elem> = (0..self.base.size())
+            .map(|_| logic.bool_add_variable())
+            .collect();
+        logic.bool_add_exactly_one(&lits, self.encoding);
+        lits.into_iter().collect()
+    }
+}
+
+impl<DOM> Indexable for OneHot<DOM>
+where
+    DOM: Indexable,
+{
+    fn size(&self) -> usize {
+        self.base.size()
+    }
+
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert!(index < self.base.size());
+        let mut vec: LOGIC::Vector = Vector::with_values(self.base.size(), logic.bool_zero());
+        vec.set(index, logic.bool_unit());
+        vec
+    }
+
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        assert_eq!(elem.len(), self.base.size());
+        let index = elem.copy_iter().position(|v| v);
+        assert!(index.is_some());
+        index.unwrap()
+    }
+
+    fn onehot<LOGIC>(&self, _logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        elem.copy_iter().collect()
+    }
+}
+
+impl<DOM> DirectedGraph for OneHot<DOM>
+where
+    DOM: Indexable + DirectedGraph,
+{
+    fn is_edge<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        debug_assert_eq!(elem0.len(), self.base.size());
+        debug_assert_eq!(elem1.len(), self.base.size());
+        let mut calc = Logic();
+        let elems: Vec<_> = (0..self.base.size())
+            .map(|index| self.base.get_elem(&calc, index))
+            .collect();
+
+        let mut result = logic.bool_zero();
+        for (a, elem) in elem0.copy_iter().zip(elems.iter()) {
+            let mut test = logic.bool_zero();
+            for (b, other) in elem1.copy_iter().zip(elems.iter()) {
+                if self.base.is_edge(&mut calc, elem.slice(), other.slice()) {
+                    test = logic.bool_or(test, b);
+                }
+            }
+            let test = logic.bool_and(a, test);
+            result = logic.bool_or(result, test);
+        }
+        result
+    }
+}
+
+impl<DOM> PartialOrder for OneHot<DOM> where DOM: Indexable + PartialOrder {}
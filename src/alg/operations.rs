@@ -16,8 +16,8 @@
 */
 
 use super::{
-    BitSlice, Boolean, BooleanLogic, BoundedOrder, Domain, Indexable, Monoid, Power, Relations,
-    Slice, UnaryOperations, Vector,
+    BitSlice, BitVec, Boolean, BooleanLogic, BooleanSolver, BoundedOrder, Domain, Indexable, Logic,
+    Monoid, Power, RelationRef, Relations, Slice, UnaryOperations, Vector, XorShift,
 };
 
 /// A domain containing operations of a fixed arity.
@@ -48,6 +48,16 @@ where
         }
     }
 
+    /// Creates a new domain of operations described by the given descriptor.
+    pub fn from_ref(desc: &RelationRef<DOM>) -> Self {
+        Self::new(desc.domain().clone(), desc.arity())
+    }
+
+    /// Returns the unnamed descriptor of the operations in this domain.
+    pub fn to_ref(&self) -> RelationRef<DOM> {
+        RelationRef::new(self.domain().clone(), self.arity)
+    }
+
     /// Returns the arity (rank) of all operations in the domain.
     pub fn arity(&self) -> usize {
         self.arity
@@ -111,6 +121,99 @@ where
         result
     }
 
+    /// Returns the operation with its arguments permuted, where the old
+    /// argument at position `i` is moved to position `permutation[i]`.
+    pub fn reorder_arguments<'a, SLICE>(&self, elem: SLICE, permutation: &[usize]) -> SLICE::Vector
+    where
+        SLICE: Slice<'a>,
+    {
+        assert_eq!(permutation.len(), self.arity());
+        debug_assert!((0..self.arity()).all(|i| permutation.contains(&i)));
+        self.polymer(elem, self.arity(), permutation)
+    }
+
+    /// Returns the operation of one smaller arity obtained by identifying
+    /// the two given arguments. The identified argument takes the position
+    /// of the smaller one, and the later arguments are shifted down.
+    pub fn identify_arguments<'a, SLICE>(
+        &self,
+        elem: SLICE,
+        coord0: usize,
+        coord1: usize,
+    ) -> SLICE::Vector
+    where
+        SLICE: Slice<'a>,
+    {
+        assert!(coord0 < self.arity() && coord1 < self.arity() && coord0 != coord1);
+        let (low, high) = (coord0.min(coord1), coord0.max(coord1));
+        let mapping: Vec<usize> = (0..self.arity())
+            .map(|i| match i.cmp(&high) {
+                std::cmp::Ordering::Less => i,
+                std::cmp::Ordering::Equal => low,
+                std::cmp::Ordering::Greater => i - 1,
+            })
+            .collect();
+        self.polymer(elem, self.arity() - 1, &mapping)
+    }
+
+    /// Returns the operation with the given number of new arguments added
+    /// at the end, on which the operation does not depend.
+    pub fn add_dummy_arguments<'a, SLICE>(&self, elem: SLICE, count: usize) -> SLICE::Vector
+    where
+        SLICE: Slice<'a>,
+    {
+        let mapping: Vec<usize> = (0..self.arity()).collect();
+        self.polymer(elem, self.arity() + count, &mapping)
+    }
+
+    /// Returns the unary operation obtained by identifying all arguments.
+    pub fn diagonal<'a, SLICE>(&self, elem: SLICE) -> SLICE::Vector
+    where
+        SLICE: Slice<'a>,
+    {
+        let mapping = vec![0; self.arity()];
+        self.polymer(elem, 1, &mapping)
+    }
+
+    /// Returns the composition of the given outer operation of this domain
+    /// with the list of inner operations of the given arity, which is the
+    /// operation `f(g_1(x), ..., g_n(x))` of the same arity as the inner
+    /// ones. The number of inner operations must be the arity of the outer.
+    pub fn compose<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        outer: LOGIC::Slice<'_>,
+        arity: usize,
+        inners: &[LOGIC::Slice<'_>],
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(outer.len(), self.num_bits());
+        assert_eq!(inners.len(), self.arity());
+        let inner = Operations::new(self.domain().clone(), arity);
+        for elem in inners {
+            assert_eq!(elem.len(), inner.num_bits());
+        }
+
+        let values = self.power.split_elem(outer);
+        let args = Power::new(self.domain().clone(), self.arity());
+        let mut result: LOGIC::Vector = Vector::with_capacity(inner.num_bits());
+        for index in 0..inner.power.exponent() {
+            let tuple: LOGIC::Vector = args.make_elem(
+                inners
+                    .iter()
+                    .map(|&elem| inner.power.part(elem, index).copy_iter().collect()),
+            );
+            let onehot = args.onehot(logic, tuple.slice());
+            result.extend(
+                self.domain()
+                    .select_by_onehot(logic, onehot.slice(), &values),
+            );
+        }
+        result
+    }
+
     /// Returns the graph of the given operation, which is a relation
     /// of arity one larger than this operation.
     pub fn as_relation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
@@ -156,7 +259,41 @@ where
         dom.is_top(logic, range.slice())
     }
 
-    /// Returns the unary identity operation.
+    /// Returns true if the given element is an injective operation, that is
+    /// every value is taken at most once.
+    pub fn is_injective<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let graph = self.as_relation(logic, elem);
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        for value in 0..size {
+            let column = graph.copy_iter().skip(value).step_by(size);
+            let test = logic.bool_fold_amo(column);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the given element is a bijective operation, that is
+    /// every value is taken exactly once.
+    pub fn is_bijective<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let graph = self.as_relation(logic, elem);
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        for value in 0..size {
+            let column = graph.copy_iter().skip(value).step_by(size);
+            let test = logic.bool_fold_one(column);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns the projection operation to the given coordinate.
     pub fn get_projection<LOGIC>(&self, logic: &mut LOGIC, coord: usize) -> LOGIC::Vector
     where
         LOGIC: BooleanLogic,
@@ -164,7 +301,125 @@ where
         assert!(coord < self.arity());
         let dom = UnaryOperations::new(self.domain().clone());
         let result = dom.get_identity(logic);
-        self.polymer(result.slice(), self.arity(), &[coord])
+        let unary = Operations::new(self.domain().clone(), 1);
+        unary.polymer(result.slice(), self.arity(), &[coord])
+    }
+
+    /// Returns true if the given operation is constant.
+    pub fn is_constant<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_bits());
+        let first = self.power.part(elem, 0);
+        let mut result = logic.bool_unit();
+        for part in self.power.part_iter(elem).skip(1) {
+            let test = self.domain().equals(logic, first, part);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the given operation is the projection to the given
+    /// coordinate.
+    pub fn is_projection<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        coord: usize,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let proj = self.get_projection(logic, coord);
+        self.equals(logic, elem, proj.slice())
+    }
+
+    /// Returns true if the value of the given operation depends only on the
+    /// arguments at the given coordinates, that is it does not change when
+    /// any of the other arguments is changed.
+    pub fn depends_only_on<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        coords: &[usize],
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_bits());
+        assert!(coords.iter().all(|&i| i < self.arity()));
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        let mut stride = 1;
+        for coord in 0..self.arity() {
+            if !coords.contains(&coord) {
+                for index in 0..self.power.exponent() {
+                    if (index / stride) % size != 0 {
+                        continue;
+                    }
+                    let part0 = self.power.part(elem, index);
+                    for value in 1..size {
+                        let part1 = self.power.part(elem, index + value * stride);
+                        let test = self.domain().equals(logic, part0, part1);
+                        result = logic.bool_and(result, test);
+                    }
+                }
+            }
+            stride *= size;
+        }
+        result
+    }
+
+    /// Returns the class of the given concrete operation according to the
+    /// number of its essential arguments, as in the Slupecki criterion.
+    pub fn classify(&self, elem: BitSlice<'_>) -> OperationClass {
+        let mut logic = Logic();
+        let essential = (0..self.arity())
+            .filter(|&i| {
+                let others: Vec<usize> = (0..self.arity()).filter(|&j| j != i).collect();
+                !self.depends_only_on(&mut logic, elem, &others)
+            })
+            .count();
+        if essential == 0 {
+            OperationClass::Constant
+        } else if essential == 1 {
+            if (0..self.arity()).any(|i| self.is_projection(&mut logic, elem, i)) {
+                OperationClass::Projection
+            } else {
+                OperationClass::EssentiallyUnary
+            }
+        } else if self.is_surjective(&mut logic, elem) {
+            OperationClass::EssentialSurjective
+        } else {
+            OperationClass::EssentialNonSurjective
+        }
+    }
+
+    /// Returns the table of the given concrete operation, which can be used
+    /// for fast repeated evaluation of the operation.
+    pub fn get_table(&self, elem: BitSlice<'_>) -> OperationTable {
+        assert_eq!(elem.len(), self.num_bits());
+        let values = self
+            .power
+            .part_iter(elem)
+            .map(|part| self.domain().get_index(part))
+            .collect();
+        OperationTable::new(self.domain().size(), self.arity(), values)
+    }
+
+    /// Returns the element of this domain corresponding to the given table.
+    pub fn from_table<LOGIC>(&self, logic: &LOGIC, table: &OperationTable) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(table.size(), self.domain().size());
+        assert_eq!(table.arity(), self.arity());
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for &value in table.values() {
+            result.extend(self.domain().get_elem(logic, value));
+        }
+        result
     }
 }
 
@@ -185,6 +440,14 @@ where
         self.power.contains(logic, elem)
     }
 
+    #[inline]
+    fn add_variable<LOGIC>(&self, logic: &mut LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanSolver,
+    {
+        self.power.add_variable(logic)
+    }
+
     #[inline]
     fn equals<LOGIC>(
         &self,
@@ -221,6 +484,11 @@ where
         self.power.get_index(elem)
     }
 
+    #[inline]
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.power.random_elem(rng)
+    }
+
     #[inline]
     fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
@@ -230,9 +498,92 @@ where
     }
 }
 
+/// The classes of operations by their essential arguments. By the Slupecki
+/// criterion, on a domain with at least three elements a clone containing
+/// all unary operations is the clone of all operations if and only if it
+/// contains an essential surjective operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OperationClass {
+    /// Operations without essential arguments.
+    Constant,
+    /// Projections to one of the arguments.
+    Projection,
+    /// Operations with one essential argument that are not projections.
+    EssentiallyUnary,
+    /// Surjective operations with at least two essential arguments.
+    EssentialSurjective,
+    /// Operations with at least two essential arguments that are not
+    /// surjective.
+    EssentialNonSurjective,
+}
+
+/// The table of a concrete operation on an indexable domain, where the
+/// arguments and the result are represented by their indices. The first
+/// argument is the least significant one when indexing the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationTable {
+    size: usize,
+    arity: usize,
+    values: Vec<usize>,
+}
+
+impl OperationTable {
+    /// Creates a new operation table of the given size and arity.
+    pub fn new(size: usize, arity: usize, values: Vec<usize>) -> Self {
+        let mut len = 1;
+        for _ in 0..arity {
+            len *= size;
+        }
+        assert_eq!(values.len(), len);
+        debug_assert!(values.iter().all(|&v| v < size));
+        Self {
+            size,
+            arity,
+            values,
+        }
+    }
+
+    /// Returns the size of the underlying domain.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the arity of the operation.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the values of the operation at all argument tuples.
+    pub fn values(&self) -> &[usize] {
+        &self.values
+    }
+
+    /// Returns the value of the operation at the given arguments.
+    pub fn apply(&self, args: &[usize]) -> usize {
+        assert_eq!(args.len(), self.arity);
+        let mut index = 0;
+        for &arg in args.iter().rev() {
+            debug_assert!(arg < self.size);
+            index = index * self.size + arg;
+        }
+        self.values[index]
+    }
+
+    /// Returns the composition of this unary operation with the other one,
+    /// that is first this operation and then the other one is applied.
+    pub fn compose(&self, other: &OperationTable) -> OperationTable {
+        assert!(self.arity == 1 && other.arity == 1);
+        assert_eq!(self.size, other.size);
+        let values = self.values.iter().map(|&v| other.apply(&[v])).collect();
+        OperationTable::new(self.size, 1, values)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::{BitVec, Domain, Logic, SmallSet, Solver, Vector, BOOLEAN};
+    use super::super::{
+        BitVec, BooleanSolver, Domain, Logic, Semigroup, SmallSet, Solver, Vector, BOOLEAN,
+    };
     use super::*;
 
     #[test]
@@ -268,4 +619,171 @@ mod tests {
         assert_eq!(graph3.get(2), solver.bool_not(elem3.get(1)));
         assert_eq!(graph3.get(3), elem3.get(1));
     }
+
+    #[test]
+    fn table() {
+        let mut logic = Logic();
+        let dom = SmallSet::new(3);
+        let ops = Operations::new(dom.clone(), 2);
+        for index in [0, 5, 100, 728] {
+            let elem = ops.get_elem(&logic, index);
+            let table = ops.get_table(elem.slice());
+            assert_eq!(ops.from_table(&logic, &table), elem);
+            for a in 0..3 {
+                for b in 0..3 {
+                    // the nullary operations are the elements of the domain
+                    let elem_a = dom.get_elem(&logic, a);
+                    let elem_b = dom.get_elem(&logic, b);
+                    let inners = [elem_a.slice(), elem_b.slice()];
+                    let value = ops.compose(&mut logic, elem.slice(), 0, &inners);
+                    assert_eq!(table.apply(&[a, b]), dom.get_index(value.slice()));
+                }
+            }
+        }
+
+        let unary = UnaryOperations::new(SmallSet::new(3));
+        for index in 0..unary.size() {
+            let elem0 = unary.get_elem(&logic, index);
+            let table0 = unary.get_table(elem0.slice());
+            assert_eq!(unary.from_table(&logic, &table0), elem0);
+
+            let elem1 = unary.get_elem(&logic, (index * 7) % unary.size());
+            let table1 = unary.get_table(elem1.slice());
+            let elem2 = Semigroup::product(&unary, &mut logic, elem1.slice(), elem0.slice());
+            let table2 = unary.get_table(elem2.slice());
+            assert_eq!(table0.compose(&table1), table2);
+        }
+    }
+
+    #[test]
+    fn projections() {
+        let mut logic = Logic();
+        for arity in 1..4 {
+            let ops = Operations::new(SmallSet::new(3), arity);
+            for coord in 0..arity {
+                let elem = ops.get_projection(&mut logic, coord);
+                assert!(ops.contains(&mut logic, elem.slice()));
+                assert!(ops.is_projection(&mut logic, elem.slice(), coord));
+
+                let table = ops.get_table(elem.slice());
+                for (pos, &value) in table.values().iter().enumerate() {
+                    let args: Vec<usize> =
+                        (0..arity).map(|i| pos / 3usize.pow(i as u32) % 3).collect();
+                    assert_eq!(value, args[coord]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn arguments() {
+        let logic = Logic();
+        let ternary = Operations::new(SmallSet::new(3), 3);
+        let binary = Operations::new(SmallSet::new(3), 2);
+        let unary = Operations::new(SmallSet::new(3), 1);
+        let quaternary = Operations::new(SmallSet::new(3), 4);
+        for index in [0, 5, 1000, 7000000, ternary.size() - 1] {
+            let elem = ternary.get_elem(&logic, index);
+            let table = ternary.get_table(elem.slice());
+
+            let elem1 = ternary.reorder_arguments(elem.slice(), &[1, 2, 0]);
+            let table1 = ternary.get_table(elem1.slice());
+            let elem2 = binary.get_table(ternary.identify_arguments(elem.slice(), 2, 0).slice());
+            let elem3 = quaternary.get_table(ternary.add_dummy_arguments(elem.slice(), 1).slice());
+            let elem4 = unary.get_table(ternary.diagonal(elem.slice()).slice());
+            for a in 0..3 {
+                assert_eq!(elem4.apply(&[a]), table.apply(&[a, a, a]));
+                for b in 0..3 {
+                    assert_eq!(elem2.apply(&[a, b]), table.apply(&[a, b, a]));
+                    for c in 0..3 {
+                        assert_eq!(table1.apply(&[c, a, b]), table.apply(&[a, b, c]));
+                        for d in 0..3 {
+                            assert_eq!(elem3.apply(&[a, b, c, d]), table.apply(&[a, b, c]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn classify() {
+        let mut logic = Logic();
+        let binary = Operations::new(SmallSet::new(2), 2);
+        let mut counts = std::collections::BTreeMap::new();
+        for index in 0..binary.size() {
+            let elem = binary.get_elem(&logic, index);
+            *counts.entry(binary.classify(elem.slice())).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&OperationClass::Constant], 2);
+        assert_eq!(counts[&OperationClass::Projection], 2);
+        assert_eq!(counts[&OperationClass::EssentiallyUnary], 2);
+        assert_eq!(counts[&OperationClass::EssentialSurjective], 10);
+
+        let ternary = Operations::new(SmallSet::new(3), 3);
+        let unary = Operations::new(SmallSet::new(3), 1);
+        let table = OperationTable::new(3, 1, vec![0, 0, 1]);
+        let elem = unary.from_table(&logic, &table);
+        let elem = unary.add_dummy_arguments(elem.slice(), 2);
+        let elem = ternary.reorder_arguments(elem.slice(), &[1, 0, 2]);
+        assert!(ternary.depends_only_on(&mut logic, elem.slice(), &[1]));
+        assert!(!ternary.depends_only_on(&mut logic, elem.slice(), &[0, 2]));
+        assert!(!ternary.is_constant(&mut logic, elem.slice()));
+        assert_eq!(
+            ternary.classify(elem.slice()),
+            OperationClass::EssentiallyUnary
+        );
+
+        let proj = ternary.get_projection(&mut logic, 2);
+        assert!(ternary.is_projection(&mut logic, proj.slice(), 2));
+        assert_eq!(ternary.classify(proj.slice()), OperationClass::Projection);
+        let table = OperationTable::new(3, 2, vec![0, 0, 0, 0, 1, 1, 0, 1, 1]);
+        let elem = Operations::new(SmallSet::new(3), 2).from_table(&logic, &table);
+        assert_eq!(
+            Operations::new(SmallSet::new(3), 2).classify(elem.slice()),
+            OperationClass::EssentialNonSurjective
+        );
+    }
+
+    #[test]
+    fn compose() {
+        let mut logic = Logic();
+        let binary = Operations::new(SmallSet::new(3), 2);
+        let ternary = Operations::new(SmallSet::new(3), 3);
+        for index in [0, 7, 100, 728] {
+            let outer = binary.get_elem(&logic, index);
+            let table = binary.get_table(outer.slice());
+            let inner0 = ternary.get_elem(&logic, index * 31 % ternary.size());
+            let inner1 = ternary.get_elem(&logic, index * 97 % ternary.size());
+            let table0 = ternary.get_table(inner0.slice());
+            let table1 = ternary.get_table(inner1.slice());
+
+            let inners = [inner0.slice(), inner1.slice()];
+            let elem = binary.compose(&mut logic, outer.slice(), 3, &inners);
+            let values = table0
+                .values()
+                .iter()
+                .zip(table1.values())
+                .map(|(&a, &b)| table.apply(&[a, b]))
+                .collect();
+            let expected = OperationTable::new(3, 3, values);
+            assert_eq!(ternary.get_table(elem.slice()), expected);
+
+            let proj0 = ternary.get_projection(&mut logic, 0);
+            let proj2 = ternary.get_projection(&mut logic, 2);
+            let inners = [proj2.slice(), proj0.slice()];
+            let elem = binary.compose(&mut logic, outer.slice(), 3, &inners);
+            assert_eq!(elem, binary.polymer(outer.slice(), 3, &[2, 0]));
+        }
+
+        let mut solver = Solver::new("");
+        let outer = binary.add_variable(&mut solver);
+        let proj0 = binary.get_projection(&mut solver, 0);
+        let proj1 = binary.get_projection(&mut solver, 1);
+        let inners = [proj0.slice(), proj1.slice()];
+        let elem = binary.compose(&mut solver, outer.slice(), 2, &inners);
+        let test = binary.equals(&mut solver, elem.slice(), outer.slice());
+        solver.bool_add_clause1(solver.bool_not(test));
+        assert!(!solver.bool_solvable());
+    }
 }
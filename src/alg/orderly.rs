@@ -0,0 +1,405 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{BinaryRelations, BitSlice, BitVec, Indexable, Logic, Slice, SmallSet, Vector};
+
+/// A small binary relation stored as the bit masks of the out-neighbors.
+#[derive(Debug, Clone)]
+struct Graph {
+    rows: Vec<u16>,
+}
+
+impl Graph {
+    /// Creates the graph of the binary relation on the given number of
+    /// vertices, where the pair `(a, b)` is at index `a + b * size`.
+    fn from_elem(size: usize, elem: BitSlice<'_>) -> Self {
+        assert_eq!(elem.len(), size * size);
+        let mut rows = vec![0; size];
+        for (a, row) in rows.iter_mut().enumerate() {
+            for b in 0..size {
+                if elem.get(a + b * size) {
+                    *row |= 1 << b;
+                }
+            }
+        }
+        Graph { rows }
+    }
+
+    /// Creates the graph whose code for the identity permutation is given.
+    fn from_code(size: usize, code: u64) -> Self {
+        let rows = (0..size)
+            .map(|a| ((code >> (a * size)) & ((1 << size) - 1)) as u16)
+            .collect();
+        Graph { rows }
+    }
+
+    fn size(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn has_edge(&self, a: usize, b: usize) -> bool {
+        (self.rows[a] >> b) & 1 != 0
+    }
+
+    /// Returns the element of the binary relations on the vertices, where
+    /// the pair `(a, b)` is at index `a + b * size`.
+    fn to_elem(&self) -> BitVec {
+        let size = self.size();
+        (0..size * size)
+            .map(|i| self.has_edge(i % size, i / size))
+            .collect()
+    }
+
+    /// Returns the adjacency matrix relabeled by the permutation, which
+    /// maps the vertices to their new positions, as a single number.
+    fn code(&self, perm: &[usize]) -> u64 {
+        let size = self.size();
+        let mut code = 0;
+        for a in 0..size {
+            for b in 0..size {
+                if self.has_edge(a, b) {
+                    code |= 1 << (perm[a] * size + perm[b]);
+                }
+            }
+        }
+        code
+    }
+
+    /// Returns the colors of the vertices obtained by iterated degree
+    /// refinement. The colors are numbered by sorting the signatures of the
+    /// vertices, so every isomorphism preserves them.
+    fn refined_colors(&self) -> Vec<usize> {
+        let size = self.size();
+        let mut colors = vec![0; size];
+        let mut count = 1;
+        loop {
+            let signatures: Vec<_> = (0..size)
+                .map(|a| {
+                    let mut outs: Vec<usize> = (0..size)
+                        .filter(|&b| self.has_edge(a, b))
+                        .map(|b| colors[b])
+                        .collect();
+                    let mut ins: Vec<usize> = (0..size)
+                        .filter(|&b| self.has_edge(b, a))
+                        .map(|b| colors[b])
+                        .collect();
+                    outs.sort_unstable();
+                    ins.sort_unstable();
+                    (colors[a], self.has_edge(a, a), outs, ins)
+                })
+                .collect();
+            let mut sorted = signatures.clone();
+            sorted.sort();
+            sorted.dedup();
+            colors = signatures
+                .iter()
+                .map(|sig| sorted.binary_search(sig).unwrap())
+                .collect();
+            if sorted.len() == count {
+                return colors;
+            }
+            count = sorted.len();
+        }
+    }
+
+    /// Returns the automorphisms of the graph. Only color preserving
+    /// bijections are tried, and partial ones are extended only if they
+    /// preserve the edges among the already mapped vertices.
+    fn automorphisms(&self) -> Vec<Vec<usize>> {
+        let colors = self.refined_colors();
+        let mut perm = Vec::with_capacity(self.size());
+        let mut used = vec![false; self.size()];
+        let mut result = Vec::new();
+        self.extend_automorphism(&colors, &mut perm, &mut used, &mut result);
+        result
+    }
+
+    fn extend_automorphism(
+        &self,
+        colors: &[usize],
+        perm: &mut Vec<usize>,
+        used: &mut [bool],
+        result: &mut Vec<Vec<usize>>,
+    ) {
+        let a = perm.len();
+        if a == self.size() {
+            result.push(perm.clone());
+            return;
+        }
+        for b in 0..self.size() {
+            if used[b]
+                || colors[a] != colors[b]
+                || (0..a).any(|c| {
+                    self.has_edge(a, c) != self.has_edge(b, perm[c])
+                        || self.has_edge(c, a) != self.has_edge(perm[c], b)
+                })
+            {
+                continue;
+            }
+            used[b] = true;
+            perm.push(b);
+            self.extend_automorphism(colors, perm, used, result);
+            perm.pop();
+            used[b] = false;
+        }
+    }
+
+    /// Calls the function with every permutation that maps the vertices of
+    /// each color into the block of positions of that color, where the
+    /// blocks follow the order of the colors. The minimal code over these
+    /// labelings is the same for isomorphic graphs.
+    fn for_each_labeling<FUNC>(&self, mut func: FUNC)
+    where
+        FUNC: FnMut(&[usize]),
+    {
+        let size = self.size();
+        let colors = self.refined_colors();
+        let mut starts = vec![0; size + 1];
+        for &color in colors.iter() {
+            starts[color + 1] += 1;
+        }
+        for color in 0..size {
+            starts[color + 1] += starts[color];
+        }
+        let mut perm = vec![0; size];
+        let mut used = vec![false; size];
+        Self::extend_labeling(&colors, &starts, &mut perm, &mut used, 0, &mut func);
+    }
+
+    fn extend_labeling<FUNC>(
+        colors: &[usize],
+        starts: &[usize],
+        perm: &mut [usize],
+        used: &mut [bool],
+        vertex: usize,
+        func: &mut FUNC,
+    ) where
+        FUNC: FnMut(&[usize]),
+    {
+        if vertex == perm.len() {
+            func(perm);
+            return;
+        }
+        let color = colors[vertex];
+        for pos in starts[color]..starts[color + 1] {
+            if !used[pos] {
+                used[pos] = true;
+                perm[vertex] = pos;
+                Self::extend_labeling(colors, starts, perm, used, vertex + 1, func);
+                used[pos] = false;
+            }
+        }
+    }
+
+    /// Returns the smallest code of the graph over the color respecting
+    /// labelings, which is the same for isomorphic graphs.
+    fn canonical_code(&self) -> u64 {
+        let mut best = u64::MAX;
+        self.for_each_labeling(|perm| best = best.min(self.code(perm)));
+        best
+    }
+
+    /// Returns true if the last vertex is in the orbit of the vertex that
+    /// the canonical labeling puts last, that is, if some labeling
+    /// yielding the minimal code keeps the last vertex in place.
+    fn is_canonical_extension(&self) -> bool {
+        let last = self.size() - 1;
+        let mut best = (u64::MAX, false);
+        self.for_each_labeling(|perm| {
+            let code = self.code(perm);
+            if code < best.0 {
+                best = (code, perm[last] == last);
+            } else if code == best.0 && perm[last] == last {
+                best.1 = true;
+            }
+        });
+        best.1
+    }
+}
+
+/// Returns the canonical code of the binary relation on the given number of
+/// elements, which is equal for two relations if and only if they are
+/// isomorphic. The size must be at most eight.
+pub(crate) fn canonical_code(size: usize, elem: BitSlice<'_>) -> u64 {
+    assert!(size <= 8);
+    Graph::from_elem(size, elem).canonical_code()
+}
+
+/// Returns the binary relation whose canonical code is given.
+pub(crate) fn relation_from_code(size: usize, code: u64) -> BitVec {
+    Graph::from_code(size, code).to_elem()
+}
+
+/// Returns the canonical form of the binary relation on the given number
+/// of elements, which is the same relation for isomorphic ones. The size
+/// must be at most eight.
+pub fn canonical_relation(size: usize, elem: BitSlice<'_>) -> BitVec {
+    relation_from_code(size, canonical_code(size, elem))
+}
+
+/// An orderly generator of binary relations up to isomorphism by the
+/// canonical augmentation method of McKay. Each structure is built by
+/// adding a new vertex to a canonical representative of one size less,
+/// and it is kept only if the new vertex is canonical and its neighborhood
+/// is minimal under the automorphisms of the parent. The predicate must be
+/// hereditary, that is closed under induced substructures, which holds
+/// for graphs, digraphs and posets.
+pub struct OrderlyGenerator<PRED>
+where
+    PRED: FnMut(&BinaryRelations<SmallSet>, BitSlice<'_>) -> bool,
+{
+    symmetric: bool,
+    pred: PRED,
+}
+
+impl<PRED> OrderlyGenerator<PRED>
+where
+    PRED: FnMut(&BinaryRelations<SmallSet>, BitSlice<'_>) -> bool,
+{
+    /// Creates a new generator of the relations satisfying the given
+    /// hereditary predicate. If symmetric is set, then only symmetric
+    /// relations are generated.
+    pub fn new(symmetric: bool, pred: PRED) -> Self {
+        Self { symmetric, pred }
+    }
+
+    /// Returns the child of the graph with the new vertex whose edges
+    /// are given by the bits of the pattern.
+    fn extend(&self, graph: &Graph, pattern: u32) -> Graph {
+        let size = graph.size();
+        let mut rows = graph.rows.clone();
+        rows.push(0);
+        for i in 0..size {
+            if (pattern >> i) & 1 != 0 {
+                rows[i] |= 1 << size;
+            }
+            let bit = if self.symmetric { i } else { i + size };
+            if (pattern >> bit) & 1 != 0 {
+                rows[size] |= 1 << i;
+            }
+        }
+        let bit = if self.symmetric { size } else { 2 * size };
+        if (pattern >> bit) & 1 != 0 {
+            rows[size] |= 1 << size;
+        }
+        Graph { rows }
+    }
+
+    /// Returns true if no automorphism maps the pattern to a smaller one.
+    fn is_minimal_pattern(&self, autos: &[Vec<usize>], pattern: u32) -> bool {
+        let size = autos[0].len();
+        let blocks = if self.symmetric { 1 } else { 2 };
+        autos.iter().all(|perm| {
+            let mut image = pattern & (1 << (blocks * size));
+            for block in 0..blocks {
+                for (i, &j) in perm.iter().enumerate() {
+                    if (pattern >> (i + block * size)) & 1 != 0 {
+                        image |= 1 << (j + block * size);
+                    }
+                }
+            }
+            image >= pattern
+        })
+    }
+
+    fn search<CONSUMER>(&mut self, graph: &Graph, target: usize, consumer: &mut CONSUMER)
+    where
+        CONSUMER: FnMut(BitSlice<'_>),
+    {
+        let size = graph.size();
+        if size == target {
+            consumer(graph.to_elem().slice());
+            return;
+        }
+
+        let autos = graph.automorphisms();
+        let bits = if self.symmetric {
+            size + 1
+        } else {
+            2 * size + 1
+        };
+        let domain = BinaryRelations::new(SmallSet::new(size + 1));
+        for pattern in 0..(1u32 << bits) {
+            if !self.is_minimal_pattern(&autos, pattern) {
+                continue;
+            }
+            let child = self.extend(graph, pattern);
+            if !(self.pred)(&domain, child.to_elem().slice()) || !child.is_canonical_extension() {
+                continue;
+            }
+            self.search(&child, target, consumer);
+        }
+    }
+
+    /// Calls the consumer with exactly one representative of each
+    /// isomorphism class of the relations of the given size satisfying the
+    /// predicate. The size must be at most eight.
+    pub fn generate<CONSUMER>(&mut self, size: usize, mut consumer: CONSUMER)
+    where
+        CONSUMER: FnMut(BitSlice<'_>),
+    {
+        assert!(size <= 8);
+        let empty = Graph { rows: Vec::new() };
+        self.search(&empty, size, &mut consumer);
+    }
+
+    /// Returns the number of isomorphism classes of the relations of the
+    /// given size satisfying the predicate.
+    pub fn count(&mut self, size: usize) -> usize {
+        let mut count = 0;
+        self.generate(size, |_| count += 1);
+        count
+    }
+}
+
+/// Calls the consumer with one representative of each isomorphism class of
+/// simple graphs with the given number of vertices.
+pub fn generate_graphs<CONSUMER>(size: usize, consumer: CONSUMER)
+where
+    CONSUMER: FnMut(BitSlice<'_>),
+{
+    let pred = |dom: &BinaryRelations<SmallSet>, elem: BitSlice<'_>| {
+        let size = dom.domain().size();
+        (0..size).all(|i| !elem.get(i * (size + 1)))
+    };
+    OrderlyGenerator::new(true, pred).generate(size, consumer);
+}
+
+/// Calls the consumer with one representative of each isomorphism class of
+/// loopless directed graphs with the given number of vertices.
+pub fn generate_digraphs<CONSUMER>(size: usize, consumer: CONSUMER)
+where
+    CONSUMER: FnMut(BitSlice<'_>),
+{
+    let pred = |dom: &BinaryRelations<SmallSet>, elem: BitSlice<'_>| {
+        let size = dom.domain().size();
+        (0..size).all(|i| !elem.get(i * (size + 1)))
+    };
+    OrderlyGenerator::new(false, pred).generate(size, consumer);
+}
+
+/// Calls the consumer with one representative of each isomorphism class of
+/// partial orders with the given number of elements.
+pub fn generate_posets<CONSUMER>(size: usize, consumer: CONSUMER)
+where
+    CONSUMER: FnMut(BitSlice<'_>),
+{
+    let pred = |dom: &BinaryRelations<SmallSet>, elem: BitSlice<'_>| {
+        dom.is_partial_order(&mut Logic(), elem)
+    };
+    OrderlyGenerator::new(false, pred).generate(size, consumer);
+}
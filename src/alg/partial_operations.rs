@@ -0,0 +1,330 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Indexable, MeetSemilattice,
+    Operations, PartialOrder, Relations, Slice, Vector,
+};
+
+/// A domain containing partial operations of a fixed arity, represented by
+/// their graphs. The graph is a relation of arity one larger than the
+/// operation, whose first coordinate is the value and the remaining ones
+/// are the arguments, and which contains at most one value for each tuple
+/// of arguments. The partial operations are ordered by extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    arity: usize,
+    graphs: Relations<DOM>,
+}
+
+impl<DOM> PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the domain of partial operations of the given arity.
+    pub fn new(dom: DOM, arity: usize) -> Self {
+        Self {
+            arity,
+            graphs: Relations::new(dom, arity + 1),
+        }
+    }
+
+    /// Returns the arity of all partial operations in the domain.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the domain of the partial operations.
+    pub fn domain(&self) -> &DOM {
+        self.graphs.domain()
+    }
+
+    /// Returns the domain of the graphs of the partial operations.
+    pub fn graphs(&self) -> &Relations<DOM> {
+        &self.graphs
+    }
+
+    /// Returns the nowhere defined partial operation, which is the least
+    /// element of the extension order.
+    pub fn get_empty<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.get_bottom(logic)
+    }
+
+    /// Returns the index of the given argument tuple.
+    fn args_index(&self, args: &[usize]) -> usize {
+        assert_eq!(args.len(), self.arity);
+        let size = self.domain().size();
+        let mut index = 0;
+        for &arg in args.iter().rev() {
+            debug_assert!(arg < size);
+            index = index * size + arg;
+        }
+        index
+    }
+
+    /// Returns the number of argument tuples.
+    fn num_args(&self) -> usize {
+        self.graphs.num_bits() / self.domain().size()
+    }
+
+    /// Returns the partial operation defined everywhere that is the given
+    /// operation of the same arity.
+    pub fn from_operation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let ops = Operations::new(self.domain().clone(), self.arity);
+        ops.as_relation(logic, elem)
+    }
+
+    /// Returns true if the partial operation is defined at the given
+    /// argument tuple, given by the indices of its elements.
+    pub fn is_defined_at<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        args: &[usize],
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_bits());
+        let size = self.domain().size();
+        let start = self.args_index(args) * size;
+        logic.bool_fold_any(elem.range(start, start + size).copy_iter())
+    }
+
+    /// Returns the relation of the given arity containing the argument
+    /// tuples where the partial operation is defined.
+    pub fn domain_of<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.fold_any(logic, elem, 1)
+    }
+
+    /// Returns true if the partial operation is defined everywhere.
+    pub fn is_total<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let defined = self.domain_of(logic, elem);
+        logic.bool_fold_all(defined.copy_iter())
+    }
+
+    /// Returns the restriction of the partial operation to the argument
+    /// tuples contained in the given relation of the same arity.
+    pub fn restriction<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        subset: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_bits());
+        assert_eq!(subset.len(), self.num_args());
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for (index, v) in elem.copy_iter().enumerate() {
+            result.push(logic.bool_and(v, subset.get(index / size)));
+        }
+        result
+    }
+
+    /// Returns the composition of the given outer partial operation of this
+    /// domain with the list of inner partial operations of the given arity,
+    /// which is the partial operation `f(g_1(x), ..., g_n(x))` defined
+    /// exactly where all inner ones are defined and the outer one is
+    /// defined at their values.
+    pub fn compose<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        outer: LOGIC::Slice<'_>,
+        arity: usize,
+        inners: &[LOGIC::Slice<'_>],
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(outer.len(), self.num_bits());
+        assert_eq!(inners.len(), self.arity);
+        let inner = PartialOperations::new(self.domain().clone(), arity);
+        for elem in inners {
+            assert_eq!(elem.len(), inner.num_bits());
+        }
+
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(inner.num_bits());
+        for point in 0..inner.num_args() {
+            let mut values: Vec<LOGIC::Elem> = vec![logic.bool_zero(); size];
+            for args in 0..self.num_args() {
+                let mut test = logic.bool_unit();
+                let mut index = args;
+                for elem in inners {
+                    let v = elem.get(index % size + point * size);
+                    test = logic.bool_and(test, v);
+                    index /= size;
+                }
+                for (value, lit) in values.iter_mut().enumerate() {
+                    let v = logic.bool_and(test, outer.get(value + args * size));
+                    *lit = logic.bool_or(*lit, v);
+                }
+            }
+            result.extend(values);
+        }
+        result
+    }
+}
+
+impl<DOM> Domain for PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    fn num_bits(&self) -> usize {
+        self.graphs.num_bits()
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.is_partial_operation(logic, elem)
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.equals(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> Indexable for PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    fn size(&self) -> usize {
+        let size = self.domain().size();
+        let mut result = 1;
+        for _ in 0..self.num_args() {
+            result *= size + 1;
+        }
+        result
+    }
+
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, mut index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_values(self.num_bits(), logic.bool_zero());
+        for args in 0..self.num_args() {
+            let digit = index % (size + 1);
+            index /= size + 1;
+            if digit > 0 {
+                result.set(digit - 1 + args * size, logic.bool_unit());
+            }
+        }
+        result
+    }
+
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        assert_eq!(elem.len(), self.num_bits());
+        let size = self.domain().size();
+        let mut index = 0;
+        for args in (0..self.num_args()).rev() {
+            let block = elem.range(args * size, (args + 1) * size);
+            let digit = block.copy_iter().position(|v| v).map_or(0, |v| v + 1);
+            index = index * (size + 1) + digit;
+        }
+        index
+    }
+
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_bits());
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.size());
+        result.push(logic.bool_unit());
+        for args in 0..self.num_args() {
+            let block = elem.range(args * size, (args + 1) * size);
+            let undefined = logic.bool_fold_any(block.copy_iter());
+            let undefined = logic.bool_not(undefined);
+
+            let temp: LOGIC::Vector = result.copy_iter().collect();
+            result.clear();
+            for v1 in std::iter::once(undefined).chain(block.copy_iter()) {
+                for v0 in temp.copy_iter() {
+                    result.push(logic.bool_and(v0, v1));
+                }
+            }
+        }
+        debug_assert_eq!(result.len(), self.size());
+        result
+    }
+}
+
+impl<DOM> DirectedGraph for PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    fn is_edge<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.is_edge(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> PartialOrder for PartialOperations<DOM> where DOM: Indexable {}
+
+impl<DOM> MeetSemilattice for PartialOperations<DOM>
+where
+    DOM: Indexable,
+{
+    fn meet<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.graphs.meet(logic, elem0, elem1)
+    }
+}
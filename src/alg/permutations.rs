@@ -16,8 +16,8 @@
 */
 
 use super::{
-    BinaryRelations, BitSlice, BooleanLogic, Domain, Group, Indexable, Monoid, Semigroup, Slice,
-    Vector,
+    BinaryRelations, BitSlice, BooleanLogic, Domain, Group, Indexable, Monoid, PermutationGroup,
+    Semigroup, Slice, Vector,
 };
 
 /// The class of all permutations of the given indexable domain.
@@ -64,6 +64,36 @@ where
     {
         self.0.is_even_permutation(logic, elem)
     }
+
+    /// Returns true if the permutation has exactly the given number of fixed
+    /// points.
+    pub fn has_fixed_points<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        count: usize,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.has_fixed_points(logic, elem, count)
+    }
+
+    /// Returns true if the permutation has no fixed points.
+    pub fn is_derangement<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_fixed_point_free(logic, elem)
+    }
+
+    /// Returns true if the permutation is its own inverse.
+    pub fn is_involution<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_symmetric(logic, elem)
+    }
 }
 
 impl<DOM> Domain for SymmetricGroup<DOM>
@@ -216,6 +246,24 @@ where
     }
 }
 
+impl<DOM> PermutationGroup for SymmetricGroup<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn degree(&self) -> usize {
+        self.domain().size()
+    }
+
+    #[inline]
+    fn as_permutation<LOGIC>(&self, _logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        elem.copy_iter().collect()
+    }
+}
+
 /// The class of all even permutations of the given indexable domain.
 #[derive(Debug, Clone, PartialEq)]
 pub struct AlternatingGroup<DOM>(BinaryRelations<DOM>)
@@ -394,3 +442,21 @@ where
         self.0.converse(elem)
     }
 }
+
+impl<DOM> PermutationGroup for AlternatingGroup<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn degree(&self) -> usize {
+        self.domain().size()
+    }
+
+    #[inline]
+    fn as_permutation<LOGIC>(&self, _logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        elem.copy_iter().collect()
+    }
+}
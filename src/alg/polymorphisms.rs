@@ -0,0 +1,305 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeMap;
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Domain, Indexable, OperationClass, Operations,
+    Preservation, Relations, Slice, Solver, Vector,
+};
+
+/// The clone of operations on an indexable domain that preserve each of
+/// the given relations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolymorphismClone<DOM>
+where
+    DOM: Indexable,
+{
+    domain: DOM,
+    relations: Vec<(Relations<DOM>, BitVec)>,
+}
+
+impl<DOM> PolymorphismClone<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the clone of all operations of the given domain, which are
+    /// the polymorphisms of the empty list of relations.
+    pub fn new(domain: DOM) -> Self {
+        Self {
+            domain,
+            relations: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying domain of the operations.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Adds a new relation of the given arity that must be preserved.
+    pub fn add_relation(&mut self, arity: usize, relation: BitVec) {
+        let rels = Relations::new(self.domain.clone(), arity);
+        assert_eq!(relation.len(), rels.num_bits());
+        self.relations.push((rels, relation));
+    }
+
+    /// Returns the list of preserved relations with their arities.
+    pub fn relations(&self) -> impl Iterator<Item = (usize, BitSlice<'_>)> {
+        self.relations
+            .iter()
+            .map(|(rels, relation)| (rels.arity(), relation.slice()))
+    }
+
+    /// Returns the domain of all operations of the given arity.
+    pub fn operations(&self, arity: usize) -> Operations<DOM> {
+        Operations::new(self.domain.clone(), arity)
+    }
+
+    /// Returns true if the given operation of the given arity preserves
+    /// all relations.
+    pub fn contains_operation<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        arity: usize,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for (rels, relation) in self.relations.iter() {
+            let pres = Preservation::new(self.domain.clone(), arity, rels.arity());
+            let relation = rels.lift(logic, relation.slice());
+            let test = pres.preserves(logic, elem, relation.slice());
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Adds a new operation variable of the given arity to the solver that
+    /// is required to preserve all relations.
+    pub fn add_operation<LOGIC>(&self, logic: &mut LOGIC, arity: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanSolver,
+    {
+        let elem = self.operations(arity).add_variable(logic);
+        let test = self.contains_operation(logic, arity, elem.slice());
+        logic.bool_add_clause1(test);
+        elem
+    }
+
+    /// Returns an operation of the given arity that preserves all relations,
+    /// or `None` if there is none.
+    pub fn find_operation_of_arity(&self, arity: usize) -> Option<BitVec> {
+        let mut logic = Solver::new("");
+        let elem = self.add_operation(&mut logic, arity);
+        logic.bool_find_one_model(&[], elem.copy_iter())
+    }
+
+    /// Returns the number of operations of the given arity in each class
+    /// of operations, enumerating all members of the clone of that arity.
+    pub fn classify_operations_of_arity(&self, arity: usize) -> BTreeMap<OperationClass, usize> {
+        let ops = self.operations(arity);
+        let mut logic = Solver::new("");
+        let elem = self.add_operation(&mut logic, arity);
+        let mut result = BTreeMap::new();
+        for op in logic.bool_all_models(elem.copy_iter()) {
+            *result.entry(ops.classify(op.slice())).or_insert(0) += 1;
+        }
+        result
+    }
+
+    /// Returns the number of operations of the given arity that preserve
+    /// all relations.
+    pub fn count_operations_of_arity(&self, arity: usize) -> usize {
+        let mut logic = Solver::new("");
+        let elem = self.add_operation(&mut logic, arity);
+        logic.bool_find_num_models_method1(elem.copy_iter())
+    }
+
+    /// Returns a Siggers polymorphism, which satisfies the identity
+    /// `s(r,a,r,e) = s(a,r,e,a)`, or `None` if there is none. The relations
+    /// have such a polymorphism if and only if the polymorphism algebra of
+    /// their core is a Taylor algebra.
+    pub fn find_siggers_operation(&self) -> Option<BitVec> {
+        let ops = self.operations(4);
+        let mut logic = Solver::new("");
+        let elem = self.add_operation(&mut logic, 4);
+        let lhs = ops.polymer(elem.slice(), 3, &[0, 1, 0, 2]);
+        let rhs = ops.polymer(elem.slice(), 3, &[1, 0, 2, 1]);
+        let test = self
+            .operations(3)
+            .equals(&mut logic, lhs.slice(), rhs.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_one_model(&[], elem.copy_iter())
+    }
+
+    /// Adds a new weak near-unanimity polymorphism of the given arity to the
+    /// solver, and returns it together with the binary operation obtained by
+    /// placing the single different argument first.
+    fn add_weak_near_unanimity<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        arity: usize,
+    ) -> (LOGIC::Vector, LOGIC::Vector)
+    where
+        LOGIC: BooleanSolver,
+    {
+        let ops = self.operations(arity);
+        let elem = self.add_operation(logic, arity);
+        let minor = |i: usize| {
+            let mapping: Vec<usize> = (0..arity).map(|j| (i == j) as usize).collect();
+            ops.polymer(elem.slice(), 2, &mapping)
+        };
+        let first: LOGIC::Vector = minor(0);
+        for i in 1..arity {
+            let other: LOGIC::Vector = minor(i);
+            let test = self
+                .operations(2)
+                .equals(logic, first.slice(), other.slice());
+            logic.bool_add_clause1(test);
+        }
+        (elem, first)
+    }
+
+    /// Returns a pair of weak near-unanimity polymorphisms of arities 3 and 4
+    /// that satisfy `w3(y,x,x) = w4(y,x,x,x)`, or `None` if there are none.
+    /// The relations have such polymorphisms if and only if the constraint
+    /// satisfaction problem of their core has bounded width.
+    pub fn find_bounded_width_operations(&self) -> Option<(BitVec, BitVec)> {
+        let mut logic = Solver::new("");
+        let (elem3, minor3) = self.add_weak_near_unanimity(&mut logic, 3);
+        let (elem4, minor4) = self.add_weak_near_unanimity(&mut logic, 4);
+        let test = self
+            .operations(2)
+            .equals(&mut logic, minor3.slice(), minor4.slice());
+        logic.bool_add_clause1(test);
+        let len = elem3.len();
+        let model = logic.bool_find_one_model(&[], elem3.copy_iter().chain(elem4.copy_iter()))?;
+        let model = model.slice();
+        Some((
+            model.head(len).copy_iter().collect(),
+            model.tail(len).copy_iter().collect(),
+        ))
+    }
+}
+
+/// The relational clone of relations on an indexable domain that are
+/// invariant under each of the given operations, which is the dual of
+/// `PolymorphismClone` in the Pol-Inv Galois connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvariantRelations<DOM>
+where
+    DOM: Indexable,
+{
+    domain: DOM,
+    operations: Vec<(Operations<DOM>, BitVec)>,
+}
+
+impl<DOM> InvariantRelations<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the relational clone of all relations of the given domain,
+    /// which are the invariants of the empty list of operations.
+    pub fn new(domain: DOM) -> Self {
+        Self {
+            domain,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying domain of the relations.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Adds a new operation of the given arity that must preserve the
+    /// relations.
+    pub fn add_operation(&mut self, arity: usize, operation: BitVec) {
+        let ops = Operations::new(self.domain.clone(), arity);
+        assert_eq!(operation.len(), ops.num_bits());
+        self.operations.push((ops, operation));
+    }
+
+    /// Returns the list of operations with their arities.
+    pub fn operations(&self) -> impl Iterator<Item = (usize, BitSlice<'_>)> {
+        self.operations
+            .iter()
+            .map(|(ops, operation)| (ops.arity(), operation.slice()))
+    }
+
+    /// Returns the domain of all relations of the given arity.
+    pub fn relations(&self, arity: usize) -> Relations<DOM> {
+        Relations::new(self.domain.clone(), arity)
+    }
+
+    /// Returns true if the given relation of the given arity is preserved
+    /// by all operations.
+    pub fn contains_relation<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        arity: usize,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for (ops, operation) in self.operations.iter() {
+            let pres = Preservation::new(self.domain.clone(), ops.arity(), arity);
+            let operation = ops.lift(logic, operation.slice());
+            let test = pres.preserves(logic, operation.slice(), elem);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Adds a new relation variable of the given arity to the solver that
+    /// is required to be preserved by all operations.
+    pub fn add_relation<LOGIC>(&self, logic: &mut LOGIC, arity: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanSolver,
+    {
+        let elem = self.relations(arity).add_variable(logic);
+        let test = self.contains_relation(logic, arity, elem.slice());
+        logic.bool_add_clause1(test);
+        elem
+    }
+
+    /// Returns all relations of the given arity that are preserved by all
+    /// operations.
+    pub fn find_relations_of_arity(&self, arity: usize) -> Vec<BitVec> {
+        let mut logic = Solver::new("");
+        let elem = self.add_relation(&mut logic, arity);
+        let result = logic.bool_all_models(elem.copy_iter()).collect();
+        result
+    }
+
+    /// Returns all relations of arity at most the given bound, together
+    /// with their arities, that are preserved by all operations.
+    pub fn find_relations_up_to_arity(&self, max_arity: usize) -> Vec<(usize, BitVec)> {
+        let mut result = Vec::new();
+        for arity in 0..=max_arity {
+            for relation in self.find_relations_of_arity(arity) {
+                result.push((arity, relation));
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,361 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    find_homomorphisms, BinaryRelations, BitSlice, BitVec, BooleanSolver, DirectedGraph, Domain,
+    Indexable, Logic, Slice, SmallSet, Solver, Stabilizer, Vector,
+};
+
+/// The largest poset for which linear extensions are counted by dynamic
+/// programming over the down-sets.
+const MAX_DYNAMIC_SIZE: usize = 20;
+
+/// A concrete finite partial order on the set `0..size`, where `i <= j`
+/// holds if the bit at index `j + i * size` is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Poset {
+    size: usize,
+    order: BitVec,
+}
+
+impl Poset {
+    /// Creates a new poset from the given partial order relation.
+    pub fn new(size: usize, order: BitVec) -> Self {
+        let rels = BinaryRelations::new(SmallSet::new(size));
+        assert_eq!(order.len(), rels.num_bits());
+        assert!(rels.is_partial_order(&mut Logic(), order.slice()));
+        Self { size, order }
+    }
+
+    /// Creates a new poset from a partial order of the given domain, where
+    /// the elements are replaced with their indices.
+    pub fn from_relation<DOM>(rels: &BinaryRelations<DOM>, elem: BitSlice<'_>) -> Self
+    where
+        DOM: Indexable,
+    {
+        Self::new(rels.domain().size(), elem.copy_iter().collect())
+    }
+
+    /// Returns the number of elements of the poset.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the partial order relation of the poset.
+    pub fn relation(&self) -> BitSlice<'_> {
+        self.order.slice()
+    }
+
+    /// Returns true if the first element is less than or equal to the
+    /// second one.
+    pub fn leq(&self, elem0: usize, elem1: usize) -> bool {
+        self.order.get(elem1 + elem0 * self.size)
+    }
+
+    /// Returns true if the first element is strictly less than the second.
+    pub fn less_than(&self, elem0: usize, elem1: usize) -> bool {
+        elem0 != elem1 && self.leq(elem0, elem1)
+    }
+
+    /// Returns a partition of the poset into the minimum number of chains,
+    /// which is the width of the poset by Dilworth's theorem. The chains
+    /// are calculated from a maximum matching of the comparability graph.
+    pub fn chain_decomposition(&self) -> Vec<Vec<usize>> {
+        fn augment(
+            poset: &Poset,
+            elem: usize,
+            visited: &mut [bool],
+            matched: &mut [Option<usize>],
+        ) -> bool {
+            for other in 0..poset.size {
+                if poset.less_than(elem, other) && !visited[other] {
+                    visited[other] = true;
+                    let free = match matched[other] {
+                        None => true,
+                        Some(prev) => augment(poset, prev, visited, matched),
+                    };
+                    if free {
+                        matched[other] = Some(elem);
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+
+        let mut matched: Vec<Option<usize>> = vec![None; self.size];
+        for elem in 0..self.size {
+            let mut visited = vec![false; self.size];
+            augment(self, elem, &mut visited, &mut matched);
+        }
+
+        let mut next = vec![None; self.size];
+        for (elem, prev) in matched.iter().enumerate() {
+            if let Some(prev) = prev {
+                next[*prev] = Some(elem);
+            }
+        }
+
+        let mut chains = Vec::new();
+        for start in (0..self.size).filter(|&e| matched[e].is_none()) {
+            let mut chain = vec![start];
+            while let Some(elem) = next[*chain.last().unwrap()] {
+                chain.push(elem);
+            }
+            chains.push(chain);
+        }
+        chains
+    }
+
+    /// Returns the bit masks of the strict down-sets of the elements.
+    fn lower_masks(&self) -> Vec<usize> {
+        (0..self.size)
+            .map(|elem| {
+                (0..self.size)
+                    .filter(|&other| self.less_than(other, elem))
+                    .fold(0, |mask, other| mask | 1 << other)
+            })
+            .collect()
+    }
+
+    /// Returns the number of ways each down-set, given by its bit mask,
+    /// can be completed to a linear extension. The entries of the other
+    /// subsets are zero.
+    fn completions(&self) -> Vec<usize> {
+        assert!(self.size <= MAX_DYNAMIC_SIZE);
+        let lower = self.lower_masks();
+        let full = (1usize << self.size) - 1;
+
+        let mut counts = vec![0; full + 1];
+        counts[full] = 1;
+        for mask in (0..full).rev() {
+            if (0..self.size).any(|e| mask & 1 << e != 0 && lower[e] & !mask != 0) {
+                continue;
+            }
+            counts[mask] = (0..self.size)
+                .filter(|&e| mask & 1 << e == 0 && lower[e] & !mask == 0)
+                .map(|e| counts[mask | 1 << e])
+                .sum();
+        }
+        counts
+    }
+
+    /// Returns the number of linear extensions of the poset. Small posets
+    /// are handled with dynamic programming over the down-sets, larger
+    /// ones by enumerating the extensions with a SAT solver.
+    pub fn count_linear_extensions(&self) -> usize {
+        if self.size <= MAX_DYNAMIC_SIZE {
+            self.completions()[0]
+        } else {
+            self.count_linear_extensions_sat()
+        }
+    }
+
+    /// Returns the number of linear extensions of the poset by counting
+    /// the total orders containing the partial order with a SAT solver.
+    pub fn count_linear_extensions_sat(&self) -> usize {
+        let rels = BinaryRelations::new(SmallSet::new(self.size));
+        let mut logic = Solver::new("");
+        let elem = rels.add_variable(&mut logic);
+        let test = rels.is_total_order(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let order = rels.lift(&logic, self.order.slice());
+        let test = rels.is_edge(&mut logic, order.slice(), elem.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_num_models_method1(elem.copy_iter())
+    }
+
+    /// Returns a uniformly random linear extension of the poset as a list
+    /// of its elements in increasing order. The given function must return
+    /// a uniformly random number below its argument.
+    pub fn sample_linear_extension<RAND>(&self, mut rand: RAND) -> Vec<usize>
+    where
+        RAND: FnMut(usize) -> usize,
+    {
+        let counts = self.completions();
+        let lower = self.lower_masks();
+
+        let mut result = Vec::with_capacity(self.size);
+        let mut mask = 0;
+        while result.len() < self.size {
+            let mut pick = rand(counts[mask]);
+            for elem in 0..self.size {
+                if mask & 1 << elem != 0 || lower[elem] & !mask != 0 {
+                    continue;
+                }
+                let count = counts[mask | 1 << elem];
+                if pick < count {
+                    result.push(elem);
+                    mask |= 1 << elem;
+                    break;
+                }
+                pick -= count;
+            }
+        }
+        result
+    }
+
+    /// Returns true if the second element covers the first one.
+    pub fn covers(&self, elem0: usize, elem1: usize) -> bool {
+        self.less_than(elem0, elem1)
+            && !(0..self.size).any(|e| self.less_than(elem0, e) && self.less_than(e, elem1))
+    }
+
+    /// Returns the elements sorted by the size of their down-sets, which is
+    /// a linear extension of the poset.
+    fn sorted_elements(&self) -> Vec<usize> {
+        let mut elems: Vec<usize> = (0..self.size).collect();
+        elems.sort_by_key(|&e| (0..self.size).filter(|&f| self.leq(f, e)).count());
+        elems
+    }
+
+    /// Returns the zeta matrix of the poset, where the entry at `[i][j]` is
+    /// one if `i <= j` and zero otherwise.
+    pub fn zeta_matrix(&self) -> Vec<Vec<i64>> {
+        (0..self.size)
+            .map(|i| (0..self.size).map(|j| self.leq(i, j) as i64).collect())
+            .collect()
+    }
+
+    /// Returns the matrix of the Möbius function of the poset, which is the
+    /// inverse of the zeta matrix. The entry at `[i][j]` is `mu(i, j)`.
+    pub fn mobius_matrix(&self) -> Vec<Vec<i64>> {
+        let elems = self.sorted_elements();
+        let mut result = vec![vec![0; self.size]; self.size];
+        for (x, row) in result.iter_mut().enumerate() {
+            row[x] = 1;
+            for &y in elems.iter() {
+                if self.less_than(x, y) {
+                    row[y] = -(0..self.size)
+                        .filter(|&z| self.leq(x, z) && self.less_than(z, y))
+                        .map(|z| row[z])
+                        .sum::<i64>();
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the least element of the poset, if it exists.
+    pub fn bottom(&self) -> Option<usize> {
+        (0..self.size).find(|&e| (0..self.size).all(|f| self.leq(e, f)))
+    }
+
+    /// Returns the largest element of the poset, if it exists.
+    pub fn top(&self) -> Option<usize> {
+        (0..self.size).find(|&e| (0..self.size).all(|f| self.leq(f, e)))
+    }
+
+    /// Returns the rank function of the poset if it has a least element
+    /// and it is graded, that is, all maximal chains between any two
+    /// elements have the same length.
+    pub fn rank_function(&self) -> Option<Vec<usize>> {
+        let bottom = self.bottom()?;
+        let mut rank = vec![0; self.size];
+        for &y in self.sorted_elements().iter() {
+            if y != bottom {
+                let x = (0..self.size).find(|&x| self.covers(x, y)).unwrap();
+                rank[y] = rank[x] + 1;
+            }
+        }
+        for x in 0..self.size {
+            for y in 0..self.size {
+                if self.covers(x, y) && rank[y] != rank[x] + 1 {
+                    return None;
+                }
+            }
+        }
+        Some(rank)
+    }
+
+    /// Returns the coefficients of the characteristic polynomial
+    /// `sum_x mu(0, x) t^(r(1) - r(x))` of a graded poset with least and
+    /// largest elements, where the coefficient of `t^i` is at index `i`.
+    pub fn characteristic_polynomial(&self) -> Option<Vec<i64>> {
+        let bottom = self.bottom()?;
+        let top = self.top()?;
+        let rank = self.rank_function()?;
+        let mobius = self.mobius_matrix();
+
+        let mut result = vec![0; rank[top] + 1];
+        for x in 0..self.size {
+            result[rank[top] - rank[x]] += mobius[bottom][x];
+        }
+        Some(result)
+    }
+
+    /// Returns the product of two elements of the incidence algebra of the
+    /// poset given as matrices, where the entry at `[x][y]` of the result
+    /// is the sum of `f[x][z] * g[z][y]` for all `x <= z <= y`.
+    pub fn convolution(&self, f: &[Vec<i64>], g: &[Vec<i64>]) -> Vec<Vec<i64>> {
+        let mut result = vec![vec![0; self.size]; self.size];
+        for (x, row) in result.iter_mut().enumerate() {
+            for (y, value) in row.iter_mut().enumerate() {
+                *value = (0..self.size)
+                    .filter(|&z| self.leq(x, z) && self.leq(z, y))
+                    .map(|z| f[x][z] * g[z][y])
+                    .sum();
+            }
+        }
+        result
+    }
+
+    /// Returns the value of the order polynomial at `k`, which is the number
+    /// of order preserving maps from the poset to the `k`-element chain. The
+    /// maps are counted as multichains of down-sets with dynamic programming.
+    pub fn order_polynomial(&self, k: usize) -> usize {
+        assert!(self.size <= MAX_DYNAMIC_SIZE);
+        if k == 0 {
+            return (self.size == 0) as usize;
+        }
+
+        let lower = self.lower_masks();
+        let full = (1usize << self.size) - 1;
+        let is_downset =
+            |mask: usize| (0..self.size).all(|e| mask & 1 << e == 0 || lower[e] & !mask == 0);
+        let downsets: Vec<bool> = (0..=full).map(is_downset).collect();
+
+        let mut counts: Vec<usize> = downsets.iter().map(|&d| d as usize).collect();
+        for _ in 1..k {
+            for e in 0..self.size {
+                for mask in 0..=full {
+                    if mask & 1 << e != 0 {
+                        counts[mask] += counts[mask ^ 1 << e];
+                    }
+                }
+            }
+            for (count, &downset) in counts.iter_mut().zip(downsets.iter()) {
+                if !downset {
+                    *count = 0;
+                }
+            }
+        }
+        counts[full]
+    }
+
+    /// Returns the value of the order polynomial at `k` by enumerating the
+    /// homomorphisms from the poset to the `k`-element chain with a SAT
+    /// solver.
+    pub fn order_polynomial_sat(&self, k: usize) -> usize {
+        let mut source = Stabilizer::new(SmallSet::new(self.size));
+        source.add_relation(2, self.order.clone());
+        let mut target = Stabilizer::new(SmallSet::new(k));
+        let chain = (0..k * k).map(|i| i % k >= i / k).collect();
+        target.add_relation(2, chain);
+        find_homomorphisms(&source, &target).len()
+    }
+}
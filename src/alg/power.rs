@@ -16,11 +16,12 @@
 */
 
 use super::{
-    BitSlice, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Group, Indexable,
-    Lattice, MeetSemilattice, Monoid, PartialOrder, Semigroup, Slice, Vector,
+    BitSlice, BitVec, BooleanLattice, BooleanLogic, BooleanSolver, BoundedOrder, DirectedGraph,
+    Domain, Group, Indexable, Lattice, MeetSemilattice, Monoid, PartialOrder, Semigroup, Slice,
+    Vector, XorShift,
 };
 
-use std::iter::{ExactSizeIterator, Extend, FusedIterator};
+use std::iter::{self, ExactSizeIterator, FusedIterator};
 
 /// A helper iterator to go through the parts of an element.
 pub struct PartIter<'a, ELEM>
@@ -108,6 +109,31 @@ where
         PartIter::new(elem, self.base().num_bits())
     }
 
+    /// Creates an element of the power from its parts.
+    pub fn make_elem<VEC, ITER>(&self, parts: ITER) -> VEC
+    where
+        VEC: Vector,
+        ITER: IntoIterator<Item = VEC>,
+    {
+        let mut result: VEC = Vector::with_capacity(self.num_bits());
+        for part in parts {
+            debug_assert_eq!(part.len(), self.base().num_bits());
+            result.extend(part);
+        }
+        debug_assert_eq!(result.len(), self.num_bits());
+        result
+    }
+
+    /// Returns copies of the parts of an element.
+    pub fn split_elem<'a, ELEM>(&self, elem: ELEM) -> Vec<ELEM::Vector>
+    where
+        ELEM: Slice<'a>,
+    {
+        self.part_iter(elem)
+            .map(|part| part.copy_iter().collect())
+            .collect()
+    }
+
     /// Returns the part of an element at the given index.
     pub fn part<'a, ELEM>(&self, elem: ELEM, index: usize) -> ELEM
     where
@@ -158,6 +184,20 @@ where
         result
     }
 
+    fn add_variable<LOGIC>(&self, logic: &mut LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanSolver,
+    {
+        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for _ in 0..self.exponent {
+            let part = self.base.add_variable(logic);
+            for lit in part.copy_iter() {
+                elem.push(lit);
+            }
+        }
+        elem
+    }
+
     fn equals<LOGIC>(
         &self,
         logic: &mut LOGIC,
@@ -167,6 +207,10 @@ where
     where
         LOGIC: BooleanLogic,
     {
+        if self.base.num_bits() == 1 {
+            return logic.bool_cmp_equ(elem0.copy_iter().zip(elem1.copy_iter()));
+        }
+
         let mut result = logic.bool_unit();
         for (part0, part1) in self.part_iter(elem0).zip(self.part_iter(elem1)) {
             let v = self.base.equals(logic, part0, part1);
@@ -195,13 +239,12 @@ where
     {
         let mut index = index;
         let base_size = self.base.size();
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for _ in 0..self.exponent {
-            let other = self.base.get_elem(logic, index % base_size);
-            result.extend(other);
+        let result = self.make_elem((0..self.exponent).map(|_| {
+            let part = self.base.get_elem(logic, index % base_size);
             index /= base_size;
-        }
-        assert!(index == 0 && result.len() == self.num_bits());
+            part
+        }));
+        assert!(index == 0);
         result
     }
 
@@ -218,12 +261,16 @@ where
         index
     }
 
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.make_elem((0..self.exponent).map(|_| self.base.random_elem(rng)))
+    }
+
     fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
         LOGIC: BooleanLogic,
     {
         let mut result: LOGIC::Vector = Vector::with_capacity(self.size());
-        let mut temp: LOGIC::Vector = Vector::new();
+        let mut temp = logic.bool_scratch();
 
         result.push(logic.bool_unit());
         for part in self.part_iter(elem) {
@@ -237,8 +284,10 @@ where
                     result.push(logic.bool_and(v0, v1));
                 }
             }
+            logic.bool_recycle(part);
         }
 
+        logic.bool_recycle(temp);
         debug_assert_eq!(result.len(), self.size());
         result
     }
@@ -277,11 +326,7 @@ where
         LOGIC: BooleanLogic,
     {
         let part = self.base.get_top(logic);
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for _ in 0..self.exponent {
-            elem.extend(part.copy_iter());
-        }
-        elem
+        self.make_elem(iter::repeat(part).take(self.exponent))
     }
 
     fn is_top<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -301,11 +346,7 @@ where
         LOGIC: BooleanLogic,
     {
         let part = self.base.get_bottom(logic);
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for _ in 0..self.exponent {
-            elem.extend(part.copy_iter());
-        }
-        elem
+        self.make_elem(iter::repeat(part).take(self.exponent))
     }
 
     fn is_bottom<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -334,11 +375,11 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for (part0, part1) in self.part_iter(elem0).zip(self.part_iter(elem1)) {
-            elem.extend(self.base.meet(logic, part0, part1));
-        }
-        elem
+        self.make_elem(
+            self.part_iter(elem0)
+                .zip(self.part_iter(elem1))
+                .map(|(part0, part1)| self.base.meet(logic, part0, part1)),
+        )
     }
 }
 
@@ -355,11 +396,11 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for (part0, part1) in self.part_iter(elem0).zip(self.part_iter(elem1)) {
-            elem.extend(self.base.join(logic, part0, part1));
-        }
-        elem
+        self.make_elem(
+            self.part_iter(elem0)
+                .zip(self.part_iter(elem1))
+                .map(|(part0, part1)| self.base.join(logic, part0, part1)),
+        )
     }
 }
 
@@ -371,11 +412,10 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for part in self.part_iter(elem) {
-            result.extend(self.base.complement(logic, part));
-        }
-        result
+        self.make_elem(
+            self.part_iter(elem)
+                .map(|part| self.base.complement(logic, part)),
+        )
     }
 }
 
@@ -392,11 +432,11 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for (part0, part1) in self.part_iter(elem0).zip(self.part_iter(elem1)) {
-            elem.extend(Semigroup::product(&self.base, logic, part0, part1));
-        }
-        elem
+        self.make_elem(
+            self.part_iter(elem0)
+                .zip(self.part_iter(elem1))
+                .map(|(part0, part1)| Semigroup::product(&self.base, logic, part0, part1)),
+        )
     }
 }
 
@@ -409,11 +449,7 @@ where
         LOGIC: BooleanLogic,
     {
         let part = self.base.get_identity(logic);
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for _ in 0..self.exponent {
-            elem.extend(part.copy_iter());
-        }
-        elem
+        self.make_elem(iter::repeat(part).take(self.exponent))
     }
 
     fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -437,10 +473,9 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        for part in self.part_iter(elem) {
-            result.extend(self.base.inverse(logic, part));
-        }
-        result
+        self.make_elem(
+            self.part_iter(elem)
+                .map(|part| self.base.inverse(logic, part)),
+        )
     }
 }
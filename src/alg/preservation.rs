@@ -17,7 +17,7 @@
 
 use super::{
     BipartiteGraph, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Indexable, MeetSemilattice,
-    Operations, Relations, Slice, Vector,
+    Operations, RelationRef, Relations, Slice, Vector,
 };
 
 /// The preservation relation bipartite graph between the domain
@@ -30,6 +30,8 @@ where
 {
     ops: Operations<DOM>,
     rels: Relations<DOM>,
+    op_ref: RelationRef<DOM>,
+    rel_ref: RelationRef<DOM>,
 }
 
 impl<DOM> Preservation<DOM>
@@ -38,12 +40,34 @@ where
 {
     /// Creates a new preservation relation over the given domain.
     pub fn new(domain: DOM, op_arity: usize, rel_arity: usize) -> Self {
+        Self::from_refs(
+            RelationRef::new(domain.clone(), op_arity),
+            RelationRef::new(domain, rel_arity),
+        )
+    }
+
+    /// Creates a new preservation relation from the descriptors of the
+    /// operations and relations, which must have the same domain.
+    pub fn from_refs(op_ref: RelationRef<DOM>, rel_ref: RelationRef<DOM>) -> Self {
+        assert!(op_ref.domain() == rel_ref.domain());
         Self {
-            ops: Operations::new(domain.clone(), op_arity),
-            rels: Relations::new(domain, rel_arity),
+            ops: Operations::from_ref(&op_ref),
+            rels: Relations::from_ref(&rel_ref),
+            op_ref,
+            rel_ref,
         }
     }
 
+    /// Returns the descriptor of the operations.
+    pub fn op_ref(&self) -> &RelationRef<DOM> {
+        &self.op_ref
+    }
+
+    /// Returns the descriptor of the relations.
+    pub fn rel_ref(&self) -> &RelationRef<DOM> {
+        &self.rel_ref
+    }
+
     /// Returns the underlying domain.
     pub fn domain(&self) -> &DOM {
         self.rels.domain()
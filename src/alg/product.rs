@@ -16,8 +16,8 @@
 */
 
 use super::{
-    BitSlice, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Group, Indexable,
-    Lattice, MeetSemilattice, Monoid, PartialOrder, Semigroup, Slice, Vector,
+    BitSlice, BitVec, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain, Group,
+    Indexable, Lattice, MeetSemilattice, Monoid, PartialOrder, Semigroup, Slice, Vector, XorShift,
 };
 
 /// The product of two domains.
@@ -63,6 +63,29 @@ where
         debug_assert_eq!(result.len(), self.dom1().num_bits());
         result
     }
+
+    /// Creates an element of the product from its two parts.
+    pub fn make_elem<VEC>(&self, part0: VEC, part1: VEC) -> VEC
+    where
+        VEC: Vector,
+    {
+        debug_assert_eq!(part0.len(), self.dom0().num_bits());
+        debug_assert_eq!(part1.len(), self.dom1().num_bits());
+        let mut result: VEC = Vector::with_capacity(self.num_bits());
+        result.extend(part0);
+        result.extend(part1);
+        result
+    }
+
+    /// Returns copies of the two parts of an element.
+    pub fn split_elem<'a, ELEM>(&self, elem: ELEM) -> (ELEM::Vector, ELEM::Vector)
+    where
+        ELEM: Slice<'a>,
+    {
+        let part0 = self.part0(elem).copy_iter().collect();
+        let part1 = self.part1(elem).copy_iter().collect();
+        (part0, part1)
+    }
 }
 
 impl<DOM0, DOM1> Domain for Product2<DOM0, DOM1>
@@ -131,11 +154,9 @@ where
         LOGIC: BooleanLogic,
     {
         let size0 = self.dom0.size();
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        result.extend(self.dom0.get_elem(logic, index % size0));
-        result.extend(self.dom1.get_elem(logic, index / size0));
-        debug_assert!(result.len() == self.num_bits());
-        result
+        let part0 = self.dom0.get_elem(logic, index % size0);
+        let part1 = self.dom1.get_elem(logic, index / size0);
+        self.make_elem(part0, part1)
     }
 
     fn get_index(&self, elem: BitSlice<'_>) -> usize {
@@ -147,6 +168,12 @@ where
         part0 + size0 * self.dom1.get_index(elem.tail(bits0))
     }
 
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        let part0 = self.dom0.random_elem(rng);
+        let part1 = self.dom1.random_elem(rng);
+        self.make_elem(part0, part1)
+    }
+
     fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
         LOGIC: BooleanLogic,
@@ -208,10 +235,9 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.append(&mut self.dom0.get_top(logic));
-        elem.append(&mut self.dom1.get_top(logic));
-        elem
+        let part0 = self.dom0.get_top(logic);
+        let part1 = self.dom1.get_top(logic);
+        self.make_elem(part0, part1)
     }
 
     fn is_top<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -228,10 +254,9 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.append(&mut self.dom0.get_bottom(logic));
-        elem.append(&mut self.dom1.get_bottom(logic));
-        elem
+        let part0 = self.dom0.get_bottom(logic);
+        let part1 = self.dom1.get_bottom(logic);
+        self.make_elem(part0, part1)
     }
 
     fn is_bottom<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -260,10 +285,9 @@ where
         LOGIC: BooleanLogic,
     {
         let bits0 = self.dom0.num_bits();
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.extend(self.dom0.meet(logic, elem0.head(bits0), elem1.head(bits0)));
-        elem.extend(self.dom1.meet(logic, elem0.tail(bits0), elem1.tail(bits0)));
-        elem
+        let part0 = self.dom0.meet(logic, elem0.head(bits0), elem1.head(bits0));
+        let part1 = self.dom1.meet(logic, elem0.tail(bits0), elem1.tail(bits0));
+        self.make_elem(part0, part1)
     }
 }
 
@@ -282,10 +306,9 @@ where
         LOGIC: BooleanLogic,
     {
         let bits0 = self.dom0.num_bits();
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.extend(self.dom0.join(logic, elem0.head(bits0), elem1.head(bits0)));
-        elem.extend(self.dom1.join(logic, elem0.tail(bits0), elem1.tail(bits0)));
-        elem
+        let part0 = self.dom0.join(logic, elem0.head(bits0), elem1.head(bits0));
+        let part1 = self.dom1.join(logic, elem0.tail(bits0), elem1.tail(bits0));
+        self.make_elem(part0, part1)
     }
 }
 
@@ -299,10 +322,9 @@ where
         LOGIC: BooleanLogic,
     {
         let bits0 = self.dom0.num_bits();
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        result.extend(self.dom0.complement(logic, elem.head(bits0)));
-        result.extend(self.dom1.complement(logic, elem.tail(bits0)));
-        result
+        let part0 = self.dom0.complement(logic, elem.head(bits0));
+        let part1 = self.dom1.complement(logic, elem.tail(bits0));
+        self.make_elem(part0, part1)
     }
 }
 
@@ -321,20 +343,9 @@ where
         LOGIC: BooleanLogic,
     {
         let bits0 = self.dom0.num_bits();
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.extend(Semigroup::product(
-            &self.dom0,
-            logic,
-            elem0.head(bits0),
-            elem1.head(bits0),
-        ));
-        elem.extend(Semigroup::product(
-            &self.dom1,
-            logic,
-            elem0.tail(bits0),
-            elem1.tail(bits0),
-        ));
-        elem
+        let part0 = Semigroup::product(&self.dom0, logic, elem0.head(bits0), elem1.head(bits0));
+        let part1 = Semigroup::product(&self.dom1, logic, elem0.tail(bits0), elem1.tail(bits0));
+        self.make_elem(part0, part1)
     }
 }
 
@@ -347,10 +358,9 @@ where
     where
         LOGIC: BooleanLogic,
     {
-        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        elem.append(&mut self.dom0.get_identity(logic));
-        elem.append(&mut self.dom1.get_identity(logic));
-        elem
+        let part0 = self.dom0.get_identity(logic);
+        let part1 = self.dom1.get_identity(logic);
+        self.make_elem(part0, part1)
     }
 
     fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
@@ -374,9 +384,403 @@ where
         LOGIC: BooleanLogic,
     {
         let bits0 = self.dom0.num_bits();
-        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
-        result.extend(self.dom0.inverse(logic, elem.head(bits0)));
-        result.extend(self.dom1.inverse(logic, elem.tail(bits0)));
+        let part0 = self.dom0.inverse(logic, elem.head(bits0));
+        let part1 = self.dom1.inverse(logic, elem.tail(bits0));
+        self.make_elem(part0, part1)
+    }
+}
+
+/// The product of an arbitrary number of domains of the same type, like
+/// small sets of different sizes. The elements are the concatenations of
+/// the elements of the factors, and are displayed as flat tuples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductN<DOM> {
+    doms: Vec<DOM>,
+    offsets: Vec<usize>,
+}
+
+impl<DOM> ProductN<DOM>
+where
+    DOM: Domain,
+{
+    /// Creates the product of the given domains.
+    pub fn new(doms: Vec<DOM>) -> Self {
+        let mut offsets = Vec::with_capacity(doms.len() + 1);
+        offsets.push(0);
+        for dom in doms.iter() {
+            offsets.push(offsets.last().unwrap() + dom.num_bits());
+        }
+        Self { doms, offsets }
+    }
+
+    /// Returns the factors of the product.
+    pub fn doms(&self) -> &[DOM] {
+        &self.doms
+    }
+
+    /// Returns the part of an element belonging to the given factor.
+    pub fn part<'a, ELEM>(&self, elem: ELEM, index: usize) -> ELEM
+    where
+        ELEM: Slice<'a>,
+    {
+        debug_assert_eq!(elem.len(), self.num_bits());
+        elem.range(self.offsets[index], self.offsets[index + 1])
+    }
+
+    /// Creates an element of the product from its parts.
+    pub fn make_elem<VEC, ITER>(&self, parts: ITER) -> VEC
+    where
+        VEC: Vector,
+        ITER: IntoIterator<Item = VEC>,
+    {
+        let mut result: VEC = Vector::with_capacity(self.num_bits());
+        for (dom, part) in self.doms.iter().zip(parts) {
+            debug_assert_eq!(part.len(), dom.num_bits());
+            result.extend(part);
+        }
+        debug_assert_eq!(result.len(), self.num_bits());
+        result
+    }
+
+    /// Returns copies of the parts of an element.
+    pub fn split_elem<'a, ELEM>(&self, elem: ELEM) -> Vec<ELEM::Vector>
+    where
+        ELEM: Slice<'a>,
+    {
+        (0..self.doms.len())
+            .map(|i| self.part(elem, i).copy_iter().collect())
+            .collect()
+    }
+
+    /// Calculates the conjunction of the given test on the parts of an
+    /// element.
+    fn fold_test<LOGIC, TEST>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        mut test: TEST,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+        TEST: FnMut(&DOM, &mut LOGIC, LOGIC::Slice<'_>) -> LOGIC::Elem,
+    {
+        let mut result = logic.bool_unit();
+        for (i, dom) in self.doms.iter().enumerate() {
+            let v = test(dom, logic, self.part(elem, i));
+            result = logic.bool_and(result, v);
+        }
+        result
+    }
+
+    /// Calculates the conjunction of the given test on the pairs of parts
+    /// of two elements.
+    fn fold_test2<LOGIC, TEST>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+        mut test: TEST,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+        TEST: FnMut(&DOM, &mut LOGIC, LOGIC::Slice<'_>, LOGIC::Slice<'_>) -> LOGIC::Elem,
+    {
+        let mut result = logic.bool_unit();
+        for (i, dom) in self.doms.iter().enumerate() {
+            let v = test(dom, logic, self.part(elem0, i), self.part(elem1, i));
+            result = logic.bool_and(result, v);
+        }
         result
     }
+
+    /// Applies the given binary operation on the pairs of parts of two
+    /// elements.
+    fn map_op2<LOGIC, OP>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+        mut op: OP,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+        OP: FnMut(&DOM, &mut LOGIC, LOGIC::Slice<'_>, LOGIC::Slice<'_>) -> LOGIC::Vector,
+    {
+        let parts: Vec<LOGIC::Vector> = self
+            .doms
+            .iter()
+            .enumerate()
+            .map(|(i, dom)| op(dom, logic, self.part(elem0, i), self.part(elem1, i)))
+            .collect();
+        self.make_elem(parts)
+    }
+}
+
+impl<DOM> Domain for ProductN<DOM>
+where
+    DOM: Domain,
+{
+    fn num_bits(&self) -> usize {
+        *self.offsets.last().unwrap()
+    }
+
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "(")?;
+        for (i, dom) in self.doms.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            dom.display_elem(f, self.part(elem, i))?;
+        }
+        write!(f, ")")
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test(logic, elem, |dom, logic, part| dom.contains(logic, part))
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test2(logic, elem0, elem1, |dom, logic, part0, part1| {
+            dom.equals(logic, part0, part1)
+        })
+    }
+}
+
+impl<DOM> Indexable for ProductN<DOM>
+where
+    DOM: Indexable,
+{
+    fn size(&self) -> usize {
+        self.doms.iter().map(|dom| dom.size()).product()
+    }
+
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, mut index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let parts: Vec<LOGIC::Vector> = self
+            .doms
+            .iter()
+            .map(|dom| {
+                let part = dom.get_elem(logic, index % dom.size());
+                index /= dom.size();
+                part
+            })
+            .collect();
+        self.make_elem(parts)
+    }
+
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        debug_assert!(elem.len() == self.num_bits());
+        let mut index = 0;
+        for (i, dom) in self.doms.iter().enumerate().rev() {
+            index = index * dom.size() + dom.get_index(self.part(elem, i));
+        }
+        index
+    }
+
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.make_elem(self.doms.iter().map(|dom| dom.random_elem(rng)))
+    }
+
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result: LOGIC::Vector = Vector::from_elem(logic.bool_unit());
+        for (i, dom) in self.doms.iter().enumerate() {
+            let part = dom.onehot(logic, self.part(elem, i));
+            let mut next: LOGIC::Vector = Vector::with_capacity(result.len() * part.len());
+            for v1 in part.copy_iter() {
+                for v0 in result.copy_iter() {
+                    next.push(logic.bool_and(v0, v1));
+                }
+            }
+            result = next;
+        }
+
+        debug_assert_eq!(result.len(), self.size());
+        result
+    }
+}
+
+impl<DOM> DirectedGraph for ProductN<DOM>
+where
+    DOM: DirectedGraph,
+{
+    fn is_edge<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test2(logic, elem0, elem1, |dom, logic, part0, part1| {
+            dom.is_edge(logic, part0, part1)
+        })
+    }
+}
+
+impl<DOM> PartialOrder for ProductN<DOM> where DOM: PartialOrder {}
+
+impl<DOM> BoundedOrder for ProductN<DOM>
+where
+    DOM: BoundedOrder,
+{
+    fn get_top<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.make_elem(self.doms.iter().map(|dom| dom.get_top(logic)))
+    }
+
+    fn is_top<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test(logic, elem, |dom, logic, part| dom.is_top(logic, part))
+    }
+
+    fn get_bottom<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.make_elem(self.doms.iter().map(|dom| dom.get_bottom(logic)))
+    }
+
+    fn is_bottom<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test(logic, elem, |dom, logic, part| dom.is_bottom(logic, part))
+    }
+}
+
+impl<DOM> MeetSemilattice for ProductN<DOM>
+where
+    DOM: MeetSemilattice,
+{
+    fn meet<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.map_op2(logic, elem0, elem1, |dom, logic, part0, part1| {
+            dom.meet(logic, part0, part1)
+        })
+    }
+}
+
+impl<DOM> Lattice for ProductN<DOM>
+where
+    DOM: Lattice,
+{
+    fn join<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.map_op2(logic, elem0, elem1, |dom, logic, part0, part1| {
+            dom.join(logic, part0, part1)
+        })
+    }
+}
+
+impl<DOM> BooleanLattice for ProductN<DOM>
+where
+    DOM: BooleanLattice,
+{
+    fn complement<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let parts: Vec<LOGIC::Vector> = self
+            .doms
+            .iter()
+            .enumerate()
+            .map(|(i, dom)| dom.complement(logic, self.part(elem, i)))
+            .collect();
+        self.make_elem(parts)
+    }
+}
+
+impl<DOM> Semigroup for ProductN<DOM>
+where
+    DOM: Semigroup,
+{
+    fn product<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.map_op2(logic, elem0, elem1, |dom, logic, part0, part1| {
+            Semigroup::product(dom, logic, part0, part1)
+        })
+    }
+}
+
+impl<DOM> Monoid for ProductN<DOM>
+where
+    DOM: Monoid,
+{
+    fn get_identity<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.make_elem(self.doms.iter().map(|dom| dom.get_identity(logic)))
+    }
+
+    fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.fold_test(logic, elem, |dom, logic, part| dom.is_identity(logic, part))
+    }
+}
+
+impl<DOM> Group for ProductN<DOM>
+where
+    DOM: Group,
+{
+    fn inverse<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let parts: Vec<LOGIC::Vector> = self
+            .doms
+            .iter()
+            .enumerate()
+            .map(|(i, dom)| dom.inverse(logic, self.part(elem, i)))
+            .collect();
+        self.make_elem(parts)
+    }
 }
@@ -16,10 +16,72 @@
 */
 
 use super::{
-    BitSlice, Boolean, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain,
-    Indexable, Lattice, MeetSemilattice, PartIter, PartialOrder, Power, Slice, Vector,
+    BitSlice, BitVec, Boolean, BooleanLattice, BooleanLogic, BoundedOrder, DirectedGraph, Domain,
+    Indexable, Lattice, MeetSemilattice, PartIter, PartialOrder, Power, Slice, Vector, XorShift,
 };
 
+/// A descriptor of relations or operations of a fixed arity over a common
+/// domain, with an optional name used in reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationRef<DOM>
+where
+    DOM: Indexable,
+{
+    domain: DOM,
+    arity: usize,
+    name: Option<String>,
+}
+
+impl<DOM> RelationRef<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates a new unnamed descriptor with the given domain and arity.
+    pub fn new(domain: DOM, arity: usize) -> Self {
+        Self {
+            domain,
+            arity,
+            name: None,
+        }
+    }
+
+    /// Returns the same descriptor with the given name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Returns the underlying domain.
+    pub fn domain(&self) -> &DOM {
+        &self.domain
+    }
+
+    /// Returns the arity.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the name, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl<DOM> std::fmt::Display for RelationRef<DOM>
+where
+    DOM: Indexable,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} on {} elements",
+            self.name().unwrap_or("_"),
+            self.arity,
+            self.domain.size()
+        )
+    }
+}
+
 /// A domain containing relations of a fixed arity.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Relations<DOM>
@@ -50,6 +112,16 @@ where
         }
     }
 
+    /// Creates a new domain of relations described by the given descriptor.
+    pub fn from_ref(desc: &RelationRef<DOM>) -> Self {
+        Self::new(desc.domain().clone(), desc.arity())
+    }
+
+    /// Returns the unnamed descriptor of the relations in this domain.
+    pub fn to_ref(&self) -> RelationRef<DOM> {
+        RelationRef::new(self.domain.clone(), self.arity)
+    }
+
     /// Returns the arity (rank) of all relations in the domain.
     pub fn arity(&self) -> usize {
         self.arity
@@ -417,6 +489,11 @@ where
         self.power.get_index(elem)
     }
 
+    #[inline]
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.power.random_elem(rng)
+    }
+
     #[inline]
     fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
@@ -0,0 +1,170 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, Domain, Indexable, Logic, Power, Relations, Slice, Tuples,
+    Vector,
+};
+
+/// The domain of relations of a fixed arity with a fixed number of tuples,
+/// where each relation is encoded as the strictly increasing list of its
+/// tuples. This is much more compact than the dense bit-per-tuple encoding
+/// of `Relations` when the relations are small but the arity is large.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseRelations<DOM>
+where
+    DOM: Indexable,
+{
+    dense: Relations<DOM>,
+    tuples: Power<Tuples<DOM>>,
+}
+
+impl<DOM> SparseRelations<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the domain of relations of the given arity containing
+    /// exactly `count` many tuples.
+    pub fn new(dom: DOM, arity: usize, count: usize) -> Self {
+        let tuples = Power::new(Tuples::new(dom, arity), count);
+        let dense = tuples.base().relations();
+        Self { dense, tuples }
+    }
+
+    /// Returns the underlying domain of the relations.
+    pub fn domain(&self) -> &DOM {
+        self.dense.domain()
+    }
+
+    /// Returns the arity of the relations.
+    pub fn arity(&self) -> usize {
+        self.dense.arity()
+    }
+
+    /// Returns the number of tuples in each relation.
+    pub fn count(&self) -> usize {
+        self.tuples.exponent()
+    }
+
+    /// Returns the domain of the corresponding dense relations.
+    pub fn dense(&self) -> &Relations<DOM> {
+        &self.dense
+    }
+
+    /// Returns the domain of the tuples of the relations.
+    pub fn tuples(&self) -> &Tuples<DOM> {
+        self.tuples.base()
+    }
+
+    /// Returns the tuple of the relation with the given index.
+    pub fn tuple<'a, ELEM>(&self, elem: ELEM, index: usize) -> ELEM
+    where
+        ELEM: Slice<'a>,
+    {
+        self.tuples.part(elem, index)
+    }
+
+    /// Returns the dense encoding of the given sparse relation.
+    pub fn to_dense<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result: LOGIC::Vector =
+            Vector::with_values(self.dense.num_bits(), logic.bool_zero());
+        for tuple in self.tuples.part_iter(elem) {
+            let onehot = self.tuples().to_singleton(logic, tuple);
+            for (i, a) in onehot.copy_iter().enumerate() {
+                let b = logic.bool_or(result.get(i), a);
+                result.set(i, b);
+            }
+        }
+        result
+    }
+
+    /// Returns true if the given sparse relation has the same tuples as
+    /// the given dense relation.
+    pub fn is_dense_of<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        dense: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let other = self.to_dense(logic, elem);
+        self.dense.equals(logic, other.slice(), dense)
+    }
+
+    /// Returns the sparse encoding of the given dense relation, or `None`
+    /// if the relation does not have the right number of tuples.
+    pub fn from_dense(&self, dense: BitSlice<'_>) -> Option<BitVec> {
+        assert_eq!(dense.len(), self.dense.num_bits());
+        let logic = Logic();
+        let mut tuples: Vec<BitVec> = dense
+            .copy_iter()
+            .enumerate()
+            .filter(|(_, b)| *b)
+            .map(|(i, _)| self.tuples().get_elem(&logic, i))
+            .collect();
+        if tuples.len() != self.count() {
+            return None;
+        }
+
+        tuples.sort_by_key(|t| t.copy_iter().rev().collect::<Vec<bool>>());
+        let mut result: BitVec = Vector::with_capacity(self.num_bits());
+        for tuple in tuples {
+            result.extend(tuple.copy_iter());
+        }
+        Some(result)
+    }
+}
+
+impl<DOM> Domain for SparseRelations<DOM>
+where
+    DOM: Indexable,
+{
+    fn num_bits(&self) -> usize {
+        self.tuples.num_bits()
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = self.tuples.contains(logic, elem);
+        for index in 1..self.count() {
+            let tuple0 = self.tuple(elem, index - 1);
+            let tuple1 = self.tuple(elem, index);
+            let test = self.tuples().precedes(logic, tuple0, tuple1);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.tuples.equals(logic, elem0, elem1)
+    }
+}
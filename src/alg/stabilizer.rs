@@ -0,0 +1,461 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, DirectedGraph, Domain, Group, Indexable,
+    MeetSemilattice, Monoid, PermutationGroup, Relations, Semigroup, Slice, Solver, SymmetricGroup,
+    Vector,
+};
+
+/// The automorphism group of a relational structure, which is the
+/// stabilizer of its relations. Create it with `new` over the universe,
+/// add the relations of the structure with `add_relation`, then use
+/// `find_all` for the automorphisms, `generators` for an irredundant
+/// generating set and `order` for the size of the group.
+pub type Automorphisms<DOM> = Stabilizer<DOM>;
+
+/// The subgroup of the symmetric group of an indexable domain consisting
+/// of those permutations that map each of the given relations onto itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    group: SymmetricGroup<DOM>,
+    relations: Vec<(Relations<DOM>, BitVec)>,
+}
+
+impl<DOM> Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the stabilizer of the empty structure, which is the full
+    /// symmetric group of the given domain.
+    pub fn new(dom: DOM) -> Self {
+        Self {
+            group: SymmetricGroup::new(dom),
+            relations: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying domain of the permutations.
+    pub fn domain(&self) -> &DOM {
+        self.group.domain()
+    }
+
+    /// Returns the symmetric group containing this stabilizer.
+    pub fn group(&self) -> &SymmetricGroup<DOM> {
+        &self.group
+    }
+
+    /// Adds a new relation of the given arity that must be stabilized.
+    pub fn add_relation(&mut self, arity: usize, relation: BitVec) {
+        let rels = Relations::new(self.domain().clone(), arity);
+        assert_eq!(relation.len(), rels.num_bits());
+        self.relations.push((rels, relation));
+    }
+
+    /// Returns the list of stabilized relations with their arities.
+    pub fn relations(&self) -> impl Iterator<Item = (usize, BitSlice<'_>)> {
+        self.relations
+            .iter()
+            .map(|(rels, relation)| (rels.arity(), relation.slice()))
+    }
+
+    /// Returns the image of the relation under the given permutation.
+    pub fn image<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        rels: &Relations<DOM>,
+        elem: LOGIC::Slice<'_>,
+        relation: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        debug_assert_eq!(elem.len(), self.group.num_bits());
+        debug_assert_eq!(relation.len(), rels.num_bits());
+
+        let arity = rels.arity();
+        let dom = rels.change_arity(2 * arity);
+        let mapping: Vec<usize> = (0..arity).collect();
+        let mut result = rels.polymer(relation, 2 * arity, &mapping);
+
+        let perm = rels.change_arity(2);
+        for i in 0..arity {
+            let tmp = perm.polymer(elem, 2 * arity, &[arity + i, i]);
+            result = dom.meet(logic, result.slice(), tmp.slice());
+        }
+
+        dom.fold_any(logic, result.slice(), arity)
+    }
+
+    /// Returns true if the given permutation maps all relations onto
+    /// themselves. Since the relations are finite, it is enough to check
+    /// that the images are subsets of the original relations.
+    pub fn stabilizes<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for (rels, relation) in self.relations.iter() {
+            let relation = rels.lift(logic, relation.slice());
+            let image = self.image(logic, rels, elem, relation.slice());
+            let test = rels.is_edge(logic, image.slice(), relation.slice());
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns an irredundant generating set of the stabilizer, which is
+    /// selected from a strong generating set found by SAT queries. Membership
+    /// in the already generated subgroup is decided with the help of a
+    /// stabilizer chain maintained by the Schreier-Sims algorithm.
+    pub fn generators(&self) -> Vec<BitVec> {
+        let size = self.domain().size();
+        let mut chain = StabilizerChain::new(size);
+        self.strong_generators()
+            .0
+            .into_iter()
+            .filter(|elem| chain.extend(Self::permutation(elem.slice(), size)))
+            .collect()
+    }
+
+    /// Returns the order of the stabilizer, which is the product of the
+    /// orbit lengths of the points along the chain of pointwise stabilizers,
+    /// or `None` if it does not fit into an `usize`.
+    pub fn order(&self) -> Option<usize> {
+        self.strong_generators()
+            .1
+            .into_iter()
+            .try_fold(1usize, |acc, len| acc.checked_mul(len))
+    }
+
+    /// Returns all elements of the stabilizer enumerated by a SAT solver.
+    pub fn find_all(&self) -> Vec<BitVec> {
+        let mut logic = Solver::new("");
+        let elem = self.add_variable(&mut logic);
+        let test = self.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let result = logic.bool_all_models(elem.copy_iter()).collect();
+        result
+    }
+
+    /// Returns a strong generating set of the stabilizer relative to the
+    /// base `0, 1, ...` together with the orbit lengths of the base points.
+    /// The levels are processed backwards, so the generators found so far
+    /// fix the previous points, and a SAT query is made only for the points
+    /// outside of the orbit they generate.
+    fn strong_generators(&self) -> (Vec<BitVec>, Vec<usize>) {
+        let size = self.domain().size();
+        let mut logic = Solver::new("");
+        let elem = self.add_variable(&mut logic);
+        let test = self.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+
+        let mut result = Vec::new();
+        let mut perms: Vec<Vec<usize>> = Vec::new();
+        let mut lengths = vec![0; size];
+        let mut assumptions = Vec::with_capacity(size);
+        for level in (0..size).rev() {
+            assumptions.clear();
+            assumptions.extend((0..level).map(|i| elem.get(i + i * size)));
+            let mut orbit = Self::orbit(&perms, level, size);
+            for target in level + 1..size {
+                if orbit[target] {
+                    continue;
+                }
+                assumptions.push(elem.get(target + level * size));
+                if let Some(model) = logic.bool_find_one_model(&assumptions, elem.copy_iter()) {
+                    perms.push(Self::permutation(model.slice(), size));
+                    result.push(model);
+                    orbit = Self::orbit(&perms, level, size);
+                }
+                assumptions.pop();
+            }
+            lengths[level] = orbit.iter().filter(|&&b| b).count();
+        }
+
+        (result, lengths)
+    }
+
+    /// Returns the list of images of the given concrete permutation.
+    fn permutation(elem: BitSlice<'_>, size: usize) -> Vec<usize> {
+        (0..size)
+            .map(|i| (0..size).find(|&j| elem.get(j + i * size)).unwrap())
+            .collect()
+    }
+
+    /// Returns the characteristic vector of the orbit of the given point
+    /// under the group generated by the given permutations.
+    fn orbit(perms: &[Vec<usize>], point: usize, size: usize) -> Vec<bool> {
+        let mut result = vec![false; size];
+        result[point] = true;
+        let mut stack = vec![point];
+        while let Some(a) = stack.pop() {
+            for perm in perms.iter() {
+                if !result[perm[a]] {
+                    result[perm[a]] = true;
+                    stack.push(perm[a]);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<DOM> Domain for Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn num_bits(&self) -> usize {
+        self.group.num_bits()
+    }
+
+    #[inline]
+    fn display_elem(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        elem: BitSlice<'_>,
+    ) -> std::fmt::Result {
+        self.group.display_elem(f, elem)
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let test0 = self.group.contains(logic, elem);
+        let test1 = self.stabilizes(logic, elem);
+        logic.bool_and(test0, test1)
+    }
+
+    #[inline]
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.equals(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> Semigroup for Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn product<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.product(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> Monoid for Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn get_identity<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.get_identity(logic)
+    }
+
+    #[inline]
+    fn is_identity<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.is_identity(logic, elem)
+    }
+}
+
+impl<DOM> Group for Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn inverse<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.inverse(logic, elem)
+    }
+}
+
+impl<DOM> PermutationGroup for Stabilizer<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn degree(&self) -> usize {
+        self.group.degree()
+    }
+
+    #[inline]
+    fn as_permutation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.group.as_permutation(logic, elem)
+    }
+}
+
+/// A stabilizer chain of a concrete permutation group given by a base and
+/// a strong generating set. Permutations are stored as lists of images.
+#[derive(Debug)]
+struct StabilizerChain {
+    size: usize,
+    base: Vec<usize>,
+    strong: Vec<Vec<usize>>,
+    transversals: Vec<Vec<Option<Vec<usize>>>>,
+}
+
+impl StabilizerChain {
+    /// Creates the stabilizer chain of the trivial group.
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            base: Vec::new(),
+            strong: Vec::new(),
+            transversals: Vec::new(),
+        }
+    }
+
+    /// Returns the permutation that first applies the first then the second.
+    fn multiply(perm0: &[usize], perm1: &[usize]) -> Vec<usize> {
+        perm0.iter().map(|&i| perm1[i]).collect()
+    }
+
+    /// Returns the inverse of the given permutation.
+    fn invert(perm: &[usize]) -> Vec<usize> {
+        let mut result = vec![0; perm.len()];
+        for (i, &j) in perm.iter().enumerate() {
+            result[j] = i;
+        }
+        result
+    }
+
+    /// Returns true if the permutation is the identity.
+    fn is_identity(perm: &[usize]) -> bool {
+        perm.iter().enumerate().all(|(i, &j)| i == j)
+    }
+
+    /// Recalculates the orbit transversals of all levels.
+    fn update_transversals(&mut self) {
+        self.transversals.clear();
+        for level in 0..self.base.len() {
+            let gens: Vec<&Vec<usize>> = self
+                .strong
+                .iter()
+                .filter(|g| self.base[..level].iter().all(|&b| g[b] == b))
+                .collect();
+
+            let mut transversal = vec![None; self.size];
+            transversal[self.base[level]] = Some((0..self.size).collect::<Vec<usize>>());
+            let mut orbit = vec![self.base[level]];
+            let mut index = 0;
+            while index < orbit.len() {
+                let point = orbit[index];
+                index += 1;
+                for g in gens.iter() {
+                    if transversal[g[point]].is_none() {
+                        let elem = Self::multiply(transversal[point].as_ref().unwrap(), g);
+                        transversal[g[point]] = Some(elem);
+                        orbit.push(g[point]);
+                    }
+                }
+            }
+            self.transversals.push(transversal);
+        }
+    }
+
+    /// Sifts the given permutation through the chain and returns the
+    /// residue, which is the identity if and only if the permutation
+    /// belongs to the group.
+    fn sift(&self, mut perm: Vec<usize>) -> Vec<usize> {
+        for (level, &point) in self.base.iter().enumerate() {
+            match &self.transversals[level][perm[point]] {
+                Some(elem) => perm = Self::multiply(&perm, &Self::invert(elem)),
+                None => break,
+            }
+        }
+        perm
+    }
+
+    /// Adds a new strong generator, extending the base if needed.
+    fn add_strong(&mut self, perm: Vec<usize>) {
+        if self.base.iter().all(|&b| perm[b] == b) {
+            let point = (0..self.size).find(|&i| perm[i] != i).unwrap();
+            self.base.push(point);
+        }
+        self.strong.push(perm);
+        self.update_transversals();
+    }
+
+    /// Extends the group with the given permutation and returns true if it
+    /// was not already a member of the group.
+    fn extend(&mut self, perm: Vec<usize>) -> bool {
+        let perm = self.sift(perm);
+        if Self::is_identity(&perm) {
+            return false;
+        }
+
+        self.add_strong(perm);
+        'outer: loop {
+            for level in 0..self.base.len() {
+                let gens: Vec<Vec<usize>> = self
+                    .strong
+                    .iter()
+                    .filter(|g| self.base[..level].iter().all(|&b| g[b] == b))
+                    .cloned()
+                    .collect();
+                for point in 0..self.size {
+                    let elem0 = match &self.transversals[level][point] {
+                        Some(elem) => elem,
+                        None => continue,
+                    };
+                    for g in gens.iter() {
+                        let elem1 = self.transversals[level][g[point]].as_ref().unwrap();
+                        let schreier =
+                            Self::multiply(&Self::multiply(elem0, g), &Self::invert(elem1));
+                        let residue = self.sift(schreier);
+                        if !Self::is_identity(&residue) {
+                            self.add_strong(residue);
+                            continue 'outer;
+                        }
+                    }
+                }
+            }
+            return true;
+        }
+    }
+}
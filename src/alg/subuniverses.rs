@@ -0,0 +1,229 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, BoundedOrder, DirectedGraph, Domain, Indexable,
+    Lattice, Logic, MeetSemilattice, Operations, PartialOrder, Preservation, Relations, Slice,
+    Solver, Vector,
+};
+
+/// The domain of subuniverses of a finite algebra, which are the subsets
+/// of the underlying set closed under each of the given operations. The
+/// subuniverses are represented as unary relations and ordered by
+/// inclusion, and they form a lattice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    subsets: Relations<DOM>,
+    operations: Vec<(Operations<DOM>, BitVec)>,
+}
+
+impl<DOM> Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the domain of subuniverses of the algebra without operations,
+    /// which are all subsets of the given domain.
+    pub fn new(domain: DOM) -> Self {
+        Self {
+            subsets: Relations::new(domain, 1),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying domain of the algebra.
+    pub fn domain(&self) -> &DOM {
+        self.subsets.domain()
+    }
+
+    /// Adds a new operation of the given arity to the algebra.
+    pub fn add_operation(&mut self, arity: usize, operation: BitVec) {
+        let ops = Operations::new(self.domain().clone(), arity);
+        assert_eq!(operation.len(), ops.num_bits());
+        self.operations.push((ops, operation));
+    }
+
+    /// Returns the list of operations with their arities.
+    pub fn operations(&self) -> impl Iterator<Item = (usize, BitSlice<'_>)> {
+        self.operations
+            .iter()
+            .map(|(ops, operation)| (ops.arity(), operation.slice()))
+    }
+
+    /// Returns the union of the given subset and its images under all
+    /// operations.
+    fn closure_step<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result: LOGIC::Vector = elem.copy_iter().collect();
+        for (ops, operation) in self.operations.iter() {
+            let pres = Preservation::new(self.domain().clone(), ops.arity(), 1);
+            let operation = ops.lift(logic, operation.slice());
+            let relations = vec![elem; ops.arity()];
+            let image = pres.evaluate(logic, operation.slice(), &relations);
+            result = self.subsets.join(logic, result.slice(), image.slice());
+        }
+        result
+    }
+
+    /// Returns the subuniverse generated by the given subset, which is the
+    /// least fixed point of the closure step above the subset.
+    pub fn generate<LOGIC>(&self, logic: &mut LOGIC, subset: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(subset.len(), self.subsets.num_bits());
+        let mut result = self.closure_step(logic, subset);
+        for _ in 1..self.domain().size() {
+            result = self.closure_step(logic, result.slice());
+        }
+        result
+    }
+
+    /// Returns all subuniverses of the algebra.
+    pub fn find_all(&self) -> Vec<BitVec> {
+        let mut logic = Solver::new("");
+        let elem = self.add_variable(&mut logic);
+        let test = self.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let result = logic.bool_all_models(elem.copy_iter()).collect();
+        result
+    }
+
+    /// Returns the number of subuniverses of the algebra.
+    pub fn count(&self) -> usize {
+        let mut logic = Solver::new("");
+        let elem = self.add_variable(&mut logic);
+        let test = self.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_num_models_method1(elem.copy_iter())
+    }
+}
+
+impl<DOM> Domain for Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    fn num_bits(&self) -> usize {
+        self.subsets.num_bits()
+    }
+
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for (ops, operation) in self.operations.iter() {
+            let pres = Preservation::new(self.domain().clone(), ops.arity(), 1);
+            let operation = ops.lift(logic, operation.slice());
+            let test = pres.preserves(logic, operation.slice(), elem);
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.subsets.equals(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> DirectedGraph for Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    fn is_edge<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.subsets.is_edge(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> PartialOrder for Subuniverses<DOM> where DOM: Indexable {}
+
+impl<DOM> BoundedOrder for Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    fn get_top<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.subsets.get_top(logic)
+    }
+
+    fn get_bottom<LOGIC>(&self, logic: &LOGIC) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut calc = Logic();
+        let empty = self.subsets.get_bottom(&calc);
+        let bottom = self.generate(&mut calc, empty.slice());
+        self.lift(logic, bottom.slice())
+    }
+}
+
+impl<DOM> MeetSemilattice for Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    fn meet<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.subsets.meet(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> Lattice for Subuniverses<DOM>
+where
+    DOM: Indexable,
+{
+    fn join<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let union = self.subsets.join(logic, elem0, elem1);
+        self.generate(logic, union.slice())
+    }
+}
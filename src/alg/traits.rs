@@ -17,7 +17,10 @@
 
 use std::fmt::Debug;
 
-use super::{BitSlice, BitVec, BooleanLogic, BooleanSolver, Slice, Solver, Vector};
+use super::{
+    BitSlice, BitVec, BooleanLogic, BooleanSolver, Logic, OnehotEncoding, Slice, Solver, Vector,
+    XorShift,
+};
 
 /// An arbitrary set of elements that can be representable by bit vectors.
 pub trait Domain: Clone + PartialEq + Debug {
@@ -82,6 +85,52 @@ pub trait Domain: Clone + PartialEq + Debug {
     where
         LOGIC: BooleanLogic;
 
+    /// Returns the first element if the condition is true, otherwise the
+    /// second one.
+    fn select<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        cond: LOGIC::Elem,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        debug_assert_eq!(elem0.len(), self.num_bits());
+        debug_assert_eq!(elem1.len(), self.num_bits());
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for (a, b) in elem0.copy_iter().zip(elem1.copy_iter()) {
+            result.push(logic.bool_ite(cond, a, b));
+        }
+        result
+    }
+
+    /// Returns the element from the list whose index is marked in the one
+    /// hot vector. The result is meaningful only when exactly one bit of
+    /// the one hot vector is true.
+    fn select_by_onehot<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        onehot: LOGIC::Slice<'_>,
+        elems: &[LOGIC::Vector],
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(onehot.len(), elems.len());
+        let mut result: LOGIC::Vector = Vector::with_values(self.num_bits(), logic.bool_zero());
+        for (sel, elem) in onehot.copy_iter().zip(elems.iter()) {
+            debug_assert_eq!(elem.len(), self.num_bits());
+            for (i, a) in elem.copy_iter().enumerate() {
+                let b = logic.bool_and(sel, a);
+                let b = logic.bool_or(result.get(i), b);
+                result.set(i, b);
+            }
+        }
+        result
+    }
+
     /// Adds a new variable to the given solver, which is just a list of
     /// fresh literals. It also enforces that the returned variable
     /// is contained in the domain, but adding the appropriate constraint.
@@ -97,6 +146,31 @@ pub trait Domain: Clone + PartialEq + Debug {
         logic.bool_add_clause1(test);
         elem
     }
+
+    /// Returns a fresh literal of the solver that is true exactly when the
+    /// given element is contained in the domain.
+    fn reify_contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanSolver,
+    {
+        let test = self.contains(logic, elem);
+        logic.bool_reify(test)
+    }
+
+    /// Returns a fresh literal of the solver that is true exactly when the
+    /// two elements are equal.
+    fn reify_equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanSolver,
+    {
+        let test = self.equals(logic, elem0, elem1);
+        logic.bool_reify(test)
+    }
 }
 
 /// A helper structure for displaying domain elements.
@@ -130,6 +204,13 @@ pub trait Indexable: Domain {
     /// Returns the index of the given element.
     fn get_index(&self, elem: BitSlice<'_>) -> usize;
 
+    /// Returns a uniformly random element of the domain. Composite domains
+    /// sample their coordinates independently, so the size of the domain
+    /// does not need to fit into an index.
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.get_elem(&Logic(), rng.below(self.size()))
+    }
+
     /// Returns the one hot encoding of the given element.
     fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
@@ -142,6 +223,86 @@ pub trait Indexable: Domain {
         }
         result
     }
+
+    /// Returns true if the given elements are pairwise different. Small
+    /// families are compared pairwise, while for larger ones the one hot
+    /// encodings of the elements are computed and each element of the
+    /// domain is required to be taken at most once.
+    fn all_different<LOGIC>(&self, logic: &mut LOGIC, elems: &[LOGIC::Vector]) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        if elems.len() * elems.len() <= 2 * self.size() {
+            for (i, elem0) in elems.iter().enumerate() {
+                for elem1 in elems[i + 1..].iter() {
+                    let test = self.equals(logic, elem0.slice(), elem1.slice());
+                    let test = logic.bool_not(test);
+                    result = logic.bool_and(result, test);
+                }
+            }
+        } else {
+            let onehots: Vec<LOGIC::Vector> = elems
+                .iter()
+                .map(|elem| self.onehot(logic, elem.slice()))
+                .collect();
+            for index in 0..self.size() {
+                let test = logic.bool_fold_amo(onehots.iter().map(|v| v.get(index)));
+                result = logic.bool_and(result, test);
+            }
+        }
+        result
+    }
+
+    /// Adds a new variable to the given solver together with its one hot
+    /// encoding. The one hot literals are constrained with the given
+    /// encoding, and they are channeled to the bits of the element with
+    /// binary clauses, so no equality tests are built. The returned pair
+    /// contains the element and its one hot encoding.
+    fn add_onehot_variable<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        encoding: OnehotEncoding,
+    ) -> (LOGIC::Vector, LOGIC::Vector)
+    where
+        LOGIC: BooleanSolver,
+    {
+        let mut elem: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for _ in 0..self.num_bits() {
+            elem.push(logic.bool_add_variable());
+        }
+        let mut onehot: LOGIC::Vector = Vector::with_capacity(self.size());
+        for _ in 0..self.size() {
+            onehot.push(logic.bool_add_variable());
+        }
+        let lits: Vec<LOGIC::Elem> = onehot.copy_iter().collect();
+        logic.bool_add_exactly_one(&lits, encoding);
+
+        for (index, lit) in lits.into_iter().enumerate() {
+            let lit = logic.bool_not(lit);
+            let value = self.get_elem(&Logic(), index);
+            for (a, b) in elem.copy_iter().zip(value.copy_iter()) {
+                let a = if b { a } else { logic.bool_not(a) };
+                logic.bool_add_clause2(lit, a);
+            }
+        }
+        (elem, onehot)
+    }
+
+    /// Evaluates the given predicate on all elements of the domain and
+    /// returns the results as a bit vector indexed by the elements. This
+    /// is a fast alternative to model counting for small domains.
+    fn evaluate_all<PRED>(&self, logic: &mut Logic, mut pred: PRED) -> BitVec
+    where
+        PRED: FnMut(&mut Logic, BitSlice<'_>) -> bool,
+    {
+        let mut result: BitVec = Vector::with_capacity(self.size());
+        for index in 0..self.size() {
+            let elem = self.get_elem(logic, index);
+            result.push(pred(logic, elem.slice()));
+        }
+        result
+    }
 }
 
 /// A directed graph on a domain.
@@ -367,6 +528,26 @@ pub trait Monoid: Semigroup {
         let unit = self.get_identity(logic);
         self.equals(logic, elem, unit.slice())
     }
+
+    /// Returns the given power of the element using repeated squaring.
+    fn pow<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>, exp: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = self.get_identity(logic);
+        let mut square: LOGIC::Vector = elem.copy_iter().collect();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = self.product(logic, result.slice(), square.slice());
+            }
+            exp /= 2;
+            if exp > 0 {
+                square = self.product(logic, square.slice(), square.slice());
+            }
+        }
+        result
+    }
 }
 
 pub trait Group: Monoid {
@@ -374,6 +555,64 @@ pub trait Group: Monoid {
     fn inverse<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
     where
         LOGIC: BooleanLogic;
+
+    /// Returns the conjugate `h^-1 g h` of the first element by the second.
+    fn conjugate<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let inv1 = self.inverse(logic, elem1);
+        let elem2 = self.product(logic, inv1.slice(), elem0);
+        self.product(logic, elem2.slice(), elem1)
+    }
+
+    /// Returns the commutator `g^-1 h^-1 g h` of the given elements.
+    fn commutator<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let inv0 = self.inverse(logic, elem0);
+        let elem2 = self.conjugate(logic, elem0, elem1);
+        self.product(logic, inv0.slice(), elem2.slice())
+    }
+
+    /// Returns true if the two elements commute.
+    fn is_commuting<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let elem2 = self.product(logic, elem0, elem1);
+        let elem3 = self.product(logic, elem1, elem0);
+        self.equals(logic, elem2.slice(), elem3.slice())
+    }
+}
+
+/// A group acting faithfully on the set `0..degree` by permutations.
+pub trait PermutationGroup: Group {
+    /// Returns the number of points the group is acting on.
+    fn degree(&self) -> usize;
+
+    /// Returns the permutation of the given group element as a binary
+    /// relation on `0..degree`, where the bit at index `j + i * degree`
+    /// is set if the permutation maps `i` to `j`.
+    fn as_permutation<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic;
 }
 
 /// A binary relation between two domains.
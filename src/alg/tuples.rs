@@ -0,0 +1,212 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    BitSlice, BitVec, BooleanLogic, Domain, Indexable, PartIter, Power, Relations, Slice, XorShift,
+};
+
+/// The domain of tuples of a fixed length over an indexable domain. The
+/// elements are encoded as in the power domain, with the first coordinate
+/// stored first. Comparisons of coordinates use the order of their bit
+/// encodings, which agrees with the index order for small sets and powers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuples<DOM>(Power<DOM>)
+where
+    DOM: Indexable;
+
+impl<DOM> Tuples<DOM>
+where
+    DOM: Indexable,
+{
+    /// Creates the domain of tuples of the given length.
+    #[inline]
+    pub fn new(dom: DOM, length: usize) -> Self {
+        Self(Power::new(dom, length))
+    }
+
+    /// Returns the domain of the coordinates.
+    #[inline]
+    pub fn domain(&self) -> &DOM {
+        self.0.base()
+    }
+
+    /// Returns the length of the tuples.
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.0.exponent()
+    }
+
+    /// Returns the given coordinate of the tuple.
+    #[inline]
+    pub fn coord<'a, ELEM>(&self, elem: ELEM, index: usize) -> ELEM
+    where
+        ELEM: Slice<'a>,
+    {
+        self.0.part(elem, index)
+    }
+
+    /// Returns an iterator over the coordinates of the tuple.
+    #[inline]
+    pub fn coord_iter<'a, ELEM>(&self, elem: ELEM) -> PartIter<'a, ELEM>
+    where
+        ELEM: Slice<'a>,
+    {
+        self.0.part_iter(elem)
+    }
+
+    /// Returns true if the coordinates of the tuple are pairwise distinct.
+    pub fn is_distinct<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for i in 0..self.length() {
+            for j in (i + 1)..self.length() {
+                let test = self
+                    .domain()
+                    .equals(logic, self.coord(elem, i), self.coord(elem, j));
+                let test = logic.bool_not(test);
+                result = logic.bool_and(result, test);
+            }
+        }
+        result
+    }
+
+    /// Returns true if the coordinates of the tuple are weakly increasing.
+    pub fn is_sorted<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for i in 1..self.length() {
+            let coord0 = self.coord(elem, i - 1);
+            let coord1 = self.coord(elem, i);
+            let test = logic.bool_cmp_leq(coord0.copy_iter().zip(coord1.copy_iter()));
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the coordinates of the tuple are strictly increasing.
+    pub fn is_increasing<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let mut result = logic.bool_unit();
+        for i in 1..self.length() {
+            let coord0 = self.coord(elem, i - 1);
+            let coord1 = self.coord(elem, i);
+            let test = logic.bool_cmp_ltn(coord0.copy_iter().zip(coord1.copy_iter()));
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the first tuple precedes the second one in the order
+    /// of their indices, where the last coordinate is the most significant.
+    pub fn precedes<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        logic.bool_cmp_ltn(elem0.copy_iter().zip(elem1.copy_iter()))
+    }
+
+    /// Returns the relation whose only member is the given tuple.
+    pub fn to_singleton<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.onehot(logic, elem)
+    }
+
+    /// Returns the domain of relations that contain these tuples.
+    pub fn relations(&self) -> Relations<DOM> {
+        Relations::new(self.domain().clone(), self.length())
+    }
+}
+
+impl<DOM> Domain for Tuples<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn num_bits(&self) -> usize {
+        self.0.num_bits()
+    }
+
+    #[inline]
+    fn contains<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.contains(logic, elem)
+    }
+
+    #[inline]
+    fn equals<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem0: LOGIC::Slice<'_>,
+        elem1: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.equals(logic, elem0, elem1)
+    }
+}
+
+impl<DOM> Indexable for Tuples<DOM>
+where
+    DOM: Indexable,
+{
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    #[inline]
+    fn get_elem<LOGIC>(&self, logic: &LOGIC, index: usize) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.get_elem(logic, index)
+    }
+
+    #[inline]
+    fn get_index(&self, elem: BitSlice<'_>) -> usize {
+        self.0.get_index(elem)
+    }
+
+    #[inline]
+    fn random_elem(&self, rng: &mut XorShift) -> BitVec {
+        self.0.random_elem(rng)
+    }
+
+    #[inline]
+    fn onehot<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.onehot(logic, elem)
+    }
+}
@@ -16,7 +16,8 @@
 */
 
 use super::{
-    BinaryRelations, BitSlice, BooleanLogic, Domain, Indexable, Monoid, Power, Semigroup, SmallSet,
+    BinaryRelations, BitSlice, BooleanLogic, Domain, Indexable, Monoid, OperationTable, Power,
+    Semigroup, Slice, SmallSet, Vector,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -47,6 +48,152 @@ where
     {
         self.0.is_permutation(logic, elem)
     }
+
+    /// Returns true if the given unary operation is injective, that is every
+    /// column of its graph contains at most one element.
+    pub fn is_injective<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        for value in 0..size {
+            let test = logic.bool_fold_amo((0..size).map(|i| elem.get(value + i * size)));
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the given unary operation is surjective, that is every
+    /// column of its graph contains at least one element.
+    pub fn is_surjective<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        for value in 0..size {
+            let test = logic.bool_fold_any((0..size).map(|i| elem.get(value + i * size)));
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the given unary operation is bijective, that is every
+    /// column of its graph contains exactly one element.
+    pub fn is_bijective<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result = logic.bool_unit();
+        for value in 0..size {
+            let test = logic.bool_fold_one((0..size).map(|i| elem.get(value + i * size)));
+            result = logic.bool_and(result, test);
+        }
+        result
+    }
+
+    /// Returns true if the unary operation has exactly the given number
+    /// of fixed points.
+    pub fn has_fixed_points<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+        count: usize,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.has_fixed_points(logic, elem, count)
+    }
+
+    /// Returns true if the unary operation has no fixed points.
+    pub fn is_fixed_point_free<LOGIC>(
+        &self,
+        logic: &mut LOGIC,
+        elem: LOGIC::Slice<'_>,
+    ) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_fixed_point_free(logic, elem)
+    }
+
+    /// Returns true if the unary operation is a permutation without fixed
+    /// points.
+    pub fn is_derangement<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_derangement(logic, elem)
+    }
+
+    /// Returns true if the unary operation is its own inverse.
+    pub fn is_involution<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        self.0.is_symmetric(logic, elem)
+    }
+
+    /// Returns the kernel of the given unary operation, which is the
+    /// equivalence relation of the pairs of elements with the same image.
+    pub fn kernel<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(size * size);
+        for j in 0..size {
+            for i in 0..size {
+                let mut value = logic.bool_zero();
+                for v in 0..size {
+                    let test = logic.bool_and(elem.get(v + i * size), elem.get(v + j * size));
+                    value = logic.bool_or(value, test);
+                }
+                result.push(value);
+            }
+        }
+        result
+    }
+
+    /// Returns the image of the given unary operation as a unary relation.
+    pub fn image<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        let mut result: LOGIC::Vector = Vector::with_capacity(size);
+        for value in 0..size {
+            let test = logic.bool_fold_any((0..size).map(|i| elem.get(value + i * size)));
+            result.push(test);
+        }
+        result
+    }
+
+    /// Returns the table of the given concrete unary operation.
+    pub fn get_table(&self, elem: BitSlice<'_>) -> OperationTable {
+        let size = self.domain().size();
+        let dom = Power::new(SmallSet::new(size), size);
+        let values = dom.part_iter(elem).map(|part| dom.base().get_index(part));
+        OperationTable::new(size, 1, values.collect())
+    }
+
+    /// Returns the unary operation corresponding to the given table.
+    pub fn from_table<LOGIC>(&self, logic: &LOGIC, table: &OperationTable) -> LOGIC::Vector
+    where
+        LOGIC: BooleanLogic,
+    {
+        let size = self.domain().size();
+        assert!(table.size() == size && table.arity() == 1);
+        let dom = SmallSet::new(size);
+        let mut result: LOGIC::Vector = Vector::with_capacity(self.num_bits());
+        for &value in table.values() {
+            result.extend(dom.get_elem(logic, value));
+        }
+        result
+    }
 }
 
 impl<DOM> Domain for UnaryOperations<DOM>
@@ -15,13 +15,92 @@
 * along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::{env, fmt};
+
+use super::{
+    AlternatingGroup, BinaryRelations, BitVec, BooleanLattice, BooleanLogic, BooleanSolver,
+    BoundedOrder, DirectProduct, Domain, FixedSet, Group, Indexable, Lattice, Logic,
+    MeetSemilattice, Monoid, OneHot, OnehotEncoding, Operations, PartialOperations, PartialOrder,
+    PermutationGroup, Power, Product2, ProductN, Relations, Semigroup, Slice, SmallSet, Solver,
+    SymmetricGroup, Tuples, UnaryOperations, Vector, WreathProduct, BOOLEAN,
+};
+
+#[cfg(test)]
 use super::{
-    AlternatingGroup, BinaryRelations, BipartiteGraph, BooleanLattice, BooleanLogic, BooleanSolver,
-    BoundedOrder, Domain, Group, Indexable, Lattice, Logic, MeetSemilattice, Monoid, Operations,
-    PartialOrder, Power, Preservation, Product2, Relations, Semigroup, SmallSet, Solver,
-    SymmetricGroup, UnaryOperations, Vector, BOOLEAN,
+    add_equality, bell, bounded_fixpoint, canonical_basis_of_closed_sets,
+    canonical_basis_of_predicate, canonical_relation, catalan, check_num_models,
+    compatibility_reports, contract_equalities, count_homomorphisms_up_to_auto,
+    count_mono_unary_algebras, eulerian, factorial, find_homomorphisms, generate_digraphs,
+    generate_graphs, generate_posets, split_occurrences, stirling2, subfactorial, Annealing,
+    Automorphisms, BipartiteGraph, Census, CompatibilityMatrix, CompiledPredicate, CountSequence,
+    DirectedGraph, EncodingSizes, Endomap, EquitablePartition, Fingerprint, Gadget, Homomorphisms,
+    InvariantRelations, MaltsevConditions, MatrixSymmetry, MonoUnary, Neighborhood, OperationClass,
+    OperationTable, OrderlyGenerator, PolymorphismClone, Poset, Preservation, Reduction,
+    RelationRef, SoftConstraintSet, SparseRelations, Stabilizer, Subuniverses, Translation,
+    XorShift,
 };
 
+/// The effort level of the validation suite. The quick tier checks only
+/// the smallest instances, while the thorough tier adds the larger group
+/// and relation instances that can take minutes to validate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValidationTier {
+    Quick,
+    Standard,
+    Thorough,
+}
+
+impl ValidationTier {
+    /// Parses the name of a tier, returns `None` if it is unknown.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "quick" => Some(ValidationTier::Quick),
+            "standard" => Some(ValidationTier::Standard),
+            "thorough" => Some(ValidationTier::Thorough),
+            _ => None,
+        }
+    }
+
+    /// Returns the tier given by the `UASAT_VALIDATE` environment variable,
+    /// or the standard tier if it is not set.
+    pub fn from_env() -> Self {
+        match env::var("UASAT_VALIDATE") {
+            Ok(name) => {
+                Self::parse(&name).unwrap_or_else(|| panic!("unknown validation tier {}", name))
+            }
+            Err(_) => ValidationTier::Standard,
+        }
+    }
+}
+
+impl fmt::Display for ValidationTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValidationTier::Quick => "quick",
+            ValidationTier::Standard => "standard",
+            ValidationTier::Thorough => "thorough",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Runs the validation of the domain, order and group implementations on
+/// the instances of the given tier, and panics if any of them fails.
+pub fn validate_suite(tier: ValidationTier) {
+    domain_suite(tier);
+    indexable_suite(tier);
+    onehot_variable_suite(tier);
+    partial_order_suite(tier);
+    bounded_order_suite(tier);
+    meet_semilattice_suite(tier);
+    lattice_suite(tier);
+    boolean_lattice_suite(tier);
+    semigroup_suite(tier);
+    monoid_suite(tier);
+    group_suite(tier);
+    permutation_group_suite(tier);
+}
+
 pub fn validate_domain<DOM>(domain: DOM)
 where
     DOM: Domain,
@@ -51,19 +130,37 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn domain() {
+fn domain_suite(tier: ValidationTier) {
     validate_domain(BOOLEAN);
     validate_domain(SmallSet::new(5));
+    validate_domain(FixedSet::new(["a", "b", "c"]));
     validate_domain(Power::new(BOOLEAN, 3));
-    validate_domain(Power::new(SmallSet::new(3), 2));
-    validate_domain(Product2::new(BOOLEAN, SmallSet::new(3)));
-    validate_domain(Relations::new(SmallSet::new(3), 3));
-    validate_domain(BinaryRelations::new(SmallSet::new(3)));
-    validate_domain(Operations::new(SmallSet::new(2), 2));
-    validate_domain(UnaryOperations::new(SmallSet::new(3)));
+    validate_domain(OneHot::new(Power::new(BOOLEAN, 3)));
     validate_domain(SymmetricGroup::new(SmallSet::new(4)));
-    validate_domain(AlternatingGroup::new(SmallSet::new(4)));
+    if tier >= ValidationTier::Standard {
+        validate_domain(Power::new(SmallSet::new(3), 2));
+        validate_domain(Product2::new(BOOLEAN, SmallSet::new(3)));
+        validate_domain(ProductN::new(vec![
+            SmallSet::new(2),
+            SmallSet::new(3),
+            SmallSet::new(2),
+        ]));
+        validate_domain(Relations::new(SmallSet::new(3), 3));
+        validate_domain(BinaryRelations::new(SmallSet::new(3)));
+        validate_domain(Operations::new(SmallSet::new(2), 2));
+        validate_domain(PartialOperations::new(SmallSet::new(2), 2));
+        validate_domain(UnaryOperations::new(SmallSet::new(3)));
+        validate_domain(AlternatingGroup::new(SmallSet::new(4)));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_domain(Relations::new(SmallSet::new(4), 3));
+        validate_domain(SymmetricGroup::new(SmallSet::new(6)));
+    }
+}
+
+#[test]
+fn domain() {
+    domain_suite(ValidationTier::from_env());
 }
 
 fn validate_indexable<DOM>(domain: DOM, size: usize)
@@ -108,29 +205,226 @@ where
         let elem1 = small.get_elem(&logic, index);
         assert_eq!(elem0, elem1);
     }
+
+    // evaluate all works
+    let mut logic = Logic();
+    let elems = domain.evaluate_all(&mut logic, |logic, elem| domain.contains(logic, elem));
+    assert_eq!(elems.len(), size);
+    assert!(elems.copy_iter().all(|b| b));
 }
 
-#[test]
-fn indexable() {
+fn indexable_suite(tier: ValidationTier) {
     validate_indexable(BOOLEAN, 2);
     validate_indexable(SmallSet::new(5), 5);
+    validate_indexable(FixedSet::with_size(4), 4);
     validate_indexable(Power::new(BOOLEAN, 3), 8);
-    validate_indexable(Power::new(SmallSet::new(3), 2), 9);
-    validate_indexable(Product2::new(BOOLEAN, SmallSet::new(3)), 6);
-    validate_indexable(Relations::new(SmallSet::new(2), 3), 256);
-    validate_indexable(BinaryRelations::new(SmallSet::new(2)), 16);
-    validate_indexable(Operations::new(SmallSet::new(2), 2), 16);
-    validate_indexable(UnaryOperations::new(SmallSet::new(3)), 27);
+    validate_indexable(OneHot::new(Power::new(BOOLEAN, 3)), 8);
+    validate_indexable(
+        OneHot::with_encoding(SmallSet::new(5), OnehotEncoding::Commander),
+        5,
+    );
     validate_indexable(SymmetricGroup::new(SmallSet::new(0)), 1);
     validate_indexable(SymmetricGroup::new(SmallSet::new(1)), 1);
     validate_indexable(SymmetricGroup::new(SmallSet::new(2)), 2);
     validate_indexable(SymmetricGroup::new(SmallSet::new(3)), 6);
-    validate_indexable(SymmetricGroup::new(SmallSet::new(4)), 24);
     validate_indexable(AlternatingGroup::new(SmallSet::new(0)), 1);
     validate_indexable(AlternatingGroup::new(SmallSet::new(1)), 1);
     validate_indexable(AlternatingGroup::new(SmallSet::new(2)), 1);
     validate_indexable(AlternatingGroup::new(SmallSet::new(3)), 3);
-    validate_indexable(AlternatingGroup::new(SmallSet::new(6)), 360);
+    if tier >= ValidationTier::Standard {
+        validate_indexable(Power::new(SmallSet::new(3), 2), 9);
+        validate_indexable(Product2::new(BOOLEAN, SmallSet::new(3)), 6);
+        validate_indexable(
+            ProductN::new(vec![SmallSet::new(2), SmallSet::new(3), SmallSet::new(2)]),
+            12,
+        );
+        validate_indexable(Relations::new(SmallSet::new(2), 3), 256);
+        validate_indexable(BinaryRelations::new(SmallSet::new(2)), 16);
+        validate_indexable(Operations::new(SmallSet::new(2), 2), 16);
+        validate_indexable(PartialOperations::new(SmallSet::new(2), 2), 81);
+        validate_indexable(UnaryOperations::new(SmallSet::new(3)), 27);
+        validate_indexable(SymmetricGroup::new(SmallSet::new(4)), 24);
+        validate_indexable(Tuples::new(SmallSet::new(3), 2), 9);
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_indexable(AlternatingGroup::new(SmallSet::new(6)), 360);
+        validate_indexable(SymmetricGroup::new(SmallSet::new(5)), 120);
+        validate_indexable(BinaryRelations::new(SmallSet::new(3)), 512);
+    }
+}
+
+#[test]
+fn indexable() {
+    indexable_suite(ValidationTier::from_env());
+}
+
+fn validate_onehot_variable<DOM>(domain: DOM)
+where
+    DOM: Indexable,
+{
+    for &encoding in [
+        OnehotEncoding::Pairwise,
+        OnehotEncoding::Commander,
+        OnehotEncoding::Product,
+    ]
+    .iter()
+    {
+        let mut solver = Solver::new("");
+        let (elem, onehot) = domain.add_onehot_variable(&mut solver, encoding);
+        let lits: Vec<_> = elem.copy_iter().chain(onehot.copy_iter()).collect();
+        let mut count = 0;
+        for model in solver.bool_all_models(lits.iter().copied()) {
+            let mut logic = Logic();
+            let value = model.slice().head(domain.num_bits());
+            assert!(domain.contains(&mut logic, value));
+            let expected: BitVec = model.slice().tail(domain.num_bits()).copy_iter().collect();
+            assert_eq!(domain.onehot(&mut logic, value), expected);
+            count += 1;
+        }
+        assert_eq!(count, domain.size());
+    }
+}
+
+#[test]
+fn fixed_set() {
+    let logic = Logic();
+    let domain = FixedSet::new(["zero", "one", "two"]);
+    assert_eq!(domain.name(1), "one");
+    assert_eq!(domain.find_index("two"), Some(2));
+    assert_eq!(domain.find_index("three"), None);
+
+    let elem = domain.get_elem(&logic, 2);
+    assert_eq!(domain.format(elem.slice()).to_string(), "two");
+    let power = Power::new(domain.clone(), 2);
+    let elem = power.get_elem(&logic, 1);
+    assert_eq!(power.format(elem.slice()).to_string(), "[one,zero]");
+
+    let subset = domain.get_subset(|i| i != 1);
+    assert_eq!(domain.subset_names(subset.slice()), vec!["zero", "two"]);
+    assert_eq!(domain.get_subset_from_mask(0b101), subset);
+    assert!(Relations::new(domain, 1).contains(&mut Logic(), subset.slice()));
+}
+
+#[test]
+fn product_n() {
+    let logic = Logic();
+    let domain = ProductN::new(vec![SmallSet::new(2), SmallSet::new(3), SmallSet::new(4)]);
+    assert_eq!(domain.size(), 24);
+
+    let elem = domain.get_elem(&logic, 1 + 2 * 2 + 3 * 6);
+    assert_eq!(domain.format(elem.slice()).to_string(), "(1,2,3)");
+    let parts = domain.split_elem(elem.slice());
+    assert_eq!(parts.len(), 3);
+    assert_eq!(domain.doms()[1].get_index(parts[1].slice()), 2);
+    assert_eq!(domain.make_elem::<BitVec, _>(parts), elem);
+}
+
+#[test]
+fn all_different() {
+    let domain = SmallSet::new(5);
+    for &(count, expected) in [(3, 60), (5, 120), (6, 0)].iter() {
+        let mut logic = Solver::new("");
+        let vars: Vec<Vec<_>> = (0..count)
+            .map(|_| domain.add_variable(&mut logic))
+            .collect();
+        let test = domain.all_different(&mut logic, &vars);
+        logic.bool_add_clause1(test);
+        let lits: Vec<_> = vars.iter().flat_map(|v| v.iter().copied()).collect();
+        assert_eq!(
+            logic.bool_find_num_models_method1(lits.into_iter()),
+            expected
+        );
+    }
+
+    let mut logic = Logic();
+    let domain = Power::new(BOOLEAN, 2);
+    let elems: Vec<BitVec> = [0, 1, 2, 1]
+        .iter()
+        .map(|&i| domain.get_elem(&logic, i))
+        .collect();
+    assert!(domain.all_different(&mut logic, &elems[0..3]));
+    assert!(!domain.all_different(&mut logic, &elems));
+}
+
+#[test]
+fn fingerprint() {
+    let domain = SmallSet::new(5);
+    let hash = Fingerprint::new(domain.clone(), 3, 1);
+    let mut logic = Logic();
+    let elems: Vec<BitVec> = (0..5).map(|i| domain.get_elem(&logic, i)).collect();
+    for elem0 in elems.iter() {
+        for elem1 in elems.iter() {
+            let test = hash.is_different(&mut logic, elem0.slice(), elem1.slice());
+            assert!(!test || elem0 != elem1);
+        }
+    }
+    let slices: Vec<_> = elems.iter().map(|e| e.slice()).collect();
+    let collision = hash.has_collision(&slices);
+
+    for &fallback in [false, true].iter() {
+        let mut logic = Solver::new("");
+        let vars: Vec<Vec<_>> = (0..3).map(|_| domain.add_variable(&mut logic)).collect();
+        hash.add_distinct(&mut logic, &vars, fallback);
+        let lits: Vec<_> = vars.iter().flat_map(|v| v.iter().copied()).collect();
+        let count = logic.bool_find_num_models_method1(lits.into_iter());
+        if fallback || !collision {
+            assert_eq!(count, 60);
+        } else {
+            assert!(count < 60);
+        }
+    }
+}
+
+#[test]
+fn select() {
+    let mut logic = Logic();
+    let domain = SymmetricGroup::new(SmallSet::new(4));
+    let elems: Vec<BitVec> = (0..domain.size())
+        .map(|i| domain.get_elem(&logic, i))
+        .collect();
+    for (i, elem0) in elems.iter().enumerate() {
+        let elem1 = &elems[elems.len() - 1 - i];
+        let elem2 = domain.select(&mut logic, true, elem0.slice(), elem1.slice());
+        assert_eq!(&elem2, elem0);
+        let elem2 = domain.select(&mut logic, false, elem0.slice(), elem1.slice());
+        assert_eq!(&elem2, elem1);
+
+        let onehot = domain.onehot(&mut logic, elem0.slice());
+        let elem2 = domain.select_by_onehot(&mut logic, onehot.slice(), &elems);
+        assert_eq!(&elem2, elem0);
+    }
+
+    let mut logic = Solver::new("");
+    let index = SmallSet::new(domain.size());
+    let (_, onehot) = index.add_onehot_variable(&mut logic, OnehotEncoding::Commander);
+    let elems: Vec<Vec<_>> = (0..domain.size())
+        .map(|i| domain.get_elem(&logic, i))
+        .collect();
+    let elem = domain.select_by_onehot(&mut logic, onehot.slice(), &elems);
+    let test = domain.contains(&mut logic, elem.slice());
+    let test = logic.bool_not(test);
+    assert!(logic
+        .bool_find_one_model(&[test], onehot.copy_iter())
+        .is_none());
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 24);
+}
+
+fn onehot_variable_suite(tier: ValidationTier) {
+    validate_onehot_variable(BOOLEAN);
+    validate_onehot_variable(SmallSet::new(7));
+    if tier >= ValidationTier::Standard {
+        validate_onehot_variable(Power::new(SmallSet::new(3), 2));
+        validate_onehot_variable(SymmetricGroup::new(SmallSet::new(4)));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_onehot_variable(SymmetricGroup::new(SmallSet::new(5)));
+    }
+}
+
+#[test]
+fn onehot_variable() {
+    onehot_variable_suite(ValidationTier::from_env());
 }
 
 pub fn validate_partial_order<DOM>(domain: DOM)
@@ -140,14 +434,70 @@ where
     assert!(domain.test_partial_order());
 }
 
-#[test]
-fn partial_order() {
+fn partial_order_suite(tier: ValidationTier) {
     validate_partial_order(BOOLEAN);
     validate_partial_order(SmallSet::new(7));
     validate_partial_order(Power::new(BOOLEAN, 3));
-    validate_partial_order(Product2::new(BOOLEAN, BOOLEAN));
-    validate_partial_order(Relations::new(SmallSet::new(2), 3));
-    validate_partial_order(BinaryRelations::new(SmallSet::new(3)));
+    validate_partial_order(OneHot::new(Power::new(BOOLEAN, 3)));
+    if tier >= ValidationTier::Standard {
+        validate_partial_order(Product2::new(BOOLEAN, BOOLEAN));
+        validate_partial_order(Relations::new(SmallSet::new(2), 3));
+        validate_partial_order(BinaryRelations::new(SmallSet::new(3)));
+        validate_partial_order(PartialOperations::new(SmallSet::new(3), 1));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_partial_order(BinaryRelations::new(SmallSet::new(4)));
+    }
+}
+
+#[test]
+fn partial_order() {
+    partial_order_suite(ValidationTier::from_env());
+}
+
+#[test]
+fn one_hot_domain() {
+    let base = Power::new(SmallSet::new(3), 2);
+    let domain = OneHot::new(base.clone());
+    assert_eq!(domain.num_bits(), 9);
+
+    let mut logic = Logic();
+    for index0 in 0..base.size() {
+        let elem0 = base.get_elem(&logic, index0);
+        let code0 = domain.encode(&mut logic, elem0.slice());
+        assert_eq!(code0, domain.get_elem(&logic, index0));
+        assert_eq!(domain.decode(&mut logic, code0.slice()), elem0);
+        assert_eq!(
+            domain.format(code0.slice()).to_string(),
+            base.format(elem0.slice()).to_string()
+        );
+        for index1 in 0..base.size() {
+            let elem1 = base.get_elem(&logic, index1);
+            let code1 = domain.get_elem(&logic, index1);
+            assert_eq!(
+                domain.is_edge(&mut logic, code0.slice(), code1.slice()),
+                base.is_edge(&mut logic, elem0.slice(), elem1.slice())
+            );
+        }
+    }
+
+    // the pairs of comparable elements of the square of a 3-element chain
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let test = domain.is_edge(&mut logic, elem0.slice(), elem1.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem0.copy_iter().chain(elem1.copy_iter()));
+    assert_eq!(count, 36);
+
+    let mut logic = Solver::new("");
+    let elem = base.add_variable(&mut logic);
+    let code = domain.encode(&mut logic, elem.slice());
+    let back = domain.decode(&mut logic, code.slice());
+    let test = base.equals(&mut logic, elem.slice(), back.slice());
+    let test = logic.bool_not(test);
+    logic.bool_add_clause1(test);
+    assert!(!logic.bool_solvable());
 }
 
 pub fn validate_bounded_order<DOM>(domain: DOM)
@@ -184,14 +534,23 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn bounded_order() {
+fn bounded_order_suite(tier: ValidationTier) {
     validate_bounded_order(BOOLEAN);
     validate_bounded_order(SmallSet::new(7));
     validate_bounded_order(Power::new(BOOLEAN, 3));
-    validate_bounded_order(Product2::new(BOOLEAN, BOOLEAN));
-    validate_bounded_order(Relations::new(SmallSet::new(2), 3));
-    validate_bounded_order(BinaryRelations::new(SmallSet::new(3)));
+    if tier >= ValidationTier::Standard {
+        validate_bounded_order(Product2::new(BOOLEAN, BOOLEAN));
+        validate_bounded_order(Relations::new(SmallSet::new(2), 3));
+        validate_bounded_order(BinaryRelations::new(SmallSet::new(3)));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_bounded_order(BinaryRelations::new(SmallSet::new(4)));
+    }
+}
+
+#[test]
+fn bounded_order() {
+    bounded_order_suite(ValidationTier::from_env());
 }
 
 pub fn validate_meet_semilattice<DOM>(domain: DOM)
@@ -232,14 +591,24 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn meet_semilattice() {
+fn meet_semilattice_suite(tier: ValidationTier) {
     validate_meet_semilattice(BOOLEAN);
     validate_meet_semilattice(SmallSet::new(7));
     validate_meet_semilattice(Power::new(BOOLEAN, 3));
-    validate_meet_semilattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
-    validate_meet_semilattice(Relations::new(SmallSet::new(2), 3));
-    validate_meet_semilattice(BinaryRelations::new(SmallSet::new(3)));
+    if tier >= ValidationTier::Standard {
+        validate_meet_semilattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
+        validate_meet_semilattice(Relations::new(SmallSet::new(2), 3));
+        validate_meet_semilattice(BinaryRelations::new(SmallSet::new(3)));
+        validate_meet_semilattice(PartialOperations::new(SmallSet::new(3), 1));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_meet_semilattice(BinaryRelations::new(SmallSet::new(4)));
+    }
+}
+
+#[test]
+fn meet_semilattice() {
+    meet_semilattice_suite(ValidationTier::from_env());
 }
 
 pub fn validate_lattice<DOM>(domain: DOM)
@@ -280,14 +649,24 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn lattice() {
+fn lattice_suite(tier: ValidationTier) {
     validate_lattice(BOOLEAN);
     validate_lattice(SmallSet::new(7));
     validate_lattice(Power::new(BOOLEAN, 3));
-    validate_lattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
-    validate_lattice(Relations::new(SmallSet::new(2), 3));
-    validate_lattice(BinaryRelations::new(SmallSet::new(3)));
+    if tier >= ValidationTier::Standard {
+        validate_lattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
+        validate_lattice(ProductN::new(vec![SmallSet::new(2), SmallSet::new(3)]));
+        validate_lattice(Relations::new(SmallSet::new(2), 3));
+        validate_lattice(BinaryRelations::new(SmallSet::new(3)));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_lattice(BinaryRelations::new(SmallSet::new(4)));
+    }
+}
+
+#[test]
+fn lattice() {
+    lattice_suite(ValidationTier::from_env());
 }
 
 pub fn validate_boolean_lattice<DOM>(domain: DOM)
@@ -327,13 +706,22 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn boolean_lattice() {
+fn boolean_lattice_suite(tier: ValidationTier) {
     validate_boolean_lattice(BOOLEAN);
     validate_boolean_lattice(Power::new(BOOLEAN, 3));
-    validate_boolean_lattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
-    validate_boolean_lattice(Relations::new(SmallSet::new(2), 3));
-    validate_boolean_lattice(BinaryRelations::new(SmallSet::new(3)));
+    if tier >= ValidationTier::Standard {
+        validate_boolean_lattice(Product2::new(BOOLEAN, Power::new(BOOLEAN, 2)));
+        validate_boolean_lattice(Relations::new(SmallSet::new(2), 3));
+        validate_boolean_lattice(BinaryRelations::new(SmallSet::new(3)));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_boolean_lattice(BinaryRelations::new(SmallSet::new(4)));
+    }
+}
+
+#[test]
+fn boolean_lattice() {
+    boolean_lattice_suite(ValidationTier::from_env());
 }
 
 pub fn validate_semigroup<DOM>(domain: DOM)
@@ -363,22 +751,32 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn semigroup() {
+fn semigroup_suite(tier: ValidationTier) {
     validate_semigroup(BinaryRelations::new(SmallSet::new(3)));
     validate_semigroup(UnaryOperations::new(SmallSet::new(3)));
     validate_semigroup(SymmetricGroup::new(SmallSet::new(3)));
-    validate_semigroup(Product2::new(
-        SymmetricGroup::new(SmallSet::new(2)),
-        BinaryRelations::new(SmallSet::new(2)),
-    ));
-    validate_semigroup(Power::new(UnaryOperations::new(SmallSet::new(2)), 2));
-    validate_semigroup(AlternatingGroup::new(SmallSet::new(4)));
-    validate_semigroup(Product2::new(
-        SymmetricGroup::new(SmallSet::new(3)),
-        AlternatingGroup::new(SmallSet::new(3)),
-    ));
-    validate_semigroup(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+    if tier >= ValidationTier::Standard {
+        validate_semigroup(Product2::new(
+            SymmetricGroup::new(SmallSet::new(2)),
+            BinaryRelations::new(SmallSet::new(2)),
+        ));
+        validate_semigroup(Power::new(UnaryOperations::new(SmallSet::new(2)), 2));
+        validate_semigroup(AlternatingGroup::new(SmallSet::new(4)));
+        validate_semigroup(Product2::new(
+            SymmetricGroup::new(SmallSet::new(3)),
+            AlternatingGroup::new(SmallSet::new(3)),
+        ));
+        validate_semigroup(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_semigroup(BinaryRelations::new(SmallSet::new(4)));
+        validate_semigroup(SymmetricGroup::new(SmallSet::new(5)));
+    }
+}
+
+#[test]
+fn semigroup() {
+    semigroup_suite(ValidationTier::from_env());
 }
 
 pub fn validate_monoid<DOM>(domain: DOM)
@@ -421,22 +819,32 @@ where
     assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn monoid() {
+fn monoid_suite(tier: ValidationTier) {
     validate_monoid(BinaryRelations::new(SmallSet::new(3)));
     validate_monoid(UnaryOperations::new(SmallSet::new(3)));
     validate_monoid(SymmetricGroup::new(SmallSet::new(3)));
-    validate_monoid(Product2::new(
-        SymmetricGroup::new(SmallSet::new(2)),
-        BinaryRelations::new(SmallSet::new(2)),
-    ));
-    validate_monoid(Power::new(UnaryOperations::new(SmallSet::new(2)), 2));
-    validate_monoid(AlternatingGroup::new(SmallSet::new(4)));
-    validate_monoid(Product2::new(
-        SymmetricGroup::new(SmallSet::new(3)),
-        AlternatingGroup::new(SmallSet::new(3)),
-    ));
-    validate_monoid(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+    if tier >= ValidationTier::Standard {
+        validate_monoid(Product2::new(
+            SymmetricGroup::new(SmallSet::new(2)),
+            BinaryRelations::new(SmallSet::new(2)),
+        ));
+        validate_monoid(Power::new(UnaryOperations::new(SmallSet::new(2)), 2));
+        validate_monoid(AlternatingGroup::new(SmallSet::new(4)));
+        validate_monoid(Product2::new(
+            SymmetricGroup::new(SmallSet::new(3)),
+            AlternatingGroup::new(SmallSet::new(3)),
+        ));
+        validate_monoid(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_monoid(BinaryRelations::new(SmallSet::new(4)));
+        validate_monoid(SymmetricGroup::new(SmallSet::new(5)));
+    }
+}
+
+#[test]
+fn monoid() {
+    monoid_suite(ValidationTier::from_env());
 }
 
 pub fn validate_group<DOM>(domain: DOM)
@@ -468,103 +876,1810 @@ where
     let test0 = domain.is_identity(&mut logic, elem2.slice());
     logic.bool_add_clause1(logic.bool_not(test0));
     assert!(!logic.bool_solvable());
+
+    // commutator is identity iff commuting
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let elem2 = domain.commutator(&mut logic, elem0.slice(), elem1.slice());
+    let test0 = domain.is_identity(&mut logic, elem2.slice());
+    let test1 = domain.is_commuting(&mut logic, elem0.slice(), elem1.slice());
+    let test2 = logic.bool_xor(test0, test1);
+    logic.bool_add_clause1(test2);
+    assert!(!logic.bool_solvable());
 }
 
-#[test]
-fn group() {
+fn group_suite(tier: ValidationTier) {
     validate_group(SymmetricGroup::new(SmallSet::new(3)));
     validate_group(AlternatingGroup::new(SmallSet::new(3)));
-    validate_group(Product2::new(
-        SymmetricGroup::new(SmallSet::new(3)),
-        AlternatingGroup::new(SmallSet::new(3)),
-    ));
-    validate_group(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+    if tier >= ValidationTier::Standard {
+        validate_group(Product2::new(
+            SymmetricGroup::new(SmallSet::new(3)),
+            AlternatingGroup::new(SmallSet::new(3)),
+        ));
+        validate_group(Power::new(SymmetricGroup::new(SmallSet::new(3)), 2));
+        validate_group(ProductN::new(vec![
+            SymmetricGroup::new(SmallSet::new(2)),
+            SymmetricGroup::new(SmallSet::new(3)),
+        ]));
+    }
+    if tier >= ValidationTier::Thorough {
+        validate_group(SymmetricGroup::new(SmallSet::new(5)));
+        validate_group(AlternatingGroup::new(SmallSet::new(6)));
+    }
 }
 
 #[test]
-fn binary_relations() {
-    let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(4));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_transitive(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 3994);
+fn group() {
+    group_suite(ValidationTier::from_env());
+}
 
+#[test]
+fn group_power() {
     let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(7));
+    let domain = SymmetricGroup::new(SmallSet::new(4));
     let elem = domain.add_variable(&mut logic);
-    let test = domain.is_equivalence(&mut logic, elem.slice());
+    let elem3 = domain.pow(&mut logic, elem.slice(), 3);
+    let test = domain.is_identity(&mut logic, elem3.slice());
     logic.bool_add_clause1(test);
     let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 877);
+    assert_eq!(count, 9);
 
     let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(5));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_partial_order(&mut logic, elem.slice());
+    let domain = SymmetricGroup::new(SmallSet::new(3));
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let test = domain.is_commuting(&mut logic, elem0.slice(), elem1.slice());
     logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 4231);
+    let count = logic.bool_find_num_models_method1(elem0.copy_iter().chain(elem1.copy_iter()));
+    assert_eq!(count, 18);
+}
 
-    let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(4));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_partial_operation(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 625);
+pub fn validate_permutation_group<DOM>(domain: DOM)
+where
+    DOM: PermutationGroup,
+{
+    let symmetric = SymmetricGroup::new(SmallSet::new(domain.degree()));
 
+    // permutation is in the symmetric group
     let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(5));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_permutation(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 120);
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.as_permutation(&mut logic, elem0.slice());
+    let test = symmetric.contains(&mut logic, elem1.slice());
+    logic.bool_add_clause1(logic.bool_not(test));
+    assert!(!logic.bool_solvable());
 
+    // action is a homomorphism
     let mut logic = Solver::new("");
-    let domain = BinaryRelations::new(SmallSet::new(5));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_total_order(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 120);
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let elem2 = domain.product(&mut logic, elem0.slice(), elem1.slice());
+    let elem2 = domain.as_permutation(&mut logic, elem2.slice());
+    let elem0 = domain.as_permutation(&mut logic, elem0.slice());
+    let elem1 = domain.as_permutation(&mut logic, elem1.slice());
+    let elem3 = symmetric.product(&mut logic, elem0.slice(), elem1.slice());
+    let test = symmetric.equals(&mut logic, elem2.slice(), elem3.slice());
+    logic.bool_add_clause1(logic.bool_not(test));
+    assert!(!logic.bool_solvable());
 
+    // action is faithful
     let mut logic = Solver::new("");
-    let domain = SymmetricGroup::new(SmallSet::new(5));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_even_permutation(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 60);
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.as_permutation(&mut logic, elem0.slice());
+    let test0 = domain.is_identity(&mut logic, elem0.slice());
+    let test1 = symmetric.is_identity(&mut logic, elem1.slice());
+    let test2 = logic.bool_xor(test0, test1);
+    logic.bool_add_clause1(test2);
+    assert!(!logic.bool_solvable());
+}
+
+fn permutation_group_suite(tier: ValidationTier) {
+    validate_permutation_group(SymmetricGroup::new(SmallSet::new(3)));
+    validate_permutation_group(AlternatingGroup::new(SmallSet::new(4)));
+
+    let domain = WreathProduct::new(
+        SymmetricGroup::new(SmallSet::new(2)),
+        SymmetricGroup::new(SmallSet::new(2)),
+    );
+    validate_domain(domain.clone());
+    validate_indexable(domain.clone(), 8);
+    validate_group(domain.clone());
+    validate_permutation_group(domain);
+
+    if tier >= ValidationTier::Standard {
+        let domain = DirectProduct::new(
+            SymmetricGroup::new(SmallSet::new(2)),
+            SymmetricGroup::new(SmallSet::new(3)),
+        );
+        validate_domain(domain.clone());
+        validate_indexable(domain.clone(), 12);
+        validate_group(domain.clone());
+        validate_permutation_group(domain);
+    }
+
+    if tier >= ValidationTier::Thorough {
+        let domain = WreathProduct::new(
+            SymmetricGroup::new(SmallSet::new(3)),
+            AlternatingGroup::new(SmallSet::new(3)),
+        );
+        validate_indexable(domain.clone(), 648);
+        validate_group(domain.clone());
+        validate_permutation_group(domain);
+        validate_permutation_group(SymmetricGroup::new(SmallSet::new(5)));
+    }
 }
 
 #[test]
-fn unary_operations() {
-    let mut logic = Solver::new("");
-    let domain = UnaryOperations::new(SmallSet::new(6));
-    let elem = domain.add_variable(&mut logic);
-    let test = domain.is_permutation(&mut logic, elem.slice());
-    logic.bool_add_clause1(test);
-    let count = logic.bool_find_num_models_method1(elem.copy_iter());
-    assert_eq!(count, 720);
+fn permutation_group() {
+    permutation_group_suite(ValidationTier::from_env());
 }
 
 #[test]
-fn compatible_operations() {
-    let mut logic = Solver::new("");
-    let pres = Preservation::new(SmallSet::new(2), 2, 2);
+fn polymorphisms() {
+    let mut clone = PolymorphismClone::new(SmallSet::new(2));
+    assert_eq!(clone.count_operations_of_arity(2), 16);
 
-    let rel = logic.bool_lift_vec([true, true, false, true].iter().cloned());
-    assert_eq!(
-        pres.dom1().contains(&mut logic, rel.slice()),
-        logic.bool_lift(true)
-    );
-    let op = pres.dom0().add_variable(&mut logic);
-    let test = pres.is_edge(&mut logic, op.slice(), rel.slice());
-    logic.bool_add_clause1(test);
+    let order: BitVec = [true, false, true, true].iter().copied().collect();
+    clone.add_relation(2, order);
+    assert_eq!(clone.count_operations_of_arity(1), 3);
+    assert_eq!(clone.count_operations_of_arity(2), 6);
 
-    let count = logic.bool_find_num_models_method1(op.copy_iter());
-    assert_eq!(count, 6);
+    clone.add_relation(1, [true, false].iter().copied().collect());
+    clone.add_relation(1, [false, true].iter().copied().collect());
+    assert_eq!(clone.count_operations_of_arity(2), 4);
+    assert_eq!(clone.relations().count(), 3);
+    let classes = clone.classify_operations_of_arity(2);
+    assert_eq!(classes[&OperationClass::Projection], 2);
+    assert_eq!(classes[&OperationClass::EssentialSurjective], 2);
+
+    let mut logic = Logic();
+    let op = clone.find_operation_of_arity(3).unwrap();
+    assert!(clone.contains_operation(&mut logic, 3, op.slice()));
+    let neg = clone.operations(1).get_elem(&logic, 1);
+    assert!(!clone.contains_operation(&mut logic, 1, neg.slice()));
+}
+
+#[test]
+fn invariant_relations() {
+    let domain = SmallSet::new(2);
+    let mut logic = Logic();
+    let mut invs = InvariantRelations::new(domain.clone());
+    assert_eq!(invs.find_relations_of_arity(1).len(), 4);
+
+    let unary = Operations::new(domain.clone(), 1);
+    invs.add_operation(1, unary.get_elem(&logic, 1));
+    assert_eq!(invs.find_relations_of_arity(1).len(), 2);
+    assert_eq!(invs.find_relations_of_arity(2).len(), 4);
+    assert_eq!(invs.find_relations_up_to_arity(2).len(), 2 + 2 + 4);
+
+    let mut invs = InvariantRelations::new(domain.clone());
+    let binary = Operations::new(domain, 2);
+    let meet = binary.from_table(&logic, &OperationTable::new(2, 2, vec![0, 0, 0, 1]));
+    invs.add_operation(2, meet.clone());
+    assert_eq!(invs.operations().count(), 1);
+    let order: BitVec = [true, false, true, true].iter().copied().collect();
+    assert!(invs.contains_relation(&mut logic, 2, order.slice()));
+    let other: BitVec = [false, true, true, false].iter().copied().collect();
+    assert!(!invs.contains_relation(&mut logic, 2, other.slice()));
+
+    let mut pols = PolymorphismClone::new(SmallSet::new(2));
+    for relation in invs.find_relations_of_arity(2) {
+        pols.add_relation(2, relation);
+    }
+    assert!(pols.contains_operation(&mut logic, 2, meet.slice()));
+}
+
+#[test]
+fn maltsev_conditions() {
+    let logic = Logic();
+    let domain = SmallSet::new(2);
+    let ternary = Operations::new(domain.clone(), 3);
+    let binary = Operations::new(domain.clone(), 2);
+    let table = |arity: usize, func: fn(&[usize]) -> usize| {
+        let len = 1 << arity;
+        let values = (0..len)
+            .map(|i| func(&(0..arity).map(|j| (i >> j) & 1).collect::<Vec<_>>()))
+            .collect();
+        OperationTable::new(2, arity, values)
+    };
+
+    let mut alg = MaltsevConditions::new(domain.clone());
+    assert!(alg.find_taylor_term().is_none());
+    assert!(alg.find_maltsev_term().is_none());
+
+    let minority = table(3, |a| a[0] ^ a[1] ^ a[2]);
+    alg.add_operation(3, ternary.from_table(&logic, &minority).slice());
+    let term = alg.find_maltsev_term().unwrap();
+    let term = ternary.get_table(term.slice());
+    for x in 0..2 {
+        for y in 0..2 {
+            assert_eq!(term.apply(&[x, y, y]), x);
+            assert_eq!(term.apply(&[y, y, x]), x);
+        }
+    }
+    assert!(alg.find_majority_term().is_none());
+    assert!(alg.find_taylor_term().is_some());
+
+    let mut alg = MaltsevConditions::new(domain.clone());
+    let majority = table(3, |a| (a[0] & a[1]) | (a[1] & a[2]) | (a[0] & a[2]));
+    alg.add_operation(3, ternary.from_table(&logic, &majority).slice());
+    assert!(alg.find_majority_term().is_some());
+    assert!(alg.find_near_unanimity_term(4).is_some());
+    assert!(alg.find_maltsev_term().is_none());
+
+    let mut alg = MaltsevConditions::new(domain);
+    let meet = table(2, |a| a[0] & a[1]);
+    alg.add_operation(2, binary.from_table(&logic, &meet).slice());
+    assert_eq!(alg.operations().len(), 1);
+    assert!(alg.find_maltsev_term().is_none());
+    assert!(alg.find_majority_term().is_none());
+    let term = alg.find_weak_near_unanimity_term(3).unwrap();
+    assert!(!ternary.is_constant(&mut Logic(), term.slice()));
+    let term = alg.find_taylor_term().unwrap();
+    let term = Operations::new(SmallSet::new(2), 4).get_table(term.slice());
+    for r in 0..2 {
+        for a in 0..2 {
+            for e in 0..2 {
+                assert_eq!(term.apply(&[r, a, r, e]), term.apply(&[a, r, e, a]));
+            }
+        }
+    }
+}
+
+#[test]
+fn subuniverses() {
+    let logic = Logic();
+    let domain = SmallSet::new(3);
+    let unary = Operations::new(domain.clone(), 1);
+    let binary = Operations::new(domain.clone(), 2);
+
+    let mut algebra = Subuniverses::new(domain.clone());
+    assert_eq!(algebra.count(), 8);
+    let succ = OperationTable::new(3, 1, vec![1, 2, 0]);
+    algebra.add_operation(1, unary.from_table(&logic, &succ));
+    assert_eq!(algebra.find_all().len(), 2);
+    let single = Relations::new(domain.clone(), 1)
+        .get_singleton(&logic, &[domain.get_elem(&logic, 1).slice()]);
+    let generated = algebra.generate(&mut Logic(), single.slice());
+    assert_eq!(generated, algebra.get_top(&logic));
+    validate_bounded_order(algebra.clone());
+    validate_lattice(algebra);
+
+    let mut algebra = Subuniverses::new(domain.clone());
+    let zero = OperationTable::new(3, 1, vec![0, 0, 0]);
+    algebra.add_operation(1, unary.from_table(&logic, &zero));
+    let meet = OperationTable::new(3, 2, (0..9).map(|i| usize::min(i % 3, i / 3)).collect());
+    algebra.add_operation(2, binary.from_table(&logic, &meet));
+    assert_eq!(algebra.operations().count(), 2);
+    assert_eq!(algebra.count(), 5);
+    let subsets = Relations::new(domain, 1);
+    let bottom = algebra.get_bottom(&logic);
+    assert_eq!(subsets.get_index(bottom.slice()), 0);
+    let single = subsets.get_elem(&logic, 4);
+    let generated = algebra.generate(&mut Logic(), single.slice());
+    assert_eq!(subsets.get_index(generated.slice()), 5);
+    validate_lattice(algebra);
+}
+
+#[test]
+fn stabilizer() {
+    let size = 4;
+    let mut cycle = BitVec::with_values(size * size, false);
+    for i in 0..size {
+        cycle.set((i + 1) % size + i * size, true);
+        cycle.set(i + ((i + 1) % size) * size, true);
+    }
+    let mut domain = Stabilizer::new(SmallSet::new(size));
+    domain.add_relation(2, cycle);
+    validate_domain(domain.clone());
+    validate_group(domain.clone());
+    validate_permutation_group(domain.clone());
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.contains(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 8);
+
+    let mut logic = Logic();
+    let gens = domain.generators();
+    assert!(!gens.is_empty() && gens.len() <= 3);
+    let mut elems = vec![domain.get_identity(&logic)];
+    let mut index = 0;
+    while index < elems.len() {
+        for gen in gens.iter() {
+            assert!(domain.contains(&mut logic, gen.slice()));
+            let elem = domain.product(&mut logic, elems[index].slice(), gen.slice());
+            if !elems.contains(&elem) {
+                elems.push(elem);
+            }
+        }
+        index += 1;
+    }
+    assert_eq!(elems.len(), 8);
+    assert_eq!(domain.order(), Some(8));
+    let all = domain.find_all();
+    assert_eq!(all.len(), 8);
+    assert!(elems.iter().all(|elem| all.contains(elem)));
+
+    let mut poset = Automorphisms::new(SmallSet::new(4));
+    let mut leq = BitVec::with_values(16, false);
+    for (i, j) in [(0, 0), (1, 1), (2, 2), (3, 3), (0, 1), (0, 2), (0, 3)] {
+        leq.set(j + i * 4, true);
+    }
+    poset.add_relation(2, leq);
+    assert_eq!(poset.order(), Some(6));
+    assert_eq!(poset.find_all().len(), 6);
+    assert_eq!(Automorphisms::new(SmallSet::new(4)).order(), Some(24));
+    assert_eq!(Automorphisms::new(SmallSet::new(1)).order(), Some(1));
+    assert_eq!(
+        Automorphisms::new(SmallSet::new(12)).order(),
+        Some(479001600)
+    );
+    assert_eq!(Automorphisms::new(SmallSet::new(21)).order(), None);
+}
+
+#[test]
+fn homomorphisms() {
+    let cycle = |size: usize| {
+        let mut rel = BitVec::with_values(size * size, false);
+        for i in 0..size {
+            rel.set((i + 1) % size + i * size, true);
+            rel.set(i + ((i + 1) % size) * size, true);
+        }
+        rel
+    };
+
+    let mut target = Stabilizer::new(SmallSet::new(4));
+    target.add_relation(2, cycle(4));
+
+    let mut source = Stabilizer::new(SmallSet::new(2));
+    source.add_relation(2, [false, true, true, false].iter().copied().collect());
+    assert_eq!(find_homomorphisms(&source, &target).len(), 8);
+    assert_eq!(count_homomorphisms_up_to_auto(&source, &target), 1);
+
+    let mut source = Stabilizer::new(SmallSet::new(3));
+    let mut path = BitVec::with_values(9, false);
+    for (i, j) in [(0, 1), (1, 0), (1, 2), (2, 1)] {
+        path.set(j + i * 3, true);
+    }
+    source.add_relation(2, path);
+    assert_eq!(find_homomorphisms(&source, &target).len(), 16);
+    assert_eq!(count_homomorphisms_up_to_auto(&source, &target), 2);
+
+    let homs = Homomorphisms::from_structures(&source, &target);
+    assert_eq!(homs.count_homomorphisms(), 16);
+    let map = homs.find_homomorphism().unwrap();
+    let images = homs.get_images(map.slice());
+    assert_eq!((images[0] + 4 - images[1]) % 2, 1);
+    assert!(homs.find_isomorphism().is_none());
+
+    let mut other = Stabilizer::new(SmallSet::new(4));
+    let mut rel = BitVec::with_values(16, false);
+    for (i, j) in [(0, 2), (2, 1), (1, 3), (3, 0)] {
+        rel.set(j + i * 4, true);
+        rel.set(i + j * 4, true);
+    }
+    other.add_relation(2, rel);
+    let homs = Homomorphisms::from_structures(&other, &target);
+    assert_eq!(homs.count_homomorphisms(), 32);
+    let map = homs.find_isomorphism().unwrap();
+    let mut logic = Logic();
+    assert!(homs.is_isomorphism(&mut logic, map.slice()));
+    let mut images = homs.get_images(map.slice());
+    images.sort();
+    assert_eq!(images, vec![0, 1, 2, 3]);
+
+    let mut homs = Homomorphisms::new(SmallSet::new(2), SmallSet::new(3));
+    homs.add_relation(
+        1,
+        [true, false].iter().copied().collect(),
+        [false, true, true].iter().copied().collect(),
+    );
+    assert_eq!(homs.count_homomorphisms(), 6);
+    assert!(homs.find_isomorphism().is_none());
+}
+
+#[test]
+fn endomaps() {
+    let mut logic = Logic();
+    let domain = SymmetricGroup::new(SmallSet::new(4));
+    let cycle: BitVec = (0..16).map(|k| k % 4 == (k / 4 + 1) % 4).collect();
+    assert!(domain.contains(&mut logic, cycle.slice()));
+    let map = Translation::new(domain.clone(), cycle);
+    let identity = domain.get_identity(&logic);
+    assert_eq!(map.cycle_structure(identity.slice()), (0, 4));
+    assert!(map.find_fixed_points().is_empty());
+    assert!(map.find_periodic_points(2).is_empty());
+    assert_eq!(map.find_periodic_points(4).len(), 24);
+
+    let domain = UnaryOperations::new(SmallSet::new(2));
+    let constant: BitVec = [true, false, true, false].iter().copied().collect();
+    let map = Translation::new(domain.clone(), constant);
+    let identity = domain.get_identity(&logic);
+    assert_eq!(map.cycle_structure(identity.slice()), (1, 1));
+    assert_eq!(map.find_fixed_points().len(), 1);
+}
+
+#[test]
+fn partial_operations() {
+    let mut logic = Logic();
+    let domain = PartialOperations::new(SmallSet::new(3), 1);
+    assert_eq!(domain.size(), 64);
+
+    // the partial operation 0 -> 1, 1 -> 2 undefined at 2
+    let succ = domain.get_elem(&logic, 2 + 3 * 4);
+    assert!(domain.is_defined_at(&mut logic, succ.slice(), &[1]));
+    assert!(!domain.is_defined_at(&mut logic, succ.slice(), &[2]));
+    assert!(!domain.is_total(&mut logic, succ.slice()));
+    let defined = domain.domain_of(&mut logic, succ.slice());
+    assert_eq!(
+        defined.copy_iter().collect::<Vec<_>>(),
+        vec![true, true, false]
+    );
+
+    let square = domain.compose(&mut logic, succ.slice(), 1, &[succ.slice()]);
+    assert_eq!(domain.get_index(square.slice()), 3);
+    let subset: BitVec = [false, true, true].iter().copied().collect();
+    let restricted = domain.restriction(&mut logic, succ.slice(), subset.slice());
+    assert_eq!(domain.get_index(restricted.slice()), 3 * 4);
+    assert!(domain.is_edge(&mut logic, restricted.slice(), succ.slice()));
+
+    let ops = Operations::new(SmallSet::new(3), 1);
+    let op = ops.from_table(&logic, &OperationTable::new(3, 1, vec![1, 2, 0]));
+    let total = domain.from_operation(&mut logic, op.slice());
+    assert!(domain.is_total(&mut logic, total.slice()));
+    assert!(domain.is_edge(&mut logic, succ.slice(), total.slice()));
+    let empty = domain.get_empty(&logic);
+    assert_eq!(domain.get_index(empty.slice()), 0);
+}
+
+#[test]
+fn equitable_partition() {
+    let size = 4;
+    let graph = |edges: &[(usize, usize)]| {
+        let mut rel = BitVec::with_values(size * size, false);
+        for &(a, b) in edges {
+            rel.set(a + b * size, true);
+            rel.set(b + a * size, true);
+        }
+        rel
+    };
+
+    let cycle = graph(&[(0, 1), (1, 2), (2, 3), (3, 0)]);
+    let part = EquitablePartition::new(size, [(2, cycle.slice())]);
+    assert_eq!(part.num_colors(), 1);
+    assert_eq!(
+        part.quotient(2, cycle.slice()),
+        BitVec::with_values(1, true)
+    );
+
+    let mut path = Stabilizer::new(SmallSet::new(size));
+    path.add_relation(2, graph(&[(0, 1), (1, 2), (2, 3)]));
+    let part = EquitablePartition::from_structure(&path);
+    assert_eq!(part.colors(), &[1, 0, 0, 1]);
+    assert_eq!(part.blocks(), vec![vec![1, 2], vec![0, 3]]);
+    assert!(!part.is_discrete());
+    let (_, rel) = path.relations().next().unwrap();
+    let quot: Vec<bool> = part.quotient(2, rel).copy_iter().collect();
+    assert_eq!(quot, vec![true, true, true, false]);
+
+    let other = graph(&[(2, 0), (0, 3), (3, 1)]);
+    let star = graph(&[(0, 1), (0, 2), (0, 3)]);
+    assert!(part.may_be_isomorphic(&EquitablePartition::new(size, [(2, other.slice())])));
+    assert!(!part.may_be_isomorphic(&EquitablePartition::new(size, [(2, star.slice())])));
+}
+
+#[test]
+fn mono_unary() {
+    // a 2-cycle {0,1} with 2 -> 0 and 3 -> 2, and a fixed point 4
+    let alg = MonoUnary::new(vec![1, 0, 0, 2, 4]);
+    assert_eq!(alg.components(), vec![vec![0, 1, 2, 3], vec![4]]);
+    assert_eq!(alg.tail_lengths(), vec![0, 0, 1, 2, 0]);
+    assert_eq!(alg.cycle_lengths(), vec![2, 2, 2, 2, 1]);
+
+    let other = MonoUnary::new(vec![0, 3, 1, 4, 3]);
+    assert!(alg.is_isomorphic(&other));
+    let other = MonoUnary::new(vec![0, 3, 4, 4, 3]);
+    assert!(!alg.is_isomorphic(&other));
+
+    let table = OperationTable::new(3, 1, vec![1, 2, 0]);
+    assert_eq!(MonoUnary::from_table(&table).cycle_lengths(), vec![3, 3, 3]);
+
+    let counts: Vec<usize> = (0..7).map(count_mono_unary_algebras).collect();
+    assert_eq!(counts, vec![1, 1, 3, 7, 19, 47, 130]);
+}
+
+#[test]
+fn reified_constraints() {
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test0 = domain.is_transitive(&mut logic, elem.slice());
+    let test0 = logic.bool_reify(test0);
+    let test1 = domain.is_symmetric(&mut logic, elem.slice());
+    let test1 = logic.bool_reify(test1);
+    logic.bool_add_clause2(test0, test1);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 220);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_partial_order(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let diagonal = domain.get_identity(&logic);
+    let test = domain.reify_equals(&mut logic, elem.slice(), diagonal.slice());
+    let soft: Vec<_> = elem.copy_iter().collect();
+    let (count, model) = logic.bool_maximize(&soft, elem.copy_iter()).unwrap();
+    assert_eq!(count, 6);
+    assert!(domain.is_total_order(&mut Logic(), model.slice()));
+    let model = logic.bool_find_one_model(&[test], elem.copy_iter());
+    assert_eq!(model, Some(domain.get_identity(&Logic())));
+}
+
+#[test]
+fn soft_constraints() {
+    // the most transitive irreflexive relation containing a directed cycle
+    let size = 3;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    let cycle = domain.get_element_with(&Logic(), |i, j| j == (i + 1) % size);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    for i in 0..size {
+        let test = logic.bool_not(elem.get(i * (size + 1)));
+        logic.bool_add_clause1(test);
+    }
+    let lifted = domain.lift(&logic, cycle.slice());
+    let test = domain.is_edge(&mut logic, lifted.slice(), elem.slice());
+    logic.bool_add_clause1(test);
+
+    let mut soft = SoftConstraintSet::new();
+    for i in 0..size {
+        for j in 0..size {
+            for k in 0..size {
+                let a = logic.bool_and(elem.get(i + j * size), elem.get(j + k * size));
+                let test = logic.bool_imp(a, elem.get(i + k * size));
+                soft.add(&mut logic, &format!("{}{}{}", i, j, k), 1, test);
+            }
+        }
+    }
+
+    let best = soft.find_best(&mut logic, elem.copy_iter()).unwrap();
+    assert_eq!(best.weight, 24);
+    let names: Vec<&str> = best.violated.iter().map(|&i| soft.name(i)).collect();
+    assert_eq!(names, vec!["021", "102", "210"]);
+    assert_eq!(best.values, cycle);
+
+    soft.limit_violations(&mut logic, 2);
+    assert!(soft.find_best(&mut logic, elem.copy_iter()).is_none());
+}
+
+#[test]
+fn maximum_partial_order() {
+    // the partial order with the most edges where 0, 1 and 2, 3 are incomparable
+    let size = 6;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_partial_order(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    for (i, j) in [(0, 1), (1, 0), (2, 3), (3, 2)] {
+        let test = logic.bool_not(elem.get(i + j * size));
+        logic.bool_add_clause1(test);
+    }
+
+    let edges: Vec<_> = elem.copy_iter().collect();
+    let (count, values) = logic.bool_maximize(&edges, elem.copy_iter()).unwrap();
+    assert_eq!(count, 19);
+    assert_eq!(values.copy_iter().filter(|&b| b).count(), 19);
+}
+
+#[test]
+fn simulated_annealing() {
+    // the permutation with the most cyclic successor pairs
+    let size = 6;
+    let domain = SymmetricGroup::new(SmallSet::new(size));
+    let mut search = Annealing::new(domain, Neighborhood::Transposition(size), 1);
+    let start = search.domain().get_identity(&Logic());
+    let (value, elem) = search.maximize(start.slice(), |_, elem| {
+        (0..size)
+            .filter(|&i| elem.get(i + ((i + 1) % size) * size))
+            .count() as f64
+    });
+    assert_eq!(value, size as f64);
+    assert!(search.domain().contains(&mut Logic(), elem.slice()));
+
+    // the antisymmetric relation with the most edges
+    let size = 4;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    let mut search = Annealing::new(domain, Neighborhood::BitFlip, 2);
+    search.set_schedule(5000, 2.0, 0.05);
+    let start = search.random_elem();
+    let (value, _) = search.maximize(start.slice(), |_, elem| {
+        let mut value = elem.copy_iter().filter(|&b| b).count() as f64;
+        for i in 0..size {
+            for j in 0..i {
+                if elem.get(i + j * size) && elem.get(j + i * size) {
+                    value -= 2.0;
+                }
+            }
+        }
+        value
+    });
+    assert_eq!(value, 10.0);
+
+    // random elements of domains too large to be indexed
+    let mut rng = XorShift::new(3);
+    let domain = Operations::new(SmallSet::new(5), 3);
+    let elem = domain.random_elem(&mut rng);
+    assert!(domain.contains(&mut Logic(), elem.slice()));
+    let domain = ProductN::new(vec![BinaryRelations::new(SmallSet::new(9)); 2]);
+    let elem = domain.random_elem(&mut rng);
+    assert!(domain.contains(&mut Logic(), elem.slice()));
+}
+
+#[test]
+fn orderly_generation() {
+    let mut graphs = Vec::new();
+    let mut digraphs = Vec::new();
+    let mut posets = Vec::new();
+    for size in 0..6 {
+        graphs.push(0);
+        generate_graphs(size, |_| *graphs.last_mut().unwrap() += 1);
+        posets.push(0);
+        generate_posets(size, |_| *posets.last_mut().unwrap() += 1);
+        if size < 5 {
+            digraphs.push(0);
+            generate_digraphs(size, |_| *digraphs.last_mut().unwrap() += 1);
+        }
+    }
+    assert_eq!(graphs, vec![1, 1, 2, 4, 11, 34]);
+    assert_eq!(digraphs, vec![1, 1, 3, 16, 218]);
+    assert_eq!(posets, vec![1, 1, 2, 5, 16, 63]);
+
+    // larger sizes are feasible because of the degree refinement
+    let mut count = 0;
+    generate_graphs(7, |_| count += 1);
+    assert_eq!(count, 1044);
+
+    // the reflexive tournaments, which form a hereditary class
+    let mut gen = OrderlyGenerator::new(false, |dom, elem| dom.is_tournament(&mut Logic(), elem));
+    let mut found = Vec::new();
+    gen.generate(4, |elem| found.push(elem.copy_iter().collect::<BitVec>()));
+    assert_eq!(found.len(), 4);
+    assert_eq!(gen.count(5), 12);
+}
+
+#[test]
+fn census() {
+    let census = Census::posets(4);
+    assert_eq!(census.len(), 16);
+    let sizes: Vec<usize> = (0..=16).map(|e| census.with_edges(e).len()).collect();
+    assert_eq!(sizes.iter().sum::<usize>(), 16);
+    assert_eq!((sizes[3], sizes[4], sizes[10], sizes[11]), (0, 1, 1, 0));
+
+    // every poset is found by its isomorphism class
+    let domain = BinaryRelations::new(SmallSet::new(4));
+    let mut logic = Logic();
+    for index in 0..census.len() {
+        let elem = census.get(index);
+        assert!(domain.is_partial_order(&mut logic, elem.slice()));
+        let image: BitVec = (0..16)
+            .map(|i| elem.get((i + 1) % 4 + ((i / 4 + 1) % 4) * 4))
+            .collect();
+        assert_eq!(canonical_relation(4, image.slice()), elem);
+        assert_eq!(census.position(image.slice()), Some(index));
+    }
+
+    let chains = census.filter(|dom, elem| dom.is_total_order(&mut Logic(), elem));
+    assert_eq!(chains, census.with_edges(10).collect::<Vec<_>>());
+    let sample = census.sample(5, 1);
+    assert_eq!(sample.len(), 5);
+    assert!(sample.iter().all(|&i| i < 16));
+
+    let mut buffer = Vec::new();
+    census.write(&mut buffer).unwrap();
+    assert_eq!(buffer.len(), 10 + 17 * 4 + 16 * 2);
+    assert_eq!(Census::read(&mut buffer.as_slice()).unwrap(), census);
+    buffer[0] = b'X';
+    assert!(Census::read(&mut buffer.as_slice()).is_err());
+    assert_eq!(Census::graphs(5).iter().count(), 34);
+
+    let census = Census::digraphs(3);
+    let mut buffer = Vec::new();
+    census.write(&mut buffer).unwrap();
+    let last = buffer.len() - 1;
+    buffer[last] |= 0x80;
+    assert!(Census::read(&mut buffer.as_slice()).is_err());
+    buffer[last] &= 0x7f;
+    assert_eq!(Census::read(&mut buffer.as_slice()).unwrap(), census);
+    buffer[6..10].copy_from_slice(&[0xff; 4]);
+    assert!(Census::read(&mut buffer.as_slice()).is_err());
+}
+
+#[test]
+fn reduction_gadgets() {
+    let graph = |size: usize, edges: &[(usize, usize)]| {
+        let mut relation: BitVec = Vector::with_values(size * size, false);
+        for &(a, b) in edges {
+            relation.set(a + b * size, true);
+            relation.set(b + a * size, true);
+        }
+        let mut structure = Stabilizer::new(SmallSet::new(size));
+        structure.add_relation(2, relation);
+        structure
+    };
+    let k3 = graph(3, &[(0, 1), (1, 2), (0, 2)]);
+
+    // two ports adjacent to both ends of an edge must have the same color
+    let mut equality = Gadget::new(2, 4);
+    for (a, b) in [(2, 3), (0, 2), (0, 3), (1, 2), (1, 3)] {
+        equality.add_tuple(0, vec![a, b]);
+        equality.add_tuple(0, vec![b, a]);
+    }
+    assert!(equality.is_equality(&k3));
+    assert!(!Gadget::identity(2, 0).is_equality(&k3));
+
+    let mut crossover = Gadget::new(4, 4);
+    crossover.add_gadget(&equality, &[0, 2]);
+    crossover.add_gadget(&equality, &[1, 3]);
+    assert_eq!(crossover.size(), 8);
+    assert!(crossover.is_crossover(&k3));
+
+    // eliminating the equality relation of the extended template
+    let source = add_equality(&k3);
+    let reduction = Reduction::new(vec![Gadget::identity(2, 0), equality]);
+    assert!(reduction.verify(&source, &k3));
+
+    let path = graph(3, &[(0, 1), (1, 2)]);
+    let k4 = graph(4, &[(0, 1), (1, 2), (2, 3), (0, 3), (0, 2), (1, 3)]);
+    for (instance, colorings) in [(path, 12), (k4, 0)] {
+        assert_eq!(find_homomorphisms(&instance, &k3).len(), colorings);
+        let split = split_occurrences(&instance);
+        assert_eq!(find_homomorphisms(&split, &source).len(), colorings);
+        let reduced = reduction.apply(&split, &[2]);
+        assert_eq!(find_homomorphisms(&reduced, &k3).is_empty(), colorings == 0);
+        let (contracted, map) = contract_equalities(&split, 1);
+        assert_eq!(contracted.domain().size(), instance.domain().size());
+        assert_eq!(
+            &map[..instance.domain().size()],
+            &[0, 1, 2, 3, 4][..instance.domain().size()]
+        );
+        assert_eq!(find_homomorphisms(&contracted, &k3).len(), colorings);
+    }
+
+    // the two colorable graphs embedded into the three colorable ones
+    let k2 = graph(2, &[(0, 1)]);
+    let mut target = k3.clone();
+    target.add_relation(1, [true, true, false].iter().copied().collect());
+    let mut edge = Gadget::new(2, 2);
+    edge.add_tuple(0, vec![0, 1]);
+    edge.add_tuple(1, vec![0]);
+    edge.add_tuple(1, vec![1]);
+    let reduction = Reduction::new(vec![edge]);
+    assert!(!reduction.verify(&k2, &target));
+    assert!(reduction
+        .clone()
+        .with_encoding(vec![0, 1])
+        .verify(&k2, &target));
+    assert!(!reduction.with_encoding(vec![0, 2]).verify(&k2, &target));
+}
+
+#[test]
+fn violation_counts() {
+    let size = 3;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    let value = |bits: BitVec| bits.copy_iter().rev().fold(0, |v, b| 2 * v + b as usize);
+
+    let mut logic = Logic();
+    for index in (0..domain.size()).step_by(7) {
+        let elem = domain.get_elem(&logic, index);
+        let rel = |a: usize, b: usize| elem.get(a + b * size);
+        let mut counts = [0; 4];
+        for a in 0..size {
+            counts[0] += !rel(a, a) as usize;
+            for b in 0..size {
+                counts[1] += (a != b && rel(a, b) && !rel(b, a)) as usize;
+                counts[2] += (a < b && rel(a, b) && rel(b, a)) as usize;
+                for c in 0..size {
+                    counts[3] += (rel(a, b) && rel(b, c) && !rel(a, c)) as usize;
+                }
+            }
+        }
+
+        let elem = elem.slice();
+        let num = domain.num_reflexivity_violations(&mut logic, elem);
+        assert_eq!(value(num), counts[0]);
+        let num = domain.num_symmetry_violations(&mut logic, elem);
+        assert_eq!(value(num), counts[1]);
+        let num = domain.num_antisymmetry_violations(&mut logic, elem);
+        assert_eq!(value(num), counts[2]);
+        let num = domain.num_transitivity_violations(&mut logic, elem);
+        assert_eq!(value(num), counts[3]);
+    }
+
+    // relations with at most one transitivity violation
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let num = domain.num_transitivity_violations(&mut logic, elem.slice());
+    let bound: Vec<_> = (0..num.len()).map(|i| logic.bool_lift(i == 0)).collect();
+    let test = logic.bool_cmp_leq(num.copy_iter().zip(bound));
+    logic.bool_add_clause1(test);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 285);
+}
+
+#[test]
+fn bounded_fixpoint_iteration() {
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let step = |logic: &mut Solver, elem: &[_]| {
+        let comp = Semigroup::product(&domain, logic, elem, elem);
+        domain.join(logic, elem, comp.slice())
+    };
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let (_, test) = bounded_fixpoint(&domain, &mut logic, elem.slice(), 0, step);
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 171);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let (last, test) = bounded_fixpoint(&domain, &mut logic, elem.slice(), 2, step);
+    let test = logic.bool_not(test);
+    let unsat = logic.bool_find_one_model(&[test], elem.copy_iter());
+    assert!(unsat.is_none());
+    let test = domain.is_top(&mut logic, last.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 144);
+}
+
+#[test]
+fn canonical_basis() {
+    let set = |elems: &[bool]| -> BitVec { elems.iter().copied().collect() };
+
+    let closed = vec![set(&[false, false, false]), set(&[true, false, false])];
+    let basis = canonical_basis_of_closed_sets(3, &closed);
+    assert_eq!(basis.len(), 2);
+    assert_eq!(basis[0].premise, set(&[false, false, true]));
+    assert_eq!(basis[1].premise, set(&[false, true, false]));
+    assert_eq!(basis[1].conclusion, set(&[true, true, true]));
+
+    let basis = canonical_basis_of_predicate(3, |_, elem| {
+        (!elem.get(0) || elem.get(1)) && (!elem.get(1) || elem.get(2))
+    });
+    assert_eq!(basis.len(), 2);
+    assert_eq!(basis[0].premise, set(&[false, true, false]));
+    assert_eq!(basis[0].conclusion, set(&[false, true, true]));
+    assert_eq!(basis[1].premise, set(&[true, false, false]));
+    assert_eq!(basis[1].conclusion, set(&[true, true, true]));
+
+    let basis = canonical_basis_of_predicate(4, |_, _| true);
+    assert!(basis.is_empty());
+}
+
+#[test]
+fn linear_extensions() {
+    let rels = BinaryRelations::new(SmallSet::new(4));
+    let logic = Logic();
+
+    let poset = Poset::from_relation(&rels, rels.get_element_with(&logic, |i, j| i == j).slice());
+    assert_eq!(poset.count_linear_extensions(), 24);
+    assert_eq!(poset.count_linear_extensions_sat(), 24);
+    assert_eq!(poset.chain_decomposition().len(), 4);
+
+    let poset = Poset::from_relation(&rels, rels.get_element_with(&logic, |i, j| i <= j).slice());
+    assert_eq!(poset.count_linear_extensions(), 1);
+    assert_eq!(poset.chain_decomposition(), vec![vec![0, 1, 2, 3]]);
+
+    let elem = rels.get_element_with(&logic, |i, j| i == j || (i < 2 && j == i + 2));
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.count_linear_extensions(), 6);
+    assert_eq!(poset.count_linear_extensions_sat(), 6);
+    assert_eq!(poset.chain_decomposition().len(), 2);
+
+    let mut seed: usize = 1;
+    let mut samples = Vec::new();
+    for _ in 0..200 {
+        let ext = poset.sample_linear_extension(|bound| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        });
+        let pos = |e: usize| ext.iter().position(|&x| x == e).unwrap();
+        assert!(pos(0) < pos(2) && pos(1) < pos(3));
+        if !samples.contains(&ext) {
+            samples.push(ext);
+        }
+    }
+    assert_eq!(samples.len(), 6);
+}
+
+#[test]
+fn mobius_function() {
+    let logic = Logic();
+
+    let rels = BinaryRelations::new(SmallSet::new(8));
+    let elem = rels.get_element_with(&logic, |i, j| i & j == i);
+    let poset = Poset::from_relation(&rels, elem.slice());
+    let mobius = poset.mobius_matrix();
+    for (i, &value) in mobius[0].iter().enumerate() {
+        assert_eq!(value, (-1i64).pow((i as u32).count_ones()));
+    }
+    let zeta = poset.zeta_matrix();
+    for (i, row) in zeta.iter().enumerate() {
+        for j in 0..8 {
+            let prod: i64 = row.iter().zip(mobius.iter()).map(|(a, b)| a * b[j]).sum();
+            assert_eq!(prod, (i == j) as i64);
+        }
+    }
+    assert_eq!(poset.characteristic_polynomial(), Some(vec![-1, 3, -3, 1]));
+
+    let rels = BinaryRelations::new(SmallSet::new(3));
+    let elem = rels.get_element_with(&logic, |i, j| i <= j);
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.mobius_matrix()[0], vec![1, -1, 0]);
+    assert_eq!(poset.rank_function(), Some(vec![0, 1, 2]));
+    assert_eq!(poset.characteristic_polynomial(), Some(vec![0, -1, 1]));
+
+    let elem = rels.get_element_with(&logic, |i, j| i == j || (i == 0 && j == 1));
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.bottom(), None);
+    assert_eq!(poset.characteristic_polynomial(), None);
+}
+
+#[test]
+fn order_polynomial() {
+    let logic = Logic();
+    let rels = BinaryRelations::new(SmallSet::new(3));
+
+    let elem = rels.get_element_with(&logic, |i, j| i == j);
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.order_polynomial(0), 0);
+    assert_eq!(poset.order_polynomial(3), 27);
+    assert_eq!(poset.order_polynomial_sat(3), 27);
+
+    let elem = rels.get_element_with(&logic, |i, j| i <= j);
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.order_polynomial(1), 1);
+    assert_eq!(poset.order_polynomial(3), 10);
+    assert_eq!(poset.order_polynomial_sat(3), 10);
+
+    let elem = rels.get_element_with(&logic, |i, j| i == j || i == 0);
+    let poset = Poset::from_relation(&rels, elem.slice());
+    assert_eq!(poset.order_polynomial(4), 30);
+    assert_eq!(poset.order_polynomial_sat(4), 30);
+
+    let zeta = poset.zeta_matrix();
+    let mobius = poset.mobius_matrix();
+    let delta = poset.convolution(&zeta, &mobius);
+    assert_eq!(delta, vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]]);
+    let count = poset.convolution(&zeta, &zeta);
+    assert_eq!(count[0], vec![1, 2, 2]);
+}
+
+#[test]
+fn binary_relations() {
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(4));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_transitive(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 3994);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(7));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_equivalence(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, bell(7));
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(5));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_partial_order(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 4231);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(4));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_partial_operation(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 625);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(5));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_permutation(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, factorial(5));
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(5));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_total_order(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, factorial(5));
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_antisymmetric(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 216);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_acyclic(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 200);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(4));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_dag(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 543);
+    let count = domain
+        .evaluate_all(&mut Logic(), |logic, elem| {
+            domain.has_antisymmetric_closure(logic, elem)
+        })
+        .copy_iter()
+        .filter(|&b| b)
+        .count();
+    assert_eq!(count, 543 * 16);
+    let count = domain
+        .evaluate_all(&mut Logic(), |logic, elem| domain.is_acyclic(logic, elem))
+        .copy_iter()
+        .filter(|&b| b)
+        .count();
+    assert_eq!(count, 543 * 16);
+
+    let mut logic = Solver::new("");
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_dag(&mut logic, elem.slice());
+    let test = logic.bool_not(test);
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 512 - 25);
+
+    let mut logic = Logic();
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let count = domain
+        .evaluate_all(&mut logic, |logic, elem| {
+            domain.is_strongly_connected(logic, elem)
+        })
+        .copy_iter()
+        .filter(|&b| b)
+        .count();
+    assert_eq!(count, 144);
+    let count = domain
+        .evaluate_all(&mut logic, |logic, elem| domain.is_connected(logic, elem))
+        .copy_iter()
+        .filter(|&b| b)
+        .count();
+    assert_eq!(count, 432);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_connected(&mut logic, elem.slice());
+    let test = logic.bool_not(test);
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 512 - 432);
+
+    // simple graphs on four vertices
+    let domain = BinaryRelations::new(SmallSet::new(4));
+    let graph = |logic: &mut Solver| {
+        let elem = domain.add_variable(logic);
+        let test = domain.is_symmetric(logic, elem.slice());
+        logic.bool_add_clause1(test);
+        for i in 0..4 {
+            let test = logic.bool_not(elem.get(i * 5));
+            logic.bool_add_clause1(test);
+        }
+        elem
+    };
+
+    let mut logic = Solver::new("");
+    let elem = graph(&mut logic);
+    domain.add_hamiltonian_path(&mut logic, elem.slice());
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 34);
+
+    let mut logic = Solver::new("");
+    let elem = graph(&mut logic);
+    domain.add_chromatic_number_at_most(&mut logic, elem.slice(), 2);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 41);
+
+    let mut logic = Solver::new("");
+    let elem = graph(&mut logic);
+    domain.add_chromatic_number_at_most(&mut logic, elem.slice(), 1);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 1);
+
+    let mut logic = Solver::new("");
+    let domain = SymmetricGroup::new(SmallSet::new(5));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_even_permutation(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 60);
+}
+
+#[test]
+fn compiled_predicate() {
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let pred = CompiledPredicate::new(domain.num_bits(), |logic, elem| {
+        domain.is_transitive(logic, elem)
+    });
+
+    let mut logic = Logic();
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let expected = domain.is_transitive(&mut logic, elem.slice());
+        assert_eq!(pred.apply(&mut logic, elem.slice()), expected);
+    }
+
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let test0 = pred.apply(&mut logic, elem0.slice());
+    let test1 = pred.apply(&mut logic, elem1.slice());
+    logic.bool_add_clause1(test0);
+    logic.bool_add_clause1(test1);
+    let count = logic.bool_find_num_models_method1(elem0.copy_iter().chain(elem1.copy_iter()));
+    assert_eq!(count, 171 * 171);
+}
+
+#[test]
+fn condensation() {
+    let mut logic = Logic();
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let mut reach: Vec<bool> = elem.copy_iter().collect();
+        for k in 0..3 {
+            reach[k * 4] = true;
+        }
+        for k in 0..3 {
+            for i in 0..3 {
+                for j in 0..3 {
+                    if reach[k + i * 3] && reach[j + k * 3] {
+                        reach[j + i * 3] = true;
+                    }
+                }
+            }
+        }
+
+        let cond = domain.condensation(&mut logic, elem.slice());
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(cond.closure.get(j + i * 3), reach[j + i * 3]);
+                let comp = reach[j + i * 3] && reach[i + j * 3];
+                assert_eq!(cond.components.get(j + i * 3), comp);
+            }
+            let leader = (0..i).all(|j| !(reach[j + i * 3] && reach[i + j * 3]));
+            assert_eq!(cond.leaders.get(i), leader);
+        }
+    }
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let cond = domain.condensation(&mut logic, elem.slice());
+    let test = domain.has_chain_condensation(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    for lit in cond.leaders.copy_iter() {
+        logic.bool_add_clause1(lit);
+    }
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 96);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let cond = domain.condensation(&mut logic, elem.slice());
+    for lit in cond.leaders.copy_iter().skip(1) {
+        let lit = logic.bool_not(lit);
+        logic.bool_add_clause1(lit);
+    }
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 144);
+}
+
+#[test]
+fn make_and_split_elem() {
+    let logic = Logic();
+    let domain = Product2::new(SmallSet::new(3), Power::new(SmallSet::new(2), 3));
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let (part0, part1) = domain.split_elem(elem.slice());
+        let parts = domain.dom1().split_elem(part1.slice());
+        assert_eq!(parts.len(), 3);
+        let part1 = domain.dom1().make_elem(parts);
+        assert_eq!(domain.make_elem(part0, part1), elem);
+    }
+}
+
+#[test]
+fn tuples() {
+    let domain = Tuples::new(SmallSet::new(4), 3);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_distinct(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 24);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_sorted(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 20);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_increasing(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 4);
+
+    let mut logic = Logic();
+    let relations = domain.relations();
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let coords: Vec<_> = domain.coord_iter(elem.slice()).collect();
+        let singleton = relations.get_singleton(&logic, &coords);
+        assert_eq!(domain.to_singleton(&mut logic, elem.slice()), singleton);
+        if index > 0 {
+            let prev = domain.get_elem(&logic, index - 1);
+            assert!(domain.precedes(&mut logic, prev.slice(), elem.slice()));
+        }
+    }
+}
+
+#[test]
+fn sparse_relations() {
+    let mut logic = Solver::new("");
+    let domain = SparseRelations::new(SmallSet::new(3), 2, 2);
+    let elem = domain.add_variable(&mut logic);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 36);
+
+    let mut logic = Solver::new("");
+    let domain = SparseRelations::new(SmallSet::new(3), 2, 3);
+    let elem = domain.add_variable(&mut logic);
+    let dense = domain.to_dense(&mut logic, elem.slice());
+    let binrels = BinaryRelations::new(SmallSet::new(3));
+    let test = binrels.is_symmetric(&mut logic, dense.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 10);
+
+    let mut logic = Logic();
+    let domain = SparseRelations::new(SmallSet::new(3), 2, 2);
+    for index in 0..binrels.size() {
+        let dense = binrels.get_elem(&logic, index);
+        if let Some(elem) = domain.from_dense(dense.slice()) {
+            assert!(domain.contains(&mut logic, elem.slice()));
+            assert!(domain.is_dense_of(&mut logic, elem.slice(), dense.slice()));
+        }
+    }
+
+    let mut logic = Solver::new("");
+    let domain = SparseRelations::new(SmallSet::new(5), 4, 5);
+    let elem = domain.add_variable(&mut logic);
+    let diag = domain.dense().get_diagonal(&logic);
+    let test = domain.is_dense_of(&mut logic, elem.slice(), diag.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn evaluate_all() {
+    let mut logic = Logic();
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let elems = domain.evaluate_all(&mut logic, |logic, elem| domain.is_transitive(logic, elem));
+    assert_eq!(elems.copy_iter().filter(|b| *b).count(), 171);
+
+    let domain = SymmetricGroup::new(SmallSet::new(4));
+    let elems = domain.evaluate_all(&mut logic, |logic, elem| {
+        domain.is_even_permutation(logic, elem)
+    });
+    assert_eq!(elems.copy_iter().filter(|b| *b).count(), 12);
+}
+
+#[test]
+fn matrix_symmetry() {
+    let mut rows = MatrixSymmetry::new(3, 3);
+    rows.add_row_block(vec![0, 1, 2]);
+    let mut logic = Solver::new("");
+    let elem: Vec<_> = (0..9).map(|_| logic.bool_add_variable()).collect();
+    rows.add_lex_constraints(&mut logic, &elem);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 120);
+
+    let mut both = rows.clone();
+    both.add_col_block(vec![0, 1, 2]);
+    let perms = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut logic = Logic();
+    let mut count = 0;
+    for index in 0..512 {
+        let elem: BitVec = (0..9).map(|k| (index >> k) & 1 != 0).collect();
+        count += both.is_lex_ordered(&mut logic, elem.slice()) as usize;
+        let mut found = false;
+        for p in perms.iter() {
+            for q in perms.iter() {
+                let other: BitVec = (0..9).map(|k| elem.get(q[k % 3] + 3 * p[k / 3])).collect();
+                found |= both.is_lex_ordered(&mut logic, other.slice());
+            }
+        }
+        assert!(found);
+    }
+    assert_eq!(count, 45);
+}
+
+#[test]
+fn relation_composition() {
+    let domain = BinaryRelations::new(SmallSet::new(3));
+    let mut logic = Logic();
+    for index in 0..200 {
+        let elem0 = domain.get_elem(&logic, (index * 37) % domain.size());
+        let elem1 = domain.get_elem(&logic, (index * 101 + 7) % domain.size());
+        let elem2 = domain.product(&mut logic, elem0.slice(), elem1.slice());
+        let elem3 = domain.compose_by_polymers(&mut logic, elem0.slice(), elem1.slice());
+        let elem4 = domain.compose_packed(elem0.slice(), elem1.slice());
+        assert_eq!(elem2, elem3);
+        assert_eq!(elem2, elem4);
+
+        let elem5: BitVec = domain.converse(elem0.slice());
+        assert_eq!(domain.converse_packed(elem0.slice()), elem5);
+        let elem6 = domain.transitive_closure_packed(elem0.slice());
+        let elem7 = domain.product(&mut logic, elem6.slice(), elem6.slice());
+        assert!(domain.is_transitive(&mut logic, elem6.slice()));
+        assert!(domain.is_edge(&mut logic, elem0.slice(), elem6.slice()));
+        assert!(domain.is_edge(&mut logic, elem7.slice(), elem6.slice()));
+        assert_eq!(domain.transitive_closure(&mut logic, elem0.slice()), elem6);
+        let elem8 = domain.reflexive_transitive_closure(&mut logic, elem0.slice());
+        let elem8 = domain.product(&mut logic, elem0.slice(), elem8.slice());
+        assert_eq!(elem8, elem6);
+    }
+
+    let path = domain.get_element_with(&logic, |i, j| i == j + 1);
+    let start: BitVec = [false, true, false].iter().copied().collect();
+    let reach = domain.reachable_from(&mut logic, path.slice(), start.slice());
+    assert_eq!(
+        reach.copy_iter().collect::<Vec<_>>(),
+        vec![false, true, true]
+    );
+
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    let elem2 = domain.product(&mut logic, elem0.slice(), elem1.slice());
+    let elem3 = domain.compose_by_polymers(&mut logic, elem0.slice(), elem1.slice());
+    let test = domain.equals(&mut logic, elem2.slice(), elem3.slice());
+    logic.bool_add_clause1(logic.bool_not(test));
+    assert!(!logic.bool_solvable());
+}
+
+#[test]
+#[ignore]
+fn bench_relation_composition() {
+    use std::time::Instant;
+
+    // the direct encoding needs fewer clauses than the polymer one
+    let domain = BinaryRelations::new(SmallSet::new(12));
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    domain.compose_by_polymers(&mut logic, elem0.slice(), elem1.slice());
+    let clauses0 = logic.bool_num_clauses();
+
+    let mut logic = Solver::new("");
+    let elem0 = domain.add_variable(&mut logic);
+    let elem1 = domain.add_variable(&mut logic);
+    domain.product(&mut logic, elem0.slice(), elem1.slice());
+    let clauses1 = logic.bool_num_clauses();
+    assert!(clauses1 <= clauses0, "{} > {}", clauses1, clauses0);
+
+    // concrete composition works on packed rows
+    let size = 200;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    let elem0: BitVec = (0..domain.num_bits()).map(|i| i % 7 == 0).collect();
+    let elem1: BitVec = (0..domain.num_bits()).map(|i| i % 11 == 0).collect();
+    let start = Instant::now();
+    let elem2: BitVec = (0..size * size)
+        .map(|i| {
+            (0..size).any(|a| elem0.get(i % size + a * size) && elem1.get(a + i / size * size))
+        })
+        .collect();
+    let time0 = start.elapsed();
+    let start = Instant::now();
+    let elem3 = domain.product(&mut Logic(), elem0.slice(), elem1.slice());
+    let time1 = start.elapsed();
+    assert_eq!(elem2, elem3);
+    assert!(time1 <= time0, "{:?} > {:?}", time1, time0);
+}
+
+#[test]
+fn unary_operations() {
+    let mut logic = Solver::new("");
+    let domain = UnaryOperations::new(SmallSet::new(6));
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_permutation(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    let count = logic.bool_find_num_models_method1(elem.copy_iter());
+    assert_eq!(count, 720);
+}
+
+#[test]
+fn kernel_and_image() {
+    let domain = UnaryOperations::new(SmallSet::new(4));
+    let rels = BinaryRelations::new(SmallSet::new(4));
+    let unary = Relations::new(SmallSet::new(4), 1);
+    let mut logic = Logic();
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let table = domain.get_table(elem.slice());
+        let kernel = domain.kernel(&mut logic, elem.slice());
+        assert!(rels.is_equivalence(&mut logic, kernel.slice()));
+        let image = domain.image(&mut logic, elem.slice());
+        assert!(unary.contains(&mut logic, image.slice()));
+        for a in 0..4 {
+            assert_eq!(image.get(a), table.values().contains(&a));
+            for b in 0..4 {
+                let same = table.apply(&[a]) == table.apply(&[b]);
+                assert_eq!(kernel.get(a + 4 * b), same);
+            }
+        }
+    }
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let kernel = domain.kernel(&mut logic, elem.slice());
+    let test = rels.is_identity(&mut logic, kernel.slice());
+    let perm = domain.is_permutation(&mut logic, elem.slice());
+    let test = logic.bool_equ(test, perm);
+    logic.bool_add_clause1(logic.bool_not(test));
+    assert!(!logic.bool_solvable());
+}
+
+#[test]
+fn injective_surjective() {
+    let domain = UnaryOperations::new(SmallSet::new(4));
+    let mut logic = Logic();
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        let perm = domain.is_permutation(&mut logic, elem.slice());
+        assert_eq!(domain.is_injective(&mut logic, elem.slice()), perm);
+        assert_eq!(domain.is_surjective(&mut logic, elem.slice()), perm);
+        assert_eq!(domain.is_bijective(&mut logic, elem.slice()), perm);
+    }
+
+    let domain = Operations::new(SmallSet::new(2), 2);
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_surjective(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 14);
+
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_injective(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 0);
+
+    let domain = Operations::new(SmallSet::new(4), 1);
+    let mut logic = Solver::new("");
+    let elem = domain.add_variable(&mut logic);
+    let test = domain.is_bijective(&mut logic, elem.slice());
+    logic.bool_add_clause1(test);
+    assert_eq!(logic.bool_find_num_models_method1(elem.copy_iter()), 24);
+}
+
+#[test]
+fn derangements() {
+    let domain = SymmetricGroup::new(SmallSet::new(4));
+    let mut logic = Logic();
+    let mut counts = [0; 7];
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        counts[0] += domain.is_derangement(&mut logic, elem.slice()) as usize;
+        counts[1] += domain.is_involution(&mut logic, elem.slice()) as usize;
+        for k in 0..5 {
+            counts[k + 2] += domain.has_fixed_points(&mut logic, elem.slice(), k) as usize;
+        }
+    }
+    assert_eq!(counts, [9, 10, 9, 8, 6, 0, 1]);
+
+    let domain = UnaryOperations::new(SmallSet::new(3));
+    let mut logic = Logic();
+    let mut counts = [0; 5];
+    for index in 0..domain.size() {
+        let elem = domain.get_elem(&logic, index);
+        counts[0] += domain.is_fixed_point_free(&mut logic, elem.slice()) as usize;
+        counts[1] += domain.has_fixed_points(&mut logic, elem.slice(), 1) as usize;
+        counts[2] += domain.is_derangement(&mut logic, elem.slice()) as usize;
+        counts[3] += domain.is_involution(&mut logic, elem.slice()) as usize;
+        counts[4] += domain.has_fixed_points(&mut logic, elem.slice(), 3) as usize;
+    }
+    assert_eq!(counts, [8, 12, 2, 4, 1]);
+}
+
+#[test]
+fn combinatorial_counts() {
+    for size in 1..6 {
+        let domain = BinaryRelations::new(SmallSet::new(size));
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.is_equivalence(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        assert_eq!(
+            logic.bool_find_num_models_method1(elem.copy_iter()),
+            bell(size)
+        );
+    }
+
+    let size = 5;
+    let domain = BinaryRelations::new(SmallSet::new(size));
+    for blocks in 0..=size {
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.is_equivalence(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let leaders: Vec<_> = (0..size)
+            .map(|i| {
+                let test = logic.bool_fold_any((0..i).map(|j| elem.get(j + i * size)));
+                logic.bool_not(test)
+            })
+            .collect();
+        let test = logic.bool_fold_count(leaders.into_iter(), blocks);
+        logic.bool_add_clause1(test);
+        assert_eq!(
+            logic.bool_find_num_models_method1(elem.copy_iter()),
+            stirling2(size, blocks)
+        );
+    }
+
+    for size in 1..6 {
+        let domain = UnaryOperations::new(SmallSet::new(size));
+        let rows = Power::new(SmallSet::new(size), size);
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        for i in 0..size {
+            for j in 0..i {
+                logic.bool_add_clause1(logic.bool_not(elem.get(j + i * size)));
+            }
+            if i > 0 {
+                let row0 = rows.part(elem.slice(), i - 1);
+                let row1 = rows.part(elem.slice(), i);
+                let test = logic.bool_cmp_leq(row0.copy_iter().zip(row1.copy_iter()));
+                logic.bool_add_clause1(test);
+            }
+        }
+        assert_eq!(
+            logic.bool_find_num_models_method1(elem.copy_iter()),
+            catalan(size)
+        );
+    }
+
+    let size = 5;
+    let domain = SymmetricGroup::new(SmallSet::new(size));
+    let rows = Power::new(SmallSet::new(size), size);
+    for descents in 0..size {
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let tests: Vec<_> = (1..size)
+            .map(|i| {
+                let row0 = rows.part(elem.slice(), i - 1);
+                let row1 = rows.part(elem.slice(), i);
+                logic.bool_cmp_ltn(row1.copy_iter().zip(row0.copy_iter()))
+            })
+            .collect();
+        let test = logic.bool_fold_count(tests.into_iter(), descents);
+        logic.bool_add_clause1(test);
+        assert_eq!(
+            logic.bool_find_num_models_method1(elem.copy_iter()),
+            eulerian(size, descents)
+        );
+    }
+
+    for size in 1..6 {
+        let domain = SymmetricGroup::new(SmallSet::new(size));
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.contains(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let test = domain.is_derangement(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        assert_eq!(
+            logic.bool_find_num_models_method1(elem.copy_iter()),
+            subfactorial(size)
+        );
+    }
+}
+
+#[test]
+fn counting_methods() {
+    let count = check_num_models(|| {
+        let domain = BinaryRelations::new(SmallSet::new(4));
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.is_equivalence(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        (logic, elem)
+    });
+    assert_eq!(count, bell(4));
+}
+
+#[test]
+fn count_sequence() {
+    let mut seq = CountSequence::new("partial orders", |size| {
+        let domain = BinaryRelations::new(SmallSet::new(size));
+        let mut logic = Solver::new("");
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.is_partial_order(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        logic.bool_find_num_models_method1(elem.copy_iter())
+    });
+    assert_eq!(seq.terms(1..5), vec![1, 3, 19, 219]);
+    assert_eq!(seq.terms(3..6), vec![19, 219, 4231]);
+    assert_eq!(seq.values(), "1, 3, 19, 219, 4231");
+}
+
+#[test]
+fn compatible_operations() {
+    let mut logic = Solver::new("");
+    let pres = Preservation::new(SmallSet::new(2), 2, 2);
+
+    let rel = logic.bool_lift_vec([true, true, false, true].iter().cloned());
+    assert_eq!(
+        pres.dom1().contains(&mut logic, rel.slice()),
+        logic.bool_lift(true)
+    );
+    let op = pres.dom0().add_variable(&mut logic);
+    let test = pres.is_edge(&mut logic, op.slice(), rel.slice());
+    logic.bool_add_clause1(test);
+
+    let count = logic.bool_find_num_models_method1(op.copy_iter());
+    assert_eq!(count, 6);
+}
+
+#[test]
+fn compatibility_matrix() {
+    let pres = Preservation::new(SmallSet::new(2), 1, 1);
+    let matrix = CompatibilityMatrix::from_preservation(&pres);
+    assert_eq!((matrix.num_rows(), matrix.num_cols()), (4, 4));
+    assert!(matrix.get(2, 1) && !matrix.get(1, 1));
+
+    let rows: BitVec = [true, false, false, false].iter().copied().collect();
+    let closure = matrix.row_closure(rows.slice());
+    assert_eq!(
+        closure.copy_iter().collect::<Vec<_>>(),
+        [true, false, true, false]
+    );
+    let cols: BitVec = [false, true, true, false].iter().copied().collect();
+    let closure = matrix.col_closure(cols.slice());
+    assert_eq!(closure.copy_iter().collect::<Vec<_>>(), [true; 4]);
+
+    let report = matrix.report();
+    assert_eq!(report.num_edges, 12);
+    assert_eq!(report.num_closed_sets, 4);
+    assert_eq!(report.height, 2);
+    assert_eq!(report.num_implications, 3);
+    assert_eq!(
+        report.to_string(),
+        "4 operations, 4 relations, 12 edges, 4 closed sets of height 2, 3 implications"
+    );
+
+    let reports = compatibility_reports(SmallSet::new(2), 2, 1);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[1].num_rows, 16);
+    assert_eq!(reports[1].num_closed_sets, 4);
+}
+
+#[test]
+fn relation_refs() {
+    let op_ref = RelationRef::new(SmallSet::new(2), 2).with_name("join");
+    let rel_ref = RelationRef::new(SmallSet::new(2), 2).with_name("leq");
+    assert_eq!(rel_ref.to_string(), "leq/2 on 2 elements");
+
+    let pres = Preservation::from_refs(op_ref, rel_ref.clone());
+    assert_eq!(pres.op_ref().name(), Some("join"));
+    assert_eq!(pres.rel_arity(), 2);
+    assert_eq!(pres.dom1(), &Relations::from_ref(&rel_ref));
+    assert_eq!(pres.dom0().to_ref().arity(), 2);
+    assert_eq!(
+        Preservation::new(SmallSet::new(2), 2, 2)
+            .rel_ref()
+            .to_string(),
+        "_/2 on 2 elements"
+    );
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+#[ignore]
+fn bench_small_vectors() {
+    use crate::core::SmallSolver;
+    use std::time::Instant;
+
+    fn count<LOGIC>(mut logic: LOGIC) -> usize
+    where
+        LOGIC: BooleanSolver,
+    {
+        let domain = BinaryRelations::new(SmallSet::new(5));
+        let elem = domain.add_variable(&mut logic);
+        let test = domain.is_partial_order(&mut logic, elem.slice());
+        logic.bool_add_clause1(test);
+        let domain = Operations::new(SmallSet::new(3), 2);
+        for _ in 0..200 {
+            let elem = domain.add_variable(&mut logic);
+            domain.is_surjective(&mut logic, elem.slice());
+        }
+        logic.bool_find_num_models_method1(elem.copy_iter())
+    }
+
+    let start = Instant::now();
+    assert_eq!(count(Solver::new("")), 4231);
+    let time1 = start.elapsed();
+    let start = Instant::now();
+    assert_eq!(count(SmallSolver::new("")), 4231);
+    let time2 = start.elapsed();
+    assert!(
+        time2 * 2 <= time1 * 3,
+        "small vectors: {:?}, regular vectors: {:?}",
+        time2,
+        time1
+    );
+}
+
+#[test]
+fn encoding_sizes() {
+    let mut sizes = EncodingSizes::new(0.05);
+    let rels = BinaryRelations::new(SmallSet::new(4));
+    sizes.check("is_transitive", 155, 418, |logic| {
+        let elem = rels.add_variable(logic);
+        rels.is_transitive(logic, elem.slice());
+    });
+    sizes.check("is_partial_order", 183, 502, |logic| {
+        let elem = rels.add_variable(logic);
+        rels.is_partial_order(logic, elem.slice());
+    });
+    let pres = Preservation::new(SmallSet::new(3), 2, 2);
+    sizes.check("preservation", 2941, 8717, |logic| {
+        let op = pres.dom0().add_variable(logic);
+        let rel = pres.dom1().add_variable(logic);
+        pres.is_edge(logic, op.slice(), rel.slice());
+    });
+    sizes.assert_ok();
 }
@@ -0,0 +1,160 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Canonical benchmark instances built with the encodings of this crate,
+//! used to compare the solver backends and to test the cardinality and
+//! exclusive or constraints at scale. Every generator returns the solver
+//! and the literals whose models are counted, like `random_instance`.
+
+use super::{BooleanLogic, BooleanSolver, Literal, OnehotEncoding, Solver};
+
+/// Generates the n-queens problem, where the counted literals mark the
+/// squares of the board in row major order that contain a queen. Every row
+/// contains exactly one queen, and every column and diagonal at most one,
+/// using the given encoding of the at most one constraints.
+pub fn queens_instance(
+    solver_name: &str,
+    size: usize,
+    encoding: OnehotEncoding,
+) -> (Solver, Vec<Literal>) {
+    let mut logic = Solver::new(solver_name);
+    let board: Vec<Literal> = (0..size * size)
+        .map(|_| logic.bool_add_variable())
+        .collect();
+    let square = |row: usize, col: usize| board[row * size + col];
+
+    for row in 0..size {
+        let lits: Vec<Literal> = (0..size).map(|col| square(row, col)).collect();
+        logic.bool_add_exactly_one(&lits, encoding);
+    }
+    for col in 0..size {
+        let lits: Vec<Literal> = (0..size).map(|row| square(row, col)).collect();
+        logic.bool_add_amo(&lits, encoding);
+    }
+    for diag in 0..(2 * size).saturating_sub(1) {
+        let lits: Vec<Literal> = (0..size)
+            .filter(|&row| diag >= row && diag - row < size)
+            .map(|row| square(row, diag - row))
+            .collect();
+        logic.bool_add_amo(&lits, encoding);
+        let lits: Vec<Literal> = (0..size)
+            .filter(|&row| diag + row + 1 >= size && diag + row + 1 - size < size)
+            .map(|row| square(row, diag + row + 1 - size))
+            .collect();
+        logic.bool_add_amo(&lits, encoding);
+    }
+
+    (logic, board)
+}
+
+/// Generates the pigeonhole problem, where every pigeon is placed into
+/// exactly one of the holes and every hole contains at most one pigeon,
+/// using the given encoding of the at most one constraints. The counted
+/// literals mark the placements in pigeon major order. This is
+/// unsatisfiable if there are more pigeons than holes, and notoriously
+/// hard for resolution.
+pub fn pigeonhole_instance(
+    solver_name: &str,
+    pigeons: usize,
+    holes: usize,
+    encoding: OnehotEncoding,
+) -> (Solver, Vec<Literal>) {
+    let mut logic = Solver::new(solver_name);
+    let places: Vec<Literal> = (0..pigeons * holes)
+        .map(|_| logic.bool_add_variable())
+        .collect();
+
+    for pigeon in 0..pigeons {
+        logic.bool_add_exactly_one(&places[pigeon * holes..(pigeon + 1) * holes], encoding);
+    }
+    for hole in 0..holes {
+        let lits: Vec<Literal> = (0..pigeons)
+            .map(|pigeon| places[pigeon * holes + hole])
+            .collect();
+        logic.bool_add_amo(&lits, encoding);
+    }
+
+    (logic, places)
+}
+
+/// Generates a chain of exclusive or gates over the given number of
+/// variables whose sum is required to be the given parity. The counted
+/// literals are the variables, so there are `2^(length-1)` models for
+/// positive lengths.
+pub fn parity_instance(solver_name: &str, length: usize, parity: bool) -> (Solver, Vec<Literal>) {
+    let mut logic = Solver::new(solver_name);
+    let vars: Vec<Literal> = (0..length).map(|_| logic.bool_add_variable()).collect();
+    let sum = logic.bool_fold_sum(vars.iter().copied());
+    let test = logic.bool_equ(sum, logic.bool_lift(parity));
+    logic.bool_add_clause1(test);
+    (logic, vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::check_num_models;
+
+    #[test]
+    fn benchmarks() {
+        for encoding in [
+            OnehotEncoding::Pairwise,
+            OnehotEncoding::Commander,
+            OnehotEncoding::Product,
+        ] {
+            for (size, count) in [(1, 1), (2, 0), (3, 0), (4, 2), (5, 10), (6, 4)] {
+                let (logic, lits) = queens_instance("", size, encoding);
+                assert_eq!(logic.bool_find_num_models_method1(lits.into_iter()), count);
+            }
+
+            let (logic, lits) = pigeonhole_instance("", 3, 4, encoding);
+            assert_eq!(logic.bool_find_num_models_method1(lits.into_iter()), 24);
+            let (mut logic, _) = pigeonhole_instance("", 5, 4, encoding);
+            assert!(!logic.bool_solvable());
+        }
+
+        assert_eq!(check_num_models(|| parity_instance("", 6, true)), 32);
+        assert_eq!(check_num_models(|| parity_instance("", 5, false)), 16);
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_backends() {
+        use std::time::{Duration, Instant};
+
+        let solvers = [
+            ("cadical", cfg!(feature = "cadical")),
+            ("batsat", cfg!(feature = "batsat")),
+            ("varisat", cfg!(feature = "varisat")),
+            ("minisat", cfg!(feature = "minisat")),
+        ];
+        for (solver_name, _) in solvers.iter().filter(|(_, enabled)| *enabled) {
+            let start = Instant::now();
+            let (logic, lits) = queens_instance(solver_name, 8, OnehotEncoding::Commander);
+            assert_eq!(logic.bool_find_num_models_method1(lits.into_iter()), 92);
+            let (mut logic, _) = pigeonhole_instance(solver_name, 9, 8, OnehotEncoding::Pairwise);
+            assert!(!logic.bool_solvable());
+            let elapsed = start.elapsed();
+            assert!(
+                elapsed < Duration::from_secs(60),
+                "{} took {:?}",
+                solver_name,
+                elapsed
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Fixed width unsigned integer arithmetic on top of boolean logic. The
+//! numbers are vectors of bits with the least significant bit first, and
+//! all operations wrap around modulo two to the power of the width.
+
+use super::{BooleanLogic, BooleanSolver};
+use crate::genvec::{BitVec, Slice, Vector};
+
+/// Returns the value of the given bits, least significant bit first.
+pub fn get_binary_value<ITER>(bits: ITER) -> u64
+where
+    ITER: Iterator<Item = bool>,
+{
+    let mut value = 0;
+    for (i, b) in bits.enumerate() {
+        assert!(i < 64);
+        value |= (b as u64) << i;
+    }
+    value
+}
+
+/// An extension of boolean logic with operations on binary numbers.
+pub trait BinaryLogic: BooleanLogic {
+    /// Returns the binary number of the given width and value.
+    fn bin_lift(&self, width: usize, value: u64) -> Vec<Self::Elem> {
+        assert!(width >= 64 || value >> width == 0);
+        (0..width)
+            .map(|i| self.bool_lift(i < 64 && (value >> i) & 1 != 0))
+            .collect()
+    }
+
+    /// Returns true if the two numbers are equal.
+    fn bin_equ(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Self::Elem {
+        assert_eq!(elem0.len(), elem1.len());
+        self.bool_cmp_equ(elem0.iter().copied().zip(elem1.iter().copied()))
+    }
+
+    /// Returns true if the first number is less than or equal to the second.
+    fn bin_leq(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Self::Elem {
+        assert_eq!(elem0.len(), elem1.len());
+        self.bool_cmp_leq(elem0.iter().copied().zip(elem1.iter().copied()))
+    }
+
+    /// Returns true if the first number is less than the second.
+    fn bin_ltn(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Self::Elem {
+        assert_eq!(elem0.len(), elem1.len());
+        self.bool_cmp_ltn(elem0.iter().copied().zip(elem1.iter().copied()))
+    }
+
+    /// Returns the first or second number depending on the condition.
+    fn bin_ite(
+        &mut self,
+        cond: Self::Elem,
+        elem0: &[Self::Elem],
+        elem1: &[Self::Elem],
+    ) -> Vec<Self::Elem> {
+        assert_eq!(elem0.len(), elem1.len());
+        elem0
+            .iter()
+            .zip(elem1.iter())
+            .map(|(&a, &b)| self.bool_ite(cond, a, b))
+            .collect()
+    }
+
+    /// Returns the sum of the two numbers and the input carry, together
+    /// with the output carry.
+    fn bin_add_carry(
+        &mut self,
+        elem0: &[Self::Elem],
+        elem1: &[Self::Elem],
+        carry: Self::Elem,
+    ) -> (Vec<Self::Elem>, Self::Elem) {
+        assert_eq!(elem0.len(), elem1.len());
+        let mut carry = carry;
+        let mut result = Vec::with_capacity(elem0.len());
+        for (&a, &b) in elem0.iter().zip(elem1.iter()) {
+            result.push(self.bool_sum3(a, b, carry));
+            carry = self.bool_maj(a, b, carry);
+        }
+        (result, carry)
+    }
+
+    /// Returns the sum of the two numbers.
+    fn bin_add(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Vec<Self::Elem> {
+        let zero = self.bool_zero();
+        self.bin_add_carry(elem0, elem1, zero).0
+    }
+
+    /// Returns the difference of the two numbers.
+    fn bin_sub(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Vec<Self::Elem> {
+        let elem1: Vec<Self::Elem> = elem1.iter().map(|&b| self.bool_not(b)).collect();
+        let unit = self.bool_unit();
+        self.bin_add_carry(elem0, &elem1, unit).0
+    }
+
+    /// Returns the negative of the number.
+    fn bin_neg(&mut self, elem: &[Self::Elem]) -> Vec<Self::Elem> {
+        let zero = self.bin_lift(elem.len(), 0);
+        self.bin_sub(&zero, elem)
+    }
+
+    /// Returns the number shifted towards the more significant bits by
+    /// the given amount.
+    fn bin_shl(&self, elem: &[Self::Elem], amount: usize) -> Vec<Self::Elem> {
+        let amount = amount.min(elem.len());
+        let mut result = vec![self.bool_zero(); amount];
+        result.extend_from_slice(&elem[..elem.len() - amount]);
+        result
+    }
+
+    /// Returns the number shifted towards the less significant bits by the
+    /// given amount.
+    fn bin_shr(&self, elem: &[Self::Elem], amount: usize) -> Vec<Self::Elem> {
+        let amount = amount.min(elem.len());
+        let mut result = elem[amount..].to_vec();
+        result.resize(elem.len(), self.bool_zero());
+        result
+    }
+
+    /// Returns the first number shifted towards the more significant bits
+    /// by the value of the second number, using a barrel shifter.
+    fn bin_shl_by(&mut self, elem: &[Self::Elem], amount: &[Self::Elem]) -> Vec<Self::Elem> {
+        let mut result = elem.to_vec();
+        for (i, &bit) in amount.iter().enumerate() {
+            let shifted = self.bin_shl(&result, 1usize.checked_shl(i as u32).unwrap_or(usize::MAX));
+            result = self.bin_ite(bit, &shifted, &result);
+        }
+        result
+    }
+
+    /// Returns the first number shifted towards the less significant bits
+    /// by the value of the second number, using a barrel shifter.
+    fn bin_shr_by(&mut self, elem: &[Self::Elem], amount: &[Self::Elem]) -> Vec<Self::Elem> {
+        let mut result = elem.to_vec();
+        for (i, &bit) in amount.iter().enumerate() {
+            let shifted = self.bin_shr(&result, 1usize.checked_shl(i as u32).unwrap_or(usize::MAX));
+            result = self.bin_ite(bit, &shifted, &result);
+        }
+        result
+    }
+
+    /// Returns the product of the two numbers, computed by shifting and
+    /// adding the partial products.
+    fn bin_mul(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Vec<Self::Elem> {
+        assert_eq!(elem0.len(), elem1.len());
+        let mut result = self.bin_lift(elem0.len(), 0);
+        for (i, &bit) in elem1.iter().enumerate() {
+            let partial: Vec<Self::Elem> = self
+                .bin_shl(elem0, i)
+                .into_iter()
+                .map(|a| self.bool_and(a, bit))
+                .collect();
+            result = self.bin_add(&result, &partial);
+        }
+        result
+    }
+
+    /// Returns the quotient and the remainder of the two numbers, computed
+    /// by restoring division. Division by zero yields the quotient with all
+    /// bits set and the first number as the remainder.
+    fn bin_divmod(
+        &mut self,
+        elem0: &[Self::Elem],
+        elem1: &[Self::Elem],
+    ) -> (Vec<Self::Elem>, Vec<Self::Elem>) {
+        assert_eq!(elem0.len(), elem1.len());
+        let width = elem0.len();
+        let mut divisor = elem1.to_vec();
+        divisor.push(self.bool_zero());
+
+        let mut quotient = vec![self.bool_zero(); width];
+        let mut remainder = self.bin_lift(width, 0);
+        for i in (0..width).rev() {
+            let mut shifted = vec![elem0[i]];
+            shifted.extend_from_slice(&remainder);
+            let test = self.bin_leq(&divisor, &shifted);
+            let diff = self.bin_sub(&shifted, &divisor);
+            quotient[i] = test;
+            remainder = self.bin_ite(test, &diff[..width], &shifted[..width]);
+        }
+        (quotient, remainder)
+    }
+
+    /// Returns the quotient of the two numbers.
+    fn bin_div(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Vec<Self::Elem> {
+        self.bin_divmod(elem0, elem1).0
+    }
+
+    /// Returns the remainder of the two numbers.
+    fn bin_rem(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) -> Vec<Self::Elem> {
+        self.bin_divmod(elem0, elem1).1
+    }
+}
+
+impl<LOGIC> BinaryLogic for LOGIC where LOGIC: BooleanLogic {}
+
+/// An extension of boolean solvers with binary number variables.
+pub trait BinarySolver: BooleanSolver {
+    /// Adds a new binary number variable of the given width.
+    fn bin_add_variable(&mut self, width: usize) -> Vec<Self::Elem> {
+        (0..width).map(|_| self.bool_add_variable()).collect()
+    }
+
+    /// Adds the constraint that the two numbers are equal.
+    fn bin_add_equ(&mut self, elem0: &[Self::Elem], elem1: &[Self::Elem]) {
+        assert_eq!(elem0.len(), elem1.len());
+        for (&a, &b) in elem0.iter().zip(elem1.iter()) {
+            let test = self.bool_equ(a, b);
+            self.bool_add_clause1(test);
+        }
+    }
+
+    /// Runs the solver with the given assumptions and returns the values of
+    /// the given numbers if a solution is found.
+    fn bin_find_one_model(
+        &mut self,
+        assumptions: &[Self::Elem],
+        numbers: &[&[Self::Elem]],
+    ) -> Option<Vec<u64>> {
+        let literals = numbers.iter().flat_map(|num| num.iter().copied());
+        let model: BitVec = self.bool_find_one_model(assumptions, literals)?;
+        let mut start = 0;
+        let values = numbers
+            .iter()
+            .map(|num| {
+                let value =
+                    get_binary_value(model.slice().range(start, start + num.len()).copy_iter());
+                start += num.len();
+                value
+            })
+            .collect();
+        Some(values)
+    }
+}
+
+impl<LOGIC> BinarySolver for LOGIC where LOGIC: BooleanSolver {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Logic, Solver};
+
+    #[test]
+    fn arithmetic() {
+        let mut logic = Logic();
+        let width = 5;
+        for a in 0..32 {
+            for b in 0..32 {
+                let x = logic.bin_lift(width, a);
+                let y = logic.bin_lift(width, b);
+                let value = |v: Vec<bool>| get_binary_value(v.into_iter());
+                assert_eq!(value(logic.bin_add(&x, &y)), (a + b) % 32);
+                assert_eq!(value(logic.bin_sub(&x, &y)), (a + 32 - b) % 32);
+                assert_eq!(value(logic.bin_mul(&x, &y)), (a * b) % 32);
+                assert_eq!(logic.bin_ltn(&x, &y), a < b);
+                assert_eq!(value(logic.bin_shl_by(&x, &y[..3])), (a << (b % 8)) % 32);
+                assert_eq!(value(logic.bin_shr_by(&x, &y[..3])), a >> (b % 8));
+                let (q, r) = logic.bin_divmod(&x, &y);
+                let expected = (
+                    a.checked_div(b).unwrap_or(31),
+                    a.checked_rem(b).unwrap_or(a),
+                );
+                assert_eq!((value(q), value(r)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn factoring() {
+        let mut logic = Solver::new("");
+        let x = logic.bin_add_variable(6);
+        let y = logic.bin_add_variable(6);
+        let p = logic.bin_mul(&x, &y);
+        let n = logic.bin_lift(6, 35);
+        logic.bin_add_equ(&p, &n);
+        let one = logic.bin_lift(6, 1);
+        let eight = logic.bin_lift(6, 8);
+        for num in [&x, &y] {
+            let test = logic.bin_ltn(&one, num);
+            logic.bool_add_clause1(test);
+            let test = logic.bin_ltn(num, &eight);
+            logic.bool_add_clause1(test);
+        }
+        let values = logic.bin_find_one_model(&[], &[&x, &y]).unwrap();
+        assert_eq!(values[0] * values[1], 35);
+    }
+}
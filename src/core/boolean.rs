@@ -20,9 +20,10 @@
 //! where a given set of terms are all true.
 
 use std::iter;
+use std::sync::atomic::{AtomicU8, Ordering};
 
-use super::{create_solver, Literal, SatInterface};
-use crate::genvec::{BitSlice, BitVec, Slice, Vector};
+use super::{create_solver, Literal, SatInterface, XorShift};
+use crate::genvec::{BitSlice, BitVec, Slice, Vector, VectorPool};
 
 /// A boolean algebra supporting boolean calculation.
 pub trait BooleanLogic {
@@ -35,6 +36,24 @@ pub trait BooleanLogic {
     /// The type of slices for the element vectors.
     type Slice<'a>: Slice<'a, Item = Self::Elem, Vector = Self::Vector>;
 
+    /// Returns an empty vector for temporary use, which may reuse the
+    /// storage of a vector given back by `bool_recycle`.
+    fn bool_scratch(&mut self) -> Self::Vector {
+        Vector::new()
+    }
+
+    /// Gives back a temporary vector that is no longer needed, so that its
+    /// storage can be reused.
+    fn bool_recycle(&mut self, vec: Self::Vector) {
+        drop(vec);
+    }
+
+    /// Returns the number of clauses added to the underlying solver so far,
+    /// which is zero if there is no solver.
+    fn bool_num_clauses(&self) -> usize {
+        0
+    }
+
     /// Returns the logical true (top) element of the algebra.
     fn bool_unit(&self) -> Self::Elem {
         self.bool_lift(true)
@@ -83,6 +102,14 @@ pub trait BooleanLogic {
         self.bool_or(tmp, elem2)
     }
 
+    /// Returns the first or second value depending on the condition.
+    fn bool_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let tmp1 = self.bool_and(cond, elem1);
+        let tmp2 = self.bool_not(cond);
+        let tmp2 = self.bool_and(tmp2, elem2);
+        self.bool_or(tmp1, tmp2)
+    }
+
     /// Returns the boolean sum of three values.
     fn bool_sum3(&mut self, elem1: Self::Elem, elem2: Self::Elem, elem3: Self::Elem) -> Self::Elem {
         let tmp = self.bool_xor(elem1, elem2);
@@ -134,7 +161,9 @@ pub trait BooleanLogic {
         result
     }
 
-    /// Computes the exactly one predicate over the given elements.
+    /// Computes the exactly one predicate over the given elements. Use
+    /// `BooleanSolver::bool_add_exactly_one` to assert it, which reaches the
+    /// native cardinality constraints of the solver.
     fn bool_fold_one<ITER>(&mut self, elems: ITER) -> Self::Elem
     where
         ITER: Iterator<Item = Self::Elem>,
@@ -150,7 +179,9 @@ pub trait BooleanLogic {
         self.bool_and(min1, min2)
     }
 
-    /// Computes the at most one predicate over the given elements.
+    /// Computes the at most one predicate over the given elements. Use
+    /// `BooleanSolver::bool_add_amo` to assert it, which reaches the native
+    /// cardinality constraints of the solver.
     fn bool_fold_amo<ITER>(&mut self, elems: ITER) -> Self::Elem
     where
         ITER: Iterator<Item = Self::Elem>,
@@ -165,6 +196,70 @@ pub trait BooleanLogic {
         self.bool_not(min2)
     }
 
+    /// Returns a unary counter of the given elements, where the element
+    /// at index `i` is true if at least `i` many elements are true, for
+    /// all `i` up to and including the given bound.
+    fn bool_fold_counter<ITER>(&mut self, elems: ITER, bound: usize) -> Vec<Self::Elem>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let mut mins = vec![self.bool_zero(); bound + 1];
+        mins[0] = self.bool_unit();
+        for elem in elems {
+            for i in (1..mins.len()).rev() {
+                let tmp = self.bool_and(mins[i - 1], elem);
+                mins[i] = self.bool_or(mins[i], tmp);
+            }
+        }
+        mins
+    }
+
+    /// Returns the number of true elements as a binary number, with the
+    /// least significant bit first, which is computed by a tree of full
+    /// adders. The width is the number of bits of the number of elements.
+    fn bool_fold_binary<ITER>(&mut self, elems: ITER) -> Vec<Self::Elem>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let elems: Vec<Self::Elem> = elems.collect();
+        let width = (usize::BITS - elems.len().leading_zeros()) as usize;
+        let mut columns: Vec<Vec<Self::Elem>> = vec![Vec::new(); width];
+        if width > 0 {
+            columns[0] = elems;
+        }
+
+        let mut result = Vec::with_capacity(width);
+        for i in 0..width {
+            let mut column = std::mem::take(&mut columns[i]);
+            while column.len() >= 2 {
+                let a = column.pop().unwrap();
+                let b = column.pop().unwrap();
+                let (sum, carry) = if let Some(c) = column.pop() {
+                    (self.bool_sum3(a, b, c), self.bool_maj(a, b, c))
+                } else {
+                    (self.bool_xor(a, b), self.bool_and(a, b))
+                };
+                column.insert(0, sum);
+                if i + 1 < width {
+                    columns[i + 1].push(carry);
+                }
+            }
+            result.push(column.pop().unwrap_or_else(|| self.bool_zero()));
+        }
+        result
+    }
+
+    /// Computes the predicate that exactly the given number of elements
+    /// are true, by counting them up to one more than the target.
+    fn bool_fold_count<ITER>(&mut self, elems: ITER, count: usize) -> Self::Elem
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let mins = self.bool_fold_counter(elems, count + 1);
+        let over = self.bool_not(mins[count + 1]);
+        self.bool_and(mins[count], over)
+    }
+
     /// Returns true if the two sequences are equal.
     fn bool_cmp_equ<ITER>(&mut self, pairs: ITER) -> Self::Elem
     where
@@ -215,6 +310,50 @@ pub trait BooleanLogic {
         result
     }
 
+    /// Eliminates the selected bits of the element from the predicate by
+    /// Shannon expansion, that is, returns true if the predicate holds for
+    /// some values of the selected bits. The predicate is evaluated once
+    /// for each of the `2^k` assignments of the `k` selected bits.
+    fn bool_exists_bits<PRED>(
+        &mut self,
+        elem: Self::Slice<'_>,
+        bits: &[usize],
+        mut pred: PRED,
+    ) -> Self::Elem
+    where
+        PRED: FnMut(&mut Self, Self::Slice<'_>) -> Self::Elem,
+    {
+        let mut elem: Self::Vector = elem.copy_iter().collect();
+        let mut result = self.bool_zero();
+        for mask in 0..(1usize << bits.len()) {
+            for (i, &bit) in bits.iter().enumerate() {
+                elem.set(bit, self.bool_lift((mask >> i) & 1 != 0));
+            }
+            let value = pred(self, elem.slice());
+            result = self.bool_or(result, value);
+        }
+        result
+    }
+
+    /// Eliminates the selected bits of the element from the predicate by
+    /// Shannon expansion, that is, returns true if the predicate holds for
+    /// all values of the selected bits.
+    fn bool_forall_bits<PRED>(
+        &mut self,
+        elem: Self::Slice<'_>,
+        bits: &[usize],
+        mut pred: PRED,
+    ) -> Self::Elem
+    where
+        PRED: FnMut(&mut Self, Self::Slice<'_>) -> Self::Elem,
+    {
+        let result = self.bool_exists_bits(elem, bits, |logic, elem| {
+            let value = pred(logic, elem);
+            logic.bool_not(value)
+        });
+        self.bool_not(result)
+    }
+
     /// Lifts each element to this boolean algebra.
     fn bool_lift_vec<ITER>(&self, elems: ITER) -> Vec<Self::Elem>
     where
@@ -222,6 +361,25 @@ pub trait BooleanLogic {
     {
         elems.map(|elem| self.bool_lift(elem)).collect()
     }
+
+    /// Returns the values of the given elements if all of them are
+    /// constants, so concrete algorithms can be used on them.
+    fn bool_lower_vec<ITER>(&self, elems: ITER) -> Option<BitVec>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        elems
+            .map(|elem| {
+                if self.bool_is_unit(elem) {
+                    Some(true)
+                } else if self.bool_is_zero(elem) {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 /// The two element boolean algebra with native `bool` elements.
@@ -278,6 +436,63 @@ impl BooleanLogic for Logic {
     fn bool_imp(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
         elem1 <= elem2
     }
+
+    fn bool_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        if cond {
+            elem1
+        } else {
+            elem2
+        }
+    }
+}
+
+/// The clause encoding used by the SAT solver backed boolean algebra to
+/// express the equality of two long sequences of literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqualityEncoding {
+    /// A linear chain of equivalence and conjunction gates.
+    Chain,
+    /// A balanced binary tree of conjunctions over the equivalence gates,
+    /// which keeps the depth of the circuit logarithmic.
+    Tree,
+    /// A single auxiliary literal that is true exactly when none of the
+    /// differences (the XOR of the pairs) are true. This adds only one
+    /// long clause instead of a chain of conjunctions.
+    Flat,
+}
+
+/// The globally selected equality encoding.
+static EQUALITY_ENCODING: AtomicU8 = AtomicU8::new(EqualityEncoding::Chain as u8);
+
+/// Selects the equality encoding used by all solvers created afterwards.
+/// The default is the chain encoding, which is the one used before the
+/// encodings became selectable.
+pub fn set_equality_encoding(encoding: EqualityEncoding) {
+    EQUALITY_ENCODING.store(encoding as u8, Ordering::Relaxed);
+}
+
+/// Returns the currently selected equality encoding.
+pub fn get_equality_encoding() -> EqualityEncoding {
+    match EQUALITY_ENCODING.load(Ordering::Relaxed) {
+        0 => EqualityEncoding::Chain,
+        1 => EqualityEncoding::Tree,
+        _ => EqualityEncoding::Flat,
+    }
+}
+
+/// The clause encoding used to express that at most one of a list of
+/// literals is true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnehotEncoding {
+    /// Pairwise exclusion clauses, which is quadratic in the number of
+    /// literals but adds no auxiliary variables.
+    Pairwise,
+    /// The commander encoding of Klieber and Kwon, where groups of three
+    /// literals are controlled by a commander literal recursively.
+    Commander,
+    /// The product encoding of Chen, where the literals are arranged in a
+    /// grid and the rows and columns are constrained recursively.
+    Product,
 }
 
 /// The free boolean algebra backed by a SAT solver.
@@ -286,16 +501,39 @@ pub struct Solver {
     solver: Box<dyn SatInterface>,
     unit: Literal,
     zero: Literal,
+    encoding: EqualityEncoding,
+    pool: VectorPool<Vec<Literal>>,
 }
 
 impl Solver {
     /// Creates a new free boolean algebra.
     pub fn new(solver_name: &str) -> Self {
-        let mut solver = create_solver(solver_name);
+        Self::with_interface(create_solver(solver_name))
+    }
+
+    /// Creates a new free boolean algebra backed by the given SAT solver.
+    pub fn with_interface(mut solver: Box<dyn SatInterface>) -> Self {
         let unit = solver.add_variable();
         let zero = solver.negate(unit);
         solver.add_clause(&[unit]);
-        Solver { solver, unit, zero }
+        let encoding = get_equality_encoding();
+        Solver {
+            solver,
+            unit,
+            zero,
+            encoding,
+            pool: Default::default(),
+        }
+    }
+
+    /// Returns the equality encoding used by this solver.
+    pub fn get_encoding(&self) -> EqualityEncoding {
+        self.encoding
+    }
+
+    /// Sets the equality encoding used by this solver.
+    pub fn set_encoding(&mut self, encoding: EqualityEncoding) {
+        self.encoding = encoding;
     }
 
     /// Returns the name of the solver
@@ -321,6 +559,18 @@ impl BooleanLogic for Solver {
 
     type Slice<'a> = &'a [Literal];
 
+    fn bool_scratch(&mut self) -> Self::Vector {
+        self.pool.take()
+    }
+
+    fn bool_num_clauses(&self) -> usize {
+        self.solver.num_clauses()
+    }
+
+    fn bool_recycle(&mut self, vec: Self::Vector) {
+        self.pool.give(vec);
+    }
+
     fn bool_unit(&self) -> Self::Elem {
         self.unit
     }
@@ -388,6 +638,96 @@ impl BooleanLogic for Solver {
             elem3
         }
     }
+
+    fn bool_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        if cond == self.unit || elem1 == elem2 {
+            elem1
+        } else if cond == self.zero {
+            elem2
+        } else if elem1 == self.unit || elem1 == cond {
+            self.bool_or(cond, elem2)
+        } else if elem2 == self.zero || elem2 == cond {
+            self.bool_and(cond, elem1)
+        } else if elem1 == self.zero || elem1 == self.solver.negate(cond) {
+            let not_cond = self.solver.negate(cond);
+            self.bool_and(not_cond, elem2)
+        } else if elem2 == self.unit || elem2 == self.solver.negate(cond) {
+            let not_cond = self.solver.negate(cond);
+            self.bool_or(not_cond, elem1)
+        } else if elem1 == self.solver.negate(elem2) {
+            self.bool_equ(cond, elem1)
+        } else {
+            let not_cond = self.solver.negate(cond);
+            let not_elem1 = self.solver.negate(elem1);
+            let not_elem2 = self.solver.negate(elem2);
+            let elem3 = self.solver.add_variable();
+            let not_elem3 = self.solver.negate(elem3);
+            self.solver.add_clause(&[not_cond, not_elem1, elem3]);
+            self.solver.add_clause(&[not_cond, elem1, not_elem3]);
+            self.solver.add_clause(&[cond, not_elem2, elem3]);
+            self.solver.add_clause(&[cond, elem2, not_elem3]);
+            self.solver.add_clause(&[not_elem1, not_elem2, elem3]);
+            self.solver.add_clause(&[elem1, elem2, not_elem3]);
+            elem3
+        }
+    }
+
+    fn bool_cmp_equ<ITER>(&mut self, pairs: ITER) -> Self::Elem
+    where
+        ITER: Iterator<Item = (Self::Elem, Self::Elem)>,
+    {
+        match self.encoding {
+            EqualityEncoding::Chain => {
+                let mut result = self.unit;
+                for (a, b) in pairs {
+                    let c = self.bool_equ(a, b);
+                    result = self.bool_and(result, c);
+                }
+                result
+            }
+            EqualityEncoding::Tree => {
+                let mut level: Vec<Literal> = pairs.map(|(a, b)| self.bool_equ(a, b)).collect();
+                while level.len() > 1 {
+                    let mut next = Vec::with_capacity((level.len() + 1) / 2);
+                    for chunk in level.chunks(2) {
+                        next.push(if chunk.len() == 2 {
+                            self.bool_and(chunk[0], chunk[1])
+                        } else {
+                            chunk[0]
+                        });
+                    }
+                    level = next;
+                }
+                level.pop().unwrap_or(self.unit)
+            }
+            EqualityEncoding::Flat => {
+                let mut diffs = Vec::new();
+                for (a, b) in pairs {
+                    let d = self.bool_xor(a, b);
+                    if d == self.unit {
+                        return self.zero;
+                    } else if d != self.zero {
+                        diffs.push(d);
+                    }
+                }
+                if diffs.is_empty() {
+                    self.unit
+                } else if diffs.len() == 1 {
+                    self.solver.negate(diffs[0])
+                } else {
+                    let elem = self.solver.add_variable();
+                    let not_elem = self.solver.negate(elem);
+                    for &d in diffs.iter() {
+                        let not_d = self.solver.negate(d);
+                        self.solver.add_clause(&[not_elem, not_d]);
+                    }
+                    diffs.push(elem);
+                    self.solver.add_clause(&diffs);
+                    elem
+                }
+            }
+        }
+    }
 }
 
 /// Constraint solving over a boolean algebra.
@@ -413,6 +753,225 @@ pub trait BooleanSolver: BooleanLogic + Sized {
         self.bool_add_clause(&[lit0, lit1, lit2]);
     }
 
+    /// Adds the constraint that at most one of the given literals is true
+    /// natively if the underlying solver supports it, and returns false
+    /// otherwise. The default implementation does nothing.
+    fn bool_add_native_amo(&mut self, _lits: &[Self::Elem]) -> bool {
+        false
+    }
+
+    /// Adds the constraint that at most the given number of literals are
+    /// true, natively if the underlying solver supports it, and with a unary
+    /// counter otherwise.
+    fn bool_add_at_most(&mut self, lits: &[Self::Elem], bound: usize) {
+        if bound >= lits.len() {
+            return;
+        }
+        let mins = self.bool_fold_counter(lits.iter().copied(), bound + 1);
+        let over = self.bool_not(mins[bound + 1]);
+        self.bool_add_clause1(over);
+    }
+
+    /// Adds clauses that at most one of the given literals is true
+    /// using the specified encoding. Both the commander and product
+    /// encodings add only linearly many clauses.
+    fn bool_add_amo(&mut self, lits: &[Self::Elem], encoding: OnehotEncoding) {
+        if self.bool_add_native_amo(lits) {
+            return;
+        }
+        if lits.len() <= 4 || encoding == OnehotEncoding::Pairwise {
+            for (i, &a) in lits.iter().enumerate() {
+                let a = self.bool_not(a);
+                for &b in lits[i + 1..].iter() {
+                    let b = self.bool_not(b);
+                    self.bool_add_clause2(a, b);
+                }
+            }
+        } else if encoding == OnehotEncoding::Commander {
+            let mut commanders = Vec::with_capacity((lits.len() + 2) / 3);
+            let mut clause = Vec::with_capacity(4);
+            for group in lits.chunks(3) {
+                self.bool_add_amo(group, OnehotEncoding::Pairwise);
+                let c = self.bool_add_variable();
+                clause.clear();
+                clause.push(self.bool_not(c));
+                for &a in group {
+                    let not_a = self.bool_not(a);
+                    self.bool_add_clause2(not_a, c);
+                    clause.push(a);
+                }
+                self.bool_add_clause(&clause);
+                commanders.push(c);
+            }
+            self.bool_add_amo(&commanders, encoding);
+        } else {
+            let rows = (lits.len() as f64).sqrt().ceil() as usize;
+            let cols = (lits.len() + rows - 1) / rows;
+            let rows: Vec<Self::Elem> = (0..rows).map(|_| self.bool_add_variable()).collect();
+            let cols: Vec<Self::Elem> = (0..cols).map(|_| self.bool_add_variable()).collect();
+            for (k, &a) in lits.iter().enumerate() {
+                let not_a = self.bool_not(a);
+                self.bool_add_clause2(not_a, rows[k / cols.len()]);
+                self.bool_add_clause2(not_a, cols[k % cols.len()]);
+            }
+            self.bool_add_amo(&rows, encoding);
+            self.bool_add_amo(&cols, encoding);
+        }
+    }
+
+    /// Adds clauses that exactly one of the given literals is true
+    /// using the specified encoding.
+    fn bool_add_exactly_one(&mut self, lits: &[Self::Elem], encoding: OnehotEncoding) {
+        self.bool_add_clause(lits);
+        self.bool_add_amo(lits, encoding);
+    }
+
+    /// Returns a fresh variable that is equivalent to the given element,
+    /// with the iff clauses added. Predicates can be reified this way even
+    /// if they are simplified to constants or shared gates, so the result
+    /// can be used freely as an assumption, a soft constraint or a part of
+    /// a composite condition.
+    fn bool_reify(&mut self, elem: Self::Elem) -> Self::Elem {
+        let var = self.bool_add_variable();
+        let not_var = self.bool_not(var);
+        let not_elem = self.bool_not(elem);
+        self.bool_add_clause2(not_var, elem);
+        self.bool_add_clause2(var, not_elem);
+        var
+    }
+
+    /// Returns the lexicographically smallest model with respect to the
+    /// given literals, where the first literal is the most significant and
+    /// false is smaller than true. The literals are fixed one by one using
+    /// assumptions, and a literal that is already false in the last model
+    /// is fixed without calling the solver, so the solver can be reused
+    /// afterwards.
+    fn bool_find_minimal_model<ITER>(&mut self, literals: ITER) -> Option<BitVec>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let literals: Vec<Self::Elem> = literals.collect();
+        let mut model = self.bool_find_one_model(&[], literals.iter().copied())?;
+        let mut assumptions = Vec::with_capacity(literals.len());
+        for (index, &lit) in literals.iter().enumerate() {
+            let not_lit = self.bool_not(lit);
+            if model.get(index) {
+                assumptions.push(not_lit);
+                if let Some(next) = self.bool_find_one_model(&assumptions, literals.iter().copied())
+                {
+                    model = next;
+                } else {
+                    assumptions.pop();
+                    assumptions.push(lit);
+                }
+            } else {
+                assumptions.push(not_lit);
+            }
+        }
+        Some(model)
+    }
+
+    /// Finds a model that maximizes the total weight of the satisfied
+    /// objective literals, and returns this weight together with the value
+    /// of the given literals. This is the core guided OLL algorithm: all
+    /// objective literals are assumed, and every unsatisfiable core is
+    /// relaxed with a unary counter of its violated literals, so the
+    /// weights can be large and the solver can be reused afterwards.
+    fn bool_maximize_weighted<ITER>(
+        &mut self,
+        objective: &[(Self::Elem, usize)],
+        literals: ITER,
+    ) -> Option<(usize, BitVec)>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+        Self::Elem: PartialEq,
+    {
+        // assumed literals with their remaining weight and counter position
+        let mut assumed = Vec::new();
+        let mut counters: Vec<Vec<Self::Elem>> = Vec::new();
+        let add = |assumed: &mut Vec<_>, lit, weight, pos: Option<(usize, usize)>| match assumed
+            .iter_mut()
+            .find(|a: &&mut (Self::Elem, usize, _)| a.0 == lit)
+        {
+            Some(a) => a.1 += weight,
+            None => assumed.push((lit, weight, pos)),
+        };
+        for &(lit, weight) in objective.iter().filter(|a| a.1 > 0) {
+            add(&mut assumed, lit, weight, None);
+        }
+
+        loop {
+            let assumptions: Vec<Self::Elem> = assumed.iter().map(|a| a.0).collect();
+            let core = match self.bool_unsat_core(&assumptions) {
+                None => break,
+                Some(core) if core.is_empty() => return None,
+                Some(core) => core,
+            };
+
+            let mut indices: Vec<usize> = core
+                .iter()
+                .map(|&lit| assumptions.iter().position(|&a| a == lit).unwrap())
+                .collect();
+            indices.sort_unstable();
+            indices.dedup();
+            let weight = indices.iter().map(|&i| assumed[i].1).min().unwrap();
+
+            let mut violated = Vec::with_capacity(indices.len());
+            let mut relaxed = Vec::new();
+            for &i in indices.iter() {
+                assumed[i].1 -= weight;
+                violated.push(self.bool_not(assumed[i].0));
+                if let Some((counter, bound)) = assumed[i].2 {
+                    if bound + 1 < counters[counter].len() {
+                        relaxed.push((counter, bound + 1));
+                    }
+                }
+            }
+            assumed.retain(|a| a.1 > 0);
+
+            for (counter, bound) in relaxed {
+                let lit = self.bool_not(counters[counter][bound]);
+                add(&mut assumed, lit, weight, Some((counter, bound)));
+            }
+            if violated.len() >= 2 {
+                let len = violated.len();
+                let counter = self.bool_fold_counter(violated.into_iter(), len);
+                let lit = self.bool_not(counter[2]);
+                counters.push(counter);
+                add(&mut assumed, lit, weight, Some((counters.len() - 1, 2)));
+            }
+        }
+
+        let assumptions: Vec<Self::Elem> = assumed.iter().map(|a| a.0).collect();
+        let model =
+            self.bool_find_one_model(&assumptions, objective.iter().map(|a| a.0).chain(literals))?;
+        let weight = objective
+            .iter()
+            .zip(model.copy_iter())
+            .filter(|(_, v)| *v)
+            .map(|(a, _)| a.1)
+            .sum();
+        let values = model.copy_iter().skip(objective.len()).collect();
+        Some((weight, values))
+    }
+
+    /// Finds a model that satisfies the largest number of the objective
+    /// literals with the core guided algorithm of `bool_maximize_weighted`,
+    /// and returns this number together with the value of the given
+    /// literals.
+    fn bool_maximize<ITER>(
+        &mut self,
+        objective: &[Self::Elem],
+        literals: ITER,
+    ) -> Option<(usize, BitVec)>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+        Self::Elem: PartialEq,
+    {
+        let objective: Vec<(Self::Elem, usize)> = objective.iter().map(|&a| (a, 1)).collect();
+        self.bool_maximize_weighted(&objective, literals)
+    }
+
     /// Returns if the current set of clauses is solvable.
     fn bool_solvable(&mut self) -> bool;
 
@@ -426,26 +985,80 @@ pub trait BooleanSolver: BooleanLogic + Sized {
     where
         ITER: Iterator<Item = Self::Elem>;
 
+    /// Runs the solver with the given assumptions, and returns `None` if
+    /// a solution is found, or the subset of the assumptions that were
+    /// used to prove unsatisfiability. The default implementation returns
+    /// all assumptions.
+    fn bool_unsat_core(&mut self, assumptions: &[Self::Elem]) -> Option<Vec<Self::Elem>> {
+        if self
+            .bool_find_one_model(assumptions, iter::empty())
+            .is_some()
+        {
+            None
+        } else {
+            Some(assumptions.to_vec())
+        }
+    }
+
+    /// Returns an iterator that lazily enumerates the models with respect to
+    /// the given literals. The blocking clauses of the found models are
+    /// guarded by a fresh selector literal that is disabled when the
+    /// iterator is dropped, so the solver can be reused afterwards.
+    fn bool_all_models<ITER>(&mut self, literals: ITER) -> ModelIter<'_, Self>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        self.bool_all_models_under(&[], literals)
+    }
+
+    /// Returns an iterator that lazily enumerates the models with respect to
+    /// the given literals where the assumptions hold, see `bool_all_models`.
+    fn bool_all_models_under<ITER>(
+        &mut self,
+        assumptions: &[Self::Elem],
+        literals: ITER,
+    ) -> ModelIter<'_, Self>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let selector = self.bool_add_variable();
+        let mut assumptions = assumptions.to_vec();
+        assumptions.push(selector);
+        ModelIter {
+            logic: self,
+            literals: literals.collect(),
+            assumptions,
+            selector,
+            done: false,
+        }
+    }
+
+    /// Adds a clause that excludes the given model of the literals. If a
+    /// guard literal is given, then the clause is active only when the
+    /// guard is true.
+    fn bool_add_blocking_clause(
+        &mut self,
+        guard: Option<Self::Elem>,
+        literals: &[Self::Elem],
+        model: BitSlice<'_>,
+    ) {
+        assert_eq!(literals.len(), model.len());
+        let mut clause: Vec<Self::Elem> = Vec::with_capacity(literals.len() + 1);
+        if let Some(guard) = guard {
+            clause.push(self.bool_not(guard));
+        }
+        for (l, b) in literals.copy_iter().zip(model.copy_iter()) {
+            clause.push(self.bool_xor(self.bool_lift(b), l));
+        }
+        self.bool_add_clause(&clause);
+    }
+
     /// Returns the number of models with respect to the given elements.
     fn bool_find_num_models_method1<ITER>(mut self, literals: ITER) -> usize
     where
         ITER: Iterator<Item = Self::Elem>,
     {
-        let mut count = 0;
-        let literals: Vec<Self::Elem> = literals.collect();
-        let mut clause: Vec<Self::Elem> = Vec::with_capacity(literals.len());
-        while let Some(result) = self.bool_find_one_model(&[], literals.copy_iter()) {
-            count += 1;
-            clause.clear();
-            clause.extend(
-                literals
-                    .copy_iter()
-                    .zip(result.into_iter())
-                    .map(|(l, b)| self.bool_xor(self.bool_lift(b), l)),
-            );
-            self.bool_add_clause(&clause);
-        }
-        count
+        self.bool_all_models(literals).count()
     }
 
     /// Returns the number of models with respect to the given literals.
@@ -515,6 +1128,285 @@ pub trait BooleanSolver: BooleanLogic + Sized {
 
         count
     }
+
+    /// Returns the number of models with respect to the given literals where
+    /// the assumptions hold. The blocking clauses of the found models are
+    /// guarded by a fresh selector that is disabled at the end, so the
+    /// solver can be reused with other assumptions.
+    fn bool_find_num_models_under(
+        &mut self,
+        assumptions: &[Self::Elem],
+        literals: &[Self::Elem],
+    ) -> usize {
+        self.bool_all_models_under(assumptions, literals.copy_iter())
+            .count()
+    }
+
+    /// Returns an estimate of the number of models with respect to the given
+    /// literals using hash based approximate counting in the style of
+    /// ApproxMC. The result is within a factor of `1 + epsilon` of the exact
+    /// count with probability at least `1 - delta`. Random exclusive or
+    /// constraints cut the models into cells small enough to enumerate, and
+    /// the median of the scaled cell sizes is returned. The hashes are
+    /// generated by an xorshift generator, so the result is deterministic.
+    fn bool_find_num_models_approx<ITER>(
+        mut self,
+        literals: ITER,
+        epsilon: f64,
+        delta: f64,
+    ) -> usize
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        assert!(epsilon > 0.0 && delta > 0.0 && delta < 1.0);
+        let literals: Vec<Self::Elem> = literals.collect();
+        let threshold =
+            1 + (9.84 * (1.0 + epsilon / (1.0 + epsilon)) * (1.0 + 1.0 / epsilon).powi(2)) as usize;
+        let rounds = (17.0 * (3.0 / delta).log2()).ceil() as usize;
+
+        let count = self
+            .bool_all_models(literals.copy_iter())
+            .take(threshold)
+            .count();
+        if count < threshold {
+            return count;
+        }
+
+        let mut rng = XorShift::new(0);
+        let mut estimates: Vec<f64> = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            // the hash constraints and blocking clauses of a round are guarded
+            // by an activation literal that is retired at the end, and the
+            // models of the previous cell are filtered by the new hash
+            let active = self.bool_add_variable();
+            let not_active = self.bool_not(active);
+            let mut models: Vec<BitVec> = Vec::new();
+            for hashes in 1..=literals.len() {
+                let mask: BitVec = (0..=literals.len()).map(|_| rng.next_bool()).collect();
+                let mut sum = self.bool_lift(mask.get(0));
+                for (lit, bit) in literals.copy_iter().zip(mask.copy_iter().skip(1)) {
+                    if bit {
+                        sum = self.bool_xor(sum, lit);
+                    }
+                }
+                self.bool_add_clause2(not_active, sum);
+                models.retain(|model| {
+                    let sum = model.copy_iter().zip(mask.copy_iter().skip(1));
+                    sum.fold(mask.get(0), |acc, (a, b)| acc ^ (a & b))
+                });
+
+                while models.len() < threshold {
+                    match self.bool_find_one_model(&[active], literals.copy_iter()) {
+                        None => break,
+                        Some(model) => {
+                            self.bool_add_blocking_clause(Some(active), &literals, model.slice());
+                            models.push(model);
+                        }
+                    }
+                }
+                if models.len() < threshold {
+                    estimates.push(models.len() as f64 * (hashes as f64).exp2());
+                    break;
+                }
+            }
+            self.bool_add_clause1(not_active);
+        }
+
+        if estimates.is_empty() {
+            return count;
+        }
+        estimates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        estimates[estimates.len() / 2].round() as usize
+    }
+
+    /// Returns at most the given number of models with respect to the given
+    /// literals that are pairwise far apart in Hamming distance. Each new
+    /// model is chosen greedily, by binary search on the largest distance
+    /// that it can have from all previously found models.
+    fn bool_find_diverse_models<ITER>(mut self, count: usize, literals: ITER) -> Vec<BitVec>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let literals: Vec<Self::Elem> = literals.collect();
+        let len = literals.len();
+
+        // element d is true if the distance from the model is at least d
+        let mut distances: Vec<Vec<Self::Elem>> = Vec::with_capacity(count);
+        let mut models: Vec<BitVec> = Vec::with_capacity(count);
+        let mut assumptions: Vec<Self::Elem> = Vec::with_capacity(count);
+
+        let min_distance = |models: &[BitVec], model: &BitVec| {
+            models
+                .iter()
+                .map(|m| {
+                    m.copy_iter()
+                        .zip(model.copy_iter())
+                        .filter(|(a, b)| a != b)
+                        .count()
+                })
+                .min()
+                .unwrap_or(len)
+        };
+
+        while models.len() < count {
+            assumptions.clear();
+            assumptions.extend(distances.iter().map(|d| d[1]));
+            let Some(mut model) = self.bool_find_one_model(&assumptions, literals.copy_iter())
+            else {
+                break;
+            };
+
+            let mut low = min_distance(&models, &model);
+            let mut high = len + 1;
+            while low + 1 < high {
+                let mid = (low + high) / 2;
+                assumptions.clear();
+                assumptions.extend(distances.iter().map(|d| d[mid]));
+                match self.bool_find_one_model(&assumptions, literals.copy_iter()) {
+                    Some(result) => {
+                        low = min_distance(&models, &result);
+                        model = result;
+                    }
+                    None => high = mid,
+                }
+            }
+
+            let diffs: Vec<Self::Elem> = literals
+                .copy_iter()
+                .zip(model.copy_iter())
+                .map(|(l, b)| self.bool_xor(l, self.bool_lift(b)))
+                .collect();
+            let mins = self.bool_fold_counter(diffs.into_iter(), len + 1);
+            distances.push(mins);
+            models.push(model);
+        }
+
+        models
+    }
+
+    /// Returns a model with respect to the given literals whose Hamming
+    /// distance from the reference is minimal, together with this distance.
+    /// The bound on the distance is tightened incrementally until the
+    /// problem becomes unsatisfiable.
+    fn bool_find_closest_model<ITER>(
+        &mut self,
+        literals: ITER,
+        reference: BitSlice<'_>,
+    ) -> Option<(BitVec, usize)>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        self.bool_find_extremal_model(literals, reference, false)
+    }
+
+    /// Returns a model with respect to the given literals whose Hamming
+    /// distance from the reference is maximal, together with this distance.
+    fn bool_find_farthest_model<ITER>(
+        &mut self,
+        literals: ITER,
+        reference: BitSlice<'_>,
+    ) -> Option<(BitVec, usize)>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        self.bool_find_extremal_model(literals, reference, true)
+    }
+
+    /// Returns a model with minimal or maximal Hamming distance from the
+    /// reference, depending on the given flag.
+    fn bool_find_extremal_model<ITER>(
+        &mut self,
+        literals: ITER,
+        reference: BitSlice<'_>,
+        maximize: bool,
+    ) -> Option<(BitVec, usize)>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        let literals: Vec<Self::Elem> = literals.collect();
+        assert_eq!(literals.len(), reference.len());
+        let len = literals.len();
+
+        let diffs: Vec<Self::Elem> = literals
+            .copy_iter()
+            .zip(reference.copy_iter())
+            .map(|(l, b)| self.bool_xor(l, self.bool_lift(b)))
+            .collect();
+        let mins = self.bool_fold_counter(diffs.into_iter(), len + 1);
+
+        let distance = |model: &BitVec| {
+            model
+                .copy_iter()
+                .zip(reference.copy_iter())
+                .filter(|(a, b)| a != b)
+                .count()
+        };
+
+        let mut best = self.bool_find_one_model(&[], literals.copy_iter())?;
+        let mut dist = distance(&best);
+        loop {
+            let bound = if maximize {
+                mins[dist + 1]
+            } else if dist == 0 {
+                break;
+            } else {
+                self.bool_not(mins[dist])
+            };
+            match self.bool_find_one_model(&[bound], literals.copy_iter()) {
+                Some(model) => {
+                    dist = distance(&model);
+                    best = model;
+                }
+                None => break,
+            }
+        }
+
+        Some((best, dist))
+    }
+}
+
+/// An iterator over the models of a solver, see `bool_all_models`.
+pub struct ModelIter<'a, LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    logic: &'a mut LOGIC,
+    literals: Vec<LOGIC::Elem>,
+    assumptions: Vec<LOGIC::Elem>,
+    selector: LOGIC::Elem,
+    done: bool,
+}
+
+impl<LOGIC> Iterator for ModelIter<'_, LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    type Item = BitVec;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let logic = &mut *self.logic;
+        let result = logic.bool_find_one_model(&self.assumptions, self.literals.copy_iter());
+        match result {
+            None => self.done = true,
+            Some(ref model) => {
+                logic.bool_add_blocking_clause(Some(self.selector), &self.literals, model.slice())
+            }
+        }
+        result
+    }
+}
+
+impl<LOGIC> Drop for ModelIter<'_, LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    fn drop(&mut self) {
+        let not_selector = self.logic.bool_not(self.selector);
+        self.logic.bool_add_clause1(not_selector);
+    }
 }
 
 impl BooleanSolver for Solver {
@@ -544,6 +1436,27 @@ impl BooleanSolver for Solver {
             None
         }
     }
+
+    fn bool_add_native_amo(&mut self, lits: &[Self::Elem]) -> bool {
+        if self.solver.has_native_cardinality() {
+            self.solver.add_amo_clause(lits);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn bool_add_at_most(&mut self, lits: &[Self::Elem], bound: usize) {
+        self.solver.add_card_clause(lits, bound);
+    }
+
+    fn bool_unsat_core(&mut self, assumptions: &[Self::Elem]) -> Option<Vec<Self::Elem>> {
+        if self.solver.solve_with(assumptions) {
+            None
+        } else {
+            Some(self.solver.unsat_core(assumptions))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -573,4 +1486,393 @@ mod tests {
         assert_eq!(s.get(0), true);
         assert_eq!(s.get(1), true);
     }
+
+    #[test]
+    fn reify_and_max_model() {
+        let mut alg = Solver::new("");
+        let a = alg.bool_add_variable();
+        let b = alg.bool_add_variable();
+        let c = alg.bool_add_variable();
+        let unit = alg.bool_unit();
+        let d = alg.bool_reify(unit);
+        assert_ne!(d, unit);
+        let s = alg.bool_find_one_model(&[alg.bool_not(d)], [a].iter().copied());
+        assert!(s.is_none());
+
+        // at most one of a, b and c, while the soft literals prefer all
+        let ab = alg.bool_and(a, b);
+        let ac = alg.bool_and(a, c);
+        let bc = alg.bool_and(b, c);
+        let t = alg.bool_or(ab, ac);
+        let t = alg.bool_or(t, bc);
+        let t = alg.bool_reify(t);
+        alg.bool_add_clause1(alg.bool_not(t));
+        let (count, model) = alg
+            .bool_maximize(&[a, b, c, d], [a, b, c].iter().copied())
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(model.copy_iter().filter(|&v| v).count(), 1);
+        assert!(alg.bool_solvable());
+    }
+
+    #[test]
+    fn maximize() {
+        let mut alg = Solver::new("");
+        let vars: Vec<Literal> = (0..6).map(|_| alg.bool_add_variable()).collect();
+
+        // adjacent variables cannot be both true on a cycle of length 6
+        for i in 0..6 {
+            let a = alg.bool_not(vars[i]);
+            let b = alg.bool_not(vars[(i + 1) % 6]);
+            alg.bool_add_clause2(a, b);
+        }
+        let (count, model) = alg.bool_maximize(&vars, vars.iter().copied()).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(model.copy_iter().filter(|&v| v).count(), 3);
+        let (count, _) = alg.bool_maximize(&vars, iter::empty()).unwrap();
+        assert_eq!(count, 3);
+
+        let weights = [1, 5, 1, 1, 1, 100];
+        let objective: Vec<(Literal, usize)> = vars.iter().copied().zip(weights).collect();
+        let (weight, model) = alg
+            .bool_maximize_weighted(&objective, vars.iter().copied())
+            .unwrap();
+        assert_eq!(weight, 106);
+        assert!(model.get(1) && model.get(5) && model.get(3));
+
+        alg.bool_add_clause1(alg.bool_zero());
+        assert!(alg.bool_maximize(&vars, iter::empty()).is_none());
+    }
+
+    #[test]
+    fn minimal_model() {
+        let mut alg = Solver::new("");
+        let a = alg.bool_add_variable();
+        let b = alg.bool_add_variable();
+        let c = alg.bool_add_variable();
+        let d = alg.bool_or(a, b);
+        alg.bool_add_clause1(d);
+        let d = alg.bool_xor(b, c);
+        alg.bool_add_clause1(d);
+
+        let s = alg
+            .bool_find_minimal_model([a, b, c].iter().copied())
+            .unwrap();
+        assert_eq!(s.copy_iter().collect::<Vec<_>>(), vec![false, true, false]);
+        let s = alg
+            .bool_find_minimal_model([c, b, a].iter().copied())
+            .unwrap();
+        assert_eq!(s.copy_iter().collect::<Vec<_>>(), vec![false, true, false]);
+        let s = alg
+            .bool_find_minimal_model([b, c, a].iter().copied())
+            .unwrap();
+        assert_eq!(s.copy_iter().collect::<Vec<_>>(), vec![false, true, true]);
+
+        alg.bool_add_clause1(alg.bool_not(a));
+        alg.bool_add_clause1(alg.bool_not(b));
+        assert!(alg.bool_find_minimal_model([a].iter().copied()).is_none());
+    }
+
+    #[test]
+    fn fold_binary() {
+        let mut alg = Logic();
+        for len in 0..12usize {
+            for count in 0..=len {
+                let elems = (0..len).map(|i| i < count);
+                let bits = alg.bool_fold_binary(elems);
+                assert_eq!(bits.len() as u32, usize::BITS - len.leading_zeros());
+                let value = bits.iter().rev().fold(0, |v, &b| 2 * v + b as usize);
+                assert_eq!(value, count);
+            }
+        }
+    }
+
+    #[test]
+    fn if_then_else() {
+        let mut alg = Logic();
+        for i in 0..8 {
+            let (c, a, b) = (i & 1 != 0, i & 2 != 0, i & 4 != 0);
+            assert_eq!(alg.bool_ite(c, a, b), if c { a } else { b });
+        }
+
+        let mut alg = Solver::new("");
+        let c = alg.bool_add_variable();
+        let a = alg.bool_add_variable();
+        let b = alg.bool_add_variable();
+        let e = alg.bool_ite(c, a, b);
+        let x = alg.bool_and(c, a);
+        let y = alg.bool_not(c);
+        let y = alg.bool_and(y, b);
+        let f = alg.bool_or(x, y);
+        let g = alg.bool_xor(e, f);
+        assert!(alg
+            .bool_find_one_model(&[g], [c, a, b].iter().copied())
+            .is_none());
+
+        // the special cases reduce to smaller gates
+        let not_a = alg.bool_not(a);
+        let not_c = alg.bool_not(c);
+        let unit = alg.bool_unit();
+        let zero = alg.bool_zero();
+        let cases = [
+            (c, unit, b),
+            (c, a, zero),
+            (c, zero, b),
+            (c, a, unit),
+            (c, c, b),
+            (c, not_c, b),
+            (c, a, c),
+            (c, a, not_c),
+            (c, a, not_a),
+        ];
+        for (c, a, b) in cases {
+            let e = alg.bool_ite(c, a, b);
+            let x = alg.bool_and(c, a);
+            let y = alg.bool_not(c);
+            let y = alg.bool_and(y, b);
+            let f = alg.bool_or(x, y);
+            let g = alg.bool_xor(e, f);
+            assert!(alg.bool_find_one_model(&[g], iter::empty()).is_none());
+        }
+    }
+
+    #[test]
+    fn quantifiers() {
+        let mut alg = Logic();
+        for i in 0..8 {
+            let elem: BitVec = (0..3).map(|j| (i >> j) & 1 != 0).collect();
+            let (x, y) = (elem.get(1), elem.get(2));
+            let test = alg.bool_exists_bits(elem.slice(), &[0], |alg, e| {
+                let a = alg.bool_and(e.get(0), e.get(1));
+                alg.bool_or(a, e.get(2))
+            });
+            assert_eq!(test, x || y);
+            let test = alg.bool_forall_bits(elem.slice(), &[0, 2], |alg, e| {
+                let a = alg.bool_or(e.get(0), e.get(1));
+                alg.bool_or(a, e.get(2))
+            });
+            assert_eq!(test, x);
+        }
+
+        let mut alg = Solver::new("");
+        let elem: Vec<Literal> = (0..4).map(|_| alg.bool_add_variable()).collect();
+        let test = alg.bool_exists_bits(&elem, &[0, 1], |alg, e| {
+            let a = alg.bool_xor(e[0], e[2]);
+            let b = alg.bool_xor(e[1], e[3]);
+            alg.bool_and(a, b)
+        });
+        let test = alg.bool_not(test);
+        assert!(alg
+            .bool_find_one_model(&[test], elem.iter().copied())
+            .is_none());
+    }
+
+    #[test]
+    fn equality_encodings() {
+        for &encoding in [
+            EqualityEncoding::Chain,
+            EqualityEncoding::Tree,
+            EqualityEncoding::Flat,
+        ]
+        .iter()
+        {
+            for &(equal, count) in [(false, 992), (true, 32)].iter() {
+                let mut alg = Solver::new("");
+                alg.set_encoding(encoding);
+                let a: Vec<Literal> = (0..5).map(|_| alg.bool_add_variable()).collect();
+                let b: Vec<Literal> = (0..5).map(|_| alg.bool_add_variable()).collect();
+                let e = alg.bool_cmp_equ(a.iter().copied().zip(b.iter().copied()));
+                let e = if equal { e } else { alg.bool_not(e) };
+                alg.bool_add_clause1(e);
+                let lits = a.iter().chain(b.iter()).copied();
+                assert_eq!(alg.bool_find_num_models_method1(lits), count);
+            }
+        }
+    }
+
+    #[test]
+    fn onehot_encodings() {
+        for &encoding in [
+            OnehotEncoding::Pairwise,
+            OnehotEncoding::Commander,
+            OnehotEncoding::Product,
+        ]
+        .iter()
+        {
+            for &size in [1, 5, 13].iter() {
+                let mut alg = Solver::new("");
+                let lits: Vec<Literal> = (0..size).map(|_| alg.bool_add_variable()).collect();
+                alg.bool_add_exactly_one(&lits, encoding);
+                let count = alg.bool_find_num_models_method1(lits.iter().copied());
+                assert_eq!(count, size);
+            }
+        }
+    }
+
+    #[test]
+    fn cardinality_constraints() {
+        for bound in 0..=5 {
+            let mut alg = Solver::new("");
+            let lits: Vec<Literal> = (0..5).map(|_| alg.bool_add_variable()).collect();
+            alg.bool_add_at_most(&lits, bound);
+            let count = alg.bool_find_num_models_method1(lits.iter().copied());
+            assert_eq!(count, [1, 6, 16, 26, 31, 32][bound]);
+        }
+
+        let mut alg = Solver::new("");
+        let lits: Vec<Literal> = (0..5).map(|_| alg.bool_add_variable()).collect();
+        let test = alg.bool_fold_amo(lits.iter().copied());
+        let test = alg.bool_not(test);
+        alg.bool_add_clause1(test);
+        alg.bool_add_amo(&lits[1..], OnehotEncoding::Pairwise);
+        let count = alg.bool_find_num_models_method1(lits.iter().copied());
+        assert_eq!(count, 4);
+    }
+
+    /// A backend that claims native cardinality support and counts the
+    /// native constraints before passing them to a real solver.
+    struct NativeCardinality {
+        inner: Box<dyn SatInterface>,
+        native: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl SatInterface for NativeCardinality {
+        fn add_variable(&mut self) -> Literal {
+            self.inner.add_variable()
+        }
+
+        fn negate(&self, lit: Literal) -> Literal {
+            self.inner.negate(lit)
+        }
+
+        fn add_clause(&mut self, lits: &[Literal]) {
+            self.inner.add_clause(lits);
+        }
+
+        fn has_native_cardinality(&self) -> bool {
+            true
+        }
+
+        fn add_amo_clause(&mut self, lits: &[Literal]) {
+            self.native.set(self.native.get() + 1);
+            self.inner.add_amo_clause(lits);
+        }
+
+        fn add_card_clause(&mut self, lits: &[Literal], bound: usize) {
+            self.native.set(self.native.get() + 1);
+            self.inner.add_card_clause(lits, bound);
+        }
+
+        fn solve_with(&mut self, lits: &[Literal]) -> bool {
+            self.inner.solve_with(lits)
+        }
+
+        fn get_value(&self, lit: Literal) -> bool {
+            self.inner.get_value(lit)
+        }
+
+        fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+            self.inner.unsat_core(assumptions)
+        }
+
+        fn get_name(&self) -> &'static str {
+            "native"
+        }
+
+        fn num_variables(&self) -> u32 {
+            self.inner.num_variables()
+        }
+
+        fn num_clauses(&self) -> usize {
+            self.inner.num_clauses()
+        }
+    }
+
+    #[test]
+    fn native_cardinality() {
+        let native = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut alg = Solver::with_interface(Box::new(NativeCardinality {
+            inner: create_solver(""),
+            native: native.clone(),
+        }));
+        let lits: Vec<Literal> = (0..6).map(|_| alg.bool_add_variable()).collect();
+        alg.bool_add_exactly_one(&lits[..4], OnehotEncoding::Commander);
+        assert_eq!(native.get(), 1);
+        // the commander encoding is bypassed
+        assert_eq!(alg.num_variables(), 6);
+        alg.bool_add_at_most(&lits[2..], 2);
+        assert_eq!(native.get(), 2);
+
+        let count = alg.bool_find_num_models_method1(lits.iter().copied());
+        assert_eq!(count, 2 * 4 + 2 * 3);
+    }
+
+    #[test]
+    fn diverse_models() {
+        let mut alg = Solver::new("");
+        let lits: Vec<Literal> = (0..6).map(|_| alg.bool_add_variable()).collect();
+        let tmp = alg.bool_fold_count(lits.copy_iter(), 3);
+        alg.bool_add_clause1(tmp);
+
+        let models = alg.bool_find_diverse_models(3, lits.copy_iter());
+        assert_eq!(models.len(), 3);
+        assert!(models[0]
+            .copy_iter()
+            .zip(models[1].copy_iter())
+            .all(|(a, b)| a != b));
+        for (i, model0) in models.iter().enumerate() {
+            assert_eq!(model0.copy_iter().filter(|&b| b).count(), 3);
+            for model1 in models[(i + 1)..].iter() {
+                let dist = model0
+                    .copy_iter()
+                    .zip(model1.copy_iter())
+                    .filter(|(a, b)| a != b)
+                    .count();
+                assert!(dist >= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn extremal_models() {
+        let mut alg = Solver::new("");
+        let lits: Vec<Literal> = (0..6).map(|_| alg.bool_add_variable()).collect();
+        let tmp = alg.bool_fold_count(lits.copy_iter(), 2);
+        alg.bool_add_clause1(tmp);
+
+        let reference: BitVec = [true, true, true, true, false, false]
+            .iter()
+            .copied()
+            .collect();
+        let (model, dist) = alg
+            .bool_find_closest_model(lits.copy_iter(), reference.slice())
+            .unwrap();
+        assert_eq!(dist, 2);
+        assert_eq!(model.copy_iter().filter(|&b| b).count(), 2);
+        assert!(!model.get(4) && !model.get(5));
+
+        let (model, dist) = alg
+            .bool_find_farthest_model(lits.copy_iter(), reference.slice())
+            .unwrap();
+        assert_eq!(dist, 6);
+        assert!(model.get(4) && model.get(5));
+    }
+
+    #[test]
+    fn all_models() {
+        let mut alg = Solver::new("");
+        let lits: Vec<Literal> = (0..4).map(|_| alg.bool_add_variable()).collect();
+        let tmp = alg.bool_fold_count(lits.copy_iter(), 2);
+        alg.bool_add_clause1(tmp);
+
+        let models: Vec<BitVec> = alg.bool_all_models(lits.copy_iter()).collect();
+        assert_eq!(models.len(), 6);
+        assert!(models
+            .iter()
+            .all(|m| m.copy_iter().filter(|&b| b).count() == 2));
+
+        let first: Vec<BitVec> = alg.bool_all_models(lits.copy_iter()).take(2).collect();
+        assert_eq!(first.len(), 2);
+        assert_eq!(alg.bool_find_num_models_method1(lits.copy_iter()), 6);
+    }
 }
@@ -0,0 +1,260 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Predicates that are encoded once into a boolean circuit and then can be
+//! instantiated many times on different inputs in any boolean logic.
+
+use std::collections::HashMap;
+
+use super::BooleanLogic;
+use crate::genvec::Slice;
+
+/// A possibly negated node of a boolean circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Node {
+    value: u32,
+}
+
+impl Node {
+    fn new(index: usize, negated: bool) -> Self {
+        Node {
+            value: (index as u32) * 2 + (negated as u32),
+        }
+    }
+
+    fn index(self) -> usize {
+        (self.value / 2) as usize
+    }
+
+    fn negated(self) -> bool {
+        self.value & 1 != 0
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+/// A gate of a boolean circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Gate {
+    Or(Node, Node),
+    Xor(Node, Node),
+}
+
+/// A boolean logic that records the performed operations as a circuit with
+/// structural hashing. Node zero is the constant true, followed by the
+/// input nodes and then the gates.
+#[derive(Debug, Default)]
+pub struct Circuit {
+    num_inputs: usize,
+    gates: Vec<Gate>,
+    hashes: HashMap<Gate, Node>,
+}
+
+impl Circuit {
+    /// Creates a new circuit with the given number of inputs.
+    pub fn new(num_inputs: usize) -> Self {
+        Circuit {
+            num_inputs,
+            gates: Vec::new(),
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// Returns the list of input nodes.
+    pub fn inputs(&self) -> Vec<Node> {
+        (1..=self.num_inputs).map(|i| Node::new(i, false)).collect()
+    }
+
+    /// Returns the number of gates in the circuit.
+    pub fn num_gates(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn add_gate(&mut self, gate: Gate) -> Node {
+        let index = 1 + self.num_inputs + self.gates.len();
+        let gates = &mut self.gates;
+        *self.hashes.entry(gate).or_insert_with(|| {
+            gates.push(gate);
+            Node::new(index, false)
+        })
+    }
+}
+
+impl BooleanLogic for Circuit {
+    type Elem = Node;
+
+    type Vector = Vec<Node>;
+
+    type Slice<'a> = &'a [Node];
+
+    fn bool_lift(&self, elem: bool) -> Self::Elem {
+        Node::new(0, !elem)
+    }
+
+    fn bool_is_unit(&self, elem: Self::Elem) -> bool {
+        elem == self.bool_unit()
+    }
+
+    fn bool_is_zero(&self, elem: Self::Elem) -> bool {
+        elem == self.bool_zero()
+    }
+
+    fn bool_not(&self, elem: Self::Elem) -> Self::Elem {
+        Node {
+            value: elem.value ^ 1,
+        }
+    }
+
+    fn bool_or(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (elem1, elem2) = (elem1.min(elem2), elem1.max(elem2));
+        if self.bool_is_unit(elem1) || self.bool_is_unit(elem2) || elem1 == self.bool_not(elem2) {
+            self.bool_unit()
+        } else if self.bool_is_zero(elem1) || elem1 == elem2 {
+            elem2
+        } else if self.bool_is_zero(elem2) {
+            elem1
+        } else {
+            self.add_gate(Gate::Or(elem1, elem2))
+        }
+    }
+
+    fn bool_xor(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let negated = elem1.negated() ^ elem2.negated();
+        let elem1 = Node::new(elem1.index(), false);
+        let elem2 = Node::new(elem2.index(), false);
+        let (elem1, elem2) = (elem1.min(elem2), elem1.max(elem2));
+        let result = if elem1 == elem2 {
+            self.bool_zero()
+        } else if elem1.index() == 0 {
+            self.bool_not(elem2)
+        } else {
+            self.add_gate(Gate::Xor(elem1, elem2))
+        };
+        if negated {
+            self.bool_not(result)
+        } else {
+            result
+        }
+    }
+}
+
+/// A predicate that is encoded once into a circuit by applying it to a
+/// template vector of input nodes, and that can be instantiated cheaply on
+/// new input vectors of any boolean logic by replaying the gates.
+#[derive(Debug)]
+pub struct CompiledPredicate {
+    circuit: Circuit,
+    output: Node,
+}
+
+impl CompiledPredicate {
+    /// Compiles the given predicate with the specified number of inputs.
+    pub fn new<PRED>(num_inputs: usize, pred: PRED) -> Self
+    where
+        PRED: FnOnce(&mut Circuit, &[Node]) -> Node,
+    {
+        let mut circuit = Circuit::new(num_inputs);
+        let inputs = circuit.inputs();
+        let output = pred(&mut circuit, &inputs);
+        Self { circuit, output }
+    }
+
+    /// Returns the number of inputs of the predicate.
+    pub fn num_inputs(&self) -> usize {
+        self.circuit.num_inputs
+    }
+
+    /// Returns the number of gates of the compiled predicate.
+    pub fn num_gates(&self) -> usize {
+        self.circuit.num_gates()
+    }
+
+    /// Evaluates the predicate on the given inputs in the given logic.
+    pub fn apply<LOGIC>(&self, logic: &mut LOGIC, elem: LOGIC::Slice<'_>) -> LOGIC::Elem
+    where
+        LOGIC: BooleanLogic,
+    {
+        assert_eq!(elem.len(), self.num_inputs());
+        let mut values: Vec<LOGIC::Elem> = Vec::with_capacity(1 + self.num_inputs());
+        values.push(logic.bool_unit());
+        values.extend(elem.copy_iter());
+
+        let get = |logic: &LOGIC, values: &[LOGIC::Elem], node: Node| {
+            let value = values[node.index()];
+            if node.negated() {
+                logic.bool_not(value)
+            } else {
+                value
+            }
+        };
+
+        for &gate in self.circuit.gates.iter() {
+            let value = match gate {
+                Gate::Or(a, b) => {
+                    let a = get(logic, &values, a);
+                    let b = get(logic, &values, b);
+                    logic.bool_or(a, b)
+                }
+                Gate::Xor(a, b) => {
+                    let a = get(logic, &values, a);
+                    let b = get(logic, &values, b);
+                    logic.bool_xor(a, b)
+                }
+            };
+            values.push(value);
+        }
+
+        get(logic, &values, self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Logic;
+    use crate::genvec::{BitVec, Vector};
+
+    #[test]
+    fn compiled() {
+        let pred = CompiledPredicate::new(3, |logic, elem| {
+            let a = logic.bool_and(elem[0], elem[1]);
+            let b = logic.bool_and(elem[1], elem[0]);
+            let c = logic.bool_xor(a, b);
+            let d = logic.bool_xor(elem[2], c);
+            logic.bool_or(d, a)
+        });
+        assert_eq!(pred.num_gates(), 2);
+
+        let mut logic = Logic();
+        for i in 0..8 {
+            let elem: BitVec = (0..3).map(|j| (i >> j) & 1 != 0).collect();
+            let a = elem.get(0) && elem.get(1);
+            let expected = elem.get(2) || a;
+            assert_eq!(pred.apply(&mut logic, elem.slice()), expected);
+        }
+    }
+}
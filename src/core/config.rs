@@ -0,0 +1,311 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Named selector literals that guard optional groups of constraints, so
+//! a single encoding can answer related questions by toggling assumptions.
+
+use super::BooleanSolver;
+use crate::genvec::{BitVec, Vector};
+
+/// A set of named configuration literals of a solver. Every constraint
+/// added to a group is guarded by the selector of that group, so it is
+/// only in effect when the group is enabled in the assumptions.
+#[derive(Debug)]
+pub struct ConfigLiterals<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    selectors: Vec<(String, LOGIC::Elem)>,
+}
+
+impl<LOGIC> Default for ConfigLiterals<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    fn default() -> Self {
+        Self {
+            selectors: Vec::new(),
+        }
+    }
+}
+
+impl<LOGIC> ConfigLiterals<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    /// Creates an empty set of configuration literals.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the names of the groups in the order of their creation.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.selectors.iter().map(|(n, _)| n.as_str())
+    }
+
+    /// Returns the selector literal of the group with the given name,
+    /// allocating a new variable if the group does not exist yet.
+    pub fn selector(&mut self, logic: &mut LOGIC, name: &str) -> LOGIC::Elem {
+        if let Some((_, sel)) = self.selectors.iter().find(|(n, _)| n == name) {
+            return *sel;
+        }
+        let sel = logic.bool_add_variable();
+        self.selectors.push((name.to_string(), sel));
+        sel
+    }
+
+    /// Adds the given clause to the group with the given name.
+    pub fn add_clause(&mut self, logic: &mut LOGIC, name: &str, clause: &[LOGIC::Elem]) {
+        let sel = self.selector(logic, name);
+        let mut guarded = Vec::with_capacity(clause.len() + 1);
+        guarded.push(logic.bool_not(sel));
+        guarded.extend_from_slice(clause);
+        logic.bool_add_clause(&guarded);
+    }
+
+    /// Adds the predicate computed by the given builder to the group with
+    /// the given name. The builder typically calls the domain level methods,
+    /// like `contains` or `is_lex_ordered`, that return a single literal.
+    pub fn add_guarded<BUILD>(&mut self, logic: &mut LOGIC, name: &str, build: BUILD)
+    where
+        BUILD: FnOnce(&mut LOGIC) -> LOGIC::Elem,
+    {
+        let test = build(logic);
+        self.add_clause(logic, name, &[test]);
+    }
+
+    /// Returns the assumptions that enable the groups with the given names
+    /// and disable all other groups.
+    pub fn assumptions(&self, logic: &LOGIC, enabled: &[&str]) -> Vec<LOGIC::Elem> {
+        for name in enabled {
+            assert!(
+                self.selectors.iter().any(|(n, _)| n == name),
+                "unknown configuration {}",
+                name
+            );
+        }
+        self.selectors
+            .iter()
+            .map(|(n, sel)| {
+                if enabled.contains(&n.as_str()) {
+                    *sel
+                } else {
+                    logic.bool_not(*sel)
+                }
+            })
+            .collect()
+    }
+
+    /// Runs the solver with the given groups enabled and returns the value
+    /// of the given literals if a solution is found.
+    pub fn find_one_model<ITER>(
+        &self,
+        logic: &mut LOGIC,
+        enabled: &[&str],
+        literals: ITER,
+    ) -> Option<BitVec>
+    where
+        ITER: Iterator<Item = LOGIC::Elem>,
+    {
+        let assumptions = self.assumptions(logic, enabled);
+        logic.bool_find_one_model(&assumptions, literals)
+    }
+
+    /// Returns `None` if the constraints are satisfiable with the given
+    /// groups enabled, or the names of the enabled groups that were used
+    /// to prove unsatisfiability otherwise.
+    pub fn unsat_core(&self, logic: &mut LOGIC, enabled: &[&str]) -> Option<Vec<String>>
+    where
+        LOGIC::Elem: PartialEq,
+    {
+        let assumptions = self.assumptions(logic, enabled);
+        let core = logic.bool_unsat_core(&assumptions)?;
+        let names = self
+            .selectors
+            .iter()
+            .filter(|(n, sel)| enabled.contains(&n.as_str()) && core.contains(sel))
+            .map(|(n, _)| n.clone())
+            .collect();
+        Some(names)
+    }
+
+    /// Extends the given satisfiable set of groups greedily, in the order
+    /// of their creation, to a maximal satisfiable subset. The groups not in
+    /// the result form a minimal correction set. Returns `None` if the given
+    /// set of groups is not satisfiable.
+    pub fn find_mss(&self, logic: &mut LOGIC, seed: &[&str]) -> Option<Vec<String>> {
+        let mut enabled: Vec<bool> = self
+            .selectors
+            .iter()
+            .map(|(n, _)| seed.contains(&n.as_str()))
+            .collect();
+        self.grow(logic, &mut enabled, &[])?;
+        Some(self.selected(&enabled, true))
+    }
+
+    /// Enlarges the enabled groups to a maximal satisfiable subset, or
+    /// returns `None` if the enabled groups are not satisfiable. Groups
+    /// whose selector is already true in a found model are added for free.
+    fn grow(&self, logic: &mut LOGIC, enabled: &mut [bool], extra: &[LOGIC::Elem]) -> Option<()> {
+        let mut assumptions: Vec<LOGIC::Elem> = Vec::with_capacity(self.selectors.len());
+        let mut model = self.check(logic, enabled, extra, &mut assumptions)?;
+        for index in 0..self.selectors.len() {
+            for (e, v) in enabled.iter_mut().zip(model.copy_iter()) {
+                *e |= v;
+            }
+            if enabled[index] {
+                continue;
+            }
+            enabled[index] = true;
+            match self.check(logic, enabled, extra, &mut assumptions) {
+                Some(result) => model = result,
+                None => enabled[index] = false,
+            }
+        }
+        Some(())
+    }
+
+    /// Returns the values of the selectors in a model where the enabled
+    /// groups and the extra assumptions hold, or `None` if there is none.
+    fn check(
+        &self,
+        logic: &mut LOGIC,
+        enabled: &[bool],
+        extra: &[LOGIC::Elem],
+        assumptions: &mut Vec<LOGIC::Elem>,
+    ) -> Option<BitVec> {
+        assumptions.clear();
+        assumptions.extend_from_slice(extra);
+        assumptions.extend(
+            self.selectors
+                .iter()
+                .zip(enabled.iter())
+                .filter(|(_, &e)| e)
+                .map(|((_, sel), _)| *sel),
+        );
+        let selectors = self.selectors.iter().map(|(_, sel)| *sel);
+        logic.bool_find_one_model(assumptions, selectors)
+    }
+
+    /// Returns the names of the groups that are enabled or disabled.
+    fn selected(&self, enabled: &[bool], value: bool) -> Vec<String> {
+        self.selectors
+            .iter()
+            .zip(enabled.iter())
+            .filter(|(_, &e)| e == value)
+            .map(|((n, _), _)| n.clone())
+            .collect()
+    }
+
+    /// Enumerates all minimal correction sets of the groups, that are the
+    /// minimal sets of groups whose removal makes the constraints
+    /// satisfiable. Their complements are exactly the maximal satisfiable
+    /// subsets. The found sets are blocked by clauses guarded by a fresh
+    /// selector that is disabled at the end, so the solver can be reused.
+    pub fn all_mcs(&self, logic: &mut LOGIC) -> Vec<Vec<String>> {
+        let blocker = logic.bool_add_variable();
+        let mut result = Vec::new();
+        loop {
+            let mut enabled = vec![false; self.selectors.len()];
+            if self.grow(logic, &mut enabled, &[blocker]).is_none() {
+                break;
+            }
+
+            let mut clause = vec![logic.bool_not(blocker)];
+            clause.extend(
+                self.selectors
+                    .iter()
+                    .zip(enabled.iter())
+                    .filter(|(_, &e)| !e)
+                    .map(|((_, sel), _)| *sel),
+            );
+            logic.bool_add_clause(&clause);
+            result.push(self.selected(&enabled, false));
+        }
+        let not_blocker = logic.bool_not(blocker);
+        logic.bool_add_clause1(not_blocker);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BooleanLogic, Solver};
+
+    #[test]
+    fn config_literals() {
+        let mut logic = Solver::new("");
+        let a = logic.bool_add_variable();
+        let b = logic.bool_add_variable();
+        logic.bool_add_clause(&[a, b]);
+
+        let mut config = ConfigLiterals::new();
+        let not_a = logic.bool_not(a);
+        config.add_clause(&mut logic, "no a", &[not_a]);
+        config.add_guarded(&mut logic, "no b", |logic| logic.bool_not(b));
+        assert_eq!(config.names().collect::<Vec<_>>(), vec!["no a", "no b"]);
+
+        let lits = [a, b];
+        let model = config.find_one_model(&mut logic, &["no a"], lits.iter().copied());
+        assert_eq!(model.map(|m| (m.get(0), m.get(1))), Some((false, true)));
+        let model = config.find_one_model(&mut logic, &["no b"], lits.iter().copied());
+        assert_eq!(model.map(|m| (m.get(0), m.get(1))), Some((true, false)));
+        let model = config.find_one_model(&mut logic, &["no a", "no b"], lits.iter().copied());
+        assert!(model.is_none());
+        assert!(config
+            .find_one_model(&mut logic, &[], lits.iter().copied())
+            .is_some());
+    }
+
+    #[test]
+    fn correction_sets() {
+        let mut logic = Solver::new("");
+        let x = logic.bool_add_variable();
+        let y = logic.bool_add_variable();
+        let not_x = logic.bool_not(x);
+        let not_y = logic.bool_not(y);
+
+        let mut config = ConfigLiterals::new();
+        config.add_clause(&mut logic, "x", &[x]);
+        config.add_clause(&mut logic, "not x", &[not_x]);
+        config.add_clause(&mut logic, "y", &[y]);
+        config.add_clause(&mut logic, "imp", &[not_y, not_x]);
+
+        assert!(config.unsat_core(&mut logic, &["x", "y"]).is_none());
+        let core = config.unsat_core(&mut logic, &["x", "not x", "y"]).unwrap();
+        assert_eq!(core, vec!["x", "not x"]);
+
+        let mss = config.find_mss(&mut logic, &[]).unwrap();
+        assert_eq!(mss, vec!["x", "y"]);
+        assert!(config.find_mss(&mut logic, &["x", "not x"]).is_none());
+
+        let mut mcs = config.all_mcs(&mut logic);
+        mcs.sort();
+        assert_eq!(
+            mcs,
+            vec![
+                vec!["not x".to_string(), "imp".to_string()],
+                vec!["not x".to_string(), "y".to_string()],
+                vec!["x".to_string()],
+            ]
+        );
+        assert!(config
+            .find_one_model(&mut logic, &["x", "y"], [x, y].iter().copied())
+            .is_some());
+    }
+}
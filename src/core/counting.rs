@@ -0,0 +1,178 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Consistency checks between the different model counting methods, and
+//! parallel model counting over several solver instances.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use super::{
+    get_default_solver, set_thread_solver, BooleanLogic, BooleanSolver, Literal, Solver, XorShift,
+};
+
+/// Builds the same instance once for every model counting method, counts
+/// the models with each of them, and panics if the counts differ. The
+/// builder must return the solver and the literals whose models are
+/// counted. Returns the agreed number of models.
+pub fn check_num_models<LOGIC, BUILD>(build: BUILD) -> usize
+where
+    LOGIC: BooleanSolver,
+    BUILD: Fn() -> (LOGIC, Vec<LOGIC::Elem>),
+{
+    let (logic, literals) = build();
+    let count1 = logic.bool_find_num_models_method1(literals.into_iter());
+    let (logic, literals) = build();
+    let count2 = logic.bool_find_num_models_method2(literals.into_iter());
+    assert_eq!(
+        count1, count2,
+        "model counting methods disagree: method1 found {}, method2 found {}",
+        count1, count2
+    );
+    count1
+}
+
+/// Counts the models of an instance in the given number of worker threads
+/// by splitting the search space into cubes. The first few counted literals
+/// are fixed in every possible way, and each cube is counted under the
+/// corresponding assumptions. Every worker builds its own solver instance,
+/// so the builder must return the same instance every time, and it is run
+/// with the default solver of the calling thread.
+pub fn find_num_models_parallel<LOGIC, BUILD>(build: BUILD, threads: usize) -> usize
+where
+    LOGIC: BooleanSolver,
+    BUILD: Fn() -> (LOGIC, Vec<LOGIC::Elem>) + Sync,
+{
+    if threads <= 1 || cfg!(target_arch = "wasm32") {
+        let (logic, literals) = build();
+        return logic.bool_find_num_models_method1(literals.into_iter());
+    }
+
+    let (_, literals) = build();
+    let mut depth = 0;
+    while depth < literals.len() && (1 << depth) < 8 * threads {
+        depth += 1;
+    }
+
+    let solver_name = get_default_solver();
+    let next_cube = AtomicUsize::new(0);
+    let total = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                set_thread_solver(Some(&solver_name));
+                let (mut logic, literals) = build();
+                let mut assumptions: Vec<LOGIC::Elem> = Vec::with_capacity(depth);
+                let mut count = 0;
+                loop {
+                    let cube = next_cube.fetch_add(1, Ordering::Relaxed);
+                    if cube >= 1 << depth {
+                        break;
+                    }
+                    assumptions.clear();
+                    assumptions.extend(literals.iter().take(depth).enumerate().map(|(i, &l)| {
+                        if cube & (1 << i) != 0 {
+                            l
+                        } else {
+                            logic.bool_not(l)
+                        }
+                    }));
+                    count += logic.bool_find_num_models_under(&assumptions, &literals);
+                }
+                total.fetch_add(count, Ordering::Relaxed);
+            });
+        }
+    });
+    total.into_inner()
+}
+
+/// Generates a random instance with the given number of variables and
+/// clauses of length at most three, where the counted literals are the
+/// variables and some negated or repeated variables. The seed determines
+/// the instance.
+pub fn random_instance(
+    solver_name: &str,
+    variables: usize,
+    clauses: usize,
+    seed: u64,
+) -> (Solver, Vec<Literal>) {
+    let mut rng = XorShift::new(seed);
+    let mut next = move |bound: usize| rng.below(bound);
+
+    let mut logic = Solver::new(solver_name);
+    let vars: Vec<_> = (0..variables).map(|_| logic.bool_add_variable()).collect();
+    let lit = |logic: &Solver, next: &mut dyn FnMut(usize) -> usize| {
+        let v = vars[next(vars.len())];
+        if next(2) == 0 {
+            logic.bool_not(v)
+        } else {
+            v
+        }
+    };
+
+    for _ in 0..clauses {
+        let len = 1 + next(3);
+        let clause: Vec<_> = (0..len).map(|_| lit(&logic, &mut next)).collect();
+        logic.bool_add_clause(&clause);
+    }
+
+    let mut literals = vars.clone();
+    for _ in 0..next(3) {
+        literals.push(lit(&logic, &mut next));
+    }
+    (logic, literals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_methods() {
+        for seed in 0..20 {
+            check_num_models(|| random_instance("", 6, 8, seed));
+        }
+    }
+
+    #[test]
+    fn parallel_counting() {
+        for seed in 0..5 {
+            let build = || random_instance("", 7, 8, seed);
+            let (logic, literals) = build();
+            let count = logic.bool_find_num_models_method1(literals.into_iter());
+            assert_eq!(find_num_models_parallel(build, 3), count);
+            assert_eq!(find_num_models_parallel(build, 1), count);
+        }
+    }
+
+    #[test]
+    fn approximate_counting() {
+        let (logic, literals) = random_instance("", 8, 6, 3);
+        let exact = logic.bool_find_num_models_method1(literals.into_iter());
+        let (logic, literals) = random_instance("", 8, 6, 3);
+        assert_eq!(
+            logic.bool_find_num_models_approx(literals.into_iter(), 0.8, 0.2),
+            exact
+        );
+
+        let mut logic = Solver::new("");
+        let vars: Vec<Literal> = (0..12).map(|_| logic.bool_add_variable()).collect();
+        logic.bool_add_clause(&[vars[0], vars[1]]);
+        let estimate = logic.bool_find_num_models_approx(vars.into_iter(), 0.8, 0.2);
+        assert!(3072.0 / 1.8 <= estimate as f64 && estimate as f64 <= 3072.0 * 1.8);
+    }
+}
@@ -0,0 +1,685 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Specialized solvers for tractable fragments of SAT.
+
+use super::{create_solver, Literal, SatInterface};
+
+/// Returns the literal of the given variable with the given sign.
+fn encode(var: u32, negated: bool) -> Literal {
+    Literal {
+        value: 2 * var + negated as u32,
+    }
+}
+
+/// Returns the index of the variable of the literal.
+fn variable(lit: Literal) -> usize {
+    (lit.value >> 1) as usize
+}
+
+/// A solver for the 2-SAT fragment, where every clause has at most two
+/// literals. Satisfiability is decided by computing the strongly connected
+/// components of the implication graph in linear time.
+#[derive(Debug, Default)]
+pub struct TwoSat {
+    num_vars: u32,
+    clauses: Vec<[Literal; 2]>,
+    empty: bool,
+    model: Vec<bool>,
+}
+
+impl TwoSat {
+    /// Returns true if the clause belongs to the 2-SAT fragment.
+    pub fn is_two_sat(clause: &[Literal]) -> bool {
+        clause.len() <= 2
+    }
+
+    /// Returns the implication graph of the clauses extended with the
+    /// given unit clauses, where the nodes are the literals.
+    fn implication_graph(&self, units: &[Literal]) -> Vec<Vec<usize>> {
+        let mut graph = vec![Vec::new(); 2 * self.num_vars as usize];
+        let units = units.iter().map(|&lit| [lit, lit]);
+        for [lit0, lit1] in self.clauses.iter().copied().chain(units) {
+            graph[(lit0.value ^ 1) as usize].push(lit1.value as usize);
+            graph[(lit1.value ^ 1) as usize].push(lit0.value as usize);
+        }
+        graph
+    }
+
+    /// Returns the strongly connected components of the graph using the
+    /// algorithm of Kosaraju. The components are numbered in a topological
+    /// order of the condensation of the graph.
+    fn components(graph: &[Vec<usize>]) -> Vec<usize> {
+        let size = graph.len();
+        let mut order = Vec::with_capacity(size);
+        let mut visited = vec![false; size];
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        for start in 0..size {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            stack.push((start, 0));
+            while let Some((node, index)) = stack.pop() {
+                if index < graph[node].len() {
+                    stack.push((node, index + 1));
+                    let next = graph[node][index];
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push((next, 0));
+                    }
+                } else {
+                    order.push(node);
+                }
+            }
+        }
+
+        let mut reverse = vec![Vec::new(); size];
+        for (node, edges) in graph.iter().enumerate() {
+            for &next in edges {
+                reverse[next].push(node);
+            }
+        }
+
+        let mut component = vec![usize::MAX; size];
+        let mut count = 0;
+        let mut stack = Vec::new();
+        for &start in order.iter().rev() {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            component[start] = count;
+            stack.push(start);
+            while let Some(node) = stack.pop() {
+                for &next in reverse[node].iter() {
+                    if component[next] == usize::MAX {
+                        component[next] = count;
+                        stack.push(next);
+                    }
+                }
+            }
+            count += 1;
+        }
+        component
+    }
+
+    /// Sets the given literal and all of its consequences in the partial
+    /// assignment, and returns false if a conflict was found.
+    fn propagate(graph: &[Vec<usize>], assignment: &mut [Option<bool>], lit: usize) -> bool {
+        let mut stack = vec![lit];
+        while let Some(lit) = stack.pop() {
+            let value = lit & 1 == 0;
+            match assignment[lit >> 1] {
+                Some(old) if old == value => continue,
+                Some(_) => return false,
+                None => assignment[lit >> 1] = Some(value),
+            }
+            stack.extend(graph[lit].iter().copied());
+        }
+        true
+    }
+
+    /// Counts the satisfying assignments of the partial assignment
+    /// by branching on the first unassigned variable.
+    fn count_from(graph: &[Vec<usize>], assignment: &[Option<bool>]) -> usize {
+        let var = match assignment.iter().position(|v| v.is_none()) {
+            Some(var) => var,
+            None => return 1,
+        };
+
+        let mut count = 0;
+        for lit in [2 * var, 2 * var + 1].iter() {
+            let mut branch = assignment.to_vec();
+            if Self::propagate(graph, &mut branch, *lit) {
+                count += Self::count_from(graph, &branch);
+            }
+        }
+        count
+    }
+
+    /// Returns the number of satisfying assignments of all variables.
+    /// The consequences of each decision are propagated along the edges
+    /// of the implication graph, so failed branches are cut early.
+    pub fn count_models(&self) -> usize {
+        if self.empty {
+            return 0;
+        }
+
+        let graph = self.implication_graph(&[]);
+        let mut assignment = vec![None; self.num_vars as usize];
+        for [lit0, lit1] in self.clauses.iter() {
+            if lit0 == lit1 && !Self::propagate(&graph, &mut assignment, lit0.value as usize) {
+                return 0;
+            }
+        }
+        Self::count_from(&graph, &assignment)
+    }
+}
+
+impl SatInterface for TwoSat {
+    fn add_variable(&mut self) -> Literal {
+        self.num_vars += 1;
+        encode(self.num_vars - 1, false)
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        Literal {
+            value: lit.value ^ 1,
+        }
+    }
+
+    fn add_clause(&mut self, lits: &[Literal]) {
+        assert!(Self::is_two_sat(lits));
+        match lits.len() {
+            0 => self.empty = true,
+            1 => self.clauses.push([lits[0], lits[0]]),
+            _ => self.clauses.push([lits[0], lits[1]]),
+        }
+    }
+
+    fn solve_with(&mut self, lits: &[Literal]) -> bool {
+        if self.empty {
+            return false;
+        }
+
+        let graph = self.implication_graph(lits);
+        let component = Self::components(&graph);
+
+        self.model.clear();
+        for var in 0..self.num_vars as usize {
+            let pos = component[2 * var];
+            let neg = component[2 * var + 1];
+            if pos == neg {
+                return false;
+            }
+            self.model.push(pos > neg);
+        }
+        true
+    }
+
+    fn get_value(&self, lit: Literal) -> bool {
+        self.model[variable(lit)] ^ (lit.value & 1 != 0)
+    }
+
+    fn get_name(&self) -> &'static str {
+        "TwoSat"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.num_vars
+    }
+
+    fn num_clauses(&self) -> usize {
+        self.clauses.len() + self.empty as usize
+    }
+}
+
+/// A solver for the Horn fragment, where every clause has at most one
+/// positive literal. Satisfiability is decided by computing the minimal
+/// model with unit propagation in linear time.
+#[derive(Debug, Default)]
+pub struct HornSat {
+    num_vars: u32,
+    clauses: Vec<Vec<Literal>>,
+    model: Vec<bool>,
+}
+
+impl HornSat {
+    /// Returns true if the clause belongs to the Horn fragment.
+    pub fn is_horn(clause: &[Literal]) -> bool {
+        clause.iter().filter(|lit| lit.value & 1 == 0).count() <= 1
+    }
+
+    /// Returns the minimal model of the clauses extended with the given
+    /// unit clauses, or `None` if there is no model.
+    fn minimal_model_with(&self, units: &[Literal]) -> Option<Vec<bool>> {
+        let num_vars = self.num_vars as usize;
+        let mut model = vec![false; num_vars];
+        let mut remaining = Vec::with_capacity(self.clauses.len());
+        let mut heads = Vec::with_capacity(self.clauses.len());
+        let mut occurs = vec![Vec::new(); num_vars];
+        let mut stack = Vec::new();
+
+        for (index, clause) in self.clauses.iter().enumerate() {
+            let mut body: Vec<usize> = clause
+                .iter()
+                .filter(|lit| lit.value & 1 != 0)
+                .map(|&lit| variable(lit))
+                .collect();
+            body.sort_unstable();
+            body.dedup();
+            for &var in body.iter() {
+                occurs[var].push(index);
+            }
+            let head = clause.iter().find(|lit| lit.value & 1 == 0);
+            heads.push(head.map(|&lit| variable(lit)));
+            remaining.push(body.len());
+            if body.is_empty() {
+                stack.push(index);
+            }
+        }
+
+        let mut facts: Vec<usize> = Vec::new();
+        for &index in stack.iter() {
+            facts.push(heads[index]?);
+        }
+        for lit in units.iter().filter(|lit| lit.value & 1 == 0) {
+            facts.push(variable(*lit));
+        }
+
+        while let Some(var) = facts.pop() {
+            if model[var] {
+                continue;
+            }
+            model[var] = true;
+            for &index in occurs[var].iter() {
+                remaining[index] -= 1;
+                if remaining[index] == 0 {
+                    facts.push(heads[index]?);
+                }
+            }
+        }
+
+        for lit in units.iter().filter(|lit| lit.value & 1 != 0) {
+            if model[variable(*lit)] {
+                return None;
+            }
+        }
+        Some(model)
+    }
+
+    /// Returns the unique minimal model of the clauses, where the set of
+    /// true variables is the smallest possible, or `None` if there is no
+    /// model at all.
+    pub fn minimal_model(&self) -> Option<Vec<bool>> {
+        self.minimal_model_with(&[])
+    }
+
+    /// Returns the number of satisfying assignments of all variables.
+    pub fn count_models(&self) -> usize {
+        count_models(self.num_vars as usize, &self.clauses)
+    }
+}
+
+impl SatInterface for HornSat {
+    fn add_variable(&mut self) -> Literal {
+        self.num_vars += 1;
+        encode(self.num_vars - 1, false)
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        Literal {
+            value: lit.value ^ 1,
+        }
+    }
+
+    fn add_clause(&mut self, lits: &[Literal]) {
+        assert!(Self::is_horn(lits));
+        self.clauses.push(lits.to_vec());
+    }
+
+    fn solve_with(&mut self, lits: &[Literal]) -> bool {
+        match self.minimal_model_with(lits) {
+            Some(model) => {
+                self.model = model;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn get_value(&self, lit: Literal) -> bool {
+        self.model[variable(lit)] ^ (lit.value & 1 != 0)
+    }
+
+    fn get_name(&self) -> &'static str {
+        "HornSat"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.num_vars
+    }
+
+    fn num_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+}
+
+/// Counts the satisfying assignments of the clauses by branching on the
+/// variables and doing unit propagation after each decision.
+fn count_models(num_vars: usize, clauses: &[Vec<Literal>]) -> usize {
+    fn value(assignment: &[Option<bool>], lit: Literal) -> Option<bool> {
+        assignment[variable(lit)].map(|v| v ^ (lit.value & 1 != 0))
+    }
+
+    fn propagate(assignment: &mut [Option<bool>], clauses: &[Vec<Literal>]) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for clause in clauses {
+                let mut unassigned = None;
+                let mut count = 0;
+                let mut satisfied = false;
+                for &lit in clause {
+                    match value(assignment, lit) {
+                        Some(true) => satisfied = true,
+                        Some(false) => {}
+                        None => {
+                            unassigned = Some(lit);
+                            count += 1;
+                        }
+                    }
+                }
+                if satisfied {
+                    continue;
+                } else if count == 0 {
+                    return false;
+                } else if count == 1 {
+                    let lit = unassigned.unwrap();
+                    assignment[variable(lit)] = Some(lit.value & 1 == 0);
+                    changed = true;
+                }
+            }
+        }
+        true
+    }
+
+    fn count(assignment: &mut [Option<bool>], clauses: &[Vec<Literal>]) -> usize {
+        if !propagate(assignment, clauses) {
+            return 0;
+        }
+        let var = match assignment.iter().position(|v| v.is_none()) {
+            Some(var) => var,
+            None => return 1,
+        };
+        let mut result = 0;
+        for &val in [false, true].iter() {
+            let mut branch = assignment.to_vec();
+            branch[var] = Some(val);
+            result += count(&mut branch, clauses);
+        }
+        result
+    }
+
+    let mut assignment = vec![None; num_vars];
+    count(&mut assignment, clauses)
+}
+
+/// A solver that records the clauses while they all belong to a tractable
+/// fragment and solves them with the specialized solver, preferring 2-SAT
+/// over Horn. When the first clause outside of both fragments is added,
+/// then all clauses are replayed into a general SAT solver, which is used
+/// from then on.
+pub struct FragmentSolver {
+    fallback_name: String,
+    fallback: Option<Box<dyn SatInterface>>,
+    variables: Vec<Literal>,
+    num_vars: u32,
+    clauses: Vec<Vec<Literal>>,
+    two_sat: Option<TwoSat>,
+    horn_sat: Option<HornSat>,
+}
+
+impl FragmentSolver {
+    /// Creates a new solver that uses the SAT solver with the given name
+    /// for clauses outside of the supported fragments.
+    pub fn new(fallback_name: &str) -> Self {
+        Self {
+            fallback_name: fallback_name.to_string(),
+            fallback: None,
+            variables: Vec::new(),
+            num_vars: 0,
+            clauses: Vec::new(),
+            two_sat: Some(Default::default()),
+            horn_sat: Some(Default::default()),
+        }
+    }
+
+    /// Returns true if the clauses are still solved by a specialized solver.
+    pub fn is_fragment(&self) -> bool {
+        self.fallback.is_none()
+    }
+
+    /// Returns the specialized solver currently in use, if any.
+    fn fragment(&self) -> Option<&dyn SatInterface> {
+        if let Some(sat) = self.two_sat.as_ref() {
+            Some(sat)
+        } else if let Some(sat) = self.horn_sat.as_ref() {
+            Some(sat)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the name of the fragment solver currently in use, if any.
+    pub fn fragment_name(&self) -> Option<&'static str> {
+        self.fragment().map(|sat| sat.get_name())
+    }
+
+    /// Returns the minimal model of the clauses if they all belong to the
+    /// Horn fragment, otherwise `None` is returned.
+    pub fn minimal_model(&self) -> Option<Vec<bool>> {
+        self.horn_sat.as_ref()?.minimal_model()
+    }
+
+    /// Translates the given literal to the literal of the fallback solver.
+    fn translate(fallback: &dyn SatInterface, variables: &[Literal], lit: Literal) -> Literal {
+        let pos = variables[variable(lit)];
+        if lit.value & 1 != 0 {
+            fallback.negate(pos)
+        } else {
+            pos
+        }
+    }
+
+    /// Switches to the general SAT solver by replaying all clauses.
+    fn switch_to_fallback(&mut self) {
+        let mut fallback = create_solver(&self.fallback_name);
+        for _ in 0..self.num_vars {
+            self.variables.push(fallback.add_variable());
+        }
+        for clause in self.clauses.iter() {
+            let lits: Vec<Literal> = clause
+                .iter()
+                .map(|&lit| Self::translate(fallback.as_ref(), &self.variables, lit))
+                .collect();
+            fallback.add_clause(&lits);
+        }
+        self.clauses = Vec::new();
+        self.fallback = Some(fallback);
+    }
+}
+
+impl SatInterface for FragmentSolver {
+    fn add_variable(&mut self) -> Literal {
+        self.num_vars += 1;
+        if let Some(fallback) = self.fallback.as_mut() {
+            self.variables.push(fallback.add_variable());
+        }
+        if let Some(sat) = self.two_sat.as_mut() {
+            sat.add_variable();
+        }
+        if let Some(sat) = self.horn_sat.as_mut() {
+            sat.add_variable();
+        }
+        encode(self.num_vars - 1, false)
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        Literal {
+            value: lit.value ^ 1,
+        }
+    }
+
+    fn add_clause(&mut self, lits: &[Literal]) {
+        if !TwoSat::is_two_sat(lits) {
+            self.two_sat = None;
+        }
+        if !HornSat::is_horn(lits) {
+            self.horn_sat = None;
+        }
+        if self.fallback.is_none() && self.fragment().is_none() {
+            self.switch_to_fallback();
+        }
+
+        let variables = &self.variables;
+        match self.fallback.as_mut() {
+            Some(fallback) => {
+                let lits: Vec<Literal> = lits
+                    .iter()
+                    .map(|&lit| Self::translate(fallback.as_ref(), variables, lit))
+                    .collect();
+                fallback.add_clause(&lits);
+            }
+            None => {
+                self.clauses.push(lits.to_vec());
+                if let Some(sat) = self.two_sat.as_mut() {
+                    sat.add_clause(lits);
+                }
+                if let Some(sat) = self.horn_sat.as_mut() {
+                    sat.add_clause(lits);
+                }
+            }
+        }
+    }
+
+    fn solve_with(&mut self, lits: &[Literal]) -> bool {
+        let variables = &self.variables;
+        if let Some(fallback) = self.fallback.as_mut() {
+            let lits: Vec<Literal> = lits
+                .iter()
+                .map(|&lit| Self::translate(fallback.as_ref(), variables, lit))
+                .collect();
+            fallback.solve_with(&lits)
+        } else if let Some(sat) = self.two_sat.as_mut() {
+            sat.solve_with(lits)
+        } else {
+            self.horn_sat.as_mut().unwrap().solve_with(lits)
+        }
+    }
+
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        match self.fallback.as_ref() {
+            Some(fallback) => {
+                let lits: Vec<Literal> = assumptions
+                    .iter()
+                    .map(|&lit| Self::translate(fallback.as_ref(), &self.variables, lit))
+                    .collect();
+                let core = fallback.unsat_core(&lits);
+                assumptions
+                    .iter()
+                    .zip(lits.iter())
+                    .filter(|(_, lit)| core.contains(lit))
+                    .map(|(&lit, _)| lit)
+                    .collect()
+            }
+            None => assumptions.to_vec(),
+        }
+    }
+
+    fn get_value(&self, lit: Literal) -> bool {
+        match self.fallback.as_ref() {
+            Some(fallback) => {
+                fallback.get_value(Self::translate(fallback.as_ref(), &self.variables, lit))
+            }
+            None => self.fragment().unwrap().get_value(lit),
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Fragment"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.num_vars
+    }
+
+    fn num_clauses(&self) -> usize {
+        match self.fallback.as_ref() {
+            Some(fallback) => fallback.num_clauses(),
+            None => self.clauses.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_sat() {
+        let mut sat: TwoSat = Default::default();
+        let vars: Vec<Literal> = (0..4).map(|_| sat.add_variable()).collect();
+        for i in 0..3 {
+            sat.add_clause(&[sat.negate(vars[i]), vars[i + 1]]);
+        }
+        assert_eq!(sat.count_models(), 5);
+        assert!(sat.solve_with(&[vars[1]]));
+        assert!(sat.get_value(vars[2]) && sat.get_value(vars[3]));
+        assert!(!sat.solve_with(&[vars[0], sat.negate(vars[3])]));
+
+        sat.add_clause(&[vars[0]]);
+        assert_eq!(sat.count_models(), 1);
+        sat.add_clause(&[sat.negate(vars[3]), sat.negate(vars[2])]);
+        assert_eq!(sat.count_models(), 0);
+        assert!(!sat.solve());
+    }
+
+    #[test]
+    fn fragment() {
+        let mut sat = FragmentSolver::new("");
+        let a = sat.add_variable();
+        let b = sat.add_variable();
+        sat.add_clause(&[a, sat.negate(b)]);
+        assert!(sat.solve_with(&[b]));
+        assert_eq!(sat.fragment_name(), Some("TwoSat"));
+        assert!(sat.get_value(a));
+
+        let c = sat.add_variable();
+        sat.add_clause(&[sat.negate(a), sat.negate(b), c]);
+        assert_eq!(sat.fragment_name(), Some("HornSat"));
+        assert!(sat.solve_with(&[a, b]));
+        assert!(sat.get_value(c));
+        assert!(!sat.solve_with(&[a, b, sat.negate(c)]));
+
+        sat.add_clause(&[a, b, c]);
+        assert!(!sat.is_fragment());
+        assert!(sat.solve_with(&[a, b]));
+        assert!(sat.get_value(c));
+        assert!(!sat.solve_with(&[a, b, sat.negate(c)]));
+    }
+
+    #[test]
+    fn horn_sat() {
+        let mut sat: HornSat = Default::default();
+        let vars: Vec<Literal> = (0..4).map(|_| sat.add_variable()).collect();
+        sat.add_clause(&[vars[0]]);
+        sat.add_clause(&[sat.negate(vars[0]), sat.negate(vars[1]), vars[2]]);
+        sat.add_clause(&[sat.negate(vars[0]), vars[3]]);
+        assert_eq!(sat.minimal_model(), Some(vec![true, false, false, true]));
+        assert_eq!(sat.count_models(), 3);
+        assert!(sat.solve_with(&[vars[1]]));
+        assert!(sat.get_value(vars[2]));
+
+        sat.add_clause(&[sat.negate(vars[2]), sat.negate(vars[3])]);
+        assert!(!sat.solve_with(&[vars[1]]));
+        assert_eq!(sat.count_models(), 1);
+        sat.add_clause(&[sat.negate(vars[3])]);
+        assert_eq!(sat.minimal_model(), None);
+        assert_eq!(sat.count_models(), 0);
+    }
+}
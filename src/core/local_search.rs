@@ -0,0 +1,436 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Stochastic local search for finding models of satisfiable instances.
+
+use super::{create_solver, Literal, SatInterface, XorShift};
+
+/// Returns the index of the variable of the literal.
+fn variable(lit: Literal) -> usize {
+    (lit.value >> 1) as usize
+}
+
+/// A WalkSAT style local search solver. It starts from a random assignment
+/// and repeatedly flips a variable of a random unsatisfied clause, choosing
+/// either one that breaks the fewest satisfied clauses or, with the noise
+/// probability, a random one. The search is incomplete: a false result only
+/// means that no model was found within the flip limit, so this solver is
+/// meant for finding models of large satisfiable instances.
+#[derive(Debug)]
+pub struct WalkSat {
+    num_vars: u32,
+    clauses: Vec<Vec<Literal>>,
+    empty: bool,
+    model: Vec<bool>,
+    max_flips: usize,
+    noise: f64,
+    rng: XorShift,
+    flips: usize,
+}
+
+impl Default for WalkSat {
+    fn default() -> Self {
+        Self::with_limits(100000, 0.5, 1)
+    }
+}
+
+impl WalkSat {
+    /// Creates a new local search solver with the given maximum number of
+    /// flips per solve call, noise probability and random seed.
+    pub fn with_limits(max_flips: usize, noise: f64, seed: u64) -> Self {
+        assert!((0.0..=1.0).contains(&noise));
+        Self {
+            num_vars: 0,
+            clauses: Vec::new(),
+            empty: false,
+            model: Vec::new(),
+            max_flips,
+            noise,
+            rng: XorShift::new(seed),
+            flips: 0,
+        }
+    }
+
+    /// Returns the number of flips performed by the last solve call.
+    pub fn flips(&self) -> usize {
+        self.flips
+    }
+
+    /// Runs the local search on the clauses extended with the given unit
+    /// clauses, and stores the model if one was found.
+    fn search(&mut self, units: &[Literal]) -> bool {
+        let units: Vec<Vec<Literal>> = units.iter().map(|&lit| vec![lit]).collect();
+        let clauses: Vec<&[Literal]> = self
+            .clauses
+            .iter()
+            .chain(units.iter())
+            .map(|c| c.as_slice())
+            .collect();
+
+        let mut occurs = vec![Vec::new(); 2 * self.num_vars as usize];
+        for (index, clause) in clauses.iter().enumerate() {
+            for lit in clause.iter() {
+                occurs[lit.value as usize].push(index);
+            }
+        }
+
+        let rng = &mut self.rng;
+        let mut assignment: Vec<bool> = (0..self.num_vars).map(|_| rng.next_bool()).collect();
+        let is_true =
+            |assignment: &[bool], lit: Literal| assignment[variable(lit)] == (lit.value & 1 == 0);
+
+        let mut num_true: Vec<usize> = clauses
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .filter(|&&lit| is_true(&assignment, lit))
+                    .count()
+            })
+            .collect();
+        let mut position = vec![usize::MAX; clauses.len()];
+        let mut unsat = Vec::new();
+        for (index, &count) in num_true.iter().enumerate() {
+            if count == 0 {
+                position[index] = unsat.len();
+                unsat.push(index);
+            }
+        }
+
+        self.flips = 0;
+        while !unsat.is_empty() {
+            if self.flips >= self.max_flips {
+                return false;
+            }
+            self.flips += 1;
+
+            let clause = clauses[unsat[rng.below(unsat.len())]];
+            let breaks: Vec<usize> = clause
+                .iter()
+                .map(|&lit| {
+                    occurs[(lit.value ^ 1) as usize]
+                        .iter()
+                        .filter(|&&index| num_true[index] == 1)
+                        .count()
+                })
+                .collect();
+            let best = (0..clause.len()).min_by_key(|&i| breaks[i]).unwrap();
+            let chosen = if breaks[best] != 0 && rng.next_f64() < self.noise {
+                rng.below(clause.len())
+            } else {
+                best
+            };
+
+            let lit = clause[chosen];
+            assignment[variable(lit)] = !assignment[variable(lit)];
+            for &index in occurs[lit.value as usize].iter() {
+                num_true[index] += 1;
+                if num_true[index] == 1 {
+                    let pos = position[index];
+                    let last = unsat.pop().unwrap();
+                    if last != index {
+                        unsat[pos] = last;
+                        position[last] = pos;
+                    }
+                    position[index] = usize::MAX;
+                }
+            }
+            for &index in occurs[(lit.value ^ 1) as usize].iter() {
+                num_true[index] -= 1;
+                if num_true[index] == 0 {
+                    position[index] = unsat.len();
+                    unsat.push(index);
+                }
+            }
+        }
+
+        self.model = assignment;
+        true
+    }
+}
+
+impl SatInterface for WalkSat {
+    fn add_variable(&mut self) -> Literal {
+        self.num_vars += 1;
+        Literal {
+            value: 2 * (self.num_vars - 1),
+        }
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        Literal {
+            value: lit.value ^ 1,
+        }
+    }
+
+    fn add_clause(&mut self, lits: &[Literal]) {
+        let mut clause: Vec<Literal> = Vec::with_capacity(lits.len());
+        for &lit in lits.iter() {
+            if clause.contains(&self.negate(lit)) {
+                return;
+            } else if !clause.contains(&lit) {
+                clause.push(lit);
+            }
+        }
+        if clause.is_empty() {
+            self.empty = true;
+        }
+        self.clauses.push(clause);
+    }
+
+    fn solve_with(&mut self, lits: &[Literal]) -> bool {
+        !self.empty && self.search(lits)
+    }
+
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        if self.empty {
+            Vec::new()
+        } else {
+            assumptions.to_vec()
+        }
+    }
+
+    fn get_value(&self, lit: Literal) -> bool {
+        self.model[variable(lit)] == (lit.value & 1 == 0)
+    }
+
+    fn get_name(&self) -> &'static str {
+        "WalkSat"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.num_vars
+    }
+
+    fn num_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+}
+
+/// A complete solver that first runs a local search for a model, and only
+/// when that fails hands the clauses over to a conflict driven solver. This
+/// pays off for large satisfiable existence questions, where a single
+/// witness is needed and local search often finds one quickly.
+#[derive(Debug)]
+pub struct HybridSolver {
+    local: WalkSat,
+    fallback_name: String,
+    fallback: Option<Box<dyn SatInterface>>,
+    variables: Vec<Literal>,
+    num_synced: usize,
+    used_fallback: bool,
+    skip_local: bool,
+}
+
+impl HybridSolver {
+    /// Creates a new hybrid solver that uses the complete SAT solver with
+    /// the given name when the local search fails.
+    pub fn new(fallback_name: &str) -> Self {
+        Self::with_local_search(fallback_name, Default::default())
+    }
+
+    /// Creates a new hybrid solver with the given local search solver,
+    /// which must not have any variables yet.
+    pub fn with_local_search(fallback_name: &str, local: WalkSat) -> Self {
+        assert_eq!(local.num_variables(), 0);
+        assert!(fallback_name != "hybrid");
+        Self {
+            local,
+            fallback_name: fallback_name.to_string(),
+            fallback: None,
+            variables: Vec::new(),
+            num_synced: 0,
+            used_fallback: false,
+            skip_local: false,
+        }
+    }
+
+    /// Returns true if the last solve call was answered by the local search.
+    pub fn used_local_search(&self) -> bool {
+        !self.used_fallback
+    }
+
+    /// Maps the literal to the corresponding literal of the fallback solver.
+    fn translate(fallback: &dyn SatInterface, variables: &[Literal], lit: Literal) -> Literal {
+        let pos = variables[variable(lit)];
+        if lit.value & 1 != 0 {
+            fallback.negate(pos)
+        } else {
+            pos
+        }
+    }
+
+    /// Creates the fallback solver if needed and adds the variables and
+    /// clauses that were added since the last call.
+    fn sync_fallback(&mut self) {
+        if self.fallback.is_none() {
+            self.fallback = Some(create_solver(&self.fallback_name));
+        }
+        let fallback = self.fallback.as_mut().unwrap();
+        let variables = &mut self.variables;
+        while variables.len() < self.local.num_vars as usize {
+            variables.push(fallback.add_variable());
+        }
+        for clause in self.local.clauses[self.num_synced..].iter() {
+            let lits: Vec<Literal> = clause
+                .iter()
+                .map(|&lit| Self::translate(fallback.as_ref(), variables, lit))
+                .collect();
+            fallback.add_clause(&lits);
+        }
+        self.num_synced = self.local.clauses.len();
+    }
+}
+
+impl SatInterface for HybridSolver {
+    fn add_variable(&mut self) -> Literal {
+        self.local.add_variable()
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        self.local.negate(lit)
+    }
+
+    fn add_clause(&mut self, lits: &[Literal]) {
+        self.local.add_clause(lits);
+    }
+
+    fn solve_with(&mut self, lits: &[Literal]) -> bool {
+        self.used_fallback = false;
+        if self.local.empty {
+            return false;
+        } else if !self.skip_local && self.local.solve_with(lits) {
+            return true;
+        }
+
+        self.used_fallback = true;
+        self.sync_fallback();
+        let fallback = self.fallback.as_mut().unwrap();
+        let variables = &self.variables;
+        let lits: Vec<Literal> = lits
+            .iter()
+            .map(|&lit| Self::translate(fallback.as_ref(), variables, lit))
+            .collect();
+        let result = fallback.solve_with(&lits);
+        // once the fallback proved unsatisfiability without assumptions it
+        // answers all later calls
+        self.skip_local |= !result && lits.is_empty();
+        result
+    }
+
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        match self.fallback.as_ref() {
+            Some(fallback) if self.used_fallback => {
+                let lits: Vec<Literal> = assumptions
+                    .iter()
+                    .map(|&lit| Self::translate(fallback.as_ref(), &self.variables, lit))
+                    .collect();
+                let core = fallback.unsat_core(&lits);
+                assumptions
+                    .iter()
+                    .zip(lits.iter())
+                    .filter(|(_, lit)| core.contains(lit))
+                    .map(|(&lit, _)| lit)
+                    .collect()
+            }
+            _ => self.local.unsat_core(assumptions),
+        }
+    }
+
+    fn get_value(&self, lit: Literal) -> bool {
+        match self.fallback.as_ref() {
+            Some(fallback) if self.used_fallback => {
+                fallback.get_value(Self::translate(fallback.as_ref(), &self.variables, lit))
+            }
+            _ => self.local.get_value(lit),
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Hybrid"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.local.num_variables()
+    }
+
+    fn num_clauses(&self) -> usize {
+        self.local.num_clauses()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walksat() {
+        let mut sat = WalkSat::with_limits(10000, 0.5, 7);
+        let size = 8;
+        let board: Vec<Literal> = (0..size * size).map(|_| sat.add_variable()).collect();
+        let attacks = |a: usize, b: usize| {
+            let (r0, c0, r1, c1) = (a / size, a % size, b / size, b % size);
+            r0 == r1 || c0 == c1 || r0 + c1 == r1 + c0 || r0 + c0 == r1 + c1
+        };
+        for row in 0..size {
+            sat.add_clause(&board[row * size..(row + 1) * size]);
+        }
+        for a in 0..size * size {
+            for b in a + 1..size * size {
+                if attacks(a, b) {
+                    sat.add_clause(&[sat.negate(board[a]), sat.negate(board[b])]);
+                }
+            }
+        }
+
+        assert!(sat.solve_with(&[board[0]]));
+        assert!(sat.get_value(board[0]));
+        let queens: Vec<usize> = (0..size * size)
+            .filter(|&i| sat.get_value(board[i]))
+            .collect();
+        assert_eq!(queens.len(), size);
+        for (i, &a) in queens.iter().enumerate() {
+            assert!(queens[i + 1..].iter().all(|&b| !attacks(a, b)));
+        }
+    }
+
+    #[test]
+    fn hybrid() {
+        let mut sat = HybridSolver::new("");
+        let a = sat.add_variable();
+        let b = sat.add_variable();
+        sat.add_clause(&[a, b]);
+        assert!(sat.solve_with(&[sat.negate(a)]));
+        assert!(sat.used_local_search());
+        assert!(sat.get_value(b));
+
+        sat.add_clause(&[sat.negate(b)]);
+        assert!(sat.solve());
+        assert!(sat.get_value(a) && !sat.get_value(b));
+        assert!(!sat.solve_with(&[sat.negate(a)]));
+        assert!(!sat.used_local_search());
+        assert_eq!(sat.unsat_core(&[sat.negate(a)]), vec![sat.negate(a)]);
+        assert!(sat.solve());
+        assert!(sat.used_local_search());
+
+        sat.add_clause(&[sat.negate(a)]);
+        assert!(!sat.solve());
+        assert!(!sat.used_local_search());
+        assert!(!sat.solve_with(&[a]));
+        assert!(!sat.used_local_search());
+    }
+}
@@ -0,0 +1,177 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! An opt-in accounting of the heap memory used by the computations. The
+//! clause databases of the solvers and the element vectors are all heap
+//! allocated, so installing the counting allocator as the global allocator
+//! of the binary measures both of them:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: uasat::core::CountingAllocator = uasat::core::CountingAllocator::new();
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+thread_local! {
+    /// The number of bytes allocated minus the number of bytes freed by the
+    /// current thread, and the peak of this value since the start of the
+    /// innermost measurement.
+    static THREAD_BYTES: Cell<(isize, isize)> = const { Cell::new((0, 0)) };
+}
+
+/// Adds the given signed number of bytes to the counters of the current
+/// thread. Nothing is recorded while the thread is being torn down.
+fn add_thread_bytes(size: isize) {
+    THREAD_BYTES
+        .try_with(|bytes| {
+            let (current, peak) = bytes.get();
+            bytes.set((current + size, peak.max(current + size)));
+        })
+        .ok();
+}
+
+/// A global allocator that forwards to the system allocator and keeps
+/// track of the currently allocated and the peak number of bytes.
+#[derive(Debug, Default)]
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    /// Creates a new counting allocator.
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of bytes currently allocated.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest number of bytes allocated at the same time
+    /// since the last reset.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Resets the peak to the current number of allocated bytes.
+    pub fn reset_peak(&self) {
+        self.peak.store(self.current(), Ordering::Relaxed);
+    }
+
+    /// Runs the given closure and returns its result together with the
+    /// peak number of bytes allocated during the run above the amount
+    /// allocated at its start. Only the allocations of the current thread
+    /// are measured, so measurements in other threads do not interfere,
+    /// and measurements can be nested. The thread counters are shared by
+    /// all counting allocators, so only the global one should be used.
+    pub fn measure<RESULT, FUNC>(&self, func: FUNC) -> (RESULT, usize)
+    where
+        FUNC: FnOnce() -> RESULT,
+    {
+        let (start, outer_peak) = THREAD_BYTES.with(|bytes| bytes.get());
+        THREAD_BYTES.with(|bytes| bytes.set((start, start)));
+        let result = func();
+        let (current, peak) = THREAD_BYTES.with(|bytes| bytes.get());
+        THREAD_BYTES.with(|bytes| bytes.set((current, outer_peak.max(peak))));
+        (result, (peak - start).max(0) as usize)
+    }
+
+    fn add(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        add_thread_bytes(size as isize);
+    }
+
+    fn sub(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+        add_thread_bytes(-(size as isize));
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.sub(layout.size());
+            self.add(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// The global allocator of the unit tests, so they can check memory limits.
+#[cfg(test)]
+#[global_allocator]
+pub(crate) static TEST_ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_allocator() {
+        let alloc = CountingAllocator::new();
+        let layout = Layout::from_size_align(1000, 8).unwrap();
+        let ((), peak) = alloc.measure(|| unsafe {
+            let ptr = alloc.alloc(layout);
+            let ptr = alloc.realloc(ptr, layout, 3000);
+            assert_eq!(alloc.current(), 3000);
+            alloc.dealloc(ptr, Layout::from_size_align(3000, 8).unwrap());
+        });
+        assert_eq!(peak, 3000);
+        assert_eq!(alloc.current(), 0);
+        alloc.reset_peak();
+        assert_eq!(alloc.peak(), 0);
+
+        let (len, peak) = TEST_ALLOCATOR.measure(|| {
+            let (len, inner) = TEST_ALLOCATOR.measure(|| vec![0u8; 1 << 20].len());
+            assert!(inner >= 1 << 20);
+            len + vec![0u8; 1 << 10].len()
+        });
+        assert_eq!(len, (1 << 20) + (1 << 10));
+        assert!((1 << 20..2 << 20).contains(&peak));
+    }
+}
@@ -18,13 +18,64 @@
 //! Module for the core components that seems to have stabilized.
 
 mod solver;
-pub use solver::{create_solver, Literal, SatInterface};
+pub use solver::{
+    create_solver, get_default_solver, set_default_solver, set_thread_solver, Literal, SatInterface,
+};
+
+mod compiled;
+pub use compiled::{Circuit, CompiledPredicate, Node};
+
+mod fragments;
+pub use fragments::{FragmentSolver, HornSat, TwoSat};
+
+mod local_search;
+pub use local_search::{HybridSolver, WalkSat};
 
 mod tensor;
 pub use tensor::{Shape, Tensor, TensorAlgebra, TensorSolver};
 
+mod binary;
+pub use binary::{get_binary_value, BinaryLogic, BinarySolver};
+
 mod boolean;
-pub use boolean::{BooleanLogic, BooleanSolver, Logic, Solver};
+pub use boolean::{
+    get_equality_encoding, set_equality_encoding, BooleanLogic, BooleanSolver, EqualityEncoding,
+    Logic, ModelIter, OnehotEncoding, Solver,
+};
+
+mod benchmarks;
+pub use benchmarks::{parity_instance, pigeonhole_instance, queens_instance};
+
+mod config;
+pub use config::ConfigLiterals;
+
+mod counting;
+pub use counting::{check_num_models, find_num_models_parallel, random_instance};
+
+mod memory;
+pub use memory::CountingAllocator;
+#[cfg(test)]
+pub(crate) use memory::TEST_ALLOCATOR;
+
+mod random;
+pub use random::XorShift;
+
+mod profiler;
+pub use profiler::{EncodingSizes, GateCounts, Profiler};
+
+#[cfg(feature = "smallvec")]
+mod small;
+#[cfg(feature = "smallvec")]
+pub use small::{SmallLiterals, SmallSolver, SMALL_LITERALS};
+
+mod sequence;
+pub use sequence::{CountSequence, Term};
+
+mod soft;
+pub use soft::{SoftConstraintSet, SoftSolution};
 
 mod progress;
 pub use progress::{add_progress, del_progress, set_progress};
+
+mod stream;
+pub use stream::{ModelStream, StreamState};
@@ -0,0 +1,493 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! An instrumentation layer that counts the gates and clauses created by
+//! the high level calls of a computation, and optionally measures the peak
+//! memory used by the solver queries.
+
+use std::collections::HashMap;
+
+use super::{BooleanLogic, BooleanSolver, CountingAllocator, Literal, SatInterface, Solver};
+use crate::genvec::BitVec;
+
+/// The number of gates and clauses created within a profiling scope.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GateCounts {
+    /// The number of conjunction gates.
+    pub and: usize,
+    /// The number of disjunction gates.
+    pub or: usize,
+    /// The number of exclusive or gates.
+    pub xor: usize,
+    /// The number of if-then-else gates.
+    pub ite: usize,
+    /// The number of equality comparisons of sequences, which are passed
+    /// to the underlying logic to use its own encoding.
+    pub cmp: usize,
+    /// The number of variables added to the solver.
+    pub variables: usize,
+    /// The number of clauses added to the solver.
+    pub clauses: usize,
+    /// The total length of the clauses added to the solver.
+    pub literals: usize,
+    /// The number of clauses added to the solver by the gates.
+    pub gate_clauses: usize,
+    /// The peak number of heap bytes allocated by a single solver query,
+    /// which is only measured if the profiler has an allocator.
+    pub peak_bytes: usize,
+}
+
+impl GateCounts {
+    /// Returns the total number of gates and clauses.
+    pub fn total(&self) -> usize {
+        self.and + self.or + self.xor + self.ite + self.cmp + self.variables + self.clauses
+    }
+
+    /// Returns an estimate of the number of bytes the added clauses occupy
+    /// in the clause database of the solver, where the gate clauses are
+    /// counted with three literals.
+    pub fn clause_bytes(&self) -> usize {
+        (self.literals + 3 * self.gate_clauses) * std::mem::size_of::<Literal>()
+            + (self.clauses + self.gate_clauses) * 2 * std::mem::size_of::<usize>()
+    }
+}
+
+/// A boolean logic that forwards every operation to the wrapped one, and
+/// counts the created gates and clauses per scope. Scopes are named by the
+/// user, typically after the high level method that is called within them,
+/// and nested scopes are reported with their full path separated by `/`.
+#[derive(Debug)]
+pub struct Profiler<LOGIC>
+where
+    LOGIC: BooleanLogic,
+{
+    logic: LOGIC,
+    stack: Vec<&'static str>,
+    counts: HashMap<String, GateCounts>,
+    allocator: Option<&'static CountingAllocator>,
+}
+
+impl<LOGIC> Profiler<LOGIC>
+where
+    LOGIC: BooleanLogic,
+{
+    /// Creates a new profiler wrapping the given logic.
+    pub fn new(logic: LOGIC) -> Self {
+        Self {
+            logic,
+            stack: Vec::new(),
+            counts: HashMap::new(),
+            allocator: None,
+        }
+    }
+
+    /// Measures the peak memory of the solver queries with the given
+    /// allocator, which must be installed as the global allocator.
+    pub fn with_allocator(mut self, allocator: &'static CountingAllocator) -> Self {
+        self.allocator = Some(allocator);
+        self
+    }
+
+    /// Returns the wrapped logic.
+    pub fn inner(&self) -> &LOGIC {
+        &self.logic
+    }
+
+    /// Returns the wrapped logic and drops the collected counts.
+    pub fn into_inner(self) -> LOGIC {
+        self.logic
+    }
+
+    /// Enters a new nested scope with the given name.
+    pub fn enter(&mut self, name: &'static str) {
+        self.stack.push(name);
+    }
+
+    /// Leaves the innermost scope.
+    pub fn leave(&mut self) {
+        assert!(self.stack.pop().is_some());
+    }
+
+    /// Runs the given closure within a new nested scope.
+    pub fn scope<RESULT, FUNC>(&mut self, name: &'static str, func: FUNC) -> RESULT
+    where
+        FUNC: FnOnce(&mut Self) -> RESULT,
+    {
+        self.enter(name);
+        let result = func(self);
+        self.leave();
+        result
+    }
+
+    /// Returns the counts collected for each scope. Operations performed
+    /// outside of all scopes are collected under the empty name.
+    pub fn counts(&self) -> &HashMap<String, GateCounts> {
+        &self.counts
+    }
+
+    /// Returns a textual report of the counts, with the most expensive
+    /// scopes listed first.
+    pub fn report(&self) -> String {
+        let mut counts: Vec<(&String, &GateCounts)> = self.counts.iter().collect();
+        counts.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+        let mut result = String::new();
+        for (name, count) in counts {
+            result += &format!(
+                "{}: and={}, or={}, xor={}, ite={}, cmp={}, variables={}, clauses={}, gate_clauses={}, clause_bytes={}, peak_bytes={}\n",
+                if name.is_empty() { "<top>" } else { name },
+                count.and,
+                count.or,
+                count.xor,
+                count.ite,
+                count.cmp,
+                count.variables,
+                count.clauses,
+                count.gate_clauses,
+                count.clause_bytes(),
+                count.peak_bytes
+            );
+        }
+        result
+    }
+
+    /// Returns the counts of the current scope.
+    fn current(&mut self) -> &mut GateCounts {
+        let name = self.stack.join("/");
+        self.counts.entry(name).or_default()
+    }
+
+    /// Runs the given gate operation and records the number of clauses it
+    /// has added to the solver in the current scope.
+    fn gate<RESULT, FUNC>(&mut self, func: FUNC) -> RESULT
+    where
+        FUNC: FnOnce(&mut LOGIC) -> RESULT,
+    {
+        let before = self.logic.bool_num_clauses();
+        let result = func(&mut self.logic);
+        let added = self.logic.bool_num_clauses() - before;
+        self.current().gate_clauses += added;
+        result
+    }
+
+    /// Runs the given solver query and records its peak memory in the
+    /// current scope if the profiler has an allocator.
+    fn query<RESULT, FUNC>(&mut self, func: FUNC) -> RESULT
+    where
+        FUNC: FnOnce(&mut LOGIC) -> RESULT,
+    {
+        match self.allocator {
+            Some(allocator) => {
+                let (result, peak) = allocator.measure(|| func(&mut self.logic));
+                let current = self.current();
+                current.peak_bytes = current.peak_bytes.max(peak);
+                result
+            }
+            None => func(&mut self.logic),
+        }
+    }
+}
+
+impl<LOGIC> BooleanLogic for Profiler<LOGIC>
+where
+    LOGIC: BooleanLogic + 'static,
+{
+    type Elem = LOGIC::Elem;
+
+    type Vector = LOGIC::Vector;
+
+    type Slice<'a> = LOGIC::Slice<'a>;
+
+    fn bool_scratch(&mut self) -> Self::Vector {
+        self.logic.bool_scratch()
+    }
+
+    fn bool_recycle(&mut self, vec: Self::Vector) {
+        self.logic.bool_recycle(vec)
+    }
+
+    fn bool_num_clauses(&self) -> usize {
+        self.logic.bool_num_clauses()
+    }
+
+    fn bool_unit(&self) -> Self::Elem {
+        self.logic.bool_unit()
+    }
+
+    fn bool_zero(&self) -> Self::Elem {
+        self.logic.bool_zero()
+    }
+
+    fn bool_lift(&self, elem: bool) -> Self::Elem {
+        self.logic.bool_lift(elem)
+    }
+
+    fn bool_is_unit(&self, elem: Self::Elem) -> bool {
+        self.logic.bool_is_unit(elem)
+    }
+
+    fn bool_is_zero(&self, elem: Self::Elem) -> bool {
+        self.logic.bool_is_zero(elem)
+    }
+
+    fn bool_not(&self, elem: Self::Elem) -> Self::Elem {
+        self.logic.bool_not(elem)
+    }
+
+    fn bool_or(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.current().or += 1;
+        self.gate(|logic| logic.bool_or(elem1, elem2))
+    }
+
+    fn bool_xor(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.current().xor += 1;
+        self.gate(|logic| logic.bool_xor(elem1, elem2))
+    }
+
+    fn bool_and(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.current().and += 1;
+        self.gate(|logic| logic.bool_and(elem1, elem2))
+    }
+
+    fn bool_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.current().ite += 1;
+        self.gate(|logic| logic.bool_ite(cond, elem1, elem2))
+    }
+
+    fn bool_cmp_equ<ITER>(&mut self, pairs: ITER) -> Self::Elem
+    where
+        ITER: Iterator<Item = (Self::Elem, Self::Elem)>,
+    {
+        self.current().cmp += 1;
+        self.gate(|logic| logic.bool_cmp_equ(pairs))
+    }
+}
+
+impl<LOGIC> BooleanSolver for Profiler<LOGIC>
+where
+    LOGIC: BooleanSolver + 'static,
+{
+    fn bool_add_variable(&mut self) -> Self::Elem {
+        self.current().variables += 1;
+        self.logic.bool_add_variable()
+    }
+
+    fn bool_add_clause(&mut self, clause: &[Self::Elem]) {
+        let current = self.current();
+        current.clauses += 1;
+        current.literals += clause.len();
+        self.logic.bool_add_clause(clause)
+    }
+
+    fn bool_solvable(&mut self) -> bool {
+        self.query(|logic| logic.bool_solvable())
+    }
+
+    fn bool_find_one_model<ITER>(
+        &mut self,
+        assumptions: &[Self::Elem],
+        literals: ITER,
+    ) -> Option<BitVec>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        self.query(|logic| logic.bool_find_one_model(assumptions, literals))
+    }
+
+    fn bool_unsat_core(&mut self, assumptions: &[Self::Elem]) -> Option<Vec<Self::Elem>> {
+        self.query(|logic| logic.bool_unsat_core(assumptions))
+    }
+}
+
+/// A SAT interface that only counts the added variables and clauses, and
+/// cannot solve. It is used to measure the size of encodings independently
+/// of the simplifications done by the real solvers.
+#[derive(Debug, Default)]
+struct SizeCounter {
+    variables: u32,
+    clauses: usize,
+}
+
+impl SatInterface for SizeCounter {
+    fn add_variable(&mut self) -> Literal {
+        self.variables += 1;
+        Literal {
+            value: 2 * (self.variables - 1),
+        }
+    }
+
+    fn negate(&self, lit: Literal) -> Literal {
+        Literal {
+            value: lit.value ^ 1,
+        }
+    }
+
+    fn add_clause(&mut self, _lits: &[Literal]) {
+        self.clauses += 1;
+    }
+
+    fn solve_with(&mut self, _lits: &[Literal]) -> bool {
+        panic!("the size counter cannot solve");
+    }
+
+    fn get_value(&self, _lit: Literal) -> bool {
+        panic!("the size counter has no model");
+    }
+
+    fn get_name(&self) -> &'static str {
+        "SizeCounter"
+    }
+
+    fn num_variables(&self) -> u32 {
+        self.variables
+    }
+
+    fn num_clauses(&self) -> usize {
+        self.clauses
+    }
+}
+
+/// Records the number of variables and clauses created by encodings with
+/// fixed parameters, and compares them with the expected values, so that
+/// tests can detect when an encoding grows unexpectedly.
+#[derive(Debug)]
+pub struct EncodingSizes {
+    tolerance: f64,
+    failures: Vec<String>,
+}
+
+impl EncodingSizes {
+    /// Creates a new checker, where the measured sizes can exceed the
+    /// expected ones by the given fraction (e.g. 0.1 for ten percent).
+    pub fn new(tolerance: f64) -> Self {
+        assert!(tolerance >= 0.0);
+        Self {
+            tolerance,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Returns the number of variables and clauses the given builder adds
+    /// to a fresh solver, which cannot be used for solving.
+    pub fn measure<BUILD>(build: BUILD) -> (usize, usize)
+    where
+        BUILD: FnOnce(&mut Solver),
+    {
+        let mut logic = Solver::with_interface(Box::<SizeCounter>::default());
+        let variables = logic.num_variables() as usize;
+        let clauses = logic.num_clauses();
+        build(&mut logic);
+        (
+            logic.num_variables() as usize - variables,
+            logic.num_clauses() - clauses,
+        )
+    }
+
+    /// Measures the encoding created by the given builder, and records a
+    /// failure if it is larger than expected beyond the tolerance.
+    pub fn check<BUILD>(&mut self, name: &str, variables: usize, clauses: usize, build: BUILD)
+    where
+        BUILD: FnOnce(&mut Solver),
+    {
+        let (vars, cls) = Self::measure(build);
+        let limit = |expected: usize| (expected as f64 * (1.0 + self.tolerance)).floor() as usize;
+        if vars > limit(variables) || cls > limit(clauses) {
+            self.failures.push(format!(
+                "{}: variables={} (expected {}), clauses={} (expected {})",
+                name, vars, variables, cls, clauses
+            ));
+        }
+    }
+
+    /// Returns the descriptions of the recorded failures.
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    /// Panics with the list of failures, if there are any.
+    pub fn assert_ok(&self) {
+        assert!(
+            self.failures.is_empty(),
+            "encoding sizes regressed:\n{}",
+            self.failures.join("\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Solver;
+
+    #[test]
+    fn profiler() {
+        let mut logic = Profiler::new(Solver::new(""));
+        let a = logic.bool_add_variable();
+        let b = logic.bool_add_variable();
+        let c = logic.scope("outer", |logic| {
+            let c = logic.bool_and(a, b);
+            logic.scope("inner", |logic| logic.bool_xor(c, a))
+        });
+        logic.scope("outer", |logic| logic.bool_ite(a, b, c));
+        logic.bool_add_clause(&[c]);
+
+        let counts = logic.counts();
+        assert_eq!(counts[""].variables, 2);
+        assert_eq!(counts[""].clauses, 1);
+        assert_eq!(counts[""].literals, 1);
+        assert_eq!(counts[""].peak_bytes, 0);
+        assert_eq!(counts["outer"].and, 1);
+        assert_eq!(counts["outer/inner"].xor, 1);
+        assert_eq!(counts["outer"].ite, 1);
+        assert_eq!(counts["outer"].gate_clauses, 3 + 6);
+        assert_eq!(counts["outer/inner"].gate_clauses, 4);
+        assert!(logic.report().starts_with("<top>"));
+        assert!(logic
+            .bool_find_one_model(&[], [a, b].iter().copied())
+            .is_some());
+    }
+
+    #[test]
+    fn allocator() {
+        let mut logic = Profiler::new(Solver::new("")).with_allocator(&crate::core::TEST_ALLOCATOR);
+        let lits: Vec<Literal> = (0..200).map(|_| logic.bool_add_variable()).collect();
+        logic.scope("query", |logic| {
+            let test = logic.bool_fold_count(lits.iter().copied(), 100);
+            logic.bool_add_clause1(test);
+            assert!(logic.bool_solvable());
+        });
+        let counts = &logic.counts()["query"];
+        assert!(counts.peak_bytes > 0);
+        assert!(counts.clause_bytes() > counts.gate_clauses);
+    }
+
+    #[test]
+    fn encoding_sizes() {
+        let mut sizes = EncodingSizes::new(0.0);
+        sizes.check("and", 1, 3, |logic| {
+            let a = logic.bool_add_variable();
+            logic.bool_and(a, a);
+        });
+        assert!(sizes.failures().is_empty());
+        sizes.check("or", 1, 0, |logic| {
+            let a = logic.bool_add_variable();
+            let b = logic.bool_add_variable();
+            logic.bool_or(a, b);
+        });
+        assert_eq!(sizes.failures().len(), 1);
+        assert!(sizes.failures()[0].starts_with("or: variables=3"));
+    }
+}
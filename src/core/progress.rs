@@ -65,11 +65,12 @@ fn worker() {
 }
 
 /// Creates a new monitored value. If this is the first monitored value,
-/// then a worker thread will be started.
+/// then a worker thread will be started, except on wasm where threads
+/// are not available.
 pub fn add_progress(name: &'static str) {
     let mut monitor = MONITOR.lock().unwrap();
     monitor.vars.insert(name, 0);
-    if !monitor.running {
+    if !monitor.running && cfg!(not(target_arch = "wasm32")) {
         monitor.running = true;
         spawn(worker);
     }
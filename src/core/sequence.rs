@@ -0,0 +1,160 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Parameterized counts that are cached and reported as integer sequences,
+//! so they can be compared against the OEIS.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// A computed term of a sequence with the time it took to compute it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Term {
+    /// The value of the term.
+    pub count: usize,
+    /// The computation time in seconds.
+    pub seconds: f32,
+}
+
+/// An integer sequence whose terms are computed by the given function,
+/// typically by counting the models of a predicate over a domain of the
+/// given size. Every term is computed at most once.
+pub struct CountSequence<FUNC>
+where
+    FUNC: Fn(usize) -> usize,
+{
+    name: String,
+    func: FUNC,
+    terms: BTreeMap<usize, Term>,
+}
+
+impl<FUNC> CountSequence<FUNC>
+where
+    FUNC: Fn(usize) -> usize,
+{
+    /// Creates a new sequence with the given name and term function.
+    pub fn new(name: &str, func: FUNC) -> Self {
+        Self {
+            name: name.to_string(),
+            func,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the name of the sequence.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the term with the given index, computing it if it is not
+    /// cached yet.
+    pub fn term(&mut self, index: usize) -> usize {
+        if let Some(term) = self.terms.get(&index) {
+            return term.count;
+        }
+        let start = Instant::now();
+        let count = (self.func)(index);
+        let seconds = Instant::now().duration_since(start).as_secs_f32();
+        self.terms.insert(index, Term { count, seconds });
+        count
+    }
+
+    /// Returns the terms with the given indices.
+    pub fn terms<ITER>(&mut self, indices: ITER) -> Vec<usize>
+    where
+        ITER: Iterator<Item = usize>,
+    {
+        indices.map(|index| self.term(index)).collect()
+    }
+
+    /// Returns the cached terms in increasing order of their indices.
+    pub fn cached(&self) -> impl Iterator<Item = (usize, Term)> + '_ {
+        self.terms.iter().map(|(&index, &term)| (index, term))
+    }
+
+    /// Returns the cached values as a comma separated list, which is the
+    /// format used for searching the OEIS.
+    pub fn values(&self) -> String {
+        let values: Vec<String> = self.cached().map(|(_, t)| t.count.to_string()).collect();
+        values.join(", ")
+    }
+
+    /// Returns a textual report of the cached terms with their timing.
+    pub fn report(&self) -> String {
+        let mut result = format!("{}: {}\n", self.name, self.values());
+        for (index, term) in self.cached() {
+            result += &format!("  {}: {} in {:.3}s\n", index, term.count, term.seconds);
+        }
+        result
+    }
+
+    /// Exports the cached terms with one `index,count,seconds` line per
+    /// term, so that the timing can be tracked over time.
+    pub fn export(&self) -> String {
+        let mut result = String::new();
+        for (index, term) in self.cached() {
+            result += &format!("{},{},{}\n", index, term.count, term.seconds);
+        }
+        result
+    }
+
+    /// Imports terms previously exported, which are then not recomputed.
+    /// Returns the number of imported terms, or `None` if the text could
+    /// not be parsed.
+    pub fn import(&mut self, text: &str) -> Option<usize> {
+        let mut terms = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let mut fields = line.split(',').map(|f| f.trim());
+            let index = fields.next()?.parse().ok()?;
+            let count = fields.next()?.parse().ok()?;
+            let seconds = fields.next()?.parse().ok()?;
+            if fields.next().is_some() {
+                return None;
+            }
+            terms.push((index, Term { count, seconds }));
+        }
+        let len = terms.len();
+        self.terms.extend(terms);
+        Some(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn sequence() {
+        let calls = Cell::new(0);
+        let mut seq = CountSequence::new("powers", |n| {
+            calls.set(calls.get() + 1);
+            1 << n
+        });
+        assert_eq!(seq.terms(0..5), vec![1, 2, 4, 8, 16]);
+        assert_eq!(seq.terms(3..6), vec![8, 16, 32]);
+        assert_eq!(calls.get(), 6);
+        assert_eq!(seq.values(), "1, 2, 4, 8, 16, 32");
+        assert!(seq.report().starts_with("powers: 1, 2"));
+
+        let text = seq.export();
+        let mut other = CountSequence::new("powers", |_| 0);
+        assert_eq!(other.import(&text), Some(6));
+        assert_eq!(other.terms(0..6), vec![1, 2, 4, 8, 16, 32]);
+        assert_eq!(other.import("1,2"), None);
+    }
+}
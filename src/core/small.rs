@@ -0,0 +1,150 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A solver whose literal vectors store short elements inline.
+
+use super::{BooleanLogic, BooleanSolver, Literal, Solver};
+use crate::genvec::{BitVec, SmallSlice, SmallVec, VectorPool};
+
+/// The number of literals stored inline in a small literal vector.
+pub const SMALL_LITERALS: usize = 32;
+
+/// A literal vector that does not allocate up to `SMALL_LITERALS` literals.
+pub type SmallLiterals = SmallVec<[Literal; SMALL_LITERALS]>;
+
+/// The free boolean algebra backed by a SAT solver, which works exactly as
+/// `Solver` but uses small vectors to hold the elements of domains.
+#[derive(Debug)]
+pub struct SmallSolver {
+    solver: Solver,
+    pool: VectorPool<SmallLiterals>,
+}
+
+impl SmallSolver {
+    /// Creates a new free boolean algebra.
+    pub fn new(solver_name: &str) -> Self {
+        Self {
+            solver: Solver::new(solver_name),
+            pool: Default::default(),
+        }
+    }
+
+    /// Returns the underlying solver.
+    pub fn inner(&self) -> &Solver {
+        &self.solver
+    }
+}
+
+impl BooleanLogic for SmallSolver {
+    type Elem = Literal;
+
+    type Vector = SmallLiterals;
+
+    type Slice<'a> = SmallSlice<'a, [Literal; SMALL_LITERALS]>;
+
+    fn bool_scratch(&mut self) -> Self::Vector {
+        self.pool.take()
+    }
+
+    fn bool_recycle(&mut self, vec: Self::Vector) {
+        self.pool.give(vec);
+    }
+
+    fn bool_num_clauses(&self) -> usize {
+        self.solver.bool_num_clauses()
+    }
+
+    fn bool_unit(&self) -> Self::Elem {
+        self.solver.bool_unit()
+    }
+
+    fn bool_zero(&self) -> Self::Elem {
+        self.solver.bool_zero()
+    }
+
+    fn bool_lift(&self, elem: bool) -> Self::Elem {
+        self.solver.bool_lift(elem)
+    }
+
+    fn bool_is_unit(&self, elem: Self::Elem) -> bool {
+        self.solver.bool_is_unit(elem)
+    }
+
+    fn bool_is_zero(&self, elem: Self::Elem) -> bool {
+        self.solver.bool_is_zero(elem)
+    }
+
+    fn bool_not(&self, elem: Self::Elem) -> Self::Elem {
+        self.solver.bool_not(elem)
+    }
+
+    fn bool_or(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.solver.bool_or(elem1, elem2)
+    }
+
+    fn bool_xor(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.solver.bool_xor(elem1, elem2)
+    }
+
+    fn bool_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        self.solver.bool_ite(cond, elem1, elem2)
+    }
+
+    fn bool_cmp_equ<ITER>(&mut self, pairs: ITER) -> Self::Elem
+    where
+        ITER: Iterator<Item = (Self::Elem, Self::Elem)>,
+    {
+        self.solver.bool_cmp_equ(pairs)
+    }
+}
+
+impl BooleanSolver for SmallSolver {
+    fn bool_add_variable(&mut self) -> Self::Elem {
+        self.solver.bool_add_variable()
+    }
+
+    fn bool_add_clause(&mut self, clause: &[Self::Elem]) {
+        self.solver.bool_add_clause(clause)
+    }
+
+    fn bool_solvable(&mut self) -> bool {
+        self.solver.bool_solvable()
+    }
+
+    fn bool_find_one_model<ITER>(
+        &mut self,
+        assumptions: &[Self::Elem],
+        literals: ITER,
+    ) -> Option<BitVec>
+    where
+        ITER: Iterator<Item = Self::Elem>,
+    {
+        self.solver.bool_find_one_model(assumptions, literals)
+    }
+
+    fn bool_add_native_amo(&mut self, lits: &[Self::Elem]) -> bool {
+        self.solver.bool_add_native_amo(lits)
+    }
+
+    fn bool_add_at_most(&mut self, lits: &[Self::Elem], bound: usize) {
+        self.solver.bool_add_at_most(lits, bound)
+    }
+
+    fn bool_unsat_core(&mut self, assumptions: &[Self::Elem]) -> Option<Vec<Self::Elem>> {
+        self.solver.bool_unsat_core(assumptions)
+    }
+}
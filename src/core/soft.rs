@@ -0,0 +1,185 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Weighted soft constraints over reified predicates. Any predicate built
+//! in a solver, like the test of a domain property, can be added with a
+//! weight, and the optimizer finds a model that maximizes the total weight
+//! of the satisfied constraints.
+
+use super::BooleanSolver;
+use crate::genvec::{BitVec, Vector};
+
+/// A set of weighted soft constraints living in a solver.
+pub struct SoftConstraintSet<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    names: Vec<String>,
+    weights: Vec<usize>,
+    literals: Vec<LOGIC::Elem>,
+}
+
+impl<LOGIC> Default for SoftConstraintSet<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<LOGIC> SoftConstraintSet<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    /// Creates an empty set of soft constraints.
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            weights: Vec::new(),
+            literals: Vec::new(),
+        }
+    }
+
+    /// Adds the given predicate as a soft constraint with the given name
+    /// and positive weight, and returns its index. The predicate is
+    /// reified, so the returned index identifies a fresh variable.
+    pub fn add(
+        &mut self,
+        logic: &mut LOGIC,
+        name: &str,
+        weight: usize,
+        elem: LOGIC::Elem,
+    ) -> usize {
+        assert!(weight > 0);
+        self.names.push(name.to_string());
+        self.weights.push(weight);
+        self.literals.push(logic.bool_reify(elem));
+        self.literals.len() - 1
+    }
+
+    /// Returns the number of soft constraints.
+    pub fn len(&self) -> usize {
+        self.literals.len()
+    }
+
+    /// Returns true if there are no soft constraints.
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
+    /// Returns the name of the given soft constraint.
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    /// Returns the weight of the given soft constraint.
+    pub fn weight(&self, index: usize) -> usize {
+        self.weights[index]
+    }
+
+    /// Returns the reified literal of the given soft constraint.
+    pub fn literal(&self, index: usize) -> LOGIC::Elem {
+        self.literals[index]
+    }
+
+    /// Returns the sum of the weights of all soft constraints.
+    pub fn total_weight(&self) -> usize {
+        self.weights.iter().sum()
+    }
+
+    /// Adds the hard constraint that at most the given number of soft
+    /// constraints are violated, regardless of their weights.
+    pub fn limit_violations(&self, logic: &mut LOGIC, bound: usize) {
+        let lits: Vec<LOGIC::Elem> = self.literals.iter().map(|&a| logic.bool_not(a)).collect();
+        logic.bool_add_at_most(&lits, bound);
+    }
+
+    /// Finds a model that maximizes the total weight of the satisfied soft
+    /// constraints, and returns the value of the given literals together
+    /// with the violated constraints. The optimum is found by the core
+    /// guided search of `bool_maximize_weighted`, so the weights can be large.
+    pub fn find_best<ITER>(&self, logic: &mut LOGIC, literals: ITER) -> Option<SoftSolution>
+    where
+        ITER: Iterator<Item = LOGIC::Elem>,
+        LOGIC::Elem: PartialEq,
+    {
+        let soft: Vec<(LOGIC::Elem, usize)> = self
+            .literals
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .collect();
+
+        let (weight, model) =
+            logic.bool_maximize_weighted(&soft, self.literals.iter().copied().chain(literals))?;
+        let violated = model
+            .copy_iter()
+            .take(self.len())
+            .enumerate()
+            .filter(|(_, v)| !v)
+            .map(|(i, _)| i)
+            .collect();
+        let values = model.copy_iter().skip(self.len()).collect();
+        Some(SoftSolution {
+            weight,
+            violated,
+            values,
+        })
+    }
+}
+
+/// An optimal solution of a set of soft constraints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftSolution {
+    /// The total weight of the satisfied soft constraints.
+    pub weight: usize,
+    /// The indices of the violated soft constraints, which are the witnesses
+    /// of the missing weight.
+    pub violated: Vec<usize>,
+    /// The values of the requested literals.
+    pub values: BitVec,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BooleanLogic, Solver};
+
+    #[test]
+    fn weighted_soft_constraints() {
+        let mut logic = Solver::new("");
+        let a = logic.bool_add_variable();
+        let b = logic.bool_add_variable();
+        let not_a = logic.bool_not(a);
+        let not_b = logic.bool_not(b);
+        logic.bool_add_clause2(not_a, not_b);
+
+        let mut soft = SoftConstraintSet::new();
+        soft.add(&mut logic, "a", 1, a);
+        soft.add(&mut logic, "b", 3, b);
+        let c = logic.bool_or(a, b);
+        soft.add(&mut logic, "a or b", 2, c);
+        assert_eq!(soft.total_weight(), 6);
+
+        let best = soft.find_best(&mut logic, [a, b].iter().copied()).unwrap();
+        assert_eq!(best.weight, 5);
+        assert_eq!(best.violated, vec![0]);
+        assert_eq!(soft.name(best.violated[0]), "a");
+        assert!(!best.values.get(0) && best.values.get(1));
+    }
+}
@@ -27,6 +27,11 @@ use varisat::ExtendFormula as _;
 #[cfg(feature = "varisat")]
 use crate::genvec::{BitVec, Vector};
 
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use super::{FragmentSolver, HybridSolver};
+
 /// Uniform literal to allow runtime solver selection.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Literal {
@@ -56,6 +61,68 @@ pub trait SatInterface {
         self.add_clause(&[not_lit1, not_lit2, not_lit3]);
     }
 
+    /// Returns true if the solver handles at most one and cardinality
+    /// constraints natively, without translating them to clauses.
+    fn has_native_cardinality(&self) -> bool {
+        false
+    }
+
+    /// Adds the constraint that at most one of the literals is true. The
+    /// default implementation adds the pairwise exclusion clauses.
+    fn add_amo_clause(&mut self, lits: &[Literal]) {
+        for (i, &a) in lits.iter().enumerate() {
+            let not_a = self.negate(a);
+            for &b in lits[i + 1..].iter() {
+                let not_b = self.negate(b);
+                self.add_clause(&[not_a, not_b]);
+            }
+        }
+    }
+
+    /// Adds the constraint that at most `bound` many of the literals are
+    /// true. The default implementation uses the sequential counter encoding
+    /// of Sinz with auxiliary variables.
+    fn add_card_clause(&mut self, lits: &[Literal], bound: usize) {
+        if bound >= lits.len() {
+            return;
+        } else if bound == 0 {
+            for &a in lits {
+                let not_a = self.negate(a);
+                self.add_clause(&[not_a]);
+            }
+            return;
+        }
+
+        // the counter after literal i is at least j + 1
+        let mut prev: Vec<Literal> = Vec::new();
+        for (i, &a) in lits.iter().enumerate() {
+            let not_a = self.negate(a);
+            if i > 0 {
+                let not_full = self.negate(prev[bound - 1]);
+                self.add_clause(&[not_a, not_full]);
+            }
+            if i + 1 == lits.len() {
+                break;
+            }
+            let next: Vec<Literal> = (0..bound).map(|_| self.add_variable()).collect();
+            self.add_clause(&[not_a, next[0]]);
+            for j in 0..bound {
+                if i > 0 {
+                    let not_prev = self.negate(prev[j]);
+                    self.add_clause(&[not_prev, next[j]]);
+                    if j > 0 {
+                        let not_prev = self.negate(prev[j - 1]);
+                        self.add_clause(&[not_a, not_prev, next[j]]);
+                    }
+                } else if j > 0 {
+                    let not_next = self.negate(next[j]);
+                    self.add_clause(&[not_next]);
+                }
+            }
+            prev = next;
+        }
+    }
+
     /// Runs the solver and returns true if a solution is available.
     fn solve(&mut self) -> bool {
         self.solve_with(&[])
@@ -68,6 +135,13 @@ pub trait SatInterface {
     /// Returns the value of the literal in the found model.
     fn get_value(&self, lit: Literal) -> bool;
 
+    /// Returns the subset of the assumptions of the last unsuccessful call
+    /// of `solve_with`, which must be given again, that were used to prove
+    /// unsatisfiability. The default implementation returns all of them.
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        assumptions.to_vec()
+    }
+
     /// Returns the name of the solver
     fn get_name(&self) -> &'static str;
 
@@ -78,10 +152,65 @@ pub trait SatInterface {
     fn num_clauses(&self) -> usize;
 }
 
+lazy_static! {
+    /// The name of the solver used when the empty name is given.
+    static ref DEFAULT_SOLVER: Mutex<String> = Default::default();
+}
+
+thread_local! {
+    /// The name of the solver used in this thread when the empty name is
+    /// given, which takes precedence over the global default.
+    static THREAD_SOLVER: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Sets the name of the solver that is created for the empty name, which
+/// is used by most library methods. The empty string restores the
+/// selection of the first available solver.
+pub fn set_default_solver(name: &str) {
+    *DEFAULT_SOLVER.lock().unwrap() = name.to_string();
+}
+
+/// Sets the name of the solver that is created for the empty name in the
+/// current thread, or clears it so that the global default is used.
+pub fn set_thread_solver(name: Option<&str>) {
+    THREAD_SOLVER.with(|solver| *solver.borrow_mut() = name.map(|n| n.to_string()));
+}
+
+/// Returns the name of the solver that is created for the empty name in
+/// the current thread.
+pub fn get_default_solver() -> String {
+    THREAD_SOLVER
+        .with(|solver| solver.borrow().clone())
+        .unwrap_or_else(|| DEFAULT_SOLVER.lock().unwrap().clone())
+}
+
 /// Tries to create a SAT solver with the given name. Currently "batsat",
 /// "varisat", "minisat" and "cryptominisat" are supported, but not on all
-/// platforms. Use the empty string to match the first available solver.
+/// platforms. Use the empty string to select the configured default, and
+/// "auto" to match the first available solver.
+/// The "fragment" solver uses specialized algorithms while the clauses
+/// belong to a tractable fragment and falls back to the default solver.
+/// The "hybrid" solver runs an incomplete local search before falling back
+/// to the default solver. The fallback of these solvers is always a complete
+/// solver, so the first available one is used when the default is one of
+/// them.
 pub fn create_solver(name: &str) -> Box<dyn SatInterface> {
+    let default;
+    let name = if name.is_empty() {
+        default = get_default_solver();
+        default.as_str()
+    } else {
+        name
+    };
+
+    if name == "fragment" {
+        return Box::new(FragmentSolver::new(fallback_solver()));
+    }
+    if name == "hybrid" {
+        return Box::new(HybridSolver::new(fallback_solver()));
+    }
+    let name = if name == "auto" { "" } else { name };
+
     #[cfg(feature = "batsat")]
     {
         if name == "batsat" || name.is_empty() {
@@ -134,6 +263,15 @@ pub fn create_solver(name: &str) -> Box<dyn SatInterface> {
     panic!("Unknown SAT solver: {}", name);
 }
 
+/// Returns the name of the complete solver used as the fallback of the
+/// "fragment" and "hybrid" solvers.
+fn fallback_solver() -> &'static str {
+    match get_default_solver().as_str() {
+        "fragment" | "hybrid" => "auto",
+        _ => "",
+    }
+}
+
 impl std::fmt::Debug for dyn SatInterface {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
@@ -300,6 +438,15 @@ impl SatInterface for VariSat<'_> {
         self.solution.get(var) ^ lit.is_negative()
     }
 
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        let core = self.solver.failed_core().unwrap_or(&[]);
+        assumptions
+            .iter()
+            .copied()
+            .filter(|&lit| core.contains(&VariSat::decode(lit)))
+            .collect()
+    }
+
     fn get_name(&self) -> &'static str {
         "VariSat"
     }
@@ -459,6 +606,14 @@ impl SatInterface for BatSat {
         self.solver.value_lit(BatSat::decode(lit)) == batsat::lbool::TRUE
     }
 
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        assumptions
+            .iter()
+            .copied()
+            .filter(|&lit| self.solver.unsat_core_contains_lit(!BatSat::decode(lit)))
+            .collect()
+    }
+
     fn get_name(&self) -> &'static str {
         "BatSat"
     }
@@ -480,6 +635,7 @@ pub struct CaDiCaL {
     num_vars: u32,
 }
 
+#[cfg(feature = "cadical")]
 impl CaDiCaL {
     pub fn with_config(config: &str) -> Self {
         let solver = cadical::Solver::with_config(config).unwrap();
@@ -520,6 +676,14 @@ impl SatInterface for CaDiCaL {
         self.solver.value(lit.value as i32) == Some(true)
     }
 
+    fn unsat_core(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        assumptions
+            .iter()
+            .copied()
+            .filter(|lit| self.solver.failed(lit.value as i32))
+            .collect()
+    }
+
     fn get_name(&self) -> &'static str {
         "CaDiCaL"
     }
@@ -558,6 +722,43 @@ mod tests {
         assert!(!sat.solve());
     }
 
+    fn test_core(sat: &mut dyn SatInterface) {
+        let a = sat.add_variable();
+        let b = sat.add_variable();
+        let c = sat.add_variable();
+        sat.add_clause(&[sat.negate(a), sat.negate(b)]);
+        let assumptions = [a, b, c];
+        assert!(!sat.solve_with(&assumptions));
+        let core = sat.unsat_core(&assumptions);
+        assert!(core.contains(&a) && core.contains(&b));
+        assert!(!core.contains(&c));
+    }
+
+    #[test]
+    fn cardinality() {
+        for bound in 0..=4 {
+            let mut sat = create_solver("");
+            let lits: Vec<Literal> = (0..4).map(|_| sat.add_variable()).collect();
+            sat.add_card_clause(&lits, bound);
+            if bound == 1 {
+                sat.add_amo_clause(&lits);
+            }
+            for mask in 0..16 {
+                let assumptions: Vec<Literal> = (0..4)
+                    .map(|i| {
+                        if mask & (1 << i) != 0 {
+                            lits[i]
+                        } else {
+                            sat.negate(lits[i])
+                        }
+                    })
+                    .collect();
+                let count = (mask as u32).count_ones() as usize;
+                assert_eq!(sat.solve_with(&assumptions), count <= bound);
+            }
+        }
+    }
+
     #[cfg(feature = "minisat")]
     #[test]
     fn minisat() {
@@ -570,6 +771,8 @@ mod tests {
     fn varisat() {
         let mut sat: VariSat = Default::default();
         test(&mut sat);
+        let mut sat: VariSat = Default::default();
+        test_core(&mut sat);
     }
 
     #[cfg(feature = "cryptominisat")]
@@ -584,6 +787,8 @@ mod tests {
     fn batsat() {
         let mut sat: BatSat = Default::default();
         test(&mut sat);
+        let mut sat: BatSat = Default::default();
+        test_core(&mut sat);
     }
 
     #[cfg(feature = "cadical")]
@@ -591,5 +796,44 @@ mod tests {
     fn cadical() {
         let mut sat: CaDiCaL = Default::default();
         test(&mut sat);
+        let mut sat: CaDiCaL = Default::default();
+        test_core(&mut sat);
+    }
+
+    #[test]
+    fn fragment() {
+        let mut sat = FragmentSolver::new("");
+        test(&mut sat);
+    }
+
+    #[test]
+    fn hybrid() {
+        let mut sat = HybridSolver::new("");
+        test(&mut sat);
+        let mut sat = HybridSolver::new("");
+        test_core(&mut sat);
+    }
+
+    #[test]
+    fn default_solver() {
+        set_thread_solver(Some("fragment"));
+        assert_eq!(get_default_solver(), "fragment");
+        let mut sat = create_solver("");
+        assert_eq!(sat.get_name(), "Fragment");
+        test(sat.as_mut());
+        set_thread_solver(None);
+        assert_ne!(create_solver("").get_name(), "Fragment");
+
+        set_thread_solver(Some("hybrid"));
+        let mut sat = create_solver("");
+        assert_eq!(sat.get_name(), "Hybrid");
+        test(sat.as_mut());
+        set_thread_solver(None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn walksat_not_available() {
+        create_solver("walksat");
     }
 }
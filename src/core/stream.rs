@@ -0,0 +1,226 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Model enumeration that runs in small chunks, so that it can be driven
+//! from an event loop (such as the browser on wasm) without blocking it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::{del_progress, set_progress, BooleanSolver};
+use crate::genvec::{BitVec, Vector};
+
+/// The state of a model stream after a chunk of work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamState {
+    /// There may be more models to find.
+    Pending,
+    /// All models have been found.
+    Done,
+    /// The enumeration was cancelled.
+    Cancelled,
+}
+
+/// Enumerates the models of a solver with respect to the given literals,
+/// where each call of `step` finds at most a given number of models and
+/// then returns control to the caller. Found models are buffered until
+/// they are taken, which allows sending them in chunks as messages.
+pub struct ModelStream<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    logic: LOGIC,
+    literals: Vec<LOGIC::Elem>,
+    models: Vec<BitVec>,
+    count: usize,
+    state: StreamState,
+    cancel: Arc<AtomicBool>,
+    progress: Option<&'static str>,
+    chunk: usize,
+}
+
+impl<LOGIC> ModelStream<LOGIC>
+where
+    LOGIC: BooleanSolver,
+{
+    /// Creates a new stream enumerating the models of the given solver.
+    pub fn new<ITER>(logic: LOGIC, literals: ITER) -> Self
+    where
+        ITER: Iterator<Item = LOGIC::Elem>,
+    {
+        Self {
+            logic,
+            literals: literals.collect(),
+            models: Vec::new(),
+            count: 0,
+            state: StreamState::Pending,
+            cancel: Default::default(),
+            progress: None,
+            chunk: 1,
+        }
+    }
+
+    /// Reports the number of found models in the given progress variable,
+    /// which must be already added.
+    pub fn with_progress(mut self, name: &'static str) -> Self {
+        self.progress = Some(name);
+        self
+    }
+
+    /// Sets the number of models found in a single poll of the future.
+    pub fn with_chunk(mut self, chunk: usize) -> Self {
+        assert!(chunk > 0);
+        self.chunk = chunk;
+        self
+    }
+
+    /// Returns a flag that cancels the enumeration when set, which can be
+    /// shared with other threads or event handlers.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancel.clone()
+    }
+
+    /// Returns the current state of the enumeration.
+    pub fn state(&self) -> StreamState {
+        self.state
+    }
+
+    /// Returns the number of models found so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the models found since the last call of this method.
+    pub fn take_models(&mut self) -> Vec<BitVec> {
+        std::mem::take(&mut self.models)
+    }
+
+    /// Finds at most the given number of new models and returns the state
+    /// of the enumeration.
+    pub fn step(&mut self, budget: usize) -> StreamState {
+        for _ in 0..budget {
+            if self.state != StreamState::Pending {
+                break;
+            }
+            if self.cancel.load(Ordering::Relaxed) {
+                self.finish(StreamState::Cancelled);
+                break;
+            }
+            match self
+                .logic
+                .bool_find_one_model(&[], self.literals.iter().copied())
+            {
+                None => self.finish(StreamState::Done),
+                Some(model) => {
+                    self.logic
+                        .bool_add_blocking_clause(None, &self.literals, model.slice());
+                    self.models.push(model);
+                    self.count += 1;
+                    if let Some(name) = self.progress {
+                        set_progress(name, self.count as u64);
+                    }
+                }
+            }
+        }
+        self.state
+    }
+
+    fn finish(&mut self, state: StreamState) {
+        self.state = state;
+        if let Some(name) = self.progress.take() {
+            del_progress(name);
+        }
+    }
+}
+
+/// The future completes with the number of models, and yields back to the
+/// executor after each chunk of models.
+impl<LOGIC> Future for ModelStream<LOGIC>
+where
+    LOGIC: BooleanSolver + Unpin,
+    LOGIC::Elem: Unpin,
+{
+    type Output = (StreamState, usize);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let chunk = self.chunk;
+        match self.step(chunk) {
+            StreamState::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            state => Poll::Ready((state, self.count)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BooleanLogic, Solver};
+    use std::task::{RawWaker, RawWakerVTable, Waker};
+
+    fn waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        unsafe { Waker::from_raw(clone(std::ptr::null())) }
+    }
+
+    #[test]
+    fn stream() {
+        let mut logic = Solver::new("");
+        let lits: Vec<_> = (0..3).map(|_| logic.bool_add_variable()).collect();
+        let test = logic.bool_fold_amo(lits.iter().copied());
+        logic.bool_add_clause(&[test]);
+
+        let mut stream = ModelStream::new(logic, lits.iter().copied());
+        assert_eq!(stream.step(3), StreamState::Pending);
+        assert_eq!(stream.take_models().len(), 3);
+        assert_eq!(stream.step(3), StreamState::Done);
+        assert_eq!(stream.take_models().len(), 1);
+        assert_eq!(stream.count(), 4);
+
+        let mut logic = Solver::new("");
+        let lits: Vec<_> = (0..4).map(|_| logic.bool_add_variable()).collect();
+        let mut stream = ModelStream::new(logic, lits.iter().copied()).with_chunk(5);
+        let waker = waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut polls = 0;
+        let result = loop {
+            polls += 1;
+            if let Poll::Ready(result) = Pin::new(&mut stream).poll(&mut cx) {
+                break result;
+            }
+        };
+        assert_eq!(result, (StreamState::Done, 16));
+        assert_eq!(polls, 4);
+
+        let mut logic = Solver::new("");
+        let lits: Vec<_> = (0..4).map(|_| logic.bool_add_variable()).collect();
+        let mut stream = ModelStream::new(logic, lits.iter().copied());
+        stream.step(2);
+        stream.cancel_flag().store(true, Ordering::Relaxed);
+        assert_eq!(stream.step(2), StreamState::Cancelled);
+        assert_eq!(stream.count(), 2);
+    }
+}
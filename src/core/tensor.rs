@@ -19,9 +19,9 @@
 
 use std::ops;
 
-use super::{BooleanLogic, BooleanSolver};
+use super::{BinaryLogic, BooleanLogic, BooleanSolver};
 use crate::core::Literal;
-use crate::genvec::{BitVec, Vector};
+use crate::genvec::{BitMatrix, BitVec, Slice, Vector};
 
 /// The shape of a tensor.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -289,6 +289,62 @@ where
     pub fn reshape(&self, shape: Shape) -> Self {
         Tensor::new(shape, self.elems.clone())
     }
+
+    /// Returns the sub-tensor where the coordinates given as `Some` values
+    /// are fixed, and the ones given as `None` are kept in their original
+    /// order.
+    pub fn select(&self, coords: &[Option<usize>]) -> Self {
+        assert_eq!(coords.len(), self.shape.len());
+        let strides = self.shape.strides();
+        let mut offset = 0;
+        let mut dims = Vec::new();
+        let mut kept = Vec::new();
+        for (idx, coord) in coords.iter().enumerate() {
+            match coord {
+                Some(coord) => {
+                    assert!(*coord < self.shape[idx]);
+                    offset += *coord * strides[idx];
+                }
+                None => {
+                    dims.push(self.shape[idx]);
+                    kept.push(strides[idx]);
+                }
+            }
+        }
+
+        let shape = Shape::new(dims);
+        let mut iter = StrideIter::new(&shape);
+        for (idx, stride) in kept.into_iter().enumerate() {
+            iter.add_stride(idx, stride);
+        }
+        let elems: ELEM::Vec = iter.map(|i| self.elems.get(offset + i)).collect();
+        Tensor::new(shape, elems)
+    }
+
+    /// Returns the hyperplane of the tensor where the coordinate of the given
+    /// axis is fixed to the given index, which has one less dimension.
+    pub fn slice(&self, axis: usize, index: usize) -> Self {
+        assert!(axis < self.shape.len());
+        let coords: Vec<Option<usize>> = (0..self.shape.len())
+            .map(|i| if i == axis { Some(index) } else { None })
+            .collect();
+        self.select(&coords)
+    }
+
+    /// Concatenates the given tensors of the same shape along a new leading
+    /// dimension. The list of tensors must not be empty.
+    pub fn stack(tensors: &[Self]) -> Self {
+        assert!(!tensors.is_empty());
+        let shape = tensors[0].shape.clone();
+        assert!(tensors.iter().all(|t| t.shape == shape));
+        let mut elems: ELEM::Vec = Vector::with_capacity(shape.size() * tensors.len());
+        for i in 0..shape.size() {
+            for tensor in tensors.iter() {
+                elems.push(tensor.elems.get(i));
+            }
+        }
+        Tensor::new(shape.join(&[tensors.len()]), elems)
+    }
 }
 
 /// A tensor algebra for tensors.
@@ -318,6 +374,19 @@ pub trait TensorAlgebra {
     /// shape. The new shape must have the same size as the original one.
     fn tensor_reshape(&self, elem: Self::Elem, shape: Shape) -> Self::Elem;
 
+    /// Returns the sub-tensor where the coordinates given as `Some` values
+    /// are fixed, and the ones given as `None` are kept in their original
+    /// order.
+    fn tensor_select(&self, elem: Self::Elem, coords: &[Option<usize>]) -> Self::Elem;
+
+    /// Returns the hyperplane of the tensor where the coordinate of the given
+    /// axis is fixed to the given index.
+    fn tensor_slice(&self, elem: Self::Elem, axis: usize, index: usize) -> Self::Elem;
+
+    /// Concatenates the given tensors of the same shape along a new leading
+    /// dimension. The list of tensors must not be empty.
+    fn tensor_stack(&self, elems: Vec<Self::Elem>) -> Self::Elem;
+
     /// Returns a new tensor whose elements are all negated of the original.
     fn tensor_not(&mut self, elem: Self::Elem) -> Self::Elem;
 
@@ -341,6 +410,11 @@ pub trait TensorAlgebra {
     /// original elements.
     fn tensor_imp(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
 
+    /// Returns a new tensor whose elements are selected from the first or
+    /// second tensor depending on the condition, which is either a tensor of
+    /// the same shape or a scalar of shape `[]` applied to all elements.
+    fn tensor_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
     /// Returns a new tensor with the first dimension removed where the result
     /// is the conjunction of the elements.
     fn tensor_all(&mut self, elem: Self::Elem) -> Self::Elem;
@@ -360,6 +434,222 @@ pub trait TensorAlgebra {
     /// Returns a new tensor with the first dimension removed where the result
     /// is the at most one set predicate.
     fn tensor_amo(&mut self, elem: Self::Elem) -> Self::Elem;
+
+    /// Returns the boolean matrix product of tensors of shapes `[a, b, ..]`
+    /// and `[b, c, ..]` as a tensor of shape `[a, c, ..]`, which is the
+    /// composition of binary relations batched over the trailing dimensions.
+    fn tensor_matmul(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor whose elements are the sums of the numbers of
+    /// the original tensors, where the first dimension is the significance
+    /// of the bits starting with the least significant one. The result
+    /// wraps around and has the same shape.
+    fn tensor_num_add(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor whose elements are the differences of the
+    /// numbers of the original tensors, in two's complement.
+    fn tensor_num_sub(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor whose elements are the negatives of the numbers
+    /// of the original tensor, in two's complement.
+    fn tensor_num_neg(&mut self, elem: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor whose elements are the products of the numbers
+    /// of the original tensors, which is the same for signed and unsigned
+    /// numbers in two's complement.
+    fn tensor_num_mul(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor with the first dimension removed where the result
+    /// is the equality of the numbers.
+    fn tensor_num_equ(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor with the first dimension removed where the result
+    /// is the less than or equal relation of the unsigned numbers.
+    fn tensor_num_leq(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor with the first dimension removed where the result
+    /// is the less than relation of the unsigned numbers.
+    fn tensor_num_ltn(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor with the first dimension removed where the result
+    /// is the less than or equal relation of the signed numbers in two's
+    /// complement, where the last bit is the sign.
+    fn tensor_int_leq(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns a new tensor with the first dimension removed where the result
+    /// is the less than relation of the signed numbers in two's complement.
+    fn tensor_int_ltn(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem;
+
+    /// Returns the contraction of the given tensors over the AND/OR semiring
+    /// specified in the Einstein summation notation, like `ij,jk->ik` for the
+    /// relational product. Every letter names a coordinate, repeated letters
+    /// within a tensor select the diagonal, and the letters missing from the
+    /// output are existentially quantified. The tensors are multiplied in a
+    /// greedy order keeping the intermediate results small, and coordinates
+    /// are folded as soon as no remaining tensor refers to them.
+    fn tensor_einsum(&mut self, spec: &str, elems: Vec<Self::Elem>) -> Self::Elem {
+        let (inputs, output) = spec.split_once("->").expect("missing arrow");
+        let output: Vec<char> = output.trim().chars().collect();
+        assert_eq!(
+            elems.len(),
+            inputs.split(',').count(),
+            "operand count does not match spec"
+        );
+        let mut operands: Vec<(Vec<char>, Self::Elem)> = inputs
+            .split(',')
+            .map(|s| s.trim().chars().collect())
+            .zip(elems)
+            .collect();
+
+        let mut dims: Vec<(char, usize)> = Vec::new();
+        for (indices, elem) in operands.iter() {
+            let shape = self.shape(elem);
+            assert_eq!(indices.len(), shape.len());
+            for (&c, &d) in indices.iter().zip(shape.dims()) {
+                match dims.iter().find(|(e, _)| *e == c) {
+                    Some(&(_, e)) => assert_eq!(d, e, "inconsistent dimension"),
+                    None => dims.push((c, d)),
+                }
+            }
+        }
+        let dim = |c: char| dims.iter().find(|(e, _)| *e == c).unwrap().1;
+        for (i, c) in output.iter().enumerate() {
+            assert!(!output[..i].contains(c) && dims.iter().any(|(e, _)| e == c));
+        }
+
+        let mut current: Option<(Vec<char>, Self::Elem)> = None;
+        while !operands.is_empty() {
+            let live = current.as_ref().map_or(&[][..], |(i, _)| i.as_slice());
+            let cost = |indices: &[char]| {
+                let mut union = live.to_vec();
+                union.extend(indices.iter().filter(|c| !live.contains(c)));
+                union.sort_unstable();
+                union.dedup();
+                union.into_iter().map(dim).product::<usize>()
+            };
+            let pos = (0..operands.len())
+                .min_by_key(|&i| cost(&operands[i].0))
+                .unwrap();
+            let (indices, elem) = operands.remove(pos);
+
+            let mut elim: Vec<char> = Vec::new();
+            let mut keep: Vec<char> = Vec::new();
+            for &c in live.iter().chain(indices.iter()) {
+                if elim.contains(&c) || keep.contains(&c) {
+                    continue;
+                }
+                let used = output.contains(&c) || operands.iter().any(|(i, _)| i.contains(&c));
+                if used {
+                    keep.push(c);
+                } else {
+                    elim.push(c);
+                }
+            }
+            let order: Vec<char> = elim.iter().chain(keep.iter()).copied().collect();
+            let shape = Shape::new(order.iter().map(|&c| dim(c)).collect());
+            let lift = |this: &Self, indices: &[char], elem: Self::Elem| {
+                let mapping: Vec<usize> = indices
+                    .iter()
+                    .map(|c| order.iter().position(|d| d == c).unwrap())
+                    .collect();
+                this.tensor_polymer(elem, shape.clone(), &mapping)
+            };
+
+            let mut elem = lift(self, &indices, elem);
+            if let Some((indices, prev)) = current.take() {
+                let prev = lift(self, &indices, prev);
+                elem = self.tensor_and(prev, elem);
+            }
+            if !elim.is_empty() {
+                let size = elim.iter().map(|&c| dim(c)).product();
+                let shape = Shape::new(keep.iter().map(|&c| dim(c)).collect());
+                elem = self.tensor_reshape(elem, shape.join(&[size]));
+                elem = self.tensor_any(elem);
+            }
+            current = Some((keep, elem));
+        }
+
+        let (indices, elem) = current.expect("no tensors");
+        let mapping: Vec<usize> = indices
+            .iter()
+            .map(|c| output.iter().position(|d| d == c).unwrap())
+            .collect();
+        let shape = Shape::new(output.iter().map(|&c| dim(c)).collect());
+        self.tensor_polymer(elem, shape, &mapping)
+    }
+
+    /// Returns the least fixed point of the given monotone operation on
+    /// tensors of the given shape, by iterating it from the all false tensor
+    /// until it stops changing. The number of iterations is bounded by the
+    /// size of the shape, which suffices for every valuation of the solver
+    /// variables, and the unrolled iterates share the already created
+    /// literals.
+    fn tensor_fixed_point<OP>(&mut self, shape: Shape, mut op: OP) -> Self::Elem
+    where
+        Self::Elem: PartialEq,
+        OP: FnMut(&mut Self, Self::Elem) -> Self::Elem,
+    {
+        let mut elem = self.tensor_create(shape.clone(), |_| false);
+        for _ in 0..shape.size() {
+            let next = op(self, elem.clone());
+            assert_eq!(self.shape(&next), &shape);
+            if next == elem {
+                break;
+            }
+            elem = next;
+        }
+        elem
+    }
+}
+
+/// Returns the numbers along the first dimension of the tensor, and flips
+/// their last bits if signed is set, which maps signed numbers to unsigned
+/// ones preserving the order.
+fn tensor_numbers<ALG>(alg: &mut ALG, elem: Tensor<ALG::Elem>, signed: bool) -> Vec<Vec<ALG::Elem>>
+where
+    ALG: BooleanLogic,
+    ALG::Elem: TensorElem,
+{
+    let (head, _) = elem.shape.split1();
+    elem.elems
+        .split(head)
+        .into_iter()
+        .map(|v| {
+            let mut num: Vec<ALG::Elem> = v.copy_iter().collect();
+            if let (true, Some(last)) = (signed, num.last_mut()) {
+                *last = alg.bool_not(*last);
+            }
+            num
+        })
+        .collect()
+}
+
+/// Applies the operation on the pairs of numbers of the tensors, and
+/// returns the results along the first dimension of a tensor of the given
+/// shape.
+fn tensor_num_map<ALG, OP>(
+    alg: &mut ALG,
+    elem1: Tensor<ALG::Elem>,
+    elem2: Tensor<ALG::Elem>,
+    signed: bool,
+    shape: Shape,
+    mut op: OP,
+) -> Tensor<ALG::Elem>
+where
+    ALG: BooleanLogic,
+    ALG::Elem: TensorElem,
+    OP: FnMut(&mut ALG, &[ALG::Elem], &[ALG::Elem]) -> Vec<ALG::Elem>,
+{
+    assert_eq!(elem1.shape, elem2.shape);
+    let nums1 = tensor_numbers(alg, elem1, signed);
+    let nums2 = tensor_numbers(alg, elem2, signed);
+    let mut elems: <ALG::Elem as TensorElem>::Vec = Vector::with_capacity(shape.size());
+    for (a, b) in nums1.iter().zip(nums2.iter()) {
+        for c in op(alg, a, b) {
+            elems.push(c);
+        }
+    }
+    Tensor::new(shape, elems)
 }
 
 impl<ALG> TensorAlgebra for ALG
@@ -393,6 +683,18 @@ where
         elem.reshape(shape)
     }
 
+    fn tensor_select(&self, elem: Self::Elem, coords: &[Option<usize>]) -> Self::Elem {
+        elem.select(coords)
+    }
+
+    fn tensor_slice(&self, elem: Self::Elem, axis: usize, index: usize) -> Self::Elem {
+        elem.slice(axis, index)
+    }
+
+    fn tensor_stack(&self, elems: Vec<Self::Elem>) -> Self::Elem {
+        Tensor::stack(&elems)
+    }
+
     fn tensor_not(&mut self, elem: Self::Elem) -> Self::Elem {
         let elems = elem.elems.copy_iter().map(|b| self.bool_not(b)).collect();
         Tensor::new(elem.shape, elems)
@@ -453,6 +755,23 @@ where
         Tensor::new(elem1.shape, elems)
     }
 
+    fn tensor_ite(&mut self, cond: Self::Elem, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        assert_eq!(elem1.shape, elem2.shape);
+        let cond = if cond.shape.is_empty() {
+            cond.polymer(elem1.shape.clone(), &[])
+        } else {
+            cond
+        };
+        assert_eq!(cond.shape, elem1.shape);
+        let elems = cond
+            .elems
+            .copy_iter()
+            .zip(elem1.elems.copy_iter().zip(elem2.elems.copy_iter()))
+            .map(|(c, (a, b))| self.bool_ite(c, a, b))
+            .collect();
+        Tensor::new(elem1.shape, elems)
+    }
+
     fn tensor_all(&mut self, elem: Self::Elem) -> Self::Elem {
         let (head, shape) = elem.shape.split1();
         let elems = elem
@@ -507,6 +826,109 @@ where
             .collect();
         Tensor::new(shape, elems)
     }
+
+    fn tensor_matmul(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (a, b, rest) = elem1.shape.split2();
+        let (b2, c, rest2) = elem2.shape.split2();
+        assert_eq!(b, b2);
+        assert_eq!(rest, rest2);
+        let mut elems: <ALG::Elem as TensorElem>::Vec = Vector::with_capacity(a * c * rest.size());
+
+        // concrete tensors are multiplied on packed rows
+        if let (Some(bits1), Some(bits2)) = (
+            self.bool_lower_vec(elem1.elems.copy_iter()),
+            self.bool_lower_vec(elem2.elems.copy_iter()),
+        ) {
+            for r in 0..rest.size() {
+                let mat1 =
+                    BitMatrix::from_slice(b, a, bits1.slice().range(a * b * r, a * b * (r + 1)));
+                let mat2 =
+                    BitMatrix::from_slice(c, b, bits2.slice().range(b * c * r, b * c * (r + 1)));
+                let prod = mat2.multiply(&mat1);
+                elems.extend(prod.to_bitvec().copy_iter().map(|v| self.bool_lift(v)));
+            }
+            return Tensor::new(rest.join(&[a, c]), elems);
+        }
+
+        let mut terms = Vec::with_capacity(b);
+        for r in 0..rest.size() {
+            for k in 0..c {
+                for i in 0..a {
+                    terms.clear();
+                    for j in 0..b {
+                        let x = elem1.elems.get(i + a * (j + b * r));
+                        let y = elem2.elems.get(j + b * (k + c * r));
+                        terms.push(self.bool_and(x, y));
+                    }
+                    elems.push(self.bool_fold_any(terms.iter().copied()));
+                }
+            }
+        }
+        Tensor::new(rest.join(&[a, c]), elems)
+    }
+
+    fn tensor_num_add(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let shape = elem1.shape.clone();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            alg.bin_add(a, b)
+        })
+    }
+
+    fn tensor_num_sub(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let shape = elem1.shape.clone();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            alg.bin_sub(a, b)
+        })
+    }
+
+    fn tensor_num_neg(&mut self, elem: Self::Elem) -> Self::Elem {
+        let shape = elem.shape.clone();
+        tensor_num_map(self, elem.clone(), elem, false, shape, |alg, a, _| {
+            alg.bin_neg(a)
+        })
+    }
+
+    fn tensor_num_mul(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let shape = elem1.shape.clone();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            alg.bin_mul(a, b)
+        })
+    }
+
+    fn tensor_num_equ(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (_, shape) = elem1.shape.split1();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            vec![alg.bin_equ(a, b)]
+        })
+    }
+
+    fn tensor_num_leq(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (_, shape) = elem1.shape.split1();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            vec![alg.bin_leq(a, b)]
+        })
+    }
+
+    fn tensor_num_ltn(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (_, shape) = elem1.shape.split1();
+        tensor_num_map(self, elem1, elem2, false, shape, |alg, a, b| {
+            vec![alg.bin_ltn(a, b)]
+        })
+    }
+
+    fn tensor_int_leq(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (_, shape) = elem1.shape.split1();
+        tensor_num_map(self, elem1, elem2, true, shape, |alg, a, b| {
+            vec![alg.bin_leq(a, b)]
+        })
+    }
+
+    fn tensor_int_ltn(&mut self, elem1: Self::Elem, elem2: Self::Elem) -> Self::Elem {
+        let (_, shape) = elem1.shape.split1();
+        tensor_num_map(self, elem1, elem2, true, shape, |alg, a, b| {
+            vec![alg.bin_ltn(a, b)]
+        })
+    }
 }
 
 /// The trait for solving tensor algebra problems.
@@ -639,7 +1061,7 @@ where
 mod tests {
     use std::iter;
 
-    use super::super::Logic;
+    use super::super::{Logic, Solver};
     use super::*;
 
     #[test]
@@ -662,6 +1084,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn slicing() {
+        let alg = Logic();
+        let tensor: Tensor<usize> =
+            Tensor::create(Shape::new(vec![2, 3, 4]), |c| c[0] + 10 * c[1] + 100 * c[2]);
+        let plane = tensor.slice(1, 2);
+        assert_eq!(plane.shape, Shape::new(vec![2, 4]));
+        let line = tensor.select(&[Some(1), None, Some(3)]);
+        assert_eq!(line.shape, Shape::new(vec![3]));
+        for i in 0..2 {
+            for k in 0..4 {
+                assert_eq!(plane.very_slow_get(&[i, k]), i + 20 + 100 * k);
+            }
+        }
+        for j in 0..3 {
+            assert_eq!(line.very_slow_get(&[j]), 301 + 10 * j);
+        }
+        assert_eq!(tensor.select(&[Some(1), Some(2), Some(3)]).scalar(), 321);
+
+        let planes: Vec<_> = (0..4).map(|k| tensor.slice(2, k)).collect();
+        let stacked = Tensor::stack(&planes);
+        assert_eq!(
+            stacked,
+            tensor.polymer(Shape::new(vec![4, 2, 3]), &[1, 2, 0])
+        );
+
+        let t1 = Tensor::create(Shape::new(vec![2, 2]), |c| c[0] <= c[1]);
+        let t2 = alg.tensor_slice(t1.clone(), 0, 1);
+        assert_eq!(t2, Tensor::create(Shape::new(vec![2]), |c| c[0] == 1));
+        let t3 = alg.tensor_stack(vec![t2.clone(), t1.slice(1, 1)]);
+        assert!(t3.very_slow_get(&[1, 0]));
+        assert!(!t3.very_slow_get(&[0, 0]));
+        assert_eq!(alg.tensor_select(t3, &[Some(1), None]), t1.slice(1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "operand count does not match spec")]
+    fn einsum_extra_operand() {
+        let mut alg = Logic();
+        let t1 = Tensor::create(Shape::new(vec![2, 2]), |c| c[0] == c[1]);
+        alg.tensor_einsum("ij->ji", vec![t1.clone(), t1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slicing_invalid_axis() {
+        let tensor: Tensor<bool> = Tensor::create(Shape::new(vec![2, 2]), |_| true);
+        tensor.slice(2, 0);
+    }
+
     #[test]
     fn getset() {
         let mut alg = Logic();
@@ -690,6 +1162,129 @@ mod tests {
         assert_eq!(t3, t4);
     }
 
+    #[test]
+    fn numbers() {
+        let mut alg = Logic();
+        let width = 4;
+        let value = |t: &Tensor<bool>, i: usize| {
+            (0..width)
+                .map(|b| (t.very_slow_get(&[b, i]) as usize) << b)
+                .sum::<usize>()
+        };
+        let t1 = Tensor::create(Shape::new(vec![width, 16]), |c| (c[1] >> c[0]) & 1 != 0);
+        let t2 = Tensor::create(Shape::new(vec![width, 16]), |c| {
+            (((c[1] * 7 + 3) % 16) >> c[0]) & 1 != 0
+        });
+
+        let sum = alg.tensor_num_add(t1.clone(), t2.clone());
+        let diff = alg.tensor_num_sub(t1.clone(), t2.clone());
+        let neg = alg.tensor_num_neg(t1.clone());
+        let prod = alg.tensor_num_mul(t1.clone(), t2.clone());
+        let equ = alg.tensor_num_equ(t1.clone(), t2.clone());
+        let leq = alg.tensor_num_leq(t1.clone(), t2.clone());
+        let ltn = alg.tensor_num_ltn(t1.clone(), t2.clone());
+        let int_leq = alg.tensor_int_leq(t1.clone(), t2.clone());
+        let int_ltn = alg.tensor_int_ltn(t1, t2);
+        assert_eq!(leq.shape, Shape::new(vec![16]));
+
+        let signed = |a: usize| if a >= 8 { a as isize - 16 } else { a as isize };
+        for a in 0..16 {
+            let b = (a * 7 + 3) % 16;
+            assert_eq!(value(&sum, a), (a + b) % 16);
+            assert_eq!(value(&diff, a), (a + 16 - b) % 16);
+            assert_eq!(value(&neg, a), (16 - a) % 16);
+            assert_eq!(value(&prod, a), (a * b) % 16);
+            assert_eq!(equ.very_slow_get(&[a]), a == b);
+            assert_eq!(leq.very_slow_get(&[a]), a <= b);
+            assert_eq!(ltn.very_slow_get(&[a]), a < b);
+            assert_eq!(int_leq.very_slow_get(&[a]), signed(a) <= signed(b));
+            assert_eq!(int_ltn.very_slow_get(&[a]), signed(a) < signed(b));
+        }
+    }
+
+    #[test]
+    fn einsum() {
+        let mut alg = Logic();
+        let t1 = Tensor::create(Shape::new(vec![3, 4]), |c| (c[0] + 2 * c[1]) % 3 == 0);
+        let t2 = Tensor::create(Shape::new(vec![4, 2]), |c| c[0] == c[1] + 1);
+        let t3 = Tensor::create(Shape::new(vec![2, 5]), |c| c[0] < c[1]);
+
+        let t4 = alg.tensor_einsum("ij,jk->ik", vec![t1.clone(), t2.clone()]);
+        assert_eq!(t4.shape, Shape::new(vec![3, 2]));
+        let t5 = alg.tensor_einsum("ij,jk,kl->li", vec![t1.clone(), t2.clone(), t3.clone()]);
+        assert_eq!(t5.shape, Shape::new(vec![5, 3]));
+        for i in 0..3 {
+            for k in 0..2 {
+                let v = (0..4).any(|j| t1.very_slow_get(&[i, j]) && t2.very_slow_get(&[j, k]));
+                assert_eq!(t4.very_slow_get(&[i, k]), v);
+            }
+            for l in 0..5 {
+                let v = (0..2).any(|k| t4.very_slow_get(&[i, k]) && t3.very_slow_get(&[k, l]));
+                assert_eq!(t5.very_slow_get(&[l, i]), v);
+            }
+        }
+
+        let t6 = Tensor::create(Shape::new(vec![3, 3]), |c| c[0] == c[1] && c[0] != 1);
+        let t7 = alg.tensor_einsum("ii->i", vec![t6.clone()]);
+        assert_eq!(t7, Tensor::create(Shape::new(vec![3]), |c| c[0] != 1));
+        let t8 = alg.tensor_not(t6.clone());
+        let t8 = alg.tensor_einsum("ij,ij->", vec![t6, t8]);
+        assert!(!t8.scalar());
+        let t9 = alg.tensor_einsum("ij->ji", vec![t1.clone()]);
+        assert_eq!(t9, t1.polymer(Shape::new(vec![4, 3]), &[1, 0]));
+    }
+
+    #[test]
+    fn matmul() {
+        let mut alg = Logic();
+        let t1 = Tensor::create(Shape::new(vec![3, 4, 2]), |c| (c[0] + c[1] * c[2]) % 3 == 1);
+        let t2 = Tensor::create(Shape::new(vec![4, 5, 2]), |c| {
+            (c[0] + 2 * c[1] + c[2]) % 4 == 0
+        });
+        let t3 = alg.tensor_matmul(t1.clone(), t2.clone());
+        assert_eq!(t3.shape, Shape::new(vec![3, 5, 2]));
+        assert_eq!(t3, alg.tensor_einsum("ijr,jkr->ikr", vec![t1, t2]));
+    }
+
+    #[test]
+    fn fixed_point() {
+        let mut alg = Logic();
+        let path = Tensor::create(Shape::new(vec![4, 4]), |c| c[0] + 1 == c[1]);
+        let closure = alg.tensor_fixed_point(path.shape.clone(), |alg, elem| {
+            let elem = alg.tensor_matmul(elem, path.clone());
+            alg.tensor_or(elem, path.clone())
+        });
+        assert_eq!(closure, Tensor::create(path.shape.clone(), |c| c[0] < c[1]));
+
+        let mut sol = Solver::new("");
+        let shape = Shape::new(vec![3, 3]);
+        let rel = sol.tensor_add_variable(shape.clone());
+        let closure = sol.tensor_fixed_point(shape, |sol, elem| {
+            let elem = sol.tensor_matmul(elem, rel.clone());
+            sol.tensor_or(elem, rel.clone())
+        });
+        let test = sol.tensor_imp(closure, rel.clone());
+        let test = sol.tensor_all(test);
+        let test = sol.tensor_all(test);
+        sol.tensor_add_clause1(test);
+        assert_eq!(sol.tensor_find_num_models(&[rel]), 171);
+    }
+
+    #[test]
+    fn if_then_else() {
+        let mut alg = Logic();
+        let shape = Shape::new(vec![2, 3]);
+        let t1 = Tensor::create(shape.clone(), |c| c[0] == 0);
+        let t2 = Tensor::create(shape.clone(), |c| c[1] == 2);
+        let t3 = Tensor::create(shape.clone(), |c| c[0] == c[1]);
+        let t4 = alg.tensor_ite(t1.clone(), t2.clone(), t3.clone());
+        let t5 = Tensor::create(shape, |c| if c[0] == 0 { c[1] == 2 } else { c[0] == c[1] });
+        assert_eq!(t4, t5);
+
+        let cond = alg.tensor_create(Shape::new(vec![]), |_| false);
+        assert_eq!(alg.tensor_ite(cond, t2, t3.clone()), t3);
+    }
+
     #[test]
     fn fold() {
         let mut alg = Logic();
@@ -0,0 +1,228 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Packed boolean matrices with word parallel kernels.
+
+use super::{BitSlice, BitVec, Slice, Vector};
+
+/// Transposes the 64 by 64 bit matrix in place, where bit `j` of word `i`
+/// is the entry in row `i` and column `j`.
+pub fn transpose64(block: &mut [u64; 64]) {
+    let mut width = 32;
+    let mut mask: u64 = 0x0000_0000_ffff_ffff;
+    while width != 0 {
+        let mut k = 0;
+        while k < 64 {
+            let t = ((block[k] >> width) ^ block[k + width]) & mask;
+            block[k] ^= t << width;
+            block[k + width] ^= t;
+            k = (k + width + 1) & !width;
+        }
+        width >>= 1;
+        mask ^= mask << width;
+    }
+}
+
+/// A boolean matrix whose rows are packed into 64-bit words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words: usize,
+    data: Vec<u64>,
+}
+
+impl BitMatrix {
+    /// Creates a new zero matrix of the given dimensions.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words = (cols + 63) / 64;
+        Self {
+            rows,
+            cols,
+            words,
+            data: vec![0; rows * words],
+        }
+    }
+
+    /// Creates a matrix from the given bits, where the entry in row `i` and
+    /// column `j` is stored at index `j + i * cols`.
+    pub fn from_slice(rows: usize, cols: usize, elem: BitSlice<'_>) -> Self {
+        assert_eq!(elem.len(), rows * cols);
+        let mut result = Self::new(rows, cols);
+        for (index, b) in elem.copy_iter().enumerate() {
+            if b {
+                result.set(index / cols, index % cols, true);
+            }
+        }
+        result
+    }
+
+    /// Returns the bits of the matrix in the order of `from_slice`.
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut result: BitVec = Vector::with_capacity(self.rows * self.cols);
+        for i in 0..self.rows {
+            result.extend((0..self.cols).map(|j| self.get(i, j)));
+        }
+        result
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the entry in the given row and column.
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        assert!(row < self.rows && col < self.cols);
+        (self.data[row * self.words + col / 64] >> (col % 64)) & 1 != 0
+    }
+
+    /// Sets the entry in the given row and column.
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        assert!(row < self.rows && col < self.cols);
+        let word = &mut self.data[row * self.words + col / 64];
+        let bit = 1 << (col % 64);
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    /// Returns the packed words of the given row.
+    pub fn row(&self, row: usize) -> &[u64] {
+        &self.data[row * self.words..(row + 1) * self.words]
+    }
+
+    /// Returns the transposed matrix, which is computed by transposing the
+    /// 64 by 64 blocks of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut result = Self::new(self.cols, self.rows);
+        let mut block = [0u64; 64];
+        for bi in 0..(self.rows + 63) / 64 {
+            for bj in 0..self.words {
+                for (k, word) in block.iter_mut().enumerate() {
+                    let row = bi * 64 + k;
+                    *word = if row < self.rows {
+                        self.data[row * self.words + bj]
+                    } else {
+                        0
+                    };
+                }
+                transpose64(&mut block);
+                for (k, &word) in block.iter().enumerate() {
+                    let row = bj * 64 + k;
+                    if row < self.cols {
+                        result.data[row * result.words + bi] = word;
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the boolean product of the two matrices, where row `i` of
+    /// the result is the union of the rows of `other` selected by row `i`
+    /// of this matrix.
+    pub fn multiply(&self, other: &Self) -> Self {
+        assert_eq!(self.cols, other.rows);
+        let mut result = Self::new(self.rows, other.cols);
+        for i in 0..self.rows {
+            let target = i * result.words;
+            for k in 0..self.cols {
+                if self.get(i, k) {
+                    for w in 0..other.words {
+                        result.data[target + w] |= other.data[k * other.words + w];
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Replaces this square matrix with its transitive closure using the
+    /// algorithm of Warshall on packed rows.
+    pub fn transitive_closure(&mut self) {
+        assert_eq!(self.rows, self.cols);
+        let words = self.words;
+        for k in 0..self.rows {
+            for i in 0..self.rows {
+                if i != k && self.get(i, k) {
+                    for w in 0..words {
+                        let value = self.data[k * words + w];
+                        self.data[i * words + w] |= value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rows: usize, cols: usize, seed: usize) -> BitMatrix {
+        let elem: BitVec = (0..rows * cols)
+            .map(|i| ((i * 0x9e37 + seed * 31) >> 3) & 3 == 0)
+            .collect();
+        BitMatrix::from_slice(rows, cols, elem.slice())
+    }
+
+    #[test]
+    fn transpose() {
+        for &(rows, cols) in [(3, 5), (64, 64), (70, 130)].iter() {
+            let mat = sample(rows, cols, 1);
+            let tra = mat.transpose();
+            for i in 0..rows {
+                for j in 0..cols {
+                    assert_eq!(mat.get(i, j), tra.get(j, i));
+                }
+            }
+            assert_eq!(tra.transpose(), mat);
+        }
+    }
+
+    #[test]
+    fn multiply() {
+        let mat0 = sample(7, 70, 2);
+        let mat1 = sample(70, 9, 3);
+        let prod = mat0.multiply(&mat1);
+        for i in 0..7 {
+            for j in 0..9 {
+                let value = (0..70).any(|k| mat0.get(i, k) && mat1.get(k, j));
+                assert_eq!(prod.get(i, j), value);
+            }
+        }
+
+        let mut mat = BitMatrix::new(5, 5);
+        for i in 0..4 {
+            mat.set(i + 1, i, true);
+        }
+        mat.transitive_closure();
+        for i in 0..5 {
+            for j in 0..5 {
+                assert_eq!(mat.get(i, j), j < i);
+            }
+        }
+    }
+}
@@ -327,9 +327,8 @@ impl ExactSizeIterator for BitSlice<'_> {}
 impl DoubleEndedIterator for BitSlice<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start < self.end {
-            let elem = self.vec.get(self.start);
             self.end -= 1;
-            Some(elem)
+            Some(self.vec.get(self.end))
         } else {
             None
         }
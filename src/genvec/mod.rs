@@ -23,7 +23,18 @@ pub use traits::{Slice, Vector};
 mod bitvec;
 pub use bitvec::{BitSlice, BitVec};
 
+mod bitmatrix;
+pub use bitmatrix::{transpose64, BitMatrix};
+
 mod vec;
 
+mod pool;
+pub use pool::VectorPool;
+
+#[cfg(feature = "smallvec")]
+mod small;
+#[cfg(feature = "smallvec")]
+pub use small::{SmallSlice, SmallVec};
+
 #[cfg(test)]
 mod tests;
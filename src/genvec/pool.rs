@@ -0,0 +1,75 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::Vector;
+
+/// A bounded pool of empty vectors whose storage can be reused for
+/// temporary vectors, which avoids repeated allocations in inner loops.
+#[derive(Debug)]
+pub struct VectorPool<VEC>
+where
+    VEC: Vector,
+{
+    free: Vec<VEC>,
+    limit: usize,
+}
+
+impl<VEC> VectorPool<VEC>
+where
+    VEC: Vector,
+{
+    /// Creates a new pool that keeps at most the given number of vectors.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            free: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Returns the number of vectors available for reuse.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns true if no vector is available for reuse.
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// Returns an empty vector, reusing the storage of a returned one if
+    /// possible.
+    pub fn take(&mut self) -> VEC {
+        self.free.pop().unwrap_or_else(|| Vector::with_capacity(0))
+    }
+
+    /// Returns the vector to the pool, or drops it if the pool is full.
+    pub fn give(&mut self, mut vec: VEC) {
+        if self.free.len() < self.limit {
+            vec.clear();
+            self.free.push(vec);
+        }
+    }
+}
+
+impl<VEC> Default for VectorPool<VEC>
+where
+    VEC: Vector,
+{
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
@@ -0,0 +1,178 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Vectors that store a small number of elements inline, so short element
+//! vectors do not allocate on the heap.
+
+use std::marker::PhantomData;
+
+pub use smallvec::{Array, SmallVec};
+
+use super::{Slice, Vector};
+
+impl<A> Vector for SmallVec<A>
+where
+    A: Array,
+    A::Item: Copy,
+{
+    fn new() -> Self {
+        Self::new()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    fn with_values(len: usize, elem: Self::Item) -> Self {
+        Self::from_elem(elem, len)
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    fn truncate(&mut self, new_len: usize) {
+        assert!(new_len <= SmallVec::len(self));
+        self.truncate(new_len);
+    }
+
+    fn resize(&mut self, new_len: usize, elem: Self::Item) {
+        self.resize(new_len, elem);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    fn push(&mut self, elem: Self::Item) {
+        self.push(elem);
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other)
+    }
+
+    fn get(&self, index: usize) -> Self::Item {
+        self[index]
+    }
+
+    unsafe fn get_unchecked(&self, index: usize) -> Self::Item {
+        *<[A::Item]>::get_unchecked(self, index)
+    }
+
+    fn set(&mut self, index: usize, elem: Self::Item) {
+        self[index] = elem;
+    }
+
+    unsafe fn set_unchecked(&mut self, index: usize, elem: Self::Item) {
+        *<[A::Item]>::get_unchecked_mut(self, index) = elem;
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    type Slice<'a>
+        = SmallSlice<'a, A>
+    where
+        Self: 'a;
+
+    fn slice(&self) -> Self::Slice<'_> {
+        SmallSlice {
+            slice: self,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// A slice of a small vector, which is a regular slice that remembers the
+/// inline capacity of the vector type.
+pub struct SmallSlice<'a, A>
+where
+    A: Array,
+{
+    slice: &'a [A::Item],
+    phantom: PhantomData<A>,
+}
+
+impl<'a, A> SmallSlice<'a, A>
+where
+    A: Array,
+{
+    /// Returns the underlying regular slice.
+    pub fn as_slice(self) -> &'a [A::Item] {
+        self.slice
+    }
+}
+
+impl<A> Clone for SmallSlice<'_, A>
+where
+    A: Array,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for SmallSlice<'_, A> where A: Array {}
+
+impl<'a, A> Slice<'a> for SmallSlice<'a, A>
+where
+    A: Array,
+    A::Item: Copy,
+{
+    type Item = A::Item;
+
+    type Iter = std::iter::Copied<std::slice::Iter<'a, A::Item>>;
+
+    type Vector = SmallVec<A>;
+
+    fn len(self) -> usize {
+        self.slice.len()
+    }
+
+    fn get(self, index: usize) -> Self::Item {
+        self.slice[index]
+    }
+
+    unsafe fn get_unchecked(self, index: usize) -> Self::Item {
+        *self.slice.get_unchecked(index)
+    }
+
+    fn copy_iter(self) -> Self::Iter {
+        self.slice.iter().copied()
+    }
+
+    fn range(self, start: usize, end: usize) -> Self {
+        SmallSlice {
+            slice: &self.slice[start..end],
+            phantom: PhantomData,
+        }
+    }
+}
@@ -15,7 +15,7 @@
 * along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use super::{BitVec, Vector};
+use super::{BitVec, Vector, VectorPool};
 
 #[test]
 fn resize() {
@@ -80,6 +80,16 @@ fn iters() {
     assert_eq!(iter.next(), Some(true));
     assert_eq!(iter.next(), None);
 
+    let e1 = [true, true, false, false, true, false];
+    let v1: BitVec = e1.iter().copied().collect();
+    let rev: Vec<bool> = v1.copy_iter().rev().collect();
+    assert!(rev.iter().eq(e1.iter().rev()));
+    let mut iter = v1.copy_iter();
+    assert_eq!(iter.next(), Some(true));
+    assert_eq!(iter.next_back(), Some(false));
+    assert_eq!(iter.next_back(), Some(true));
+    assert_eq!(iter.len(), 3);
+
     let e1 = [true, false];
     let v1: BitVec = e1.iter().copied().collect();
     let mut v2: BitVec = Vector::new();
@@ -106,3 +116,32 @@ fn iters() {
         assert_eq!(v2.get(j), b4);
     }
 }
+
+#[test]
+fn pool() {
+    let mut pool: VectorPool<Vec<bool>> = VectorPool::new(1);
+    let mut v1 = pool.take();
+    v1.resize(100, true);
+    let v2 = pool.take();
+    pool.give(v1);
+    pool.give(v2);
+    assert_eq!(pool.len(), 1);
+    let v3 = pool.take();
+    assert!(v3.is_empty() && v3.capacity() >= 100);
+    assert!(pool.is_empty());
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn small() {
+    use super::{Slice, SmallVec};
+
+    let mut v1: SmallVec<[u32; 4]> = Vector::with_values(3, 7);
+    v1.push(8);
+    assert!(!v1.spilled());
+    v1.resize(6, 9);
+    assert!(v1.spilled());
+    let s = v1.slice().range(2, 5);
+    assert_eq!(s.copy_iter().collect::<Vec<_>>(), vec![7, 8, 9]);
+    assert_eq!(s.as_slice(), &[7, 8, 9]);
+}
@@ -0,0 +1,83 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A SAT based discrete mathematics and universal algebra calculator.
+//!
+//! The concrete logic, the bit vectors and the domains evaluated over them
+//! need no SAT backend, so the library can be built with
+//! `--no-default-features`, for example for a wasm target with
+//! `cargo rustc --lib --crate-type cdylib --target wasm32-unknown-unknown`.
+//! The solver backends are enabled by the `native` feature or one by one.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod alg;
+pub mod core;
+pub mod genvec;
+pub mod math;
+pub mod prelude;
+
+/// The old location of the boolean logic, which now lives in `core`.
+#[deprecated(since = "0.1.7", note = "use uasat::prelude or uasat::core instead")]
+pub mod boolean {
+    pub use crate::core::{BooleanLogic, BooleanSolver, Logic, OnehotEncoding, Solver};
+}
+
+/// The old location of the tensor algebra, which now lives in `core`.
+#[deprecated(since = "0.1.7", note = "use uasat::core instead")]
+pub mod tensor {
+    pub use crate::core::{Shape, Tensor, TensorAlgebra, TensorSolver};
+}
+
+/// The old location of the raw SAT solver interface, which now lives in
+/// `core` under the name `SatInterface`.
+#[deprecated(since = "0.1.7", note = "use uasat::core instead")]
+pub mod solver {
+    pub use crate::core::{create_solver, Literal, SatInterface};
+
+    /// The old name of the raw SAT solver interface.
+    pub use crate::core::SatInterface as Solver;
+
+    /// Adapter for the old constructor of the default solver, which is
+    /// `create_solver` with an empty name.
+    pub fn create_default() -> Box<dyn SatInterface> {
+        create_solver("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_shims() {
+        use crate::boolean::BooleanSolver as _;
+
+        let mut sat = crate::solver::create_default();
+        let a = sat.add_variable();
+        sat.add_clause(&[a]);
+        assert!(sat.solve() && sat.get_value(a));
+
+        let mut logic = crate::boolean::Solver::new("");
+        let b = logic.bool_add_variable();
+        assert!(logic
+            .bool_find_one_model(&[b], [b].iter().copied())
+            .is_some());
+        let shape = crate::tensor::Shape::new(vec![2]);
+        assert_eq!(shape.size(), 2);
+    }
+}
@@ -17,18 +17,33 @@
 
 //! A SAT based discrete mathematics and universal algebra calculator.
 
-#[macro_use]
-extern crate lazy_static;
+use uasat::{alg, math};
 
-pub mod alg;
-pub mod core;
-pub mod genvec;
-pub mod math;
+/// Prints the results of an experiment, or the reason of its failure.
+#[allow(dead_code)]
+fn report<RESULTS>(results: Result<RESULTS, math::ExperimentError>)
+where
+    RESULTS: IntoIterator<Item = math::ExperimentResult>,
+{
+    match results {
+        Ok(results) => {
+            for result in results {
+                println!("{}", result);
+            }
+        }
+        Err(err) => {
+            eprintln!("experiment failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
 
 pub fn main() {
-    // math::validate();
-    // math::extremeconn_test();
-    // math::obstruction_test();
+    // report(math::validate());
+    // report(math::extremeconn_test().map(Some));
+    // report(math::obstruction_test().map(Some));
+    // report(math::puzzles_test().map(Some));
     alg::test();
-    // math::taylor_main();
+    // report(math::taylor_main().map(Some));
+    // report(math::dichotomy_main().map(Some));
 }
@@ -15,6 +15,8 @@
 * along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::fmt;
+
 use crate::core::{Shape, Tensor, TensorAlgebra};
 
 /// Returns the list of edges of the binary relation.
@@ -30,6 +32,125 @@ pub fn edges(rel: &Tensor<bool>) -> Vec<(usize, usize)> {
     edges
 }
 
+/// A summary of the structural properties of a concrete binary relation of
+/// shape `[a, a]`, used to characterize the witnesses of experiments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationSummary {
+    /// The number of vertices.
+    pub size: usize,
+    /// The number of edges.
+    pub edges: usize,
+    /// The number of outgoing edges of each vertex.
+    pub out_degrees: Vec<usize>,
+    /// The number of incoming edges of each vertex.
+    pub in_degrees: Vec<usize>,
+    /// The number of strongly connected components.
+    pub components: usize,
+    /// The largest distance between two distinct vertices where the second
+    /// is reachable from the first, or zero if there are no such pairs.
+    pub diameter: usize,
+    /// The number of vertices without a loop.
+    pub reflexive_violations: usize,
+    /// The number of edges whose reverse is not an edge.
+    pub symmetric_violations: usize,
+    /// The number of paths of length two whose endpoints are not connected
+    /// by an edge.
+    pub transitive_violations: usize,
+}
+
+impl RelationSummary {
+    /// Calculates the summary of the given binary relation.
+    pub fn new(rel: &Tensor<bool>) -> Self {
+        let size = rel.shape()[0];
+        assert_eq!(rel.shape().dims(), &[size, size]);
+        let get = |i: usize, j: usize| rel.very_slow_get(&[i, j]);
+
+        let out_degrees: Vec<usize> = (0..size)
+            .map(|i| (0..size).filter(|&j| get(i, j)).count())
+            .collect();
+        let in_degrees: Vec<usize> = (0..size)
+            .map(|j| (0..size).filter(|&i| get(i, j)).count())
+            .collect();
+
+        let mut dist: Vec<Vec<Option<usize>>> = (0..size)
+            .map(|i| (0..size).map(|j| get(i, j).then_some(1)).collect())
+            .collect();
+        for (i, row) in dist.iter_mut().enumerate() {
+            row[i] = Some(0);
+        }
+        for k in 0..size {
+            for i in 0..size {
+                for j in 0..size {
+                    if let (Some(a), Some(b)) = (dist[i][k], dist[k][j]) {
+                        if dist[i][j].map_or(true, |c| a + b < c) {
+                            dist[i][j] = Some(a + b);
+                        }
+                    }
+                }
+            }
+        }
+
+        let components = (0..size)
+            .filter(|&i| (0..i).all(|j| dist[i][j].is_none() || dist[j][i].is_none()))
+            .count();
+        let diameter = dist.iter().flatten().flatten().copied().max().unwrap_or(0);
+
+        let mut reflexive_violations = 0;
+        let mut symmetric_violations = 0;
+        let mut transitive_violations = 0;
+        for i in 0..size {
+            if !get(i, i) {
+                reflexive_violations += 1;
+            }
+            for j in 0..size {
+                if get(i, j) && !get(j, i) {
+                    symmetric_violations += 1;
+                }
+                for k in 0..size {
+                    if get(i, j) && get(j, k) && !get(i, k) {
+                        transitive_violations += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            size,
+            edges: out_degrees.iter().sum(),
+            out_degrees,
+            in_degrees,
+            components,
+            diameter,
+            reflexive_violations,
+            symmetric_violations,
+            transitive_violations,
+        }
+    }
+}
+
+impl fmt::Display for RelationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out_degrees = self.out_degrees.clone();
+        out_degrees.sort_unstable_by(|a, b| b.cmp(a));
+        let mut in_degrees = self.in_degrees.clone();
+        in_degrees.sort_unstable_by(|a, b| b.cmp(a));
+        write!(
+            f,
+            "size={}, edges={}, out={:?}, in={:?}, components={}, diameter={}, \
+            violations: reflexive={}, symmetric={}, transitive={}",
+            self.size,
+            self.edges,
+            out_degrees,
+            in_degrees,
+            self.components,
+            self.diameter,
+            self.reflexive_violations,
+            self.symmetric_violations,
+            self.transitive_violations
+        )
+    }
+}
+
 pub trait BinaryRel: TensorAlgebra {
     /// Creates the constant full relation of the given shape.
     fn create_full_rel(&self, size0: usize, size1: usize) -> Self::Elem {
@@ -137,15 +258,7 @@ pub trait BinaryRel: TensorAlgebra {
     /// shape `[b, c]` and returns their composition as a relation of shape
     /// `[a, c]`.
     fn compose(&mut self, rel0: Self::Elem, rel1: Self::Elem) -> Self::Elem {
-        let (a, b0, rest0) = self.shape(&rel0).split2();
-        let (b1, c, rest1) = self.shape(&rel1).split2();
-        assert_eq!(b0, b1);
-        assert_eq!(rest0, rest1);
-        let shape = rest0.join(&[b0, a, c]);
-        let rel0 = self.tensor_polymer(rel0, shape.clone(), &[1, 0]);
-        let rel1 = self.tensor_polymer(rel1, shape, &[0, 2]);
-        let rel2 = self.tensor_and(rel0, rel1);
-        self.tensor_any(rel2)
+        self.tensor_matmul(rel0, rel1)
     }
 
     /// Checks if the first tensor of shape `[a, b]` is a subset of another one
@@ -246,6 +359,42 @@ pub trait BinaryRel: TensorAlgebra {
         self.tensor_all(tmp)
     }
 
+    /// Computes the alternating reachability (attractor) of a two player game
+    /// played on the directed graph of shape `[a, a]`. The positions owned by
+    /// the first player are given by the tensor `player` of shape `[a]`, and
+    /// the target set by `target` of shape `[a]`. The result is of shape `[a]`
+    /// and contains the positions from which the first player can force the
+    /// token into the target within the given number of moves. The second
+    /// player loses at positions without outgoing edges.
+    fn attractor(
+        &mut self,
+        graph: Self::Elem,
+        player: Self::Elem,
+        target: Self::Elem,
+        steps: usize,
+    ) -> Self::Elem {
+        let size = self.shape(&target)[0];
+        assert_eq!(self.shape(&graph).dims(), &[size, size]);
+        assert_eq!(self.shape(&player).dims(), &[size]);
+        let shape = Shape::new(vec![size, size]);
+        let graph = self.tensor_polymer(graph, shape.clone(), &[1, 0]);
+        let other = self.tensor_not(player.clone());
+
+        let mut result = target;
+        for _ in 0..steps {
+            let next = self.tensor_polymer(result.clone(), shape.clone(), &[0]);
+            let tmp = self.tensor_and(graph.clone(), next.clone());
+            let tmp = self.tensor_any(tmp);
+            let some = self.tensor_and(player.clone(), tmp);
+            let tmp = self.tensor_imp(graph.clone(), next);
+            let tmp = self.tensor_all(tmp);
+            let every = self.tensor_and(other.clone(), tmp);
+            let tmp = self.tensor_or(some, every);
+            result = self.tensor_or(result, tmp);
+        }
+        result
+    }
+
     /// Takes two binary relations of shape `[a, b]` and checks if they
     /// are equal, and the result is returned as a tensor of shape `[]`.
     fn is_equal_to(&mut self, rel0: Self::Elem, rel1: Self::Elem) -> Self::Elem {
@@ -263,3 +412,29 @@ pub trait BinaryRel: TensorAlgebra {
 }
 
 impl<ALG> BinaryRel for ALG where ALG: TensorAlgebra {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Logic;
+
+    #[test]
+    fn summary() {
+        let boolean = Logic();
+        let rel = boolean.create_from_edges(4, 4, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 3)]);
+        let summary = RelationSummary::new(&rel);
+        assert_eq!(summary.edges, 5);
+        assert_eq!(summary.out_degrees, vec![1, 1, 2, 1]);
+        assert_eq!(summary.in_degrees, vec![1, 1, 1, 2]);
+        assert_eq!(summary.components, 2);
+        assert_eq!(summary.diameter, 3);
+        assert_eq!(summary.reflexive_violations, 3);
+        assert_eq!(summary.symmetric_violations, 4);
+        assert_eq!(summary.transitive_violations, 4);
+
+        let summary = RelationSummary::new(&boolean.create_crown_poset(6));
+        assert_eq!(summary.reflexive_violations, 0);
+        assert_eq!(summary.transitive_violations, 0);
+        assert_eq!(summary.components, 6);
+    }
+}
@@ -17,9 +17,9 @@
 
 #![allow(dead_code)]
 
-use super::{binrel, BinaryRel};
+use super::{binrel, BinaryRel, ExperimentError, ExperimentResult};
 use crate::core::{
-    add_progress, del_progress, set_progress, Logic, Literal, Shape, Solver, Tensor, TensorAlgebra,
+    add_progress, del_progress, set_progress, Literal, Logic, Shape, Solver, Tensor, TensorAlgebra,
     TensorSolver,
 };
 
@@ -193,21 +193,48 @@ impl Blocker {
     }
 }
 
-pub fn test() {
+pub fn test() -> Result<ExperimentResult, ExperimentError> {
+    run("cadical", 6, &[0, 0, 0, 1, 3, 4, -1, -1, -1, -1, -1])
+}
+
+/// Searches for a minimal source graph that blocks the extension of the
+/// given partial map into the crown poset of the given size.
+pub fn run(
+    solver_name: &str,
+    crown_size: usize,
+    partial_map: &[isize],
+) -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new("Blocker");
     let boolean = Logic();
 
-    let target_graph = boolean.create_crown_poset(6);
-    let partial_map = [0, 0, 0, 1, 3, 4, -1, -1, -1, -1, -1];
-    let mut blocker = Blocker::new("cadical", &partial_map, target_graph);
+    let target_graph = boolean.create_crown_poset(crown_size);
+    let mut blocker = Blocker::new(solver_name, partial_map, target_graph);
 
     let source_graph = blocker.find_source_graph();
-    if source_graph.is_none() {
-        println!("source: None");
-    } else {
-        println!(
-            "source: {:?}",
-            binrel::edges(source_graph.as_ref().unwrap())
-        );
+    result.add_witness(
+        "source",
+        source_graph
+            .as_ref()
+            .map(|g| format!("{:?}", binrel::edges(g))),
+    );
+    result.add_witness(
+        "source summary",
+        source_graph.map(|g| binrel::RelationSummary::new(&g).to_string()),
+    );
+    result.add_witness(
+        "target",
+        Some(format!("{:?}", binrel::edges(&blocker.target_graph))),
+    );
+    Ok(result.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocker() {
+        let result = run("", 4, &[1, 0, -1]).unwrap();
+        assert!(result.witness("source").is_some());
     }
-    println!("target: {:?}", binrel::edges(&blocker.target_graph));
 }
@@ -0,0 +1,250 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Classification of the polymorphism algebras of small relational
+//! templates, which decides the complexity of their constraint satisfaction
+//! problems by the dichotomy theorem. The tables only cover templates with
+//! a single relation, sets of relations are not enumerated.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use super::obstruction::next_permutation;
+use super::{ExperimentError, ExperimentResult};
+use crate::alg::{PolymorphismClone, SmallSet};
+use crate::genvec::BitVec;
+
+/// The class of the polymorphism algebra of a relational template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TemplateClass {
+    /// There is no Taylor polymorphism, so the problem is NP-complete.
+    NotTaylor,
+    /// There is a Taylor polymorphism but the problem does not have
+    /// bounded width, so it is tractable but not by local consistency.
+    Taylor,
+    /// The problem has bounded width, so it is solved by local consistency.
+    BoundedWidth,
+}
+
+impl fmt::Display for TemplateClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateClass::NotTaylor => write!(f, "not Taylor"),
+            TemplateClass::Taylor => write!(f, "Taylor"),
+            TemplateClass::BoundedWidth => write!(f, "bounded width"),
+        }
+    }
+}
+
+/// Returns the class of the polymorphism algebra of the template on the
+/// domain of the given size with the given relations and their arities.
+pub fn classify_template(size: usize, relations: &[(usize, BitVec)]) -> TemplateClass {
+    let mut clone = PolymorphismClone::new(SmallSet::new(size));
+    for (arity, relation) in relations.iter() {
+        clone.add_relation(*arity, relation.clone());
+    }
+    if clone.find_siggers_operation().is_none() {
+        TemplateClass::NotTaylor
+    } else if clone.find_bounded_width_operations().is_none() {
+        TemplateClass::Taylor
+    } else {
+        TemplateClass::BoundedWidth
+    }
+}
+
+/// Returns the image of the relation of the given arity, encoded as a mask
+/// of its tuples with the first coordinate least significant, under the
+/// given permutation of the domain.
+fn permute(size: usize, arity: usize, mask: u64, perm: &[usize]) -> u64 {
+    let mut result = 0;
+    for index in (0..size.pow(arity as u32)).filter(|i| (mask >> i) & 1 != 0) {
+        let mut image = 0;
+        let mut rest = index;
+        let mut power = 1;
+        for _ in 0..arity {
+            image += perm[rest % size] * power;
+            rest /= size;
+            power *= size;
+        }
+        result |= 1 << image;
+    }
+    result
+}
+
+/// The number of templates with a single relation of the given arity on a
+/// domain of the given size in each class, counted up to isomorphism.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DichotomyRow {
+    /// The size of the domain.
+    pub size: usize,
+    /// The arity of the relation.
+    pub arity: usize,
+    /// The number of non-isomorphic templates.
+    pub templates: usize,
+    /// The number of templates without a Taylor polymorphism.
+    pub not_taylor: usize,
+    /// The number of Taylor templates without bounded width.
+    pub taylor: usize,
+    /// The number of templates of bounded width.
+    pub bounded_width: usize,
+}
+
+impl fmt::Display for DichotomyRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "size {}, arity {}: {} templates, {} not Taylor, {} Taylor, {} bounded width",
+            self.size, self.arity, self.templates, self.not_taylor, self.taylor, self.bounded_width
+        )
+    }
+}
+
+/// Classifies all templates with a single relation of arity at most the
+/// given bound on a domain of the given size, and returns one row of the
+/// summary table for each arity. Every relation is taken only if it is the
+/// smallest among its isomorphic copies. The relations must have less than
+/// 64 tuples, but the enumeration is only practical up to about 25 tuples.
+pub fn dichotomy_table(size: usize, max_arity: usize) -> Vec<DichotomyRow> {
+    (1..=max_arity)
+        .map(|arity| {
+            let len = size.pow(arity as u32);
+            assert!(len < 64);
+
+            let mut perms = Vec::new();
+            let mut perm: Vec<usize> = (0..size).collect();
+            while next_permutation(&mut perm) {
+                perms.push(perm.clone());
+            }
+
+            let mut row = DichotomyRow {
+                size,
+                arity,
+                templates: 0,
+                not_taylor: 0,
+                taylor: 0,
+                bounded_width: 0,
+            };
+            for mask in 0..(1u64 << len) {
+                if perms.iter().any(|p| permute(size, arity, mask, p) < mask) {
+                    continue;
+                }
+                let relation: BitVec = (0..len).map(|i| (mask >> i) & 1 != 0).collect();
+                row.templates += 1;
+                match classify_template(size, &[(arity, relation)]) {
+                    TemplateClass::NotTaylor => row.not_taylor += 1,
+                    TemplateClass::Taylor => row.taylor += 1,
+                    TemplateClass::BoundedWidth => row.bounded_width += 1,
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Writes the rows as a table with one line for each domain size and arity.
+pub fn write_dichotomy_table<WRITER>(writer: &mut WRITER, rows: &[DichotomyRow]) -> io::Result<()>
+where
+    WRITER: Write,
+{
+    writeln!(
+        writer,
+        "{:>4} {:>5} {:>9} {:>10} {:>6} {:>13}",
+        "size", "arity", "templates", "not Taylor", "Taylor", "bounded width"
+    )?;
+    for row in rows.iter() {
+        writeln!(
+            writer,
+            "{:>4} {:>5} {:>9} {:>10} {:>6} {:>13}",
+            row.size, row.arity, row.templates, row.not_taylor, row.taylor, row.bounded_width
+        )?;
+    }
+    Ok(())
+}
+
+/// Classifies the templates with a single relation on two and three element
+/// domains, and checks that the binary boolean ones are all tractable.
+pub fn main() -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new("Dichotomy of small templates");
+    let mut rows = Vec::new();
+    for (size, max_arity) in [(2, 3), (3, 2)] {
+        for row in dichotomy_table(size, max_arity) {
+            let desc = format!("size {} arity {} templates", row.size, row.arity);
+            result.add_count(&desc, row.templates);
+            let desc = format!(
+                "size {} arity {} NP-complete templates",
+                row.size, row.arity
+            );
+            if row.size == 2 && row.arity <= 2 {
+                result.check_count(&desc, row.not_taylor, 0)?;
+            } else {
+                result.add_count(&desc, row.not_taylor);
+            }
+            rows.push(row);
+        }
+    }
+
+    let mut table = Vec::new();
+    write_dichotomy_table(&mut table, &rows).unwrap();
+    result.add_witness(
+        "Table",
+        Some(format!("\n{}", String::from_utf8(table).unwrap())),
+    );
+    Ok(result.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dichotomy() {
+        let relation = |size: usize, arity: usize, pred: fn(&[usize]) -> bool| {
+            let len = size.pow(arity as u32);
+            let relation: BitVec = (0..len)
+                .map(|i| {
+                    let tuple: Vec<usize> = (0..arity)
+                        .map(|j| (i / size.pow(j as u32)) % size)
+                        .collect();
+                    pred(&tuple)
+                })
+                .collect();
+            vec![(arity, relation)]
+        };
+
+        let class = classify_template(3, &relation(3, 2, |t| t[0] != t[1]));
+        assert_eq!(class, TemplateClass::NotTaylor);
+        let class = classify_template(2, &relation(2, 3, |t| t.iter().sum::<usize>() == 1));
+        assert_eq!(class, TemplateClass::NotTaylor);
+        let class = classify_template(2, &relation(2, 4, |t| t.iter().sum::<usize>() % 2 == 1));
+        assert_eq!(class, TemplateClass::Taylor);
+        let class = classify_template(2, &relation(2, 2, |t| t[0] != t[1]));
+        assert_eq!(class, TemplateClass::BoundedWidth);
+        let class = classify_template(2, &relation(2, 3, |t| t[0] == 0 || t[1] == 1));
+        assert_eq!(class, TemplateClass::BoundedWidth);
+
+        let table = dichotomy_table(2, 2);
+        assert_eq!(table[0].templates, 3);
+        assert_eq!(table[1].templates, 10);
+        assert_eq!(table[1].bounded_width, 10);
+
+        let mut text = Vec::new();
+        write_dichotomy_table(&mut text, &table).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.lines().nth(2).unwrap().ends_with("10"));
+    }
+}
@@ -0,0 +1,138 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fmt;
+use std::time::Instant;
+
+/// The reason an experiment has failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExperimentError {
+    /// A computed count differs from the expected one.
+    CountMismatch {
+        desc: String,
+        expected: usize,
+        found: usize,
+    },
+    /// The experiment has panicked with the given message.
+    Panicked(String),
+}
+
+impl fmt::Display for ExperimentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExperimentError::CountMismatch {
+                desc,
+                expected,
+                found,
+            } => write!(f, "number of {} is {} instead of {}", desc, found, expected),
+            ExperimentError::Panicked(msg) => write!(f, "panicked: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ExperimentError {}
+
+/// The outcome of a successful experiment: the computed counts, the found
+/// witnesses and the running time.
+#[derive(Debug, Clone)]
+pub struct ExperimentResult {
+    /// The name of the experiment.
+    pub name: String,
+    /// The computed counts with their descriptions.
+    pub counts: Vec<(String, usize)>,
+    /// The found witnesses in textual form, or `None` if there is none.
+    pub witnesses: Vec<(String, Option<String>)>,
+    /// The running time in seconds.
+    pub seconds: f32,
+    start: Instant,
+}
+
+impl ExperimentResult {
+    /// Creates a new result and starts its timer.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            counts: Vec::new(),
+            witnesses: Vec::new(),
+            seconds: 0.0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Records a computed count.
+    pub fn add_count(&mut self, desc: &str, count: usize) {
+        self.counts.push((desc.to_string(), count));
+    }
+
+    /// Records a computed count and checks that it has the expected value.
+    pub fn check_count(
+        &mut self,
+        desc: &str,
+        count: usize,
+        expected: usize,
+    ) -> Result<(), ExperimentError> {
+        self.add_count(desc, count);
+        if count == expected {
+            Ok(())
+        } else {
+            Err(ExperimentError::CountMismatch {
+                desc: desc.to_string(),
+                expected,
+                found: count,
+            })
+        }
+    }
+
+    /// Records a witness, or the lack of it.
+    pub fn add_witness(&mut self, desc: &str, witness: Option<String>) {
+        self.witnesses.push((desc.to_string(), witness));
+    }
+
+    /// Returns the count with the given description.
+    pub fn count(&self, desc: &str) -> Option<usize> {
+        self.counts.iter().find(|(d, _)| d == desc).map(|(_, c)| *c)
+    }
+
+    /// Returns the witness with the given description.
+    pub fn witness(&self, desc: &str) -> Option<&str> {
+        self.witnesses
+            .iter()
+            .find(|(d, _)| d == desc)
+            .and_then(|(_, w)| w.as_deref())
+    }
+
+    /// Stops the timer and returns the finished result.
+    pub fn finish(mut self) -> Self {
+        self.seconds = Instant::now().duration_since(self.start).as_secs_f32();
+        self
+    }
+}
+
+impl fmt::Display for ExperimentResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (desc, count) in self.counts.iter() {
+            writeln!(f, "Number of {} is {}", desc, count)?;
+        }
+        for (desc, witness) in self.witnesses.iter() {
+            match witness {
+                Some(witness) => writeln!(f, "{}: {}", desc, witness)?,
+                None => writeln!(f, "{}: None", desc)?,
+            }
+        }
+        writeln!(f, "{} finished in {} seconds", self.name, self.seconds)
+    }
+}
@@ -17,13 +17,24 @@
 
 #![allow(dead_code, unused_imports)]
 
+use super::{ExperimentError, ExperimentResult};
 use crate::core::{
-    BooleanLogic, BooleanSolver, Logic, Literal, Shape, Solver, Tensor, TensorAlgebra,
-    TensorSolver,
+    BooleanLogic, BooleanSolver, Literal, Logic, Shape, Solver, Tensor, TensorAlgebra, TensorSolver,
 };
 
-pub fn test() {
-    let mut solver = Solver::new("minisat");
+pub fn test() -> Result<ExperimentResult, ExperimentError> {
+    run("minisat", 2, 3)
+}
+
+/// Searches for the gadget with the given number of node and extra
+/// variables on each edge of the hexagon.
+pub fn run(
+    solver_name: &str,
+    node_size: usize,
+    extr_size: usize,
+) -> Result<ExperimentResult, ExperimentError> {
+    let mut experiment = ExperimentResult::new("Extreme connectivity");
+    let mut solver = Solver::new(solver_name);
 
     let target_size = 4;
 
@@ -72,12 +83,10 @@ pub fn test() {
     );
     let target_gamma = solver.tensor_lift(target_gamma);
 
-    let node_size = 2;
     let node_vars = solver.tensor_add_variable(Shape::new(vec![target_size, 3, node_size]));
     let node_fun = solver.tensor_one(node_vars.clone());
     solver.tensor_add_clause(&[node_fun]);
 
-    let extr_size = 3;
     let extr_vars = solver.tensor_add_variable(Shape::new(vec![target_size, 6, extr_size]));
     let extr_fun = solver.tensor_one(extr_vars.clone());
     solver.tensor_add_clause(&[extr_fun]);
@@ -220,5 +229,19 @@ pub fn test() {
             edge_relation,
         ],
     );
-    println!("{:?}", result);
+    experiment.add_witness("model", result.map(|r| format!("{:?}", r)));
+    Ok(experiment.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extremeconn() {
+        // there is no gadget with a single node variable
+        let result = run("", 1, 2).unwrap();
+        assert_eq!(result.witnesses.len(), 1);
+        assert_eq!(result.witness("model"), None);
+    }
 }
@@ -0,0 +1,586 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Parameter sweeps of experiments, where a parameterized experiment is
+//! run on every point of a grid in parallel and the results are collected
+//! into a table, and a driver that weakens the parameters until the
+//! experiment fits into the time and memory budgets.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{ExperimentError, ExperimentResult};
+use crate::core::CountingAllocator;
+
+/// A point of the parameter grid, which assigns a value to each axis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridPoint {
+    values: Vec<(String, String)>,
+}
+
+impl GridPoint {
+    /// Returns the value of the parameter with the given name.
+    pub fn get(&self, name: &str) -> &str {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_else(|| panic!("unknown parameter {}", name))
+    }
+
+    /// Returns the value of the parameter with the given name as a number.
+    pub fn get_usize(&self, name: &str) -> usize {
+        let value = self.get(name);
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("parameter {} is not a number: {}", name, value))
+    }
+
+    /// Returns the list of parameter names and values.
+    pub fn values(&self) -> &[(String, String)] {
+        &self.values
+    }
+}
+
+impl fmt::Display for GridPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of the experiment at a single point of the grid.
+#[derive(Debug, Clone)]
+pub enum GridOutcome {
+    /// The experiment has finished successfully.
+    Finished(ExperimentResult),
+    /// The experiment has failed.
+    Failed(ExperimentError),
+    /// The experiment has not finished within the time limit.
+    TimedOut,
+    /// The experiment has allocated more memory than the limit.
+    OutOfMemory,
+}
+
+/// The memory limit of an experiment, given by the allocator installed as
+/// the global allocator and the maximal number of additional bytes.
+type MemoryLimit = (&'static CountingAllocator, usize);
+
+thread_local! {
+    /// The cancellation flag of the experiment running in this thread.
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Returns true if the experiment running in the current thread has
+/// exceeded its limits. Long running experiments should poll this and
+/// return early, otherwise they keep consuming time and memory after
+/// they were abandoned.
+pub fn experiment_cancelled() -> bool {
+    CANCELLED.with(|flag| {
+        flag.borrow()
+            .as_ref()
+            .map_or(false, |flag| flag.load(Ordering::Relaxed))
+    })
+}
+
+/// Returns the message of the payload of a panic.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Runs the experiment on the given point in a new thread and waits for
+/// its outcome within the limits. Experiments exceeding the limits are
+/// cancelled, and their threads are given the time limit to stop, so they
+/// do not skew the measurements of later experiments.
+fn run_limited<EXP>(
+    experiment: &Arc<EXP>,
+    point: &GridPoint,
+    timeout: Option<Duration>,
+    memory: Option<MemoryLimit>,
+) -> GridOutcome
+where
+    EXP: Fn(&GridPoint) -> Result<ExperimentResult, ExperimentError> + Send + Sync + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let start = (
+        Instant::now(),
+        memory.map_or(0, |(alloc, _)| alloc.current()),
+    );
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let experiment = experiment.clone();
+    let point = point.clone();
+    let flag = cancelled.clone();
+    let handle = thread::spawn(move || {
+        CANCELLED.with(|cell| *cell.borrow_mut() = Some(flag));
+        sender.send(experiment(&point)).ok();
+    });
+
+    let poll = Duration::from_millis(10);
+    let outcome = loop {
+        let wait = match timeout {
+            Some(timeout) => match timeout.checked_sub(start.0.elapsed()) {
+                Some(remaining) => remaining.min(poll),
+                None => break GridOutcome::TimedOut,
+            },
+            None => poll,
+        };
+        match receiver.recv_timeout(wait) {
+            Ok(Ok(result)) => return GridOutcome::Finished(result),
+            Ok(Err(err)) => return GridOutcome::Failed(err),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                let msg = handle.join().err().map_or_else(String::new, panic_message);
+                return GridOutcome::Failed(ExperimentError::Panicked(msg));
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+        if let Some((alloc, limit)) = memory {
+            if alloc.current().saturating_sub(start.1) > limit {
+                break GridOutcome::OutOfMemory;
+            }
+        }
+    };
+
+    cancelled.store(true, Ordering::Relaxed);
+    receiver
+        .recv_timeout(timeout.unwrap_or(Duration::from_secs(1)))
+        .ok();
+    outcome
+}
+
+/// A grid of experiment parameters given by a list of named axes, whose
+/// points are the combinations of the values on the axes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentGrid {
+    name: String,
+    axes: Vec<(String, Vec<String>)>,
+    threads: usize,
+    timeout: Option<Duration>,
+}
+
+impl ExperimentGrid {
+    /// Creates a new grid without axes, which has a single point. The
+    /// experiments are run on as many threads as available without time
+    /// limit.
+    pub fn new(name: &str) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            name: name.to_string(),
+            axes: Vec::new(),
+            threads,
+            timeout: None,
+        }
+    }
+
+    /// Adds a new axis with the given parameter name and values.
+    pub fn with_axis<ITER>(mut self, name: &str, values: ITER) -> Self
+    where
+        ITER: IntoIterator,
+        ITER::Item: ToString,
+    {
+        let values = values.into_iter().map(|v| v.to_string()).collect();
+        self.axes.push((name.to_string(), values));
+        self
+    }
+
+    /// Sets the number of experiments running in parallel.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads >= 1);
+        self.threads = threads;
+        self
+    }
+
+    /// Sets the time limit of the individual experiments.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns all points of the grid, where the last axis changes the
+    /// fastest.
+    pub fn points(&self) -> Vec<GridPoint> {
+        let mut result = vec![GridPoint { values: Vec::new() }];
+        for (name, values) in self.axes.iter() {
+            let mut next = Vec::with_capacity(result.len() * values.len());
+            for point in result.iter() {
+                for value in values.iter() {
+                    let mut point = point.clone();
+                    point.values.push((name.clone(), value.clone()));
+                    next.push(point);
+                }
+            }
+            result = next;
+        }
+        result
+    }
+
+    /// Runs the experiment on all points of the grid in parallel and
+    /// collects the outcomes. Experiments exceeding the time limit are
+    /// cancelled, see `experiment_cancelled`.
+    pub fn run<EXP>(&self, experiment: EXP) -> GridTable
+    where
+        EXP: Fn(&GridPoint) -> Result<ExperimentResult, ExperimentError> + Send + Sync + 'static,
+    {
+        let points = self.points();
+        let experiment = Arc::new(experiment);
+        let outcomes: Mutex<Vec<Option<GridOutcome>>> = Mutex::new(vec![None; points.len()]);
+        let next = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(points.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= points.len() {
+                        break;
+                    }
+
+                    let outcome = run_limited(&experiment, &points[index], self.timeout, None);
+                    outcomes.lock().unwrap()[index] = Some(outcome);
+                });
+            }
+        });
+
+        let rows = points
+            .into_iter()
+            .zip(outcomes.into_inner().unwrap())
+            .map(|(point, outcome)| (point, outcome.unwrap()))
+            .collect();
+        GridTable {
+            name: self.name.clone(),
+            axes: self.axes.iter().map(|(n, _)| n.clone()).collect(),
+            rows,
+        }
+    }
+}
+
+/// The aggregated outcomes of an experiment grid.
+#[derive(Debug, Clone)]
+pub struct GridTable {
+    name: String,
+    axes: Vec<String>,
+    rows: Vec<(GridPoint, GridOutcome)>,
+}
+
+impl GridTable {
+    /// Returns the points of the grid with their outcomes.
+    pub fn rows(&self) -> &[(GridPoint, GridOutcome)] {
+        &self.rows
+    }
+
+    /// Returns the first error of the failed experiments, if any.
+    pub fn first_error(&self) -> Option<&ExperimentError> {
+        self.rows.iter().find_map(|(_, outcome)| match outcome {
+            GridOutcome::Failed(err) => Some(err),
+            _ => None,
+        })
+    }
+
+    /// Returns the descriptions of the counts in the order of their first
+    /// appearance.
+    fn count_names(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for (_, outcome) in self.rows.iter() {
+            if let GridOutcome::Finished(res) = outcome {
+                for (desc, _) in res.counts.iter() {
+                    if !result.contains(&desc.as_str()) {
+                        result.push(desc);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Display for GridTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = self.count_names();
+        let mut table: Vec<Vec<String>> = Vec::with_capacity(self.rows.len() + 1);
+        let mut header = self.axes.clone();
+        header.extend(counts.iter().map(|c| c.to_string()));
+        header.push("seconds".to_string());
+        table.push(header);
+
+        for (point, outcome) in self.rows.iter() {
+            let mut line: Vec<String> = point.values.iter().map(|(_, v)| v.clone()).collect();
+            match outcome {
+                GridOutcome::Finished(res) => {
+                    for desc in counts.iter() {
+                        line.push(res.count(desc).map_or("-".to_string(), |c| c.to_string()));
+                    }
+                    line.push(format!("{:.3}", res.seconds));
+                }
+                GridOutcome::Failed(err) => line.push(format!("failed: {}", err)),
+                GridOutcome::TimedOut => line.push("timed out".to_string()),
+                GridOutcome::OutOfMemory => line.push("out of memory".to_string()),
+            }
+            table.push(line);
+        }
+
+        let mut widths = vec![0; table[0].len()];
+        for line in table.iter() {
+            for (w, cell) in widths.iter_mut().zip(line.iter()) {
+                *w = (*w).max(cell.len());
+            }
+        }
+
+        writeln!(f, "{}", self.name)?;
+        for line in table.iter() {
+            let cells: Vec<String> = line
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| format!("{:>1$}", cell, widths.get(i).copied().unwrap_or(0)))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// A driver that runs an experiment with the strongest parameters first,
+/// and if it exceeds the time or memory budget, then retries it with
+/// systematically weakened parameters until it succeeds. The axes are
+/// weakened one step at a time in a round robin order.
+#[derive(Debug, Clone)]
+pub struct ShrinkingDriver {
+    name: String,
+    axes: Vec<(String, Vec<String>)>,
+    timeout: Option<Duration>,
+    memory: Option<MemoryLimit>,
+}
+
+impl ShrinkingDriver {
+    /// Creates a new driver without axes and budgets.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            axes: Vec::new(),
+            timeout: None,
+            memory: None,
+        }
+    }
+
+    /// Adds a new axis with the given parameter name and values, which are
+    /// listed from the weakest to the strongest one.
+    pub fn with_axis<ITER>(mut self, name: &str, values: ITER) -> Self
+    where
+        ITER: IntoIterator,
+        ITER::Item: ToString,
+    {
+        let values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+        assert!(!values.is_empty());
+        self.axes.push((name.to_string(), values));
+        self
+    }
+
+    /// Sets the time budget of the individual attempts.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the memory budget of the individual attempts, which is measured
+    /// by the given allocator that must be the global allocator.
+    pub fn with_memory_limit(
+        mut self,
+        allocator: &'static CountingAllocator,
+        bytes: usize,
+    ) -> Self {
+        self.memory = Some((allocator, bytes));
+        self
+    }
+
+    /// Runs the experiment with weakened parameters until it finishes within
+    /// the budgets, fails with an error, or no parameter can be weakened.
+    pub fn run<EXP>(&self, experiment: EXP) -> ShrinkReport
+    where
+        EXP: Fn(&GridPoint) -> Result<ExperimentResult, ExperimentError> + Send + Sync + 'static,
+    {
+        let experiment = Arc::new(experiment);
+        let mut levels: Vec<usize> = self.axes.iter().map(|(_, v)| v.len() - 1).collect();
+        let mut turn = 0;
+        let mut attempts = Vec::new();
+        loop {
+            let point = GridPoint {
+                values: self
+                    .axes
+                    .iter()
+                    .zip(levels.iter())
+                    .map(|((n, v), &l)| (n.clone(), v[l].clone()))
+                    .collect(),
+            };
+            let outcome = run_limited(&experiment, &point, self.timeout, self.memory);
+            let exhausted = matches!(outcome, GridOutcome::TimedOut | GridOutcome::OutOfMemory);
+            attempts.push((point, outcome));
+            if !exhausted {
+                break;
+            }
+
+            match (0..levels.len())
+                .map(|i| (turn + i) % levels.len())
+                .find(|&i| levels[i] > 0)
+            {
+                Some(axis) => {
+                    levels[axis] -= 1;
+                    turn = axis + 1;
+                }
+                None => break,
+            }
+        }
+        ShrinkReport {
+            name: self.name.clone(),
+            attempts,
+        }
+    }
+}
+
+/// The attempts made by a shrinking driver with their outcomes.
+#[derive(Debug, Clone)]
+pub struct ShrinkReport {
+    name: String,
+    attempts: Vec<(GridPoint, GridOutcome)>,
+}
+
+impl ShrinkReport {
+    /// Returns the attempted parameters with their outcomes.
+    pub fn attempts(&self) -> &[(GridPoint, GridOutcome)] {
+        &self.attempts
+    }
+
+    /// Returns the largest configuration that succeeded with its result.
+    pub fn succeeded(&self) -> Option<(&GridPoint, &ExperimentResult)> {
+        match self.attempts.last() {
+            Some((point, GridOutcome::Finished(result))) => Some((point, result)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShrinkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        for (point, outcome) in self.attempts.iter() {
+            let outcome = match outcome {
+                GridOutcome::Finished(result) => {
+                    format!("finished in {:.3} seconds", result.seconds)
+                }
+                GridOutcome::Failed(err) => format!("failed: {}", err),
+                GridOutcome::TimedOut => "timed out".to_string(),
+                GridOutcome::OutOfMemory => "out of memory".to_string(),
+            };
+            writeln!(f, "{}: {}", point, outcome)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::count_latin_squares;
+
+    #[test]
+    fn experiment_grid() {
+        let grid = ExperimentGrid::new("Latin squares")
+            .with_axis("solver", ["batsat", ""])
+            .with_axis("size", 1..=3)
+            .with_threads(2);
+        assert_eq!(grid.points().len(), 6);
+        assert_eq!(grid.points()[1].to_string(), "solver=batsat, size=2");
+
+        let table = grid.run(|point| {
+            let mut result = ExperimentResult::new("latin");
+            let size = point.get_usize("size");
+            let count = count_latin_squares(point.get("solver"), size);
+            result.check_count("squares", count, [1, 1, 2, 12][size])?;
+            Ok(result.finish())
+        });
+        assert!(table.first_error().is_none());
+        assert_eq!(table.rows().len(), 6);
+        let text = table.to_string();
+        assert!(text.starts_with("Latin squares\n"));
+        assert!(text.lines().nth(1).unwrap().contains("squares"));
+
+        let table = ExperimentGrid::new("Timeout")
+            .with_axis("panic", [false, true])
+            .with_timeout(Duration::from_millis(10))
+            .run(|point| {
+                assert_eq!(point.get("panic"), "false", "experiment panic");
+                while !experiment_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(ExperimentResult::new("sleep").finish())
+            });
+        assert!(matches!(table.rows()[0].1, GridOutcome::TimedOut));
+        assert!(table.to_string().contains("timed out"));
+        assert!(matches!(
+            table.first_error(),
+            Some(ExperimentError::Panicked(msg)) if msg.contains("experiment panic")
+        ));
+    }
+
+    #[test]
+    fn memory_limit() {
+        let driver = ShrinkingDriver::new("Memory")
+            .with_axis("megabytes", [0, 256])
+            .with_timeout(Duration::from_secs(10))
+            .with_memory_limit(&crate::core::TEST_ALLOCATOR, 64 << 20);
+        let report = driver.run(|point| {
+            let data = vec![0u8; point.get_usize("megabytes") << 20];
+            while !data.is_empty() && !experiment_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(ExperimentResult::new("memory").finish())
+        });
+        assert_eq!(report.attempts().len(), 2);
+        assert!(matches!(report.attempts()[0].1, GridOutcome::OutOfMemory));
+        assert!(report.succeeded().is_some());
+    }
+
+    #[test]
+    fn shrinking_driver() {
+        let driver = ShrinkingDriver::new("Shrinking")
+            .with_axis("size", 1..=4)
+            .with_axis("arity", 1..=2)
+            .with_timeout(Duration::from_millis(200));
+        let report = driver.run(|point| {
+            while point.get_usize("size") + point.get_usize("arity") > 3 && !experiment_cancelled()
+            {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(ExperimentResult::new("sleep").finish())
+        });
+        assert_eq!(report.attempts().len(), 4);
+        let (point, _) = report.succeeded().unwrap();
+        assert_eq!(point.to_string(), "size=2, arity=1");
+        assert!(report.to_string().contains("size=4, arity=2: timed out"));
+    }
+}
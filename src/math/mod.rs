@@ -19,15 +19,34 @@
 
 mod binrel;
 mod blocker;
+mod dichotomy;
+mod experiment;
 mod extremeconn;
+mod grid;
 mod obstruction;
+mod puzzles;
+mod taylor;
 mod test;
 mod validate;
-mod taylor;
 
-pub use binrel::BinaryRel;
-pub use blocker::test as blocker_test;
-pub use extremeconn::test as extremeconn_test;
-pub use obstruction::test as obstruction_test;
-pub use validate::validate;
-pub use taylor::main as taylor_main;
\ No newline at end of file
+pub use binrel::{BinaryRel, RelationSummary};
+pub use blocker::{run as blocker_run, test as blocker_test};
+pub use dichotomy::main as dichotomy_main;
+pub use dichotomy::{
+    classify_template, dichotomy_table, write_dichotomy_table, DichotomyRow, TemplateClass,
+};
+pub use experiment::{ExperimentError, ExperimentResult};
+pub use extremeconn::{run as extremeconn_run, test as extremeconn_test};
+pub use grid::{
+    experiment_cancelled, ExperimentGrid, GridOutcome, GridPoint, GridTable, ShrinkReport,
+    ShrinkingDriver,
+};
+pub use obstruction::{canonical_form, Obstruction, ObstructionState, Property};
+pub use obstruction::{run as obstruction_run, test as obstruction_test};
+pub use puzzles::test as puzzles_test;
+pub use puzzles::{
+    add_cell_constraints, add_latin_constraints, add_latin_square, count_grid_colorings,
+    count_latin_squares, Sudoku,
+};
+pub use taylor::{main as taylor_main, run as taylor_run};
+pub use validate::{validate, validate_solver};
@@ -15,80 +15,308 @@
 * along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-#![allow(dead_code)]
-
-use super::binrel::BinaryRel;
-use crate::core::{
-    BooleanLogic, BooleanSolver, Logic, Literal, Shape, Solver, Tensor, TensorAlgebra,
-    TensorSolver,
-};
-
-struct Obstruction {
-    solver: Solver,
-    source: Tensor<Literal>,
-    target: Tensor<Literal>,
-    maps: Vec<Tensor<Literal>>,
+//! Search for the minimal obstructions of a template directed graph, that
+//! are the directed graphs without a homomorphism into the template whose
+//! proper induced subgraphs all map homomorphically into the template.
+
+use super::binrel::{self, BinaryRel};
+use super::{ExperimentError, ExperimentResult};
+use crate::alg::canonical_relation;
+use crate::core::{Literal, Logic, Shape, Solver, Tensor, TensorAlgebra, TensorSolver};
+use crate::genvec::{BitVec, Vector};
+use std::collections::BTreeSet;
+
+/// A property of the searched source graphs, which takes a relation of
+/// shape `[a, a]` and returns the result as a tensor of shape `[]`.
+pub type Property = fn(&mut Solver, Tensor<Literal>) -> Tensor<Literal>;
+
+/// The state of an obstruction search that can be saved and used to resume
+/// the search later, possibly with a different solver.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObstructionState {
+    /// The size of the source graphs currently searched.
+    pub size: usize,
+    /// The obstructions found so far in canonical form, in the order of
+    /// their sizes.
+    pub found: Vec<Tensor<bool>>,
+}
+
+/// Returns the next permutation in lexicographic order, or false if the
+/// given permutation was the last one.
+pub(super) fn next_permutation(perm: &mut [usize]) -> bool {
+    let Some(i) = (1..perm.len()).rev().find(|&i| perm[i - 1] < perm[i]) else {
+        return false;
+    };
+    let j = (i..perm.len())
+        .rev()
+        .find(|&j| perm[i - 1] < perm[j])
+        .unwrap();
+    perm.swap(i - 1, j);
+    perm[i..].reverse();
+    true
+}
+
+/// Returns the image of the binary relation of shape `[a, a]` under the
+/// given permutation of its universe.
+fn permute(graph: &Tensor<bool>, perm: &[usize]) -> Tensor<bool> {
+    let mut result = Tensor::create(graph.shape().clone(), |_| false);
+    for (i, j) in binrel::edges(graph) {
+        result.very_slow_set(&[perm[i], perm[j]], true);
+    }
+    result
+}
+
+/// Returns the distinct isomorphic copies of the binary relation of shape
+/// `[a, a]`.
+fn isomorphic_copies(graph: &Tensor<bool>) -> Vec<Tensor<bool>> {
+    let mut perm: Vec<usize> = (0..graph.shape()[0]).collect();
+    let mut seen = BTreeSet::new();
+    let mut copies = Vec::new();
+    loop {
+        let copy = permute(graph, &perm);
+        if seen.insert(binrel::edges(&copy)) {
+            copies.push(copy);
+        }
+        if !next_permutation(&mut perm) {
+            return copies;
+        }
+    }
+}
+
+/// Returns the canonical form of the binary relation of shape `[a, a]`,
+/// which is computed by the degree refining search of the orderly
+/// generator. Two relations are isomorphic if and only if their canonical
+/// forms are equal. The size must be at most eight.
+pub fn canonical_form(graph: &Tensor<bool>) -> Tensor<bool> {
+    let size = graph.shape()[0];
+    assert_eq!(graph.shape().dims(), &[size, size]);
+    let elem: BitVec = (0..size * size)
+        .map(|i| graph.very_slow_get(&[i % size, i / size]))
+        .collect();
+    let elem = canonical_relation(size, elem.slice());
+    Tensor::create(graph.shape().clone(), |c| elem.get(c[0] + c[1] * size))
+}
+
+/// Searches for the minimal obstructions of a fixed template graph among
+/// the source graphs satisfying a given property, up to a size bound. The
+/// source graphs of each size are found by a counterexample guided loop:
+/// every homomorphism found into the template rules out all source graphs
+/// it is compatible with. Isomorphic copies of found obstructions are
+/// excluded, so each obstruction is reported once in canonical form.
+pub struct Obstruction {
+    solver_name: String,
+    template: Tensor<bool>,
+    property: Option<Property>,
+    max_size: usize,
+    state: ObstructionState,
+    search: Option<(Solver, Tensor<Literal>)>,
 }
 
 impl Obstruction {
-    pub fn new(solver: &str, source_size: usize, target_size: usize) -> Self {
-        let mut solver = Solver::new(solver);
-        let source = solver.tensor_add_variable(Shape::new(vec![source_size, source_size]));
-        let target = solver.tensor_add_variable(Shape::new(vec![target_size, target_size]));
+    /// Creates a new search for the obstructions of the given template graph
+    /// of shape `[b, b]` with at most `max_size` many vertices. Isomorphic
+    /// copies are excluded by permutations, so the size bound must be at
+    /// most eight.
+    pub fn new(solver_name: &str, template: Tensor<bool>, max_size: usize) -> Self {
+        Self::resume(solver_name, template, max_size, ObstructionState::default())
+    }
+
+    /// Resumes a previously saved search for the obstructions of the given
+    /// template graph.
+    pub fn resume(
+        solver_name: &str,
+        template: Tensor<bool>,
+        max_size: usize,
+        state: ObstructionState,
+    ) -> Self {
+        let size = template.shape()[0];
+        assert_eq!(template.shape().dims(), &[size, size]);
+        assert!(max_size <= 8);
         Self {
-            solver,
-            source,
-            target,
-            maps: Vec::default(),
+            solver_name: solver_name.into(),
+            template,
+            property: None,
+            max_size,
+            state,
+            search: None,
         }
     }
 
-    pub fn source_size(&self) -> usize {
-        self.source.shape()[0]
+    /// Restricts the search to source graphs with the given property.
+    pub fn with_property(mut self, property: Property) -> Self {
+        assert!(self.search.is_none());
+        self.property = Some(property);
+        self
+    }
+
+    /// Returns the template graph.
+    pub fn template(&self) -> &Tensor<bool> {
+        &self.template
+    }
+
+    /// Returns the current state of the search.
+    pub fn state(&self) -> &ObstructionState {
+        &self.state
     }
 
-    pub fn target_size(&self) -> usize {
-        self.target.shape()[0]
+    /// Returns the obstructions found so far.
+    pub fn found(&self) -> &[Tensor<bool>] {
+        &self.state.found
     }
 
-    pub fn set_source_edge(&mut self, elem1: usize, elem2: usize, value: bool) {
-        let lit = self.source.very_slow_get(&[elem1, elem2]);
-        let lit = self.solver.bool_xor(lit, self.solver.bool_lift(value));
-        self.solver.bool_add_clause(&[lit]);
+    /// Returns a homomorphism from the given source graph to the template
+    /// as a function of shape `[a, b]`, or `None` if there is no such map.
+    pub fn find_homomorphism(&self, source: &Tensor<bool>) -> Option<Tensor<bool>> {
+        let shape = Shape::new(vec![source.shape()[0], self.template.shape()[0]]);
+        if source.shape()[0] == 0 {
+            return Some(Tensor::create(shape, |_| false));
+        }
+        let mut alg = Solver::new(&self.solver_name);
+        let map = alg.tensor_add_variable(shape);
+
+        let tmp = alg.is_function(map.clone());
+        alg.tensor_add_clause1(tmp);
+
+        let source = alg.tensor_lift(source.clone());
+        let template = alg.tensor_lift(self.template.clone());
+        let tmp = alg.is_compatible(map.clone(), source, template);
+        alg.tensor_add_clause1(tmp);
+
+        alg.tensor_find_one_model1(map)
     }
 
-    pub fn set_source_graph(&mut self, graph: Tensor<bool>) {
-        let graph = self.solver.tensor_lift(graph);
-        let graph = self.solver.tensor_xor(self.source.clone(), graph);
-        self.solver.tensor_add_clause1(graph);
+    /// Returns true if the given source graph is a minimal obstruction of
+    /// the template, without checking the property of the source graph.
+    pub fn is_obstruction(&self, source: &Tensor<bool>) -> bool {
+        if self.find_homomorphism(source).is_some() {
+            return false;
+        }
+        let size = source.shape()[0];
+        (0..size).all(|v| {
+            let others: Vec<usize> = (0..size).filter(|&u| u != v).collect();
+            let shape = Shape::new(vec![size - 1, size - 1]);
+            let sub = Tensor::create(shape, |c| {
+                source.very_slow_get(&[others[c[0]], others[c[1]]])
+            });
+            self.find_homomorphism(&sub).is_some()
+        })
     }
 
-    pub fn set_target_edge(&mut self, elem1: usize, elem2: usize, value: bool) {
-        let lit = self.target.very_slow_get(&[elem1, elem2]);
-        let lit = self.solver.bool_xor(lit, self.solver.bool_lift(value));
-        self.solver.bool_add_clause(&[lit]);
+    /// Creates the solver for the source graphs of the current size whose
+    /// proper induced subgraphs map into the template, and which are not
+    /// isomorphic to an already found obstruction.
+    fn start(&self) -> (Solver, Tensor<Literal>) {
+        let size = self.state.size;
+        let mut alg = Solver::new(&self.solver_name);
+        let source = alg.tensor_add_variable(Shape::new(vec![size, size]));
+
+        if let Some(property) = self.property {
+            let tmp = property(&mut alg, source.clone());
+            alg.tensor_add_clause1(tmp);
+        }
+
+        let target_size = self.template.shape()[0];
+        for v in 0..size {
+            let map = alg.tensor_add_variable(Shape::new(vec![size, target_size]));
+
+            let tmp = alg.is_partial_function(map.clone());
+            alg.tensor_add_clause1(tmp);
+
+            for u in 0..size {
+                let row = Tensor::create(Shape::new(vec![target_size]), |c| {
+                    map.very_slow_get(&[u, c[0]])
+                });
+                let tmp = alg.tensor_any(row);
+                let tmp = if u == v { alg.tensor_not(tmp) } else { tmp };
+                alg.tensor_add_clause1(tmp);
+            }
+
+            let template = alg.tensor_lift(self.template.clone());
+            let tmp = alg.is_compatible(map, source.clone(), template);
+            alg.tensor_add_clause1(tmp);
+        }
+
+        for graph in self.state.found.iter() {
+            if graph.shape()[0] != size {
+                continue;
+            }
+            for copy in isomorphic_copies(graph) {
+                let copy = alg.tensor_lift(copy);
+                let tmp = alg.is_not_equal_to(source.clone(), copy);
+                alg.tensor_add_clause1(tmp);
+            }
+        }
+
+        (alg, source)
     }
 
-    pub fn set_target_graph(&mut self, graph: Tensor<bool>) {
-        let graph = self.solver.tensor_lift(graph);
-        let graph = self.solver.tensor_xor(self.target.clone(), graph);
-        self.solver.tensor_add_clause1(graph);
+    /// Returns the next minimal obstruction in canonical form, or `None` if
+    /// there are no more obstructions within the size bound.
+    pub fn find_next(&mut self) -> Option<Tensor<bool>> {
+        if self.state.size == 0 {
+            self.state.size = 1;
+        }
+        while self.state.size <= self.max_size {
+            if self.search.is_none() {
+                self.search = Some(self.start());
+            }
+            let (alg, source) = self.search.as_mut().unwrap();
+
+            let candidate = match alg.tensor_find_one_model1(source.clone()) {
+                Some(candidate) => candidate,
+                None => {
+                    self.search = None;
+                    self.state.size += 1;
+                    continue;
+                }
+            };
+
+            if let Some(map) = self.find_homomorphism(&candidate) {
+                let (alg, source) = self.search.as_mut().unwrap();
+                let map = alg.tensor_lift(map);
+                let template = alg.tensor_lift(self.template.clone());
+                let tmp = alg.is_compatible(map, source.clone(), template);
+                let tmp = alg.tensor_not(tmp);
+                alg.tensor_add_clause1(tmp);
+                continue;
+            }
+
+            let (alg, source) = self.search.as_mut().unwrap();
+            for copy in isomorphic_copies(&candidate) {
+                let copy = alg.tensor_lift(copy);
+                let tmp = alg.is_not_equal_to(source.clone(), copy);
+                alg.tensor_add_clause1(tmp);
+            }
+
+            let graph = canonical_form(&candidate);
+            self.state.found.push(graph.clone());
+            return Some(graph);
+        }
+        None
     }
 
-    pub fn add_map(&mut self) -> usize {
-        let idx = self.maps.len();
-        let map = self
-            .solver
-            .tensor_add_variable(Shape::new(vec![self.source_size(), self.target_size()]));
-        self.maps.push(map);
-        idx
+    /// Finds all remaining minimal obstructions within the size bound, and
+    /// returns all obstructions found so far.
+    pub fn find_all(&mut self) -> &[Tensor<bool>] {
+        while self.find_next().is_some() {}
+        self.found()
     }
 }
 
-pub fn test() {
-    let mut boolean = Logic();
+pub fn test() -> Result<ExperimentResult, ExperimentError> {
+    run("", 5, 3)
+}
 
-    let mut obst = Obstruction::new("", 2, 6);
+/// Checks the poset template of the original experiment, and searches for
+/// the minimal obstructions of bipartiteness with at most `max_size` many
+/// vertices, of which there should be `expected` many.
+pub fn run(
+    solver_name: &str,
+    max_size: usize,
+    expected: usize,
+) -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new("Obstruction");
+    let mut boolean = Logic();
 
     let target = boolean.create_from_edges(
         6,
@@ -115,10 +343,55 @@ pub fn test() {
             (5, 5),
         ],
     );
-    println!(
-        "partial order: {}",
-        boolean.is_partial_order(target.clone()).scalar()
-    );
+    let partial_order = boolean.is_partial_order(target.clone()).scalar();
+    result.check_count("partial order templates", partial_order as usize, 1)?;
+
+    // every graph maps to the bottom element, which has a loop
+    let mut obst = Obstruction::new(solver_name, target, 2);
+    result.check_count("poset obstructions", obst.find_all().len(), 0)?;
+
+    let template = boolean.create_from_edges(2, 2, &[(0, 1), (1, 0)]);
+    let mut obst =
+        Obstruction::new(solver_name, template, max_size).with_property(Solver::is_symmetric);
+    let found = obst.find_all();
+
+    result.check_count("bipartite obstructions", found.len(), expected)?;
+    for graph in found {
+        let name = format!("size {}", graph.shape()[0]);
+        result.add_witness(&name, Some(format!("{:?}", binrel::edges(graph))));
+        let summary = binrel::RelationSummary::new(graph);
+        result.add_witness(&format!("{} summary", name), Some(summary.to_string()));
+    }
+    Ok(result.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obstructions() {
+        let boolean = Logic();
+        let template = boolean.create_from_edges(2, 2, &[(0, 1), (1, 0)]);
 
-    obst.set_target_graph(target);
+        let mut obst =
+            Obstruction::new("", template.clone(), 3).with_property(Solver::is_symmetric);
+        let first = obst.find_next().unwrap();
+        assert_eq!(binrel::edges(&first), vec![(0, 0)]);
+        let state = obst.state().clone();
+
+        let mut obst =
+            Obstruction::resume("", template, 3, state).with_property(Solver::is_symmetric);
+        let found = obst.find_all().to_vec();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], first);
+        assert_eq!(found[1].shape()[0], 3);
+        assert_eq!(found[1], canonical_form(&found[1]));
+        assert!(found.iter().all(|g| obst.is_obstruction(g)));
+        assert!(obst.find_next().is_none());
+
+        let result = run("", 3, 2).unwrap();
+        assert_eq!(result.count("partial order templates"), Some(1));
+        assert_eq!(result.count("poset obstructions"), Some(0));
+    }
 }
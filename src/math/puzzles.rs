@@ -0,0 +1,303 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Grid puzzles encoded with the tensor API. A filled grid is represented
+//! by a tensor of shape `[values, cols, rows]` whose element at coordinates
+//! `[v, c, r]` is true if the cell in row `r` and column `c` contains the
+//! value `v`.
+
+use std::fmt;
+
+use super::{ExperimentError, ExperimentGrid, ExperimentResult};
+use crate::core::{BooleanSolver, Literal, Shape, Solver, Tensor, TensorAlgebra, TensorSolver};
+
+/// Adds the constraints that every cell of the grid contains exactly one
+/// value to the given grid variable of shape `[values, cols, rows]`.
+pub fn add_cell_constraints(solver: &mut Solver, grid: Tensor<Literal>) {
+    let tmp = solver.tensor_one(grid);
+    solver.tensor_add_clause1(tmp);
+}
+
+/// Adds the constraints that every row and every column of the square grid
+/// variable of shape `[size, size, size]` contains each value exactly once.
+pub fn add_latin_constraints(solver: &mut Solver, grid: Tensor<Literal>) {
+    let size = grid.shape()[0];
+    assert_eq!(grid.shape().dims(), &[size, size, size]);
+    let shape = Shape::new(vec![size, size, size]);
+
+    let tmp = solver.tensor_polymer(grid.clone(), shape.clone(), &[1, 0, 2]);
+    let tmp = solver.tensor_one(tmp);
+    solver.tensor_add_clause1(tmp);
+
+    let tmp = solver.tensor_polymer(grid, shape, &[1, 2, 0]);
+    let tmp = solver.tensor_one(tmp);
+    solver.tensor_add_clause1(tmp);
+}
+
+/// Creates a new latin square variable of shape `[size, size, size]`.
+pub fn add_latin_square(solver: &mut Solver, size: usize) -> Tensor<Literal> {
+    let grid = solver.tensor_add_variable(Shape::new(vec![size, size, size]));
+    add_cell_constraints(solver, grid.clone());
+    add_latin_constraints(solver, grid.clone());
+    grid
+}
+
+/// Returns the number of latin squares of the given size.
+pub fn count_latin_squares(solver_name: &str, size: usize) -> usize {
+    let mut solver = Solver::new(solver_name);
+    let grid = add_latin_square(&mut solver, size);
+    solver.tensor_find_num_models(&[grid])
+}
+
+/// Returns the number of colorings of the cells of a rectangular grid with
+/// the given number of colors where neighboring cells have different colors.
+pub fn count_grid_colorings(solver_name: &str, rows: usize, cols: usize, colors: usize) -> usize {
+    let mut solver = Solver::new(solver_name);
+    let grid = solver.tensor_add_variable(Shape::new(vec![colors, cols, rows]));
+    add_cell_constraints(&mut solver, grid.clone());
+
+    if cols >= 2 {
+        let shape = Shape::new(vec![colors, cols - 1, rows]);
+        let left = Tensor::create(shape.clone(), |i| grid.very_slow_get(&[i[0], i[1], i[2]]));
+        let right = Tensor::create(shape, |i| grid.very_slow_get(&[i[0], i[1] + 1, i[2]]));
+        let tmp = solver.tensor_and(left, right);
+        let tmp = solver.tensor_not(tmp);
+        solver.tensor_add_clause1(tmp);
+    }
+
+    if rows >= 2 {
+        let shape = Shape::new(vec![colors, cols, rows - 1]);
+        let top = Tensor::create(shape.clone(), |i| grid.very_slow_get(&[i[0], i[1], i[2]]));
+        let bottom = Tensor::create(shape, |i| grid.very_slow_get(&[i[0], i[1], i[2] + 1]));
+        let tmp = solver.tensor_and(top, bottom);
+        let tmp = solver.tensor_not(tmp);
+        solver.tensor_add_clause1(tmp);
+    }
+
+    solver.tensor_find_num_models(&[grid])
+}
+
+/// A partially filled sudoku grid with square boxes. The side length of
+/// the grid is the square of the side length of the boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sudoku {
+    box_size: usize,
+    cells: Vec<Option<usize>>,
+}
+
+impl Sudoku {
+    /// Creates an empty sudoku grid with the given box size.
+    pub fn new(box_size: usize) -> Self {
+        assert!(box_size >= 1);
+        let size = box_size * box_size;
+        Self {
+            box_size,
+            cells: vec![None; size * size],
+        }
+    }
+
+    /// Parses a sudoku grid from the list of cells in row major order, where
+    /// the values are written as `1`-`9` followed by `A`-`Z`, and the empty
+    /// cells as `.` or `0`. Whitespace and the box separators `|`, `-` and
+    /// `+` are ignored. Returns `None` if the text is malformed.
+    pub fn parse(box_size: usize, text: &str) -> Option<Self> {
+        let mut sudoku = Self::new(box_size);
+        let size = sudoku.size();
+        let mut cells = Vec::with_capacity(size * size);
+        for c in text.chars() {
+            let value = match c {
+                '.' | '0' => None,
+                '1'..='9' => Some(c as usize - '1' as usize),
+                'A'..='Z' => Some(c as usize - 'A' as usize + 9),
+                c if c.is_whitespace() || "|-+".contains(c) => continue,
+                _ => return None,
+            };
+            if value.map_or(false, |v| v >= size) {
+                return None;
+            }
+            cells.push(value);
+        }
+        if cells.len() != size * size {
+            return None;
+        }
+        sudoku.cells = cells;
+        Some(sudoku)
+    }
+
+    /// Returns the side length of the grid.
+    pub fn size(&self) -> usize {
+        self.box_size * self.box_size
+    }
+
+    /// Returns the value of the given cell.
+    pub fn get(&self, row: usize, col: usize) -> Option<usize> {
+        self.cells[row * self.size() + col]
+    }
+
+    /// Sets the value of the given cell.
+    pub fn set(&mut self, row: usize, col: usize, value: Option<usize>) {
+        assert!(value.map_or(true, |v| v < self.size()));
+        let size = self.size();
+        self.cells[row * size + col] = value;
+    }
+
+    /// Returns the number of filled cells.
+    pub fn num_givens(&self) -> usize {
+        self.cells.iter().filter(|v| v.is_some()).count()
+    }
+
+    /// Creates the grid variable of shape `[size, size, size]` of the
+    /// solutions of this sudoku.
+    pub fn encode(&self, solver: &mut Solver) -> Tensor<Literal> {
+        let (b, n) = (self.box_size, self.size());
+        let grid = add_latin_square(solver, n);
+
+        let tmp = solver.tensor_reshape(grid.clone(), Shape::new(vec![n, b, b, b, b]));
+        let tmp = solver.tensor_polymer(tmp, Shape::new(vec![b, b, n, b, b]), &[2, 0, 3, 1, 4]);
+        let tmp = solver.tensor_reshape(tmp, Shape::new(vec![n, n, b, b]));
+        let tmp = solver.tensor_one(tmp);
+        solver.tensor_add_clause1(tmp);
+
+        for row in 0..n {
+            for col in 0..n {
+                if let Some(value) = self.get(row, col) {
+                    let lit = grid.very_slow_get(&[value, col, row]);
+                    solver.bool_add_clause1(lit);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Returns the sudoku filled according to the given solution of shape
+    /// `[size, size, size]`.
+    pub fn decode(&self, solution: &Tensor<bool>) -> Self {
+        let mut result = self.clone();
+        let n = self.size();
+        for row in 0..n {
+            for col in 0..n {
+                let value = (0..n).find(|&v| solution.very_slow_get(&[v, col, row]));
+                result.set(row, col, value);
+            }
+        }
+        result
+    }
+
+    /// Returns a solution of the sudoku, or `None` if it has none.
+    pub fn solve(&self, solver_name: &str) -> Option<Self> {
+        let mut solver = Solver::new(solver_name);
+        let grid = self.encode(&mut solver);
+        solver
+            .tensor_find_one_model1(grid)
+            .map(|solution| self.decode(&solution))
+    }
+
+    /// Returns the number of solutions of the sudoku.
+    pub fn count_solutions(&self, solver_name: &str) -> usize {
+        let mut solver = Solver::new(solver_name);
+        let grid = self.encode(&mut solver);
+        solver.tensor_find_num_models(&[grid])
+    }
+}
+
+impl fmt::Display for Sudoku {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (b, n) = (self.box_size, self.size());
+        let line: Vec<String> = (0..b).map(|_| "-".repeat(2 * b + 1)).collect();
+        for row in 0..n {
+            if row > 0 && row % b == 0 {
+                writeln!(f, "{}", line.join("+"))?;
+            }
+            for col in 0..n {
+                if col > 0 && col % b == 0 {
+                    write!(f, " |")?;
+                }
+                let c = match self.get(row, col) {
+                    None => '.',
+                    Some(v) if v < 9 => (b'1' + v as u8) as char,
+                    Some(v) => (b'A' + (v - 9) as u8) as char,
+                };
+                write!(f, " {}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A sudoku puzzle with a unique solution.
+const PUZZLE: &str = "
+    53. .7. ...
+    6.. 195 ...
+    .98 ... .6.
+    8.. .6. ..3
+    4.. 8.3 ..1
+    7.. .2. ..6
+    .6. ... 28.
+    ... 419 ..5
+    ... .8. .79";
+
+pub fn test() -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new("Puzzles");
+
+    let table = ExperimentGrid::new("Latin squares")
+        .with_axis("order", 1..=4)
+        .run(|point| {
+            let mut result = ExperimentResult::new("latin");
+            let order = point.get_usize("order");
+            let count = count_latin_squares("", order);
+            result.check_count("squares", count, [1, 1, 2, 12, 576][order])?;
+            Ok(result.finish())
+        });
+    if let Some(err) = table.first_error() {
+        return Err(err.clone());
+    }
+    result.add_witness("latin squares", Some(format!("\n{}", table)));
+    result.check_count(
+        "3-colorings of the 2x3 grid",
+        count_grid_colorings("", 2, 3, 3),
+        54,
+    )?;
+    result.check_count("shidoku grids", Sudoku::new(2).count_solutions(""), 288)?;
+
+    let puzzle = Sudoku::parse(3, PUZZLE).unwrap();
+    result.check_count("sudoku solutions", puzzle.count_solutions(""), 1)?;
+    result.add_witness("sudoku", puzzle.solve("").map(|s| format!("\n{}", s)));
+
+    Ok(result.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzles() {
+        assert_eq!(count_latin_squares("", 3), 12);
+        assert_eq!(count_grid_colorings("", 2, 2, 3), 18);
+        assert_eq!(Sudoku::new(2).count_solutions(""), 288);
+
+        let puzzle = Sudoku::parse(3, PUZZLE).unwrap();
+        assert_eq!(puzzle.num_givens(), 30);
+        let solution = puzzle.solve("").unwrap();
+        assert_eq!(solution.num_givens(), 81);
+        assert_eq!(solution.get(0, 2), Some(3));
+        assert_eq!(Sudoku::parse(3, &solution.to_string()), Some(solution));
+        assert!(Sudoku::parse(2, "12").is_none());
+        assert!(test().is_ok());
+    }
+}
@@ -17,6 +17,7 @@
 
 #![allow(dead_code)]
 
+use super::{ExperimentError, ExperimentResult};
 use crate::alg::{
     BinaryRelations, BoundedOrder, Domain, MeetSemilattice, Monoid, Semigroup, SmallSet,
 };
@@ -63,15 +64,23 @@ impl BinaryRelClone {
 
     pub fn add(&mut self, elem: BitVec) {
         if !self.contains(elem.slice()) {
-            println!("{}", self.domain.format(elem.slice()));
             self.elems.push(elem);
         }
     }
 
-    pub fn print(&self) {
-        for (idx, elem) in self.elems.iter().enumerate() {
-            println!("{}: {}", idx, self.domain.format(elem.slice()));
-        }
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
+    pub fn format_elems(&self) -> Vec<String> {
+        self.elems
+            .iter()
+            .map(|elem| self.domain.format(elem.slice()).to_string())
+            .collect()
     }
 
     pub fn close(&mut self) {
@@ -104,13 +113,44 @@ impl BinaryRelClone {
     }
 }
 
-pub fn main() {
-    let mut clone = BinaryRelClone::new(4);
-    clone.add_from_str("1100011000111001");
-    clone.add_from_str("1000000000000000");
-    clone.add_from_str("0000010000000000");
-    clone.add_from_str("0000000000100000");
-    clone.add_from_str("0000000000000001");
+pub fn main() -> Result<ExperimentResult, ExperimentError> {
+    run(
+        4,
+        &[
+            "1100011000111001",
+            "1000000000000000",
+            "0000010000000000",
+            "0000000000100000",
+            "0000000000000001",
+        ],
+    )
+}
+
+/// Computes the binary relational clone on the given number of elements
+/// generated by the relations given as strings of bits.
+pub fn run(size: usize, gens: &[&str]) -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new("Binary relational clone");
+    let mut clone = BinaryRelClone::new(size);
+    for elem in gens.iter() {
+        clone.add_from_str(elem);
+    }
     clone.close();
-    clone.print();
+    result.add_count("relations", clone.len());
+    for (idx, elem) in clone.format_elems().into_iter().enumerate() {
+        result.add_witness(&idx.to_string(), Some(elem));
+    }
+    Ok(result.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relational_clone() {
+        let result = run(2, &[]).unwrap();
+        assert_eq!(result.count("relations"), Some(2));
+        let result = run(2, &["1000"]).unwrap();
+        assert_eq!(result.count("relations"), Some(5));
+    }
 }
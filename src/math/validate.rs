@@ -15,87 +15,167 @@
 * along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::time::Instant;
-
-use super::BinaryRel;
+use super::{BinaryRel, ExperimentError, ExperimentResult};
+use crate::alg::bell;
 use crate::core::{Shape, Solver, TensorAlgebra, TensorSolver};
 
 fn check(
+    result: &mut ExperimentResult,
     solver: &str,
     desc: &str,
     shape: Shape,
     pred: fn(&mut Solver, elem: <Solver as TensorAlgebra>::Elem) -> <Solver as TensorAlgebra>::Elem,
     count: usize,
-) {
+) -> Result<(), ExperimentError> {
     let mut sol = Solver::new(solver);
     let elem = sol.tensor_add_variable(shape.clone());
     let cond = pred(&mut sol, elem.clone());
     sol.tensor_add_clause(&[cond]);
     let num = sol.tensor_find_num_models(&[elem]);
-    println!("Number of {} of shape {:?} is {}", desc, shape.dims(), num);
-    assert_eq!(num, count);
+    let desc = format!("{} of shape {:?}", desc, shape.dims());
+    result.check_count(&desc, num, count)
+}
+
+/// Checks if the first player can force the token from the first vertex
+/// to the last one, where the first player owns the even vertices.
+fn is_reachability_game(
+    sol: &mut Solver,
+    graph: <Solver as TensorAlgebra>::Elem,
+) -> <Solver as TensorAlgebra>::Elem {
+    let size = sol.shape(&graph)[0];
+    let shape = Shape::new(vec![size]);
+    let player = sol.tensor_create(shape.clone(), |i| i[0] % 2 == 0);
+    let target = sol.tensor_create(shape.clone(), |i| i[0] == size - 1);
+    let start = sol.tensor_create(shape, |i| i[0] == 0);
+    let tmp = sol.attractor(graph, player, target, size - 1);
+    let tmp = sol.tensor_and(start, tmp);
+    sol.tensor_any(tmp)
 }
 
 /// Validates the solver by calculating some numbers from the
 /// Online Encyclopedia of Integer Sequences.
-pub fn validate_solver(solver: &str) {
-    let start = Instant::now();
+pub fn validate_solver(solver: &str) -> Result<ExperimentResult, ExperimentError> {
+    let mut result = ExperimentResult::new(&format!("Solver {}", solver));
 
     check(
+        &mut result,
         solver,
         "transitive relations",
         Shape::new(vec![4, 4]),
         <Solver as BinaryRel>::is_transitive,
         3994,
-    );
+    )?;
 
     check(
+        &mut result,
         solver,
         "equivalence relations",
         Shape::new(vec![8, 8]),
         <Solver as BinaryRel>::is_equivalence,
-        4140,
-    );
+        bell(8),
+    )?;
 
     check(
+        &mut result,
         solver,
         "partial orders",
         Shape::new(vec![5, 5]),
         <Solver as BinaryRel>::is_partial_order,
         4231,
-    );
+    )?;
 
     check(
+        &mut result,
         solver,
         "functions",
         Shape::new(vec![5, 6]),
         <Solver as BinaryRel>::is_function,
         7776,
-    );
+    )?;
 
     check(
+        &mut result,
         solver,
         "partial functions",
         Shape::new(vec![5, 5]),
         <Solver as BinaryRel>::is_partial_function,
         7776,
-    );
+    )?;
+
+    check(
+        &mut result,
+        solver,
+        "won reachability games",
+        Shape::new(vec![4, 4]),
+        is_reachability_game,
+        43264,
+    )?;
 
-    let duration = Instant::now().duration_since(start).as_secs_f32();
-    println!("Solver {} finished in {} seconds\n", solver, duration);
+    Ok(result.finish())
 }
 
-pub fn validate() {
-    #[cfg(feature = "cadical")]
-    validate_solver("cadical");
-    #[cfg(feature = "cadical")]
-    validate_solver("cadical-sat");
-    #[cfg(feature = "batsat")]
-    validate_solver("batsat");
-    #[cfg(feature = "minisat")]
-    validate_solver("minisat");
-    #[cfg(feature = "varisat")]
-    validate_solver("varisat");
-    #[cfg(feature = "cryptominisat")]
-    validate_solver("cryptominisat");
+/// Validates all available solvers.
+pub fn validate() -> Result<Vec<ExperimentResult>, ExperimentError> {
+    let solvers: &[&str] = &[
+        #[cfg(feature = "cadical")]
+        "cadical",
+        #[cfg(feature = "cadical")]
+        "cadical-sat",
+        #[cfg(feature = "batsat")]
+        "batsat",
+        #[cfg(feature = "minisat")]
+        "minisat",
+        #[cfg(feature = "varisat")]
+        "varisat",
+        #[cfg(feature = "cryptominisat")]
+        "cryptominisat",
+    ];
+    solvers.iter().copied().map(validate_solver).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_counts() {
+        let mut result = ExperimentResult::new("Solver");
+        let pred = <Solver as BinaryRel>::is_transitive;
+        check(
+            &mut result,
+            "",
+            "transitive",
+            Shape::new(vec![3, 3]),
+            pred,
+            171,
+        )
+        .unwrap();
+        let pred = <Solver as BinaryRel>::is_equivalence;
+        check(
+            &mut result,
+            "",
+            "equivalence",
+            Shape::new(vec![4, 4]),
+            pred,
+            15,
+        )
+        .unwrap();
+        let pred = <Solver as BinaryRel>::is_partial_order;
+        check(&mut result, "", "orders", Shape::new(vec![3, 3]), pred, 19).unwrap();
+        let pred = <Solver as BinaryRel>::is_function;
+        check(
+            &mut result,
+            "",
+            "functions",
+            Shape::new(vec![2, 3]),
+            pred,
+            9,
+        )
+        .unwrap();
+        let pred = <Solver as BinaryRel>::is_partial_function;
+        check(&mut result, "", "partial", Shape::new(vec![2, 2]), pred, 9).unwrap();
+        let pred = is_reachability_game;
+        check(&mut result, "", "games", Shape::new(vec![2, 2]), pred, 8).unwrap();
+        assert_eq!(result.counts.len(), 6);
+    }
 }
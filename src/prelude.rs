@@ -0,0 +1,35 @@
+/*
+* Copyright (C) 2024, Miklos Maroti
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The supported stable API of the crate. Every item re-exported here
+//! follows semantic versioning: it is only removed or changed in an
+//! incompatible way together with a bump of the minor version while the
+//! crate is before 1.0. New features land only in the `alg`, `core` and
+//! `genvec` stack that this prelude is built from, so experiment code
+//! should start with
+//!
+//! ```ignore
+//! use uasat::prelude::*;
+//! ```
+
+pub use crate::alg::{
+    BinaryRelations, BooleanLattice, BoundedOrder, DirectedGraph, Domain, FixedSet, Group,
+    Indexable, Lattice, MeetSemilattice, Monoid, Operations, PartialOrder, PermutationGroup, Power,
+    Product2, ProductN, Relations, Semigroup, SmallSet, SymmetricGroup, BOOLEAN,
+};
+pub use crate::core::{BooleanLogic, BooleanSolver, Logic, Solver};
+pub use crate::genvec::{BitSlice, BitVec, Slice, Vector};